@@ -0,0 +1,56 @@
+//! Formatting benchmarks for error storms.
+//!
+//! Run with `cargo bench --bench display`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fmt::Write;
+use tyl_errors::TylError;
+
+fn errors() -> Vec<TylError> {
+    vec![
+        TylError::database("Connection pool exhausted"),
+        TylError::validation("email", "Invalid format"),
+        TylError::not_found("user", "9f1c2d4e"),
+        TylError::not_implemented_tracked("bulk export", "https://example.com/issues/42")
+            .with_planned_version("0.3.0"),
+        TylError::config_key("database.port", "an integer", "http"),
+    ]
+}
+
+fn bench_display(c: &mut Criterion) {
+    let errors = errors();
+
+    c.bench_function("display/to_string", |b| {
+        b.iter(|| {
+            for error in &errors {
+                black_box(error.to_string());
+            }
+        })
+    });
+
+    c.bench_function("display/write_to_reused_buffer", |b| {
+        let mut buffer = String::with_capacity(256);
+        b.iter(|| {
+            for error in &errors {
+                buffer.clear();
+                error.write_to(&mut buffer).unwrap();
+                black_box(&buffer);
+            }
+        })
+    });
+
+    c.bench_function("display/log_line", |b| {
+        let mut buffer = String::with_capacity(256);
+        b.iter(|| {
+            for error in &errors {
+                buffer.clear();
+                write!(buffer, "[{}] ", error.code()).unwrap();
+                error.write_to(&mut buffer).unwrap();
+                black_box(&buffer);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_display);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 use std::time::Duration;
-use tyl_errors::{ErrorCategory, TylError, TylResult};
+use tyl_errors::{with_retry, ErrorCategory, RetryPolicy, TylError, TylResult};
 
 fn main() {
     println!("TYL Errors - Retry Logic Example");
@@ -30,37 +30,21 @@ fn retry_delay_example() {
 fn simulate_retry_logic() {
     println!("\n=== Simulated Retry Logic ===");
 
+    let policy = RetryPolicy::network().with_base_delay(Duration::from_millis(10));
     let mut attempt_count = 0;
-    let max_attempts = 4;
 
-    for attempt in 1..=max_attempts {
+    let result = with_retry!(sync policy, {
         attempt_count += 1;
-
-        match simulate_network_call(attempt_count) {
-            Ok(result) => {
-                println!("Success on attempt {attempt}: {result}");
-                break;
-            }
-            Err(error) => {
-                let category = error.category();
-
-                if !category.is_retriable() {
-                    println!("Non-retriable error: {error}");
-                    break;
-                }
-
-                if attempt == max_attempts {
-                    println!("Max attempts reached. Last error: {error}");
-                    break;
-                }
-
-                let delay = category.retry_delay(attempt);
-                println!("Attempt {attempt} failed: {error} (will retry after {delay:?})");
-
-                // In real code, you'd use tokio::time::sleep(delay).await
-                std::thread::sleep(Duration::from_millis(10)); // Short delay for demo
-            }
+        let result = simulate_network_call(attempt_count);
+        if let Err(error) = &result {
+            println!("Attempt {attempt_count} failed: {error}");
         }
+        result
+    });
+
+    match result {
+        Ok(result) => println!("Success on attempt {attempt_count}: {result}"),
+        Err(error) => println!("Giving up after {attempt_count} attempts: {error}"),
     }
 }
 
@@ -0,0 +1,360 @@
+//! Rule-based routing of errors to alerting channels.
+//!
+//! This module (enabled by the `alert` feature) provides the [`AlertRouter`],
+//! which evaluates a shared, serde-loadable rule set against errors to decide where they should go
+//! (pager, ticket queue, ignore) consistently across services.
+
+use crate::context::ErrorContext;
+use crate::domain::TylDomain;
+use crate::error::{TylError, TylResult, TylSeverity};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Destination of routed errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    /// Name referenced by rules (e.g. `pager`, `ticket`, `ignore`).
+    pub name: String,
+    /// Channel-specific target, such as an on-call schedule or queue name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Condition routing matching errors to a route.
+///
+/// Every condition that is set must match; empty lists match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Name of the route for matching errors.
+    pub route: String,
+    /// Error codes to match (see [`TylError::code`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codes: Vec<String>,
+    /// Category names to match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Minimum severity to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_severity: Option<TylSeverity>,
+    /// Regular expression the failed operation must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    /// Owning teams to match (see [`TylError::owner`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Domains to match (see [`TylError::domain`]); each must be built in or
+    /// [registered](crate::TylDomain::register) when the router is built.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<String>,
+}
+
+/// Serializable rule set of an [`AlertRouter`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRouterConfig {
+    /// Available routes.
+    pub routes: Vec<Route>,
+    /// Rules evaluated in order; the first match wins.
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    /// Route used when no rule matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_route: Option<String>,
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    rule: AlertRule,
+    route: usize,
+    operation: Option<Regex>,
+}
+
+/// Routes errors to alerting channels according to an [`AlertRouterConfig`].
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{AlertRouter, TylError};
+///
+/// let router = AlertRouter::from_json(r#"{
+///     "routes": [{ "name": "pager" }, { "name": "ticket" }, { "name": "ignore" }],
+///     "rules": [
+///         { "route": "ignore", "codes": ["not_found"] },
+///         { "route": "pager", "min_severity": "Critical" },
+///         { "route": "pager", "categories": ["Transient"], "operation": "^payments\\." }
+///     ],
+///     "default_route": "ticket"
+/// }"#).unwrap();
+///
+/// let missing = TylError::config_missing("DATABASE_URL");
+/// assert_eq!(router.route(&missing).unwrap().name, "pager");
+/// assert_eq!(router.route(&TylError::not_found("user", "1")).unwrap().name, "ignore");
+///
+/// let deadlock = TylError::database("Deadlock");
+/// assert_eq!(router.route(&deadlock).unwrap().name, "ticket");
+/// let context = deadlock.to_context("payments.capture".to_string());
+/// assert_eq!(router.route_context(&deadlock, &context).unwrap().name, "pager");
+/// ```
+#[derive(Debug)]
+pub struct AlertRouter {
+    config: AlertRouterConfig,
+    rules: Vec<CompiledRule>,
+    default_route: Option<usize>,
+}
+
+impl AlertRouter {
+    /// Build a router, validating route references and compiling patterns.
+    ///
+    /// # Errors
+    /// Configuration errors naming the offending rule if a route or domain is
+    /// unknown or an operation pattern is not a valid regular expression.
+    pub fn new(config: AlertRouterConfig) -> TylResult<Self> {
+        let route_index = |name: &str, key: String| {
+            config
+                .routes
+                .iter()
+                .position(|route| route.name == name)
+                .ok_or_else(|| TylError::config_key(key, "a defined route", name))
+        };
+
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let route = route_index(&rule.route, format!("rules[{index}].route"))?;
+                let operation = rule
+                    .operation
+                    .as_deref()
+                    .map(|pattern| {
+                        Regex::new(pattern).map_err(|err| {
+                            TylError::config_key(
+                                format!("rules[{index}].operation"),
+                                "a valid regular expression",
+                                err.to_string(),
+                            )
+                        })
+                    })
+                    .transpose()?;
+                for (position, domain) in rule.domains.iter().enumerate() {
+                    if TylDomain::parse(domain).is_none() {
+                        return Err(TylError::config_key(
+                            format!("rules[{index}].domains[{position}]"),
+                            "a registered domain",
+                            domain,
+                        ));
+                    }
+                }
+                Ok(CompiledRule {
+                    rule: rule.clone(),
+                    route,
+                    operation,
+                })
+            })
+            .collect::<TylResult<Vec<_>>>()?;
+
+        let default_route = config
+            .default_route
+            .as_deref()
+            .map(|name| route_index(name, "default_route".to_string()))
+            .transpose()?;
+
+        Ok(Self {
+            config,
+            rules,
+            default_route,
+        })
+    }
+
+    /// Build a router from its JSON rule set.
+    pub fn from_json(json: &str) -> TylResult<Self> {
+        let config = serde_json::from_str(json)
+            .map_err(|err| TylError::configuration(format!("Invalid alert rules: {err}")))?;
+        Self::new(config)
+    }
+
+    /// Get the rule set of this router.
+    pub fn config(&self) -> &AlertRouterConfig {
+        &self.config
+    }
+
+    /// Find the route for an error.
+    ///
+    /// Errors whose code is [suppressed](crate::suppress) are never routed.
+    ///
+    /// Rules with an operation pattern never match, since the operation is
+    /// unknown; use [`AlertRouter::route_context`] to take it into account.
+    pub fn route(&self, error: &TylError) -> Option<&Route> {
+        self.route_operation(error, None, error.owner().as_deref())
+    }
+
+    /// Find the route for an error reported with a tracking context.
+    ///
+    /// Owner rules match the owner of the context, which includes owners
+    /// attributed by [enrichers](crate::enrich::register_owner).
+    pub fn route_context(&self, error: &TylError, context: &ErrorContext) -> Option<&Route> {
+        self.route_operation(error, Some(&context.operation), context.owner.as_deref())
+    }
+
+    fn route_operation(
+        &self,
+        error: &TylError,
+        operation: Option<&str>,
+        owner: Option<&str>,
+    ) -> Option<&Route> {
+        if error.is_suppressed() {
+            return None;
+        }
+        let code = error.code();
+        let category = error.category();
+        let severity = error.severity();
+        let domain = error.domain();
+
+        self.rules
+            .iter()
+            .find(|compiled| {
+                let rule = &compiled.rule;
+                (rule.codes.is_empty() || rule.codes.iter().any(|c| c == code))
+                    && (rule.categories.is_empty()
+                        || rule
+                            .categories
+                            .iter()
+                            .any(|c| c == category.category_name()))
+                    && rule.min_severity.map_or(true, |min| severity >= min)
+                    && compiled.operation.as_ref().map_or(true, |pattern| {
+                        operation.is_some_and(|operation| pattern.is_match(operation))
+                    })
+                    && (rule.owners.is_empty()
+                        || owner.is_some_and(|owner| rule.owners.iter().any(|o| o == owner)))
+                    && (rule.domains.is_empty()
+                        || domain.as_ref().is_some_and(|domain| {
+                            rule.domains.iter().any(|d| d == domain.as_str())
+                        }))
+            })
+            .map(|compiled| compiled.route)
+            .or(self.default_route)
+            .map(|index| &self.config.routes[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AlertRouterConfig {
+        AlertRouterConfig {
+            routes: vec![
+                Route {
+                    name: "pager".to_string(),
+                    target: Some("oncall-core".to_string()),
+                },
+                Route {
+                    name: "ticket".to_string(),
+                    target: None,
+                },
+            ],
+            rules: vec![AlertRule {
+                route: "pager".to_string(),
+                min_severity: Some(TylSeverity::Error),
+                operation: Some("^billing\\.".to_string()),
+                ..AlertRule::default()
+            }],
+            default_route: None,
+        }
+    }
+
+    #[test]
+    fn test_route_context_should_match_operation_and_severity() {
+        // Given: a router paging on billing errors
+        let router = AlertRouter::new(config()).unwrap();
+        let error = TylError::network("Gateway down");
+
+        // When: routing with and without a matching operation
+        let billing = router.route_context(&error, &error.to_context("billing.charge".to_string()));
+        let search = router.route_context(&error, &error.to_context("search.query".to_string()));
+
+        // Then: only the billing error should be paged
+        assert_eq!(billing.unwrap().target.as_deref(), Some("oncall-core"));
+        assert!(search.is_none());
+        assert!(router.route(&error).is_none());
+    }
+
+    #[test]
+    fn test_owner_rules_should_route_by_the_owning_team() {
+        // Given: a router paging the payments team on its errors
+        let mut config = config();
+        config.rules = vec![AlertRule {
+            route: "pager".to_string(),
+            owners: vec!["payments".to_string()],
+            ..AlertRule::default()
+        }];
+        config.default_route = Some("ticket".to_string());
+        let router = AlertRouter::new(config).unwrap();
+        let owned = TylError::business_logic(
+            "Card declined",
+            crate::ErrorCategory::permanent()
+                .with_owner("payments")
+                .into_classifier(),
+        );
+        let enricher = crate::enrich::register_owner("alert.test.payments.", "payments");
+
+        // When: routing errors owned through their category, an enricher or not at all
+        let by_category = router.route(&owned);
+        let error = TylError::timeout("Gateway slow");
+        let context = error.to_context("alert.test.payments.capture".to_string());
+        let by_enricher = router.route_context(&error, &context);
+        let unowned = router.route(&error);
+        crate::enrich::unregister(enricher);
+
+        // Then: only owned errors should be paged
+        assert_eq!(by_category.unwrap().name, "pager");
+        assert_eq!(by_enricher.unwrap().name, "pager");
+        assert_eq!(unowned.unwrap().name, "ticket");
+        let stats = crate::ErrorStats::new();
+        stats.record_context(&error, &context);
+        assert_eq!(stats.snapshot().by_owner["payments"], 1);
+    }
+
+    #[test]
+    fn test_new_should_reject_unknown_routes_and_bad_patterns() {
+        // Given: rules referencing a missing route and an invalid regex
+        let mut unknown = config();
+        unknown.rules[0].route = "sms".to_string();
+        let mut invalid = config();
+        invalid.rules[0].operation = Some("(".to_string());
+
+        // When/Then: building the router should fail with the offending key
+        let error = AlertRouter::new(unknown).unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[0].route");
+        let error = AlertRouter::new(invalid).unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[0].operation");
+        let mut undefined = config();
+        undefined.rules[0].domains = vec!["storage".to_string(), "alert_test_nope".to_string()];
+        let error = AlertRouter::new(undefined).unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[0].domains[1]");
+    }
+
+    #[test]
+    fn test_domain_rules_should_route_by_domain() {
+        // Given: a router paging on storage errors
+        let mut config = config();
+        config.rules = vec![AlertRule {
+            route: "pager".to_string(),
+            domains: vec!["storage".to_string()],
+            ..AlertRule::default()
+        }];
+        let router = AlertRouter::new(config).unwrap();
+
+        // When/Then: only errors of the storage domain should be paged
+        assert_eq!(
+            router.route(&TylError::database("Disk full")).unwrap().name,
+            "pager"
+        );
+        let cache = crate::ErrorCategory::transient().with_domain(TylDomain::Storage);
+        let evicted = TylError::business_logic("Cache evicted", cache.into_classifier());
+        assert_eq!(router.route(&evicted).unwrap().name, "pager");
+        assert!(router.route(&TylError::network("Reset")).is_none());
+
+        // And: reported errors should be counted per domain
+        evicted.report("alert.test.cache");
+        assert!(crate::ErrorStats::global().snapshot().by_domain["storage"] >= 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! Audit events derived from security-relevant errors.
+//!
+//! This module turns authentication failures, denied permissions and conflicts
+//! on protected resources into structured [`AuditEvent`]s and hands them to an
+//! [`AuditSink`], so compliance logging has the same shape in every service.
+//! Sinks can be fed explicitly with [`record`] or automatically from the error
+//! event stream with [`install`].
+
+use crate::context::ErrorContext;
+use crate::error::TylError;
+use crate::events::{self, SubscriptionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Context metadata key naming the acting principal.
+pub const ACTOR_KEY: &str = "actor";
+
+/// Context metadata key marking a conflict as touching a protected resource.
+///
+/// Conflicts are only audited when their context carries this key; its value
+/// is used as the audited resource.
+pub const PROTECTED_RESOURCE_KEY: &str = "protected_resource";
+
+/// Outcome recorded in an audit event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditOutcome {
+    /// The caller's identity could not be established.
+    Unauthenticated,
+    /// The caller lacked a required permission.
+    Denied,
+    /// A change to a protected resource was rejected as conflicting.
+    Conflict,
+}
+
+/// Structured audit record of a security-relevant failure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Acting principal, if known.
+    pub actor: Option<String>,
+    /// Attempted action (the permission's action, or the failed operation).
+    pub action: String,
+    /// Targeted resource, if known.
+    pub resource: Option<String>,
+    /// Outcome of the attempt.
+    pub outcome: AuditOutcome,
+    /// Identifier of the error occurrence, for correlation with logs.
+    pub error_id: Uuid,
+    /// When the failure occurred.
+    pub timestamp: DateTime<Utc>,
+    /// Stable error code.
+    pub code: String,
+}
+
+impl AuditEvent {
+    /// Build an audit event for an error, or `None` if it is not security-relevant.
+    ///
+    /// The actor is the denied subject or the context's [`ACTOR_KEY`] metadata.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::audit::{AuditEvent, AuditOutcome};
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::permission_denied("user:42", "export", "report:q3");
+    /// let context = error.to_context("reports.export".to_string());
+    ///
+    /// let event = AuditEvent::from_error(&error, &context).unwrap();
+    /// assert_eq!(event.outcome, AuditOutcome::Denied);
+    /// assert_eq!(event.actor.as_deref(), Some("user:42"));
+    /// assert_eq!(event.error_id, context.error_id);
+    ///
+    /// let timeout = TylError::network("timeout");
+    /// assert!(AuditEvent::from_error(&timeout, &timeout.to_context("x".into())).is_none());
+    /// ```
+    pub fn from_error(error: &TylError, context: &ErrorContext) -> Option<Self> {
+        let metadata_text = |key: &str| {
+            context
+                .get_metadata(key)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+
+        let (outcome, actor, action, resource) = match error {
+            TylError::Unauthenticated { .. } => (
+                AuditOutcome::Unauthenticated,
+                metadata_text(ACTOR_KEY),
+                context.operation.clone(),
+                None,
+            ),
+            TylError::PermissionDenied {
+                subject,
+                action,
+                resource,
+                ..
+            } => (
+                AuditOutcome::Denied,
+                Some(subject.clone()),
+                action.clone(),
+                Some(resource.clone()),
+            ),
+            TylError::Conflict { .. } => (
+                AuditOutcome::Conflict,
+                metadata_text(ACTOR_KEY),
+                context.operation.clone(),
+                Some(metadata_text(PROTECTED_RESOURCE_KEY)?),
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            actor,
+            action,
+            resource,
+            outcome,
+            error_id: context.error_id,
+            timestamp: context.occurred_at,
+            code: error.code().to_string(),
+        })
+    }
+}
+
+/// Destination for audit events (log pipeline, database, SIEM forwarder).
+pub trait AuditSink: Send + Sync {
+    /// Record one audit event.
+    fn record(&self, event: &AuditEvent);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditEvent) + Send + Sync,
+{
+    fn record(&self, event: &AuditEvent) {
+        self(event)
+    }
+}
+
+/// Sink keeping audit events in memory, mainly for tests.
+#[derive(Debug, Default)]
+pub struct MemoryAuditSink {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl MemoryAuditSink {
+    /// Create an empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a copy of the recorded events.
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn record(&self, event: &AuditEvent) {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event.clone());
+    }
+}
+
+/// Record an error in a sink if it is security-relevant.
+///
+/// # Returns
+/// The recorded event, or `None` if the error was not audited.
+pub fn record(
+    sink: &dyn AuditSink,
+    error: &TylError,
+    context: &ErrorContext,
+) -> Option<AuditEvent> {
+    let event = AuditEvent::from_error(error, context)?;
+    sink.record(&event);
+    Some(event)
+}
+
+/// Audit every security-relevant error reported with [`TylError::report`].
+///
+/// Returns the event stream subscription, which can be removed with
+/// [`events::unsubscribe`].
+pub fn install<S: AuditSink + 'static>(sink: Arc<S>) -> SubscriptionId {
+    events::subscribe_fn(move |event| {
+        record(sink.as_ref(), &event.error, &event.context);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicts_should_only_be_audited_on_protected_resources() {
+        // Given: a plain conflict and one on a protected resource
+        let error = TylError::conflict("Version mismatch");
+        let plain = error.to_context("accounts.update".to_string());
+        let protected = error
+            .to_context("accounts.update".to_string())
+            .with_metadata(
+                PROTECTED_RESOURCE_KEY.to_string(),
+                serde_json::json!("account:1"),
+            )
+            .with_metadata(ACTOR_KEY.to_string(), serde_json::json!("svc-billing"));
+
+        // When: building audit events
+        let sink = MemoryAuditSink::new();
+        let ignored = record(&sink, &error, &plain);
+        let audited = record(&sink, &error, &protected).unwrap();
+
+        // Then: only the protected conflict should be recorded
+        assert!(ignored.is_none());
+        assert_eq!(audited.outcome, AuditOutcome::Conflict);
+        assert_eq!(audited.resource.as_deref(), Some("account:1"));
+        assert_eq!(audited.actor.as_deref(), Some("svc-billing"));
+        assert_eq!(sink.events().len(), 1);
+    }
+
+    #[test]
+    fn test_install_should_audit_reported_errors() {
+        // Given: a sink installed on the event stream
+        let sink = Arc::new(MemoryAuditSink::new());
+        let id = install(Arc::clone(&sink));
+
+        // When: reporting an authentication failure and an unrelated error
+        TylError::unauthenticated("missing token").report("audit.test.install");
+        TylError::database("down").report("audit.test.install");
+        events::unsubscribe(id);
+
+        // Then: only the authentication failure should be audited
+        let audited: Vec<_> = sink
+            .events()
+            .into_iter()
+            .filter(|e| e.action == "audit.test.install")
+            .collect();
+        assert_eq!(audited.len(), 1);
+        assert_eq!(audited[0].outcome, AuditOutcome::Unauthenticated);
+    }
+}
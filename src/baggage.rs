@@ -0,0 +1,342 @@
+//! Baggage propagated between services alongside error contexts.
+//!
+//! Metadata describes a failure locally and never leaves the process except
+//! inside a serialized context. Baggage is the part of the context meant to
+//! travel: a few key-value pairs (region, feature cohort, request priority)
+//! that downstream services copy into the contexts of their own failures.
+//! It is carried by envelopes and by the W3C [`BAGGAGE_HEADER`]; the keys
+//! allowed and the header size are bounded by
+//! [`ErrorSettings`](crate::ErrorSettings) (`TYL_ERROR_BAGGAGE_KEYS`,
+//! `TYL_ERROR_MAX_BAGGAGE_LEN`) so baggage cannot grow without limit as it
+//! is forwarded.
+
+use crate::context::ErrorContext;
+use crate::settings::ErrorSettings;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Name of the W3C header carrying baggage between services.
+pub const BAGGAGE_HEADER: &str = "baggage";
+
+impl ErrorContext {
+    /// Add a baggage entry using builder pattern.
+    ///
+    /// See [`add_baggage`](ErrorContext::add_baggage) for the entries that
+    /// are dropped.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, ErrorContext};
+    ///
+    /// let context = ErrorContext::new("orders::create".into(), ErrorCategory::network(), "Timeout".into())
+    ///     .with_baggage("region", "eu-west-1")
+    ///     .with_baggage("cohort", "beta users");
+    /// assert_eq!(context.baggage_item("region"), Some("eu-west-1"));
+    /// assert_eq!(
+    ///     context.baggage_header().as_deref(),
+    ///     Some("cohort=beta%20users,region=eu-west-1")
+    /// );
+    ///
+    /// let downstream = ErrorContext::new("stock::reserve".into(), ErrorCategory::network(), "Timeout".into())
+    ///     .with_baggage_header("cohort=beta%20users,region=eu-west-1");
+    /// assert_eq!(downstream.baggage, context.baggage);
+    /// ```
+    pub fn with_baggage<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.add_baggage(key, value);
+        self
+    }
+
+    /// Add a baggage entry, replacing the value of an existing key.
+    ///
+    /// The entry is dropped if its key is not a valid header token, is not
+    /// allowed by `TYL_ERROR_BAGGAGE_KEYS`, or would make the
+    /// [baggage header](ErrorContext::baggage_header) longer than
+    /// `TYL_ERROR_MAX_BAGGAGE_LEN`.
+    ///
+    /// # Returns
+    /// True if the entry was added.
+    pub fn add_baggage<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> bool {
+        insert(
+            &mut self.baggage,
+            key.into(),
+            value.into(),
+            &ErrorSettings::global(),
+        )
+    }
+
+    /// Get the value of a baggage entry.
+    pub fn baggage_item(&self, key: &str) -> Option<&str> {
+        self.baggage.get(key).map(String::as_str)
+    }
+
+    /// Encode the baggage as the value of the [`BAGGAGE_HEADER`], with
+    /// percent-encoded values. `None` without baggage.
+    pub fn baggage_header(&self) -> Option<String> {
+        (!self.baggage.is_empty()).then(|| encode(&self.baggage))
+    }
+
+    /// Add the entries of a received [`BAGGAGE_HEADER`] value using builder
+    /// pattern.
+    ///
+    /// Entry properties (after `;`) are ignored, and entries are added with
+    /// [`add_baggage`](ErrorContext::add_baggage), so the allow-list and size
+    /// limit also apply to incoming baggage. Malformed entries are skipped.
+    pub fn with_baggage_header(mut self, header: &str) -> Self {
+        for entry in header.split(',') {
+            let entry = entry.split(';').next().unwrap_or_default();
+            if let Some((key, value)) = entry.split_once('=') {
+                self.add_baggage(key.trim(), decode(value.trim()));
+            }
+        }
+        self
+    }
+}
+
+/// Keep the decoded baggage entries that
+/// [`add_baggage`](ErrorContext::add_baggage) would accept under `settings`.
+pub(crate) fn limit_baggage<I>(entries: I, settings: &ErrorSettings) -> BTreeMap<String, String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut baggage = BTreeMap::new();
+    for (key, value) in entries {
+        insert(&mut baggage, key, value, settings);
+    }
+    baggage
+}
+
+/// Deserialize the baggage of a context, dropping the entries
+/// [`add_baggage`](ErrorContext::add_baggage) would reject.
+pub(crate) fn deserialize_baggage<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let entries = BTreeMap::<String, String>::deserialize(deserializer)?;
+    Ok(limit_baggage(entries, &ErrorSettings::global()))
+}
+
+/// Insert a baggage entry if its key is a valid token allowed by `settings`
+/// and the encoded baggage stays within their size limit.
+fn insert(
+    baggage: &mut BTreeMap<String, String>,
+    key: String,
+    value: String,
+    settings: &ErrorSettings,
+) -> bool {
+    if !is_token(&key) || !settings.allows_baggage_key(&key) {
+        return false;
+    }
+    let previous = baggage.insert(key.clone(), value);
+    if settings.max_baggage_len > 0 && encode(baggage).len() > settings.max_baggage_len {
+        match previous {
+            Some(previous) => baggage.insert(key, previous),
+            None => baggage.remove(&key),
+        };
+        return false;
+    }
+    true
+}
+
+/// Baggage propagation through HTTP headers. Requires the `http` feature.
+#[cfg(feature = "http")]
+impl ErrorContext {
+    /// Add the baggage of every [`BAGGAGE_HEADER`] of a request.
+    ///
+    /// Non-ASCII header values are skipped.
+    pub fn with_baggage_from(self, headers: &http::HeaderMap) -> Self {
+        headers
+            .get_all(BAGGAGE_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .fold(self, ErrorContext::with_baggage_header)
+    }
+
+    /// Set the [`BAGGAGE_HEADER`] of an outgoing request to this context's
+    /// baggage. Does nothing without baggage.
+    pub fn propagate_baggage(&self, headers: &mut http::HeaderMap) {
+        let value = self
+            .baggage_header()
+            .and_then(|header| http::HeaderValue::from_str(&header).ok());
+        if let Some(value) = value {
+            headers.insert(BAGGAGE_HEADER, value);
+        }
+    }
+}
+
+/// Check that a baggage key is an RFC 7230 token.
+fn is_token(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+/// Encode baggage entries as `key=value` pairs separated by commas.
+fn encode(baggage: &BTreeMap<String, String>) -> String {
+    let mut header = String::new();
+    for (key, value) in baggage {
+        if !header.is_empty() {
+            header.push(',');
+        }
+        header.push_str(key);
+        header.push('=');
+        for byte in value.bytes() {
+            // W3C baggage octets, except `%`, which starts an escape.
+            let plain = matches!(
+                byte,
+                0x21 | 0x23..=0x24 | 0x26..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E
+            );
+            if plain {
+                header.push(byte as char);
+            } else {
+                let _ = write!(header, "%{byte:02X}");
+            }
+        }
+    }
+    header
+}
+
+/// Decode the percent-encoded value of a baggage entry.
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+
+    fn context() -> ErrorContext {
+        ErrorContext::new(
+            "baggage.test".to_string(),
+            ErrorCategory::network(),
+            "Timeout".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_baggage_should_round_trip_through_the_header() {
+        // Given: baggage with characters that need escaping
+        let upstream = context()
+            .with_baggage("note", "50% off, \"today\"; é")
+            .with_baggage("region", "eu-west-1")
+            .with_baggage("bad key", "dropped");
+
+        // When: propagating it and reading it downstream with properties
+        let header = upstream.baggage_header().unwrap();
+        let downstream = context().with_baggage_header(&format!("{header};prop=1, ,=x"));
+
+        // Then: the entries should arrive unchanged, and only valid ones
+        assert!(!header.contains(' '));
+        assert_eq!(downstream.baggage, upstream.baggage);
+        assert_eq!(
+            downstream.baggage_item("note"),
+            Some("50% off, \"today\"; é")
+        );
+        assert_eq!(upstream.baggage.len(), 2);
+        assert_eq!(context().baggage_header(), None);
+
+        // And: serialized contexts, as sealed in envelopes, should carry it
+        let json = serde_json::to_string(&upstream).unwrap();
+        let restored: ErrorContext = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.baggage, upstream.baggage);
+    }
+
+    fn restricted_settings() -> ErrorSettings {
+        ErrorSettings {
+            max_baggage_len: 30,
+            ..ErrorSettings::default().with_baggage_keys(["region", "cohort"])
+        }
+    }
+
+    #[test]
+    fn test_baggage_should_respect_the_allow_list_and_size_limit() {
+        // Given: settings allowing two keys and a short header
+        let settings = restricted_settings();
+
+        // When: adding allowed, unknown and oversized entries
+        let mut baggage = BTreeMap::new();
+        let region = insert(&mut baggage, "region".into(), "eu-west-1".into(), &settings);
+        let user = insert(&mut baggage, "user_id".into(), "42".into(), &settings);
+        let cohort = insert(
+            &mut baggage,
+            "cohort".into(),
+            "a-very-long-cohort-name".into(),
+            &settings,
+        );
+
+        // Then: only the allowed entry within the limit should be kept
+        assert!(region && !user && !cohort);
+        assert_eq!(encode(&baggage), "region=eu-west-1");
+        assert!(insert(
+            &mut baggage,
+            "cohort".into(),
+            "beta".into(),
+            &settings
+        ));
+        assert!(!insert(
+            &mut baggage,
+            "region".into(),
+            "a-very-long-region-name".into(),
+            &settings
+        ));
+        assert_eq!(baggage["region"], "eu-west-1");
+    }
+
+    #[test]
+    fn test_decoded_baggage_should_be_limited_like_added_baggage() {
+        // Given: baggage received from a peer with other settings
+        let received = [
+            ("region", "eu-west-1"),
+            ("user_id", "42"),
+            ("bad key", "x"),
+            ("cohort", "a-very-long-cohort-name"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+
+        // When: decoding it under restricted settings
+        let baggage = limit_baggage(received, &restricted_settings());
+
+        // Then: the entries add_baggage would reject should be dropped
+        assert_eq!(encode(&baggage), "region=eu-west-1");
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_baggage_should_propagate_through_http_headers() {
+        // Given: a request carrying baggage in two headers
+        let mut incoming = http::HeaderMap::new();
+        incoming.append(BAGGAGE_HEADER, "region=eu-west-1".parse().unwrap());
+        incoming.append(BAGGAGE_HEADER, "cohort=beta".parse().unwrap());
+
+        // When: reading and propagating it
+        let context = context().with_baggage_from(&incoming);
+        let mut outgoing = http::HeaderMap::new();
+        context.propagate_baggage(&mut outgoing);
+
+        // Then: both entries should be sent downstream in one header
+        assert_eq!(outgoing[BAGGAGE_HEADER], "cohort=beta,region=eu-west-1");
+    }
+}
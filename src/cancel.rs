@@ -0,0 +1,106 @@
+//! Cancellation of retry executors.
+//!
+//! Retries must stop promptly when a service shuts down. This module provides
+//! [`ShouldCancel`], the cancellation signal accepted by
+//! [`RetryPolicy::execute_async_with_cancel`](crate::RetryPolicy::execute_async_with_cancel):
+//! it is implemented for `Fn() -> bool` closures and, with the `tokio`
+//! feature, for `tokio_util`'s `CancellationToken`.
+
+use crate::retry::{SleepFuture, Sleeper};
+use std::task::Poll;
+use std::time::Duration;
+
+/// Interval at which executors check a [`ShouldCancel`] without a
+/// [`cancelled`](ShouldCancel::cancelled) future while sleeping.
+pub const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Signal telling retry executors to stop.
+///
+/// Executors check [`is_cancelled`](Self::is_cancelled) before every attempt
+/// and abort the delay between attempts once
+/// [`cancelled`](Self::cancelled) completes; signals without such a future
+/// are checked every [`CANCEL_POLL_INTERVAL`] while sleeping.
+pub trait ShouldCancel: Send + Sync {
+    /// Check whether retrying should stop.
+    fn is_cancelled(&self) -> bool;
+
+    /// Create a future completing once cancelled, if the signal can wake
+    /// executors itself. Defaults to `None`.
+    fn cancelled(&self) -> Option<SleepFuture> {
+        None
+    }
+}
+
+impl<F> ShouldCancel for F
+where
+    F: Fn() -> bool + Send + Sync,
+{
+    fn is_cancelled(&self) -> bool {
+        self()
+    }
+}
+
+/// `CancellationToken` as a retry cancellation signal. Requires the `tokio`
+/// feature.
+#[cfg(feature = "tokio")]
+impl ShouldCancel for tokio_util::sync::CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        tokio_util::sync::CancellationToken::is_cancelled(self)
+    }
+
+    fn cancelled(&self) -> Option<SleepFuture> {
+        Some(Box::pin(self.clone().cancelled_owned()))
+    }
+}
+
+/// Signal of executors that cannot be cancelled, never consulted.
+pub(crate) struct NeverCancelled;
+
+impl ShouldCancel for NeverCancelled {
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Sleep for `delay` unless cancelled first; without a signal, just sleep.
+///
+/// # Returns
+/// True if the sleep was aborted by cancellation.
+pub(crate) async fn sleep_unless_cancelled<S, C>(
+    sleeper: &S,
+    cancel: Option<&C>,
+    delay: Duration,
+) -> bool
+where
+    S: Sleeper + ?Sized,
+    C: ShouldCancel + ?Sized,
+{
+    let Some(cancel) = cancel else {
+        sleeper.sleep(delay).await;
+        return false;
+    };
+    if let Some(cancelled) = cancel.cancelled() {
+        return first_of(cancelled, sleeper.sleep(delay)).await;
+    }
+    let mut remaining = delay;
+    while !remaining.is_zero() {
+        if cancel.is_cancelled() {
+            return true;
+        }
+        let slice = remaining.min(CANCEL_POLL_INTERVAL);
+        sleeper.sleep(slice).await;
+        remaining -= slice;
+    }
+    cancel.is_cancelled()
+}
+
+/// Wait for either future, returning true if `cancelled` completed first.
+async fn first_of(mut cancelled: SleepFuture, mut sleep: SleepFuture) -> bool {
+    std::future::poll_fn(|cx| {
+        if cancelled.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(true);
+        }
+        sleep.as_mut().poll(cx).map(|()| false)
+    })
+    .await
+}
@@ -0,0 +1,89 @@
+//! Human descriptions of error codes.
+//!
+//! This module (enabled by the `yaml` feature) embeds a catalog describing
+//! what each built-in error code means and what to do about it, compiled
+//! into the binary so CLIs and log viewers can explain errors without
+//! network access. See [`TylError::describe`] and [`describe_code`].
+
+use crate::error::TylError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The embedded catalog, keyed by error code.
+const CATALOG_YAML: &str = include_str!("catalog.yaml");
+
+/// Description of an error code from the embedded catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeDescription {
+    /// One-line summary of what happened.
+    pub summary: String,
+    /// Longer explanation of the causes.
+    pub description: String,
+    /// What the reader should do about it.
+    pub remediation: String,
+}
+
+fn catalog() -> &'static HashMap<String, CodeDescription> {
+    static CATALOG: OnceLock<HashMap<String, CodeDescription>> = OnceLock::new();
+    // The catalog is checked by the tests; an invalid one describes nothing.
+    CATALOG.get_or_init(|| serde_yaml::from_str(CATALOG_YAML).unwrap_or_default())
+}
+
+/// Look up the description of an error code, e.g. the `code` member of
+/// a received problem. Requires the `yaml` feature.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::describe_code;
+///
+/// let description = describe_code("not_found").unwrap();
+/// assert_eq!(description.summary, "The requested resource does not exist.");
+/// assert!(describe_code("no_such_code").is_none());
+/// ```
+pub fn describe_code(code: &str) -> Option<&'static CodeDescription> {
+    catalog().get(code)
+}
+
+impl TylError {
+    /// Get the description of this error's code from the embedded catalog.
+    /// Requires the `yaml` feature.
+    ///
+    /// Every built-in code is described; exhausted retries are described by
+    /// their last error's code.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let description = TylError::validation("email", "Invalid").describe().unwrap();
+    /// println!("{}\n{}", description.summary, description.remediation);
+    /// assert!(description.remediation.contains("Fix the named field"));
+    /// ```
+    pub fn describe(&self) -> Option<&'static CodeDescription> {
+        describe_code(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_should_describe_every_builtin_code() {
+        // Given: the embedded catalog
+        let catalog: HashMap<String, CodeDescription> =
+            serde_yaml::from_str(CATALOG_YAML).expect("catalog should be valid YAML");
+
+        // When/Then: every built-in code should have a complete entry
+        for error in TylError::builtin_samples() {
+            let description = catalog
+                .get(error.code())
+                .unwrap_or_else(|| panic!("`{}` should be described", error.code()));
+            assert!(!description.summary.is_empty());
+            assert!(!description.remediation.is_empty());
+        }
+        assert_eq!(catalog.len(), 17);
+        assert_eq!(describe_code("timeout"), catalog.get("timeout"));
+    }
+}
@@ -0,0 +1,286 @@
+//! tower middleware turning service failures into problem responses.
+//!
+//! This module (enabled by the `tower` feature) provides [`TylCatchLayer`],
+//! which wraps an HTTP service so that errors returned by the service and
+//! panics raised while it runs are converted into [`TylError`]s, reported to
+//! [event subscribers](crate::events) and answered with an
+//! `application/problem+json` response. The wrapped service never fails.
+
+use crate::context::idempotency_key_in;
+use crate::error::TylError;
+use crate::operation::OperationName;
+use crate::panic_capture::{catch_unwind, CaughtPanic};
+use crate::problem::{PROBLEM_JSON_CONTENT_TYPE, RETRY_SCOPE_HEADER};
+use http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use http::{Request, Response, StatusCode};
+use std::convert::Infallible;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer wrapping services in [`TylCatch`].
+///
+/// The operation of the reported [`ErrorContext`] is taken from an
+/// [`OperationName`] request extension when present, and is otherwise the
+/// request method and path (e.g. `GET /users/42`). The context is also
+/// attached to the error response as an extension, and its error ID is sent
+/// to the client as the `error_id` problem member. An `Idempotency-Key`
+/// request header is recorded as the context's idempotency key.
+///
+/// Who may retry the request is sent in the [`RETRY_SCOPE_HEADER`], and
+/// `Retry-After` is only sent when the caller may retry it (see
+/// [`TylError::retry_scope`]).
+///
+/// # Example
+/// ```rust
+/// use tower::{service_fn, Layer, ServiceExt};
+/// use tyl_errors::{TylCatchLayer, TylError};
+///
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let service = TylCatchLayer::new().layer(service_fn(|_req: http::Request<String>| async {
+///     Err::<http::Response<String>, _>(TylError::not_found("user", "42"))
+/// }));
+///
+/// let request = http::Request::get("/users/42").body(String::new()).unwrap();
+/// let response = service.oneshot(request).await.unwrap();
+/// assert_eq!(response.status(), 404);
+/// assert_eq!(response.headers()["content-type"], "application/problem+json");
+/// # });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TylCatchLayer;
+
+impl TylCatchLayer {
+    /// Create the layer.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for TylCatchLayer {
+    type Service = TylCatch<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TylCatch {
+            inner,
+            ready_error: None,
+        }
+    }
+}
+
+/// Service converting errors and panics of the inner service into problem responses.
+///
+/// Created by [`TylCatchLayer`].
+#[derive(Debug, Clone)]
+pub struct TylCatch<S> {
+    inner: S,
+    /// Failure of `poll_ready`, answered on the next call.
+    ready_error: Option<TylError>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for TylCatch<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<TylError>,
+    ResBody: From<String>,
+{
+    type Response = Response<ResBody>;
+    type Error = Infallible;
+    type Future = CatchFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.ready_error.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        match catch_unwind(AssertUnwindSafe(|| self.inner.poll_ready(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(Ok(()))) => Poll::Ready(Ok(())),
+            Ok(Poll::Ready(Err(error))) => {
+                self.ready_error = Some(error.into());
+                Poll::Ready(Ok(()))
+            }
+            Err(payload) => {
+                self.ready_error = Some(panic_error(payload));
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let operation = request
+            .extensions()
+            .get::<OperationName>()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("{} {}", request.method(), request.uri().path()));
+        let idempotency_key = idempotency_key_in(request.headers());
+
+        let state = match self.ready_error.take() {
+            Some(error) => State::Failed(Some(error)),
+            None => match catch_unwind(AssertUnwindSafe(|| self.inner.call(request))) {
+                Ok(future) => State::Running(Box::pin(future)),
+                Err(payload) => State::Failed(Some(panic_error(payload))),
+            },
+        };
+        CatchFuture {
+            state,
+            operation,
+            idempotency_key,
+        }
+    }
+}
+
+enum State<F> {
+    Running(Pin<Box<F>>),
+    Failed(Option<TylError>),
+}
+
+/// Response future of [`TylCatch`].
+pub struct CatchFuture<F> {
+    state: State<F>,
+    operation: String,
+    idempotency_key: Option<String>,
+}
+
+impl<F, ResBody, E> Future for CatchFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    E: Into<TylError>,
+    ResBody: From<String>,
+{
+    type Output = Result<Response<ResBody>, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let error = match &mut this.state {
+            State::Running(future) => {
+                match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                    Ok(Poll::Pending) => return Poll::Pending,
+                    Ok(Poll::Ready(Ok(response))) => return Poll::Ready(Ok(response)),
+                    Ok(Poll::Ready(Err(error))) => error.into(),
+                    Err(payload) => panic_error(payload),
+                }
+            }
+            State::Failed(error) => error.take().expect("CatchFuture polled after completion"),
+        };
+        this.state = State::Failed(None);
+        Poll::Ready(Ok(problem_response(
+            &error,
+            &this.operation,
+            this.idempotency_key.take(),
+        )))
+    }
+}
+
+/// Convert a caught panic into an internal error with its panic details.
+fn panic_error(panic: CaughtPanic) -> TylError {
+    panic.into_error("Service panicked")
+}
+
+/// Report the error and build its problem+json response.
+fn problem_response<B: From<String>>(
+    error: &TylError,
+    operation: &str,
+    idempotency_key: Option<String>,
+) -> Response<B> {
+    let mut context = error.to_context(operation.to_string());
+    if idempotency_key.is_some() {
+        context.idempotency_key = idempotency_key;
+        context.apply_error_id_mode(error);
+    }
+    error.report_context(&context);
+    let problem = error.to_problem_details().with_error_id(context.error_id);
+    let status = StatusCode::from_u16(problem.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    // Serializing a map of JSON values cannot fail.
+    let body = serde_json::to_string(&problem).unwrap_or_default();
+
+    let mut response = Response::new(B::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+    );
+    if let Some(value) = problem
+        .retry_after_header()
+        .and_then(|seconds| HeaderValue::from_str(&seconds).ok())
+    {
+        response.headers_mut().insert(RETRY_AFTER, value);
+    }
+    if let Some(scope) = problem.retry_scope() {
+        response
+            .headers_mut()
+            .insert(RETRY_SCOPE_HEADER, HeaderValue::from_static(scope.as_str()));
+    }
+    response.extensions_mut().insert(context);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ErrorContext;
+    use tower::{service_fn, ServiceExt};
+
+    fn request(path: &str) -> Request<String> {
+        Request::get(path).body(String::new()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_panics_should_become_problem_responses() {
+        // Given: a service that fails, panics or succeeds depending on the path
+        let service = TylCatchLayer::new().layer(service_fn(|req: Request<String>| async move {
+            match req.uri().path() {
+                "/fail" => Err(TylError::timeout("Upstream too slow")),
+                "/panic" => panic!("index out of bounds"),
+                _ => Ok(Response::new("ok".to_string())),
+            }
+        }));
+
+        // When: calling each path
+        let failed = service.clone().oneshot(request("/fail")).await.unwrap();
+        let panicked = service.clone().oneshot(request("/panic")).await.unwrap();
+        let ok = service.oneshot(request("/ok")).await.unwrap();
+
+        // Then: failures should be problem responses carrying their context
+        assert_eq!(failed.status(), StatusCode::GATEWAY_TIMEOUT);
+        let context = failed.extensions().get::<ErrorContext>().unwrap();
+        assert_eq!(context.operation, "GET /fail");
+        let problem: serde_json::Value = serde_json::from_str(failed.body()).unwrap();
+        assert_eq!(problem["error_id"], context.error_id.to_string());
+        assert_eq!(
+            failed.headers()[RETRY_AFTER],
+            TylError::timeout("Upstream too slow")
+                .retry_after_header()
+                .unwrap()
+        );
+
+        assert_eq!(failed.headers()[RETRY_SCOPE_HEADER], "caller");
+
+        assert_eq!(panicked.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let problem: serde_json::Value = serde_json::from_str(panicked.body()).unwrap();
+        assert_eq!(problem["detail"], crate::problem::SERVER_ERROR_DETAIL);
+        assert!(!panicked.body().contains("index out of bounds"));
+        assert_eq!(ok.body(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_operation_name_extension_should_name_the_context() {
+        let service = TylCatchLayer::new().layer(service_fn(|_req: Request<String>| async {
+            Err::<Response<String>, _>(TylError::conflict("Duplicate"))
+        }));
+        let mut req = request("/users");
+        req.extensions_mut()
+            .insert(OperationName::from_static("api::users::create"));
+        req.headers_mut()
+            .insert("Idempotency-Key", HeaderValue::from_static("signup-7"));
+
+        let response = service.oneshot(req).await.unwrap();
+
+        let context = response.extensions().get::<ErrorContext>().unwrap();
+        assert_eq!(context.operation, "api::users::create");
+        assert_eq!(context.idempotency_key.as_deref(), Some("signup-7"));
+    }
+}
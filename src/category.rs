@@ -3,9 +3,73 @@
 //! This module provides the extensible error category system that allows both
 //! built-in error classifications and custom user-defined categories.
 
+use crate::classifier_registry::ClassifierRegistry;
+use crate::code_registry::CodeRegistry;
+use crate::domain::TylDomain;
+use crate::error::{TylError, TylSeverity};
+use crate::escalation::EscalatingCategory;
+use crate::retry::{RetryPolicy, RetrySchedule};
+use crate::settings::ErrorSettings;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Who may retry a retriable error.
+///
+/// Some failures are only worth retrying close to where they happened: a
+/// connection reset by a sidecar is retried by the mesh or the service's own
+/// [`RetryPolicy`] with [`RetryScope::Infrastructure`], while callers
+/// retrying too would multiply the load. Scopes order from the narrowest
+/// (`None`) to the widest (`Caller`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryScope {
+    /// The error must not be retried.
+    None,
+    /// Only infrastructure (retry queues, meshes, infrastructure retry
+    /// policies) may retry the error.
+    Infrastructure,
+    /// Anyone may retry the error, including the caller of the service.
+    Caller,
+}
+
+impl RetryScope {
+    /// Get the lowercase name of the scope.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryScope::None => "none",
+            RetryScope::Infrastructure => "infrastructure",
+            RetryScope::Caller => "caller",
+        }
+    }
+
+    /// Parse a scope from its [`as_str`](Self::as_str) name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(RetryScope::None),
+            "infrastructure" => Some(RetryScope::Infrastructure),
+            "caller" => Some(RetryScope::Caller),
+            _ => None,
+        }
+    }
+
+    /// Check if a retrier acting at `retrier` scope may retry errors of this scope.
+    pub fn allows(&self, retrier: RetryScope) -> bool {
+        match self {
+            RetryScope::None => false,
+            RetryScope::Infrastructure => retrier == RetryScope::Infrastructure,
+            RetryScope::Caller => retrier != RetryScope::None,
+        }
+    }
+}
+
+impl fmt::Display for RetryScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Trait for defining custom error classification behavior.
 ///
 /// This trait allows users to define domain-specific error categories
@@ -22,6 +86,111 @@ pub trait ErrorClassifier: std::fmt::Debug + Send + Sync {
 
     /// Clone this error classifier (needed for ErrorCategory cloning).
     fn clone_box(&self) -> Box<dyn ErrorClassifier>;
+
+    /// Determine if a specific error should trigger retries.
+    ///
+    /// Override to base the decision on the error payload (message, structured
+    /// fields, metadata). Defaults to [`ErrorClassifier::is_retriable`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{ErrorClassifier, TylError};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct UpstreamHttp;
+    ///
+    /// impl ErrorClassifier for UpstreamHttp {
+    ///     fn is_retriable(&self) -> bool { true }
+    ///     fn retry_delay(&self, attempt: usize) -> Duration {
+    ///         Duration::from_millis(200 * attempt as u64)
+    ///     }
+    ///     fn category_name(&self) -> &'static str { "UpstreamHttp" }
+    ///     fn clone_box(&self) -> Box<dyn ErrorClassifier> { Box::new(self.clone()) }
+    ///
+    ///     // Only 503 responses are worth retrying.
+    ///     fn is_retriable_for(&self, error: &TylError) -> bool {
+    ///         error.to_string().contains("503")
+    ///     }
+    /// }
+    ///
+    /// let unavailable = TylError::business_logic("upstream returned 503", Box::new(UpstreamHttp));
+    /// let failure = TylError::business_logic("upstream returned 500", Box::new(UpstreamHttp));
+    /// assert!(unavailable.is_retriable());
+    /// assert!(!failure.is_retriable());
+    /// ```
+    fn is_retriable_for(&self, error: &TylError) -> bool {
+        let _ = error;
+        self.is_retriable()
+    }
+
+    /// Calculate the retry delay for a specific error and attempt number.
+    ///
+    /// Defaults to [`ErrorClassifier::retry_delay`].
+    fn retry_delay_for(&self, error: &TylError, attempt: usize) -> Duration {
+        let _ = error;
+        self.retry_delay(attempt)
+    }
+
+    /// Determine if an error is known to have happened before the operation
+    /// had any side effect (e.g. the connection was never established).
+    ///
+    /// Retry executors only retry non-idempotent operations for such errors.
+    /// Defaults to whether the error was marked with
+    /// [`TylError::with_before_side_effects`] where it was created.
+    fn failed_before_side_effects(&self, error: &TylError) -> bool {
+        error.failed_before_side_effects()
+    }
+
+    /// Maximum number of attempts at errors of this category, the first one
+    /// included: `Some(1)` disables retries.
+    ///
+    /// Retry executors, [`RetryQueue`](crate::RetryQueue) and
+    /// [`TylError::should_retry`] stop at the lower of this limit and their
+    /// own. Defaults to `None` (no category limit).
+    fn max_attempts(&self) -> Option<usize> {
+        None
+    }
+
+    /// Override the severity of a specific error.
+    ///
+    /// Used by [`TylError::severity`] in place of the variant's severity when
+    /// set. Defaults to `None`.
+    fn severity_for(&self, error: &TylError) -> Option<TylSeverity> {
+        let _ = error;
+        None
+    }
+
+    /// Restrict who may retry a specific retriable error.
+    ///
+    /// Used by [`TylError::retry_scope`]; `None` (the default) lets callers
+    /// retry it.
+    fn retry_scope_for(&self, error: &TylError) -> Option<RetryScope> {
+        let _ = error;
+        None
+    }
+
+    /// Team owning errors of this category, used for on-call routing.
+    ///
+    /// See [`TylError::owner`]. Defaults to `None`.
+    fn owner(&self) -> Option<&str> {
+        None
+    }
+
+    /// Domain errors of this category belong to.
+    ///
+    /// See [`TylError::domain`]. Defaults to `None`.
+    fn domain(&self) -> Option<&TylDomain> {
+        None
+    }
+
+    /// Code of custom errors of this category, in place of `custom`.
+    ///
+    /// See [`TylError::code`] and [`ErrorCategory::with_code`]. Defaults to
+    /// `None`.
+    fn code(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 impl Clone for Box<dyn ErrorClassifier> {
@@ -30,6 +199,52 @@ impl Clone for Box<dyn ErrorClassifier> {
     }
 }
 
+/// Maximum number of distinct custom category names restored from the wire.
+const MAX_RESTORED_CATEGORY_NAMES: usize = 1024;
+
+/// Intern a category name received from another process.
+///
+/// Names are leaked to satisfy `category_name(&self) -> &'static str`; the
+/// number of distinct names is capped to bound memory usage.
+fn intern_category_name(name: &str) -> Option<&'static str> {
+    use std::collections::HashSet;
+    use std::sync::{Mutex, OnceLock};
+
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if let Some(existing) = names.get(name) {
+        return Some(existing);
+    }
+    if names.len() >= MAX_RESTORED_CATEGORY_NAMES {
+        return None;
+    }
+    let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(leaked);
+    Some(leaked)
+}
+
+/// Rebuild a category from the name and retriability recorded on the wire,
+/// through the global [`ClassifierRegistry::restore`].
+pub(crate) fn restore_category(name: &str, retriable: bool) -> ErrorCategory {
+    ClassifierRegistry::global().restore(name, None, retriable)
+}
+
+/// Rebuild a category that is neither built-in nor registered: a classifier
+/// with that name, the given retriability and the default delays; if too
+/// many distinct names were restored already, `Unknown` is returned.
+pub(crate) fn restore_unregistered(name: &str, retriable: bool) -> ErrorCategory {
+    match intern_category_name(name) {
+        Some(name) => ErrorCategory::Custom(Box::new(FnClassifier::new(name, retriable, |a| {
+            BuiltinCategory::Unknown.retry_delay(a)
+        }))),
+        None => ErrorCategory::unknown(),
+    }
+}
+
 /// Default classifier for deserialization fallback.
 pub fn default_classifier() -> Box<dyn ErrorClassifier> {
     Box::new(BuiltinCategory::Unknown)
@@ -58,6 +273,28 @@ pub enum BuiltinCategory {
     Unknown,
 }
 
+impl BuiltinCategory {
+    /// All built-in categories, in declaration order.
+    pub const ALL: [BuiltinCategory; 9] = [
+        BuiltinCategory::Transient,
+        BuiltinCategory::Permanent,
+        BuiltinCategory::ResourceExhaustion,
+        BuiltinCategory::Network,
+        BuiltinCategory::Authentication,
+        BuiltinCategory::Validation,
+        BuiltinCategory::Internal,
+        BuiltinCategory::ServiceUnavailable,
+        BuiltinCategory::Unknown,
+    ];
+
+    /// Look up a built-in category by its `category_name()`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|builtin| builtin.category_name() == name)
+    }
+}
+
 impl ErrorClassifier for BuiltinCategory {
     fn is_retriable(&self) -> bool {
         matches!(
@@ -69,17 +306,10 @@ impl ErrorClassifier for BuiltinCategory {
         )
     }
 
+    /// Delays follow [`RetryPolicy::for_category`](crate::RetryPolicy::for_category)
+    /// without jitter: the base delay doubled per attempt, capped at 60 times it.
     fn retry_delay(&self, attempt: usize) -> Duration {
-        let base_delay = match self {
-            BuiltinCategory::Transient => Duration::from_millis(100),
-            BuiltinCategory::Network => Duration::from_millis(500),
-            BuiltinCategory::ServiceUnavailable => Duration::from_secs(1),
-            BuiltinCategory::ResourceExhaustion => Duration::from_secs(5),
-            _ => Duration::from_millis(100),
-        };
-
-        let multiplier = 2_u32.pow(attempt.min(10) as u32);
-        base_delay * multiplier.min(60)
+        RetryPolicy::for_builtin(self).backoff_delay(attempt + 1)
     }
 
     fn category_name(&self) -> &'static str {
@@ -101,9 +331,226 @@ impl ErrorClassifier for BuiltinCategory {
     }
 }
 
+/// Error classifier built from a name, a retriability flag and a delay closure.
+///
+/// Created through [`ErrorCategory::custom_fn`] for small domain-specific
+/// categories that do not warrant a dedicated struct and trait implementation.
+#[derive(Clone)]
+pub struct FnClassifier {
+    name: &'static str,
+    retriable: bool,
+    delay: Arc<dyn Fn(usize) -> Duration + Send + Sync>,
+}
+
+impl FnClassifier {
+    /// Create a classifier from its name, retriability and delay function.
+    pub fn new<F>(name: &'static str, retriable: bool, delay: F) -> Self
+    where
+        F: Fn(usize) -> Duration + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            retriable,
+            delay: Arc::new(delay),
+        }
+    }
+}
+
+impl fmt::Debug for FnClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnClassifier")
+            .field("name", &self.name)
+            .field("retriable", &self.retriable)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ErrorClassifier for FnClassifier {
+    fn is_retriable(&self) -> bool {
+        self.retriable
+    }
+
+    fn retry_delay(&self, attempt: usize) -> Duration {
+        (self.delay)(attempt)
+    }
+
+    fn category_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn clone_box(&self) -> Box<dyn ErrorClassifier> {
+        Box::new(self.clone())
+    }
+}
+
+/// How a [`ComposedClassifier`] adjusts the classifier it wraps.
+#[derive(Debug, Clone)]
+enum Combinator {
+    /// Both classifiers must agree to retry.
+    AndThen(Box<dyn ErrorClassifier>),
+    /// At most this many attempts.
+    MaxAttempts(usize),
+    /// Delays capped at this duration.
+    DelayCap(Duration),
+    /// Retries restricted to this scope.
+    RetryScope(RetryScope),
+    /// Errors owned by this team.
+    Owner(String),
+    /// Errors belonging to this domain.
+    Domain(TylDomain),
+    /// Custom errors with this code.
+    Code(&'static str),
+}
+
+/// Classifier wrapping another one with a [`Combinator`].
+///
+/// Created by the combinator methods of [`ErrorCategory`]; reports the name
+/// of the wrapped classifier.
+#[derive(Debug, Clone)]
+struct ComposedClassifier {
+    inner: Box<dyn ErrorClassifier>,
+    combinator: Combinator,
+}
+
+impl ErrorClassifier for ComposedClassifier {
+    fn is_retriable(&self) -> bool {
+        match &self.combinator {
+            Combinator::AndThen(next) => self.inner.is_retriable() && next.is_retriable(),
+            _ => self.inner.is_retriable(),
+        }
+    }
+
+    fn retry_delay(&self, attempt: usize) -> Duration {
+        let delay = self.inner.retry_delay(attempt);
+        match &self.combinator {
+            Combinator::AndThen(next) => delay.max(next.retry_delay(attempt)),
+            Combinator::DelayCap(cap) => delay.min(*cap),
+            _ => delay,
+        }
+    }
+
+    fn category_name(&self) -> &'static str {
+        self.inner.category_name()
+    }
+
+    fn clone_box(&self) -> Box<dyn ErrorClassifier> {
+        Box::new(self.clone())
+    }
+
+    fn is_retriable_for(&self, error: &TylError) -> bool {
+        let retriable = self.inner.is_retriable_for(error);
+        match &self.combinator {
+            Combinator::AndThen(next) => retriable && next.is_retriable_for(error),
+            _ => retriable,
+        }
+    }
+
+    fn retry_delay_for(&self, error: &TylError, attempt: usize) -> Duration {
+        let delay = self.inner.retry_delay_for(error, attempt);
+        match &self.combinator {
+            Combinator::AndThen(next) => delay.max(next.retry_delay_for(error, attempt)),
+            Combinator::DelayCap(cap) => delay.min(*cap),
+            _ => delay,
+        }
+    }
+
+    fn failed_before_side_effects(&self, error: &TylError) -> bool {
+        let before = self.inner.failed_before_side_effects(error);
+        match &self.combinator {
+            Combinator::AndThen(next) => before && next.failed_before_side_effects(error),
+            _ => before,
+        }
+    }
+
+    fn max_attempts(&self) -> Option<usize> {
+        let inner = self.inner.max_attempts();
+        let own = match &self.combinator {
+            Combinator::AndThen(next) => next.max_attempts(),
+            Combinator::MaxAttempts(attempts) => Some(*attempts),
+            _ => None,
+        };
+        match (inner, own) {
+            (Some(inner), Some(own)) => Some(inner.min(own)),
+            (inner, own) => inner.or(own),
+        }
+    }
+
+    fn severity_for(&self, error: &TylError) -> Option<TylSeverity> {
+        let inner = self.inner.severity_for(error);
+        match &self.combinator {
+            Combinator::AndThen(next) => inner.max(next.severity_for(error)),
+            _ => inner,
+        }
+    }
+
+    fn retry_scope_for(&self, error: &TylError) -> Option<RetryScope> {
+        let inner = self.inner.retry_scope_for(error);
+        let own = match &self.combinator {
+            Combinator::AndThen(next) => next.retry_scope_for(error),
+            Combinator::RetryScope(scope) => Some(*scope),
+            _ => None,
+        };
+        match (inner, own) {
+            (Some(inner), Some(own)) => Some(inner.min(own)),
+            (inner, own) => inner.or(own),
+        }
+    }
+
+    fn owner(&self) -> Option<&str> {
+        match &self.combinator {
+            Combinator::Owner(owner) => Some(owner),
+            Combinator::AndThen(next) => self.inner.owner().or_else(|| next.owner()),
+            _ => self.inner.owner(),
+        }
+    }
+
+    fn domain(&self) -> Option<&TylDomain> {
+        match &self.combinator {
+            Combinator::Domain(domain) => Some(domain),
+            Combinator::AndThen(next) => self.inner.domain().or_else(|| next.domain()),
+            _ => self.inner.domain(),
+        }
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        match &self.combinator {
+            Combinator::Code(code) => Some(code),
+            Combinator::AndThen(next) => self.inner.code().or_else(|| next.code()),
+            _ => self.inner.code(),
+        }
+    }
+}
+
 /// Extensible error category system.
 ///
 /// Supports both built-in categories and custom user-defined categories.
+///
+/// Categories compare, hash and order by [`category_name`](Self::category_name)
+/// only, for assertions and sorting. Custom classifiers are equal when their
+/// names are, whatever their retry behavior: combinators such as
+/// [`with_max_attempts`](Self::with_max_attempts) keep the inner name, so
+/// `network().with_max_attempts(1) == network()`, and a custom classifier
+/// named like a built-in category (e.g. a boxed [`BuiltinCategory`]) equals
+/// that category. Do not key maps by categories whose retry behavior
+/// matters: key them by name instead. Built-in categories order first, in
+/// declaration order, followed by custom ones by name.
+///
+/// Built-in categories serialize as their name (e.g. `"Network"`). Custom
+/// categories serialize as `{"name": ..., "tag": ...}`, with the tag they were
+/// registered under in the [`ClassifierRegistry`](crate::ClassifierRegistry)
+/// and their retriability, and deserialize into the registered category;
+/// unregistered ones keep their name and retriability.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{ErrorCategory, TylError};
+///
+/// assert_eq!(TylError::database("Deadlock").category(), ErrorCategory::transient());
+/// assert_eq!(ErrorCategory::network().with_max_attempts(1), ErrorCategory::network());
+/// let rate_limited = ErrorCategory::custom_fn("RateLimited", true, |_| Duration::ZERO);
+/// assert!(ErrorCategory::transient() < rate_limited);
+/// ```
 #[derive(Debug, Clone)]
 pub enum ErrorCategory {
     /// Built-in error categories with predefined behavior.
@@ -160,6 +607,160 @@ impl ErrorCategory {
         Self::Builtin(BuiltinCategory::Unknown)
     }
 
+    // === Custom Category Constructors ===
+
+    /// Create a custom category from closures instead of a dedicated classifier type.
+    ///
+    /// # Arguments
+    /// * `name` - Category name reported by `category_name()`
+    /// * `retriable` - Whether errors in this category should be retried
+    /// * `delay` - Retry delay for a given attempt number
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{ErrorCategory, TylError};
+    ///
+    /// let payment = ErrorCategory::custom_fn("Payment", true, |attempt| {
+    ///     Duration::from_secs(attempt as u64 * 2)
+    /// });
+    /// assert_eq!(payment.retry_delay(2), Duration::from_secs(4));
+    ///
+    /// let error = TylError::business_logic("Card declined", payment.into_classifier());
+    /// assert_eq!(error.category().category_name(), "Payment");
+    /// ```
+    pub fn custom_fn<F>(name: &'static str, retriable: bool, delay: F) -> Self
+    where
+        F: Fn(usize) -> Duration + Send + Sync + 'static,
+    {
+        Self::Custom(Box::new(FnClassifier::new(name, retriable, delay)))
+    }
+
+    /// Convert this category into a boxed classifier, e.g. for [`TylError::business_logic`](crate::TylError::business_logic).
+    pub fn into_classifier(self) -> Box<dyn ErrorClassifier> {
+        match self {
+            ErrorCategory::Builtin(builtin) => Box::new(builtin),
+            ErrorCategory::Custom(custom) => custom,
+        }
+    }
+
+    // === Combinators ===
+
+    /// Wrap this category with a combinator, keeping its name.
+    fn compose(self, combinator: Combinator) -> Self {
+        Self::Custom(Box::new(ComposedClassifier {
+            inner: self.into_classifier(),
+            combinator,
+        }))
+    }
+
+    /// Retry only errors both this category and `next` consider retriable.
+    ///
+    /// Delays are the longer of the two, and retry limits the lower.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::ErrorCategory;
+    ///
+    /// let billing = ErrorCategory::network()
+    ///     .and_then(ErrorCategory::custom_fn("Billing", true, |_| Duration::from_secs(5)))
+    ///     .with_delay_cap(Duration::from_secs(2))
+    ///     .with_max_attempts(2);
+    ///
+    /// assert_eq!(billing.category_name(), "Network");
+    /// assert_eq!(billing.retry_delay(1), Duration::from_secs(2));
+    /// assert_eq!(billing.max_attempts(), Some(2));
+    /// ```
+    pub fn and_then(self, next: ErrorCategory) -> Self {
+        self.compose(Combinator::AndThen(next.into_classifier()))
+    }
+
+    /// Make at most `max_attempts` attempts at errors of this category, the
+    /// first one included.
+    pub fn with_max_attempts(self, max_attempts: usize) -> Self {
+        self.compose(Combinator::MaxAttempts(max_attempts))
+    }
+
+    /// Cap the retry delays of this category.
+    pub fn with_delay_cap(self, cap: Duration) -> Self {
+        self.compose(Combinator::DelayCap(cap))
+    }
+
+    /// Restrict who may retry errors of this category.
+    ///
+    /// Combined scopes keep the narrowest one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, RetryScope, TylError};
+    ///
+    /// let sidecar = ErrorCategory::network().with_retry_scope(RetryScope::Infrastructure);
+    /// let error = TylError::business_logic("Sidecar reset the connection", sidecar.into_classifier());
+    /// assert!(error.is_retriable());
+    /// assert_eq!(error.retry_scope(), RetryScope::Infrastructure);
+    /// assert_eq!(TylError::network("Connection reset").retry_scope(), RetryScope::Caller);
+    /// ```
+    pub fn with_retry_scope(self, scope: RetryScope) -> Self {
+        self.compose(Combinator::RetryScope(scope))
+    }
+
+    /// Attribute errors of this category to the team `owner`, replacing the
+    /// owner set before.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, TylError};
+    ///
+    /// let declined = ErrorCategory::permanent().with_owner("payments");
+    /// let error = TylError::business_logic("Card declined", declined.into_classifier());
+    /// assert_eq!(error.owner().as_deref(), Some("payments"));
+    /// assert_eq!(error.to_context("checkout".into()).owner.as_deref(), Some("payments"));
+    /// ```
+    pub fn with_owner<O: Into<String>>(self, owner: O) -> Self {
+        self.compose(Combinator::Owner(owner.into()))
+    }
+
+    /// Attach errors of this category to `domain`, replacing the domain set
+    /// before.
+    ///
+    /// See [`TylError::domain`].
+    pub fn with_domain(self, domain: TylDomain) -> Self {
+        self.compose(Combinator::Domain(domain))
+    }
+
+    /// Give custom errors of this category the code `code` instead of
+    /// `custom`, claiming it for `module` in the global
+    /// [`CodeRegistry`](crate::CodeRegistry).
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{CodeRegistry, ErrorCategory, TylError};
+    ///
+    /// let declined = ErrorCategory::permanent().with_code("billing", "payment_declined");
+    /// let error = TylError::business_logic("Card declined", declined.into_classifier());
+    ///
+    /// assert_eq!(error.code(), "payment_declined");
+    /// assert!(CodeRegistry::global()
+    ///     .registrations()
+    ///     .iter()
+    ///     .any(|r| r.module == "billing" && r.code == "payment_declined"));
+    /// ```
+    pub fn with_code(self, module: &str, code: &'static str) -> Self {
+        CodeRegistry::global().register_code(module, code);
+        self.compose(Combinator::Code(code))
+    }
+
+    /// Retry errors of this category for `attempts` attempts, then treat them as permanent.
+    ///
+    /// Unlike [`with_max_attempts`](Self::with_max_attempts), the final error,
+    /// carrying its [retry telemetry](TylError::retry_telemetry), is no
+    /// longer retriable, so later layers (queues, callers) do not retry it
+    /// again, and its severity is raised. See [`EscalatingCategory`](crate::EscalatingCategory).
+    pub fn escalate_after(self, attempts: usize) -> Self {
+        EscalatingCategory::new(self, attempts).into()
+    }
+
     // === Delegation Methods ===
 
     /// Check if this error category supports retries.
@@ -171,13 +772,137 @@ impl ErrorCategory {
     }
 
     /// Calculate the suggested retry delay for this category and attempt number.
+    ///
+    /// Never shorter than `TYL_ERROR_MIN_DELAY`.
     pub fn retry_delay(&self, attempt: usize) -> Duration {
-        match self {
+        let delay = match self {
             ErrorCategory::Builtin(builtin) => builtin.retry_delay(attempt),
             ErrorCategory::Custom(custom) => custom.retry_delay(attempt),
+        };
+        ErrorSettings::global().clamp_delay(delay)
+    }
+
+    /// Preview the suggested delays before the next `retries` retries.
+    ///
+    /// The category's [`max_attempts`](Self::max_attempts) is not applied.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::ErrorCategory;
+    ///
+    /// let schedule = ErrorCategory::network().with_delay_cap(Duration::from_secs(1)).schedule(5);
+    /// assert_eq!(schedule.len(), 5);
+    /// assert!(schedule.delays.iter().all(|delay| *delay <= Duration::from_secs(1)));
+    /// ```
+    pub fn schedule(&self, retries: usize) -> RetrySchedule {
+        RetrySchedule {
+            delays: (1..=retries)
+                .map(|attempt| self.retry_delay(attempt))
+                .collect(),
+        }
+    }
+
+    /// Check if a specific error in this category supports retries.
+    ///
+    /// Lets custom classifiers inspect the error payload; builtin categories
+    /// ignore it.
+    pub fn is_retriable_for(&self, error: &TylError) -> bool {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.is_retriable_for(error),
+            ErrorCategory::Custom(custom) => custom.is_retriable_for(error),
+        }
+    }
+
+    /// Check if an error of this category happened before any side effect.
+    ///
+    /// See [`ErrorClassifier::failed_before_side_effects`].
+    pub fn failed_before_side_effects(&self, error: &TylError) -> bool {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.failed_before_side_effects(error),
+            ErrorCategory::Custom(custom) => custom.failed_before_side_effects(error),
+        }
+    }
+
+    /// Get the maximum number of attempts at errors of this category, the
+    /// first one included, if limited.
+    ///
+    /// See [`ErrorClassifier::max_attempts`].
+    pub fn max_attempts(&self) -> Option<usize> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.max_attempts(),
+            ErrorCategory::Custom(custom) => custom.max_attempts(),
+        }
+    }
+
+    /// Get the maximum number of retries of this category, if limited: one
+    /// less than its [`max_attempts`](Self::max_attempts).
+    pub(crate) fn max_retries(&self) -> Option<usize> {
+        self.max_attempts()
+            .map(|attempts| attempts.saturating_sub(1))
+    }
+
+    /// Get the severity this category assigns to a specific error, if any.
+    ///
+    /// See [`ErrorClassifier::severity_for`].
+    pub fn severity_for(&self, error: &TylError) -> Option<TylSeverity> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.severity_for(error),
+            ErrorCategory::Custom(custom) => custom.severity_for(error),
+        }
+    }
+
+    /// Get who may retry a specific error of this category, if restricted.
+    ///
+    /// See [`ErrorClassifier::retry_scope_for`].
+    pub fn retry_scope_for(&self, error: &TylError) -> Option<RetryScope> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.retry_scope_for(error),
+            ErrorCategory::Custom(custom) => custom.retry_scope_for(error),
+        }
+    }
+
+    /// Get the team owning errors of this category, if set.
+    ///
+    /// See [`ErrorClassifier::owner`].
+    pub fn owner(&self) -> Option<&str> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.owner(),
+            ErrorCategory::Custom(custom) => custom.owner(),
+        }
+    }
+
+    /// Get the code of custom errors of this category, if set.
+    ///
+    /// See [`ErrorCategory::with_code`].
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.code(),
+            ErrorCategory::Custom(custom) => custom.code(),
         }
     }
 
+    /// Get the domain errors of this category belong to, if set.
+    ///
+    /// See [`ErrorClassifier::domain`].
+    pub fn domain(&self) -> Option<&TylDomain> {
+        match self {
+            ErrorCategory::Builtin(builtin) => builtin.domain(),
+            ErrorCategory::Custom(custom) => custom.domain(),
+        }
+    }
+
+    /// Calculate the retry delay for a specific error in this category.
+    ///
+    /// Never shorter than `TYL_ERROR_MIN_DELAY`.
+    pub fn retry_delay_for(&self, error: &TylError, attempt: usize) -> Duration {
+        let delay = match self {
+            ErrorCategory::Builtin(builtin) => builtin.retry_delay_for(error, attempt),
+            ErrorCategory::Custom(custom) => custom.retry_delay_for(error, attempt),
+        };
+        ErrorSettings::global().clamp_delay(delay)
+    }
+
     /// Get the human-readable name of this error category.
     pub fn category_name(&self) -> &str {
         match self {
@@ -185,4 +910,254 @@ impl ErrorCategory {
             ErrorCategory::Custom(custom) => custom.category_name(),
         }
     }
+
+    /// Key for ordering: built-in position (custom categories last), then name.
+    fn sort_key(&self) -> (usize, &str) {
+        let name = self.category_name();
+        let position = BuiltinCategory::ALL
+            .iter()
+            .position(|builtin| builtin.category_name() == name)
+            .unwrap_or(BuiltinCategory::ALL.len());
+        (position, name)
+    }
+}
+
+impl PartialEq for ErrorCategory {
+    fn eq(&self, other: &Self) -> bool {
+        self.category_name() == other.category_name()
+    }
+}
+
+impl Eq for ErrorCategory {}
+
+impl std::hash::Hash for ErrorCategory {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.category_name().hash(state);
+    }
+}
+
+/// Serialized form of an [`ErrorCategory`].
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CategoryRepr {
+    Builtin(String),
+    Custom {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        retriable: bool,
+    },
+}
+
+impl Serialize for ErrorCategory {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = self.category_name();
+        let repr = match BuiltinCategory::from_name(name) {
+            Some(_) => CategoryRepr::Builtin(name.to_string()),
+            None => CategoryRepr::Custom {
+                name: name.to_string(),
+                tag: ClassifierRegistry::global().tag_of(name),
+                retriable: self.is_retriable(),
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCategory {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (name, tag, retriable) = match CategoryRepr::deserialize(deserializer)? {
+            CategoryRepr::Builtin(name) => (name, None, false),
+            CategoryRepr::Custom {
+                name,
+                tag,
+                retriable,
+            } => (name, tag, retriable),
+        };
+        Ok(ClassifierRegistry::global().restore(&name, tag.as_deref(), retriable))
+    }
+}
+
+impl PartialOrd for ErrorCategory {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ErrorCategory {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_fn_should_delegate_to_closures() {
+        // Given: a category built from closures
+        let category = ErrorCategory::custom_fn("RateLimited", true, |attempt| {
+            Duration::from_millis(250 * attempt as u64)
+        });
+
+        // When/Then: it should behave like a hand-written classifier
+        assert!(category.is_retriable());
+        assert_eq!(category.category_name(), "RateLimited");
+        assert_eq!(category.retry_delay(4), Duration::from_secs(1));
+
+        // And: clones should share the same behavior
+        let cloned = category.clone();
+        assert_eq!(cloned.retry_delay(2), Duration::from_millis(500));
+        assert!(format!("{cloned:?}").contains("RateLimited"));
+    }
+
+    #[test]
+    fn test_combinators_should_limit_and_escalate_retries() {
+        use crate::retry::RetryPolicy;
+
+        // Given: a network category capped to two attempts and escalated after two retries
+        let capped = ErrorCategory::network().with_max_attempts(2);
+        let escalating = ErrorCategory::network().escalate_after(2);
+        let policy = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false);
+        let run = |category: &ErrorCategory| {
+            policy.execute(|| -> crate::TylResult<()> {
+                Err(TylError::business_logic(
+                    "flaky",
+                    category.clone().into_classifier(),
+                ))
+            })
+        };
+
+        // When: running always-failing operations
+        let capped_error = run(&capped).unwrap_err();
+        let escalated_error = run(&escalating).unwrap_err();
+
+        // Then: the category limits should stop retries early
+        assert_eq!(capped_error.retry_telemetry().unwrap().attempts, 2);
+        assert!(capped_error.is_retriable());
+        assert_eq!(escalated_error.retry_telemetry().unwrap().attempts, 3);
+        assert!(!escalated_error.is_retriable());
+        assert!(!TylError::business_logic("flaky", capped.into_classifier()).should_retry(1));
+        let single = ErrorCategory::network().with_max_attempts(1);
+        assert!(run(&single).unwrap_err().retry_telemetry().is_none());
+
+        // And: and_then should require both classifiers to retry
+        let vetoed = ErrorCategory::network().and_then(ErrorCategory::permanent());
+        assert!(!vetoed.is_retriable());
+        assert_eq!(vetoed.category_name(), "Network");
+    }
+
+    #[test]
+    fn test_categories_should_compare_by_name() {
+        // Given: builtin categories and custom ones reusing or adding names
+        let network = ErrorCategory::network();
+        let capped = ErrorCategory::network().with_max_attempts(1);
+        let boxed = ErrorCategory::Custom(Box::new(BuiltinCategory::Network));
+        let custom = ErrorCategory::custom_fn("Flaky", true, |_| Duration::ZERO);
+
+        // When/Then: equality and hashing should follow the category name only
+        let hash = |category: &ErrorCategory| {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            category.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(network, capped);
+        assert_eq!(network, boxed);
+        assert_ne!(network, ErrorCategory::transient());
+        assert_eq!(hash(&network), hash(&capped));
+        assert_eq!(hash(&network), hash(&boxed));
+
+        // And: builtins should sort in declaration order before custom ones
+        let mut sorted = vec![custom.clone(), network.clone(), ErrorCategory::transient()];
+        sorted.sort();
+        assert_eq!(sorted, [ErrorCategory::transient(), network, custom]);
+    }
+
+    #[test]
+    fn test_serde_should_restore_builtin_and_registered_categories() {
+        // Given: a builtin category and a registered custom one
+        let custom = ErrorCategory::custom_fn("CategorySerdeTest", true, |_| Duration::ZERO);
+        ClassifierRegistry::global().register("category.serde_test", custom.clone());
+
+        // When/Then: builtins should round-trip through their name
+        let json = serde_json::to_string(&ErrorCategory::network()).unwrap();
+        assert_eq!(json, r#""Network""#);
+        let restored: ErrorCategory = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            restored,
+            ErrorCategory::Builtin(BuiltinCategory::Network)
+        ));
+
+        // And: registered custom categories should round-trip through their tag
+        let restored: ErrorCategory =
+            serde_json::from_value(serde_json::to_value(&custom).unwrap()).unwrap();
+        assert_eq!(restored, custom);
+        assert!(restored.is_retriable());
+
+        // And: unregistered names should keep their name and retriability
+        for (json, retriable) in [
+            (r#""Flaky""#, false),
+            (
+                r#"{"name":"Flaky","tag":"unregistered","retriable":true}"#,
+                true,
+            ),
+        ] {
+            let restored: ErrorCategory = serde_json::from_str(json).unwrap();
+            assert_eq!(restored.category_name(), "Flaky");
+            assert_eq!(restored.is_retriable(), retriable);
+        }
+    }
+
+    #[test]
+    fn test_into_classifier_should_preserve_builtin_behavior() {
+        // Given: a builtin category
+        let classifier = ErrorCategory::network().into_classifier();
+
+        // When/Then: the boxed classifier should keep the builtin semantics
+        assert!(classifier.is_retriable());
+        assert_eq!(classifier.category_name(), "Network");
+    }
+
+    #[test]
+    fn test_payload_aware_classifier_should_inspect_error() {
+        // Given: a classifier that only retries errors mentioning a lock timeout
+        #[derive(Debug, Clone)]
+        struct LockAware;
+
+        impl ErrorClassifier for LockAware {
+            fn is_retriable(&self) -> bool {
+                false
+            }
+            fn retry_delay(&self, _attempt: usize) -> Duration {
+                Duration::from_millis(10)
+            }
+            fn category_name(&self) -> &'static str {
+                "LockAware"
+            }
+            fn clone_box(&self) -> Box<dyn ErrorClassifier> {
+                Box::new(self.clone())
+            }
+            fn is_retriable_for(&self, error: &TylError) -> bool {
+                error.to_string().contains("lock timeout")
+            }
+        }
+
+        // When: classifying errors with different payloads
+        let lock = TylError::business_logic("lock timeout on orders", Box::new(LockAware));
+        let other = TylError::business_logic("syntax error", Box::new(LockAware));
+
+        // Then: only the matching payload should be retriable
+        assert!(lock.is_retriable());
+        assert!(!other.is_retriable());
+        assert!(!lock.category().is_retriable());
+        assert_eq!(
+            lock.category().retry_delay_for(&lock, 1),
+            Duration::from_millis(10)
+        );
+    }
 }
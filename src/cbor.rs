@@ -0,0 +1,478 @@
+//! Compact CBOR encoding for constrained transports.
+//!
+//! This module (enabled by the `cbor` feature) encodes [`TylError`] and
+//! [`ErrorContext`] as CBOR maps keyed by small integers for well-known fields,
+//! keeping payloads compact for IoT-facing services. Unknown fields fall back
+//! to text keys, and the category name and retriability are recorded so that
+//! custom categories survive the round-trip.
+
+use crate::baggage::limit_baggage;
+use crate::category::{restore_category, ErrorCategory};
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use crate::limits::{limit_message, limit_value};
+use crate::meta_key::MetaKey;
+use crate::settings::ErrorSettings;
+use chrono::{DateTime, Utc};
+use ciborium::Value;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+// Error map keys.
+const ERROR_VARIANT: i64 = 0;
+const ERROR_CATEGORY: i64 = 1;
+const ERROR_RETRIABLE: i64 = 2;
+
+/// Integer keys for well-known variant fields.
+const FIELD_KEYS: [(&str, i64); 15] = [
+    ("message", 10),
+    ("field", 11),
+    ("resource", 12),
+    ("id", 13),
+    ("feature", 14),
+    ("key", 15),
+    ("expected", 16),
+    ("actual", 17),
+    ("tracking_issue", 18),
+    ("planned_version", 19),
+    ("input", 20),
+    ("reason", 21),
+    ("subject", 22),
+    ("action", 23),
+    ("extras", 26),
+];
+
+// Context map keys.
+const CONTEXT_ERROR_ID: i64 = 0;
+const CONTEXT_OPERATION: i64 = 1;
+const CONTEXT_CATEGORY: i64 = 2;
+const CONTEXT_RETRIABLE: i64 = 3;
+const CONTEXT_MESSAGE: i64 = 4;
+const CONTEXT_OCCURRED_AT_MS: i64 = 5;
+const CONTEXT_ATTEMPT_COUNT: i64 = 6;
+const CONTEXT_METADATA: i64 = 7;
+const CONTEXT_SENSITIVE_KEYS: i64 = 8;
+const CONTEXT_IDEMPOTENCY_KEY: i64 = 9;
+const CONTEXT_TENANT_ID: i64 = 10;
+const CONTEXT_ORIGIN_SERVICE: i64 = 11;
+const CONTEXT_HOP_COUNT: i64 = 12;
+const CONTEXT_OWNER: i64 = 13;
+const CONTEXT_BAGGAGE: i64 = 14;
+
+impl TylError {
+    /// Encode this error as compact CBOR. Requires the `cbor` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, TylError};
+    ///
+    /// let category = ErrorCategory::custom_fn("Payment", true, |_| std::time::Duration::ZERO);
+    /// let error = TylError::business_logic("Card declined", category.into_classifier());
+    ///
+    /// let decoded = TylError::from_cbor(&error.to_cbor().unwrap()).unwrap();
+    /// assert_eq!(decoded.to_string(), error.to_string());
+    /// assert_eq!(decoded.category().category_name(), "Payment");
+    /// assert!(decoded.category().is_retriable());
+    /// ```
+    pub fn to_cbor(&self) -> TylResult<Vec<u8>> {
+        let json = serde_json::to_value(self)?;
+        let (variant, fields) = match json {
+            serde_json::Value::Object(object) if object.len() == 1 => {
+                object.into_iter().next().expect("one entry")
+            }
+            other => return Err(TylError::serialization(format!("unexpected shape {other}"))),
+        };
+
+        let category = self.category();
+        let mut entries = vec![
+            (int(ERROR_VARIANT), Value::Text(variant)),
+            (
+                int(ERROR_CATEGORY),
+                Value::Text(category.category_name().to_string()),
+            ),
+            (int(ERROR_RETRIABLE), Value::Bool(category.is_retriable())),
+        ];
+        if let serde_json::Value::Object(fields) = fields {
+            for (name, value) in fields {
+                let key = FIELD_KEYS
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map_or(Value::Text(name), |(_, key)| int(*key));
+                entries.push((key, to_cbor_value(&value)?));
+            }
+        }
+
+        encode(&Value::Map(entries))
+    }
+
+    /// Decode an error produced by [`TylError::to_cbor`]. Requires the `cbor` feature.
+    pub fn from_cbor(bytes: &[u8]) -> TylResult<Self> {
+        let mut variant = None;
+        let mut category = None;
+        let mut retriable = false;
+        let mut fields = serde_json::Map::new();
+
+        for (key, value) in decode_map(bytes)? {
+            match key {
+                Value::Integer(key) => match i64::try_from(key).unwrap_or(-1) {
+                    ERROR_VARIANT => variant = Some(text(value)?),
+                    ERROR_CATEGORY => category = Some(text(value)?),
+                    ERROR_RETRIABLE => retriable = value.as_bool().unwrap_or(false),
+                    key => {
+                        // Keys added by newer versions are ignored.
+                        if let Some((name, _)) = FIELD_KEYS.iter().find(|(_, known)| *known == key)
+                        {
+                            fields.insert(name.to_string(), from_cbor_value(value)?);
+                        }
+                    }
+                },
+                Value::Text(name) => {
+                    fields.insert(name, from_cbor_value(value)?);
+                }
+                _ => {
+                    return Err(TylError::parsing(
+                        "CBOR error keys must be integers or text",
+                    ))
+                }
+            }
+        }
+
+        let variant = variant.ok_or_else(|| TylError::parsing("CBOR error without variant"))?;
+        let mut error: TylError = serde_json::from_value(serde_json::json!({ variant: fields }))
+            .map_err(|err| TylError::parsing(format!("Invalid CBOR error payload: {err}")))?;
+
+        if let (TylError::Custom { classifier, .. }, Some(name)) = (&mut error, category) {
+            *classifier = restore_category(&name, retriable).into_classifier();
+        }
+        Ok(error)
+    }
+}
+
+impl ErrorContext {
+    /// Encode this context as compact CBOR. Requires the `cbor` feature.
+    pub fn to_cbor(&self) -> TylResult<Vec<u8>> {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(key, value)| Ok((Value::Text(key.to_string()), to_cbor_value(value)?)))
+            .collect::<TylResult<Vec<_>>>()?;
+
+        let mut entries = vec![
+            (
+                int(CONTEXT_ERROR_ID),
+                Value::Bytes(self.error_id.as_bytes().to_vec()),
+            ),
+            (int(CONTEXT_OPERATION), Value::Text(self.operation.clone())),
+            (
+                int(CONTEXT_CATEGORY),
+                Value::Text(self.category.category_name().to_string()),
+            ),
+            (
+                int(CONTEXT_RETRIABLE),
+                Value::Bool(self.category.is_retriable()),
+            ),
+            (int(CONTEXT_MESSAGE), Value::Text(self.message.clone())),
+            (
+                int(CONTEXT_OCCURRED_AT_MS),
+                int(self.occurred_at.timestamp_millis()),
+            ),
+            (
+                int(CONTEXT_ATTEMPT_COUNT),
+                Value::Integer((self.attempt_count as u64).into()),
+            ),
+            (int(CONTEXT_METADATA), Value::Map(metadata)),
+        ];
+        if !self.sensitive_keys.is_empty() {
+            let keys = self
+                .sensitive_keys
+                .iter()
+                .map(|key| Value::Text(key.to_string()));
+            entries.push((int(CONTEXT_SENSITIVE_KEYS), Value::Array(keys.collect())));
+        }
+        if let Some(key) = &self.idempotency_key {
+            entries.push((int(CONTEXT_IDEMPOTENCY_KEY), Value::Text(key.clone())));
+        }
+        if let Some(tenant_id) = &self.tenant_id {
+            entries.push((int(CONTEXT_TENANT_ID), Value::Text(tenant_id.clone())));
+        }
+        if let Some(service) = &self.origin_service {
+            entries.push((int(CONTEXT_ORIGIN_SERVICE), Value::Text(service.clone())));
+        }
+        if self.hop_count > 0 {
+            entries.push((int(CONTEXT_HOP_COUNT), int(self.hop_count.into())));
+        }
+        if let Some(owner) = &self.owner {
+            entries.push((int(CONTEXT_OWNER), Value::Text(owner.clone())));
+        }
+        if !self.baggage.is_empty() {
+            let baggage = self
+                .baggage
+                .iter()
+                .map(|(key, value)| (Value::Text(key.clone()), Value::Text(value.clone())));
+            entries.push((int(CONTEXT_BAGGAGE), Value::Map(baggage.collect())));
+        }
+
+        encode(&Value::Map(entries))
+    }
+
+    /// Decode a context produced by [`ErrorContext::to_cbor`]. Requires the `cbor` feature.
+    ///
+    /// Unlike JSON deserialization, the category is restored from its name.
+    /// Like it, the context is not [enriched](crate::enrich): it holds what
+    /// the sender recorded.
+    pub fn from_cbor(bytes: &[u8]) -> TylResult<Self> {
+        let mut context =
+            ErrorContext::new_unenriched(String::new(), ErrorCategory::unknown(), String::new());
+        let mut category_name = None;
+        let mut retriable = false;
+
+        for (key, value) in decode_map(bytes)? {
+            let key = key
+                .as_integer()
+                .and_then(|key| i64::try_from(key).ok())
+                .ok_or_else(|| TylError::parsing("CBOR context keys must be integers"))?;
+            match key {
+                CONTEXT_ERROR_ID => {
+                    let bytes = value
+                        .into_bytes()
+                        .map_err(|_| TylError::parsing("error_id must be a byte string"))?;
+                    context.error_id = Uuid::from_slice(&bytes)
+                        .map_err(|err| TylError::parsing(format!("Invalid error_id: {err}")))?;
+                }
+                CONTEXT_OPERATION => context.operation = text(value)?,
+                CONTEXT_CATEGORY => category_name = Some(text(value)?),
+                CONTEXT_RETRIABLE => retriable = value.as_bool().unwrap_or(false),
+                CONTEXT_MESSAGE => context.message = limit_message(text(value)?),
+                CONTEXT_OCCURRED_AT_MS => {
+                    let millis = integer(value)?;
+                    context.occurred_at = DateTime::<Utc>::from_timestamp_millis(millis)
+                        .ok_or_else(|| TylError::parsing("occurred_at out of range"))?;
+                }
+                CONTEXT_ATTEMPT_COUNT => context.attempt_count = integer(value)? as usize,
+                CONTEXT_METADATA => {
+                    let entries = value
+                        .into_map()
+                        .map_err(|_| TylError::parsing("metadata must be a map"))?;
+                    context.metadata = entries
+                        .into_iter()
+                        .map(|(key, value)| {
+                            Ok((text(key)?.into(), limit_value(from_cbor_value(value)?)))
+                        })
+                        .collect::<TylResult<HashMap<_, _>>>()?;
+                }
+                CONTEXT_SENSITIVE_KEYS => {
+                    let keys = value
+                        .into_array()
+                        .map_err(|_| TylError::parsing("sensitive keys must be an array"))?;
+                    context.sensitive_keys = keys
+                        .into_iter()
+                        .map(|key| text(key).map(MetaKey::from))
+                        .collect::<TylResult<HashSet<_>>>()?;
+                }
+                CONTEXT_IDEMPOTENCY_KEY => context.idempotency_key = Some(text(value)?),
+                CONTEXT_TENANT_ID => context.tenant_id = Some(text(value)?),
+                CONTEXT_ORIGIN_SERVICE => context.origin_service = Some(text(value)?),
+                CONTEXT_HOP_COUNT => {
+                    context.hop_count = u32::try_from(integer(value)?)
+                        .map_err(|_| TylError::parsing("hop_count out of range"))?;
+                }
+                CONTEXT_OWNER => context.owner = Some(text(value)?),
+                CONTEXT_BAGGAGE => {
+                    let entries = value
+                        .into_map()
+                        .map_err(|_| TylError::parsing("baggage must be a map"))?;
+                    let entries = entries
+                        .into_iter()
+                        .map(|(key, value)| Ok((text(key)?, text(value)?)))
+                        .collect::<TylResult<Vec<_>>>()?;
+                    context.baggage = limit_baggage(entries, &ErrorSettings::global());
+                }
+                _ => {} // Keys added by newer versions are ignored.
+            }
+        }
+
+        if let Some(name) = category_name {
+            context.category = restore_category(&name, retriable);
+        }
+        Ok(context)
+    }
+}
+
+fn int(value: i64) -> Value {
+    Value::Integer(value.into())
+}
+
+fn text(value: Value) -> TylResult<String> {
+    value
+        .into_text()
+        .map_err(|_| TylError::parsing("expected a CBOR text string"))
+}
+
+fn integer(value: Value) -> TylResult<i64> {
+    value
+        .as_integer()
+        .and_then(|value| i64::try_from(value).ok())
+        .ok_or_else(|| TylError::parsing("expected a CBOR integer"))
+}
+
+fn to_cbor_value(value: &serde_json::Value) -> TylResult<Value> {
+    Value::serialized(value).map_err(|err| TylError::serialization(err.to_string()))
+}
+
+fn from_cbor_value(value: Value) -> TylResult<serde_json::Value> {
+    value
+        .deserialized()
+        .map_err(|err| TylError::parsing(format!("Invalid CBOR value: {err}")))
+}
+
+fn encode(value: &Value) -> TylResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes)
+        .map_err(|err| TylError::serialization(format!("CBOR encoding failed: {err}")))?;
+    Ok(bytes)
+}
+
+fn decode_map(bytes: &[u8]) -> TylResult<Vec<(Value, Value)>> {
+    let value: Value = ciborium::de::from_reader(bytes)
+        .map_err(|err| TylError::parsing(format!("Invalid CBOR: {err}")))?;
+    value
+        .into_map()
+        .map_err(|_| TylError::parsing("expected a CBOR map"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_cbor_roundtrip_should_preserve_fields() {
+        // Given: errors with structured fields
+        let errors = [
+            TylError::not_found("user", "42"),
+            TylError::config_key("server.port", "integer", "\"http\""),
+            TylError::not_implemented_tracked("export", "https://example.com/1"),
+        ];
+
+        for error in errors {
+            // When: round-tripping through CBOR
+            let decoded = TylError::from_cbor(&error.to_cbor().unwrap()).unwrap();
+
+            // Then: display, code and category should match
+            assert_eq!(decoded.to_string(), error.to_string());
+            assert_eq!(decoded.code(), error.code());
+            assert_eq!(
+                decoded.category().category_name(),
+                error.category().category_name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde_should_decode_errors_from_binary_formats() {
+        // Given: a known and an unknown variant serialized with serde to CBOR,
+        // a format that is not human-readable
+        let known = TylError::validation("email", "Invalid").with_input("bob@");
+        let unknown = TylError::Unknown {
+            variant: "RateLimited".to_string(),
+            raw: serde_json::json!({"retry_after_ms": 1500}),
+        };
+        let serialize = |error: &TylError| {
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(error, &mut bytes).unwrap();
+            bytes
+        };
+
+        // When: deserializing them
+        let decoded: TylError = ciborium::de::from_reader(&serialize(&known)[..]).unwrap();
+        let restored: TylError = ciborium::de::from_reader(&serialize(&unknown)[..]).unwrap();
+
+        // Then: both should be restored without going through JSON
+        assert_eq!(decoded.to_string(), known.to_string());
+        assert_eq!(decoded.metadata()["input"], "bob@");
+        match restored {
+            TylError::Unknown { variant, raw } => {
+                assert_eq!(variant, "RateLimited");
+                assert_eq!(raw["retry_after_ms"], 1500);
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_integer_keys_should_be_ignored() {
+        // Given: an error encoded by a newer version with an extra integer key
+        let error = TylError::not_found("user", "42");
+        let mut entries = decode_map(&error.to_cbor().unwrap()).unwrap();
+        entries.push((int(99), Value::Text("from the future".to_string())));
+        let bytes = encode(&Value::Map(entries)).unwrap();
+
+        // When: decoding it
+        let decoded = TylError::from_cbor(&bytes).unwrap();
+
+        // Then: the known fields should be restored
+        assert_eq!(decoded.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn test_cbor_should_be_more_compact_than_json() {
+        let error = TylError::validation("email", "Must contain @ symbol");
+        let json = serde_json::to_vec(&error).unwrap();
+        assert!(error.to_cbor().unwrap().len() < json.len());
+    }
+
+    #[test]
+    fn test_context_cbor_roundtrip_should_restore_category() {
+        // Given: a context with metadata and a builtin category
+        let context = ErrorContext::new(
+            "sensor.upload".to_string(),
+            ErrorCategory::network(),
+            "Link down".to_string(),
+        )
+        .with_metadata("rssi".to_string(), serde_json::json!(-87))
+        .with_sensitive_metadata("device_key".to_string(), serde_json::json!("abc"))
+        .with_idempotency_key("upload-7")
+        .with_tenant_id("acme")
+        .with_origin_service("uploads")
+        .with_owner("edge-team")
+        .with_baggage("region", "eu-west-1");
+
+        // When: round-tripping through CBOR
+        let decoded = ErrorContext::from_cbor(&context.to_cbor().unwrap()).unwrap();
+
+        // Then: all fields including the category should survive
+        assert_eq!(decoded.error_id, context.error_id);
+        assert_eq!(decoded.operation, "sensor.upload");
+        assert_eq!(decoded.category.category_name(), "Network");
+        assert_eq!(
+            decoded.occurred_at.timestamp_millis(),
+            context.occurred_at.timestamp_millis()
+        );
+        assert_eq!(decoded.metadata["rssi"], serde_json::json!(-87));
+        assert!(decoded.is_sensitive("device_key"));
+        assert_eq!(decoded.idempotency_key.as_deref(), Some("upload-7"));
+        assert_eq!(decoded.tenant_id.as_deref(), Some("acme"));
+        assert_eq!(decoded.origin_service.as_deref(), Some("uploads"));
+        assert_eq!(decoded.owner.as_deref(), Some("edge-team"));
+        assert_eq!(decoded.baggage, context.baggage);
+    }
+
+    #[test]
+    fn test_decoded_contexts_should_not_be_enriched() {
+        // Given: a context without owner from another service, and a local
+        // enricher attributing new contexts
+        let remote = TylError::network("Reset").to_context("sensor.sync".to_string());
+        let bytes = remote.to_cbor().unwrap();
+        let enricher = crate::enrich::register(|context: &mut ErrorContext| {
+            if context.operation.is_empty() {
+                context.owner = Some("cbor-test-team".to_string());
+            }
+        });
+
+        // When: decoding it
+        let decoded = ErrorContext::from_cbor(&bytes);
+        crate::enrich::unregister(enricher);
+
+        // Then: it should keep what the sender recorded
+        assert_eq!(decoded.unwrap().owner, None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Registry of custom error categories restorable from their serialized form.
+//!
+//! Custom categories are code: their retry behavior cannot travel on the
+//! wire. This module provides [`ClassifierRegistry`], where services register
+//! their custom categories under a stable tag, so that a serialized
+//! [`ErrorCategory`] carrying that tag deserializes back into the same
+//! classifier (for config-driven overrides or errors propagated between
+//! services). Every wire format (serde, DTOs, CBOR, problem details and the
+//! retry queue) restores categories through [`ClassifierRegistry::restore`];
+//! unregistered custom categories keep their name and retriability.
+
+use crate::category::{restore_unregistered, BuiltinCategory, ErrorCategory};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Custom categories registered under stable tags.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{ClassifierRegistry, ErrorCategory};
+///
+/// let rate_limited = ErrorCategory::custom_fn("RateLimited", true, |attempt| {
+///     Duration::from_secs(attempt as u64)
+/// });
+/// ClassifierRegistry::global().register("billing.rate_limited", rate_limited.clone());
+///
+/// let json = serde_json::to_string(&rate_limited).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"name":"RateLimited","tag":"billing.rate_limited","retriable":true}"#
+/// );
+/// let restored: ErrorCategory = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored.retry_delay(3), Duration::from_secs(3));
+/// ```
+#[derive(Debug, Default)]
+pub struct ClassifierRegistry {
+    entries: RwLock<Entries>,
+    /// Whether anything was registered, read without locking.
+    populated: AtomicBool,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_tag: HashMap<String, ErrorCategory>,
+    /// Tags holding a category of each name.
+    tags_by_name: HashMap<String, BTreeSet<String>>,
+}
+
+impl ClassifierRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide registry, used by `ErrorCategory` serde.
+    pub fn global() -> &'static ClassifierRegistry {
+        static GLOBAL: OnceLock<ClassifierRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(ClassifierRegistry::new)
+    }
+
+    /// Register a category under `tag`, replacing the one registered before.
+    pub fn register<T: Into<String>>(&self, tag: T, category: ErrorCategory) {
+        let tag = tag.into();
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let name = category.category_name().to_string();
+        if let Some(previous) = entries.by_tag.insert(tag.clone(), category) {
+            let previous = previous.category_name();
+            if let Some(tags) = entries.tags_by_name.get_mut(previous) {
+                tags.remove(&tag);
+                if tags.is_empty() {
+                    entries.tags_by_name.remove(previous);
+                }
+            }
+        }
+        entries.tags_by_name.entry(name).or_default().insert(tag);
+        self.populated.store(true, Ordering::Release);
+    }
+
+    /// Get the category registered under `tag`.
+    pub fn get(&self, tag: &str) -> Option<ErrorCategory> {
+        if !self.populated.load(Ordering::Acquire) {
+            return None;
+        }
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .by_tag
+            .get(tag)
+            .cloned()
+    }
+
+    /// Get the tag a category with this name was registered under.
+    ///
+    /// If several tags hold categories with the same name, the smallest tag
+    /// is returned.
+    pub fn tag_of(&self, category_name: &str) -> Option<String> {
+        if !self.populated.load(Ordering::Acquire) {
+            return None;
+        }
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .tags_by_name
+            .get(category_name)?
+            .first()
+            .cloned()
+    }
+
+    /// Rebuild a category received on the wire.
+    ///
+    /// Built-in names restore the built-in category. Otherwise the category
+    /// registered under `tag` is used, then the one registered under the
+    /// smallest tag holding a category named `name`. Unregistered names
+    /// become a classifier with that name, the given retriability and the
+    /// default delays.
+    pub fn restore(&self, name: &str, tag: Option<&str>, retriable: bool) -> ErrorCategory {
+        if let Some(builtin) = BuiltinCategory::from_name(name) {
+            return ErrorCategory::Builtin(builtin);
+        }
+        if self.populated.load(Ordering::Acquire) {
+            let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+            let registered = tag.and_then(|tag| entries.by_tag.get(tag)).or_else(|| {
+                let tag = entries.tags_by_name.get(name)?.first()?;
+                entries.by_tag.get(tag)
+            });
+            if let Some(category) = registered {
+                return category.clone();
+            }
+        }
+        restore_unregistered(name, retriable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_register_should_resolve_tags_and_names() {
+        // Given: a registry with a custom category
+        let registry = ClassifierRegistry::new();
+        let category = ErrorCategory::custom_fn("Throttled", true, |_| Duration::from_secs(2));
+        registry.register("gateway.throttled", category);
+
+        // When/Then: it should be found by tag and its tag by name
+        let found = registry.get("gateway.throttled").unwrap();
+        assert_eq!(found.retry_delay(1), Duration::from_secs(2));
+        assert_eq!(
+            registry.tag_of("Throttled").as_deref(),
+            Some("gateway.throttled")
+        );
+        assert!(registry.get("gateway.unknown").is_none());
+        assert!(registry.tag_of("Network").is_none());
+
+        // And: replacing the category of a tag should update its name
+        registry.register("gateway.throttled", ErrorCategory::transient());
+        assert!(registry.tag_of("Throttled").is_none());
+        assert_eq!(
+            registry.tag_of("Transient").as_deref(),
+            Some("gateway.throttled")
+        );
+    }
+
+    #[test]
+    fn test_restore_should_prefer_registered_categories() {
+        // Given: a registry with a custom category
+        let registry = ClassifierRegistry::new();
+        let category = ErrorCategory::custom_fn("Throttled", true, |_| Duration::from_secs(2));
+        registry.register("gateway.throttled", category);
+
+        // When/Then: tags and names should resolve the registered category
+        for tag in [Some("gateway.throttled"), Some("gateway.unknown"), None] {
+            let restored = registry.restore("Throttled", tag, false);
+            assert_eq!(restored.retry_delay(1), Duration::from_secs(2));
+            assert!(restored.is_retriable());
+        }
+
+        // And: unregistered names should keep their name and retriability
+        let restored = registry.restore("Flaky", None, true);
+        assert_eq!(restored.category_name(), "Flaky");
+        assert!(restored.is_retriable());
+        assert!(!registry.restore("Flaky", None, false).is_retriable());
+        assert_eq!(
+            registry.restore("Network", Some("gateway.throttled"), false),
+            ErrorCategory::network()
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Wall-clock and monotonic time sources.
+//!
+//! `std::time::SystemTime` and `std::time::Instant` panic on
+//! `wasm32-unknown-unknown`. With the `wasm` feature, timestamps on that
+//! target come from `js_sys::Date` and monotonic instants from `web-time`
+//! (backed by `performance.now()`); elsewhere the standard library is used.
+
+use chrono::{DateTime, Utc};
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use web_time::Instant;
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub use std::time::Instant;
+
+/// Get the current UTC time.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(js_sys::Date::now() as i64).unwrap_or_default()
+}
+
+/// Get the current UTC time.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub(crate) fn now() -> DateTime<Utc> {
+    Utc::now()
+}
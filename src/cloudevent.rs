@@ -0,0 +1,240 @@
+//! CloudEvents mapping of reported errors.
+//!
+//! This module (enabled by the `cloudevents` feature) converts errors into
+//! CloudEvents 1.0 events for platforms exchanging errors over an event bus,
+//! and parses such events back into [`ErrorEvent`]s. The event type is
+//! `tyl.error.<code>`, the data is the serialized [`ErrorContext`], and the
+//! category name and retriability travel as extension attributes so that
+//! custom categories survive the round-trip.
+
+use crate::context::ErrorContext;
+use crate::error::{is_client_hidden, TylError, TylResult};
+use crate::events::ErrorEvent;
+use crate::problem::restore_error;
+use cloudevents::event::ExtensionValue;
+use cloudevents::{AttributesReader, Data, Event, EventBuilder, EventBuilderV10};
+
+/// Prefix of the type of error events, followed by the error code.
+pub const CLOUDEVENT_TYPE_PREFIX: &str = "tyl.error.";
+
+/// Extension attribute holding the category name.
+pub const CLOUDEVENT_CATEGORY_EXTENSION: &str = "tylcategory";
+
+/// Extension attribute holding whether the failed operation may be retried.
+pub const CLOUDEVENT_RETRIABLE_EXTENSION: &str = "tylretriable";
+
+/// Extension attribute holding the message of the error without the prefix
+/// of its `Display` output, to restore the error from.
+pub const CLOUDEVENT_MESSAGE_EXTENSION: &str = "tylmessage";
+
+impl TylError {
+    /// Convert this error into a CloudEvents 1.0 event. Requires the
+    /// `cloudevents` feature.
+    ///
+    /// `source` identifies the producing service and `subject` the failed
+    /// operation, which is also the operation of the [`ErrorContext`] sent as
+    /// data. The event ID and time are those of the context. Building the
+    /// event has no side effects: enrichers do not run, and metadata hidden
+    /// from clients in problem details (e.g. `subject`) is left out.
+    ///
+    /// # Errors
+    /// A validation error if `source` is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cloudevents::AttributesReader;
+    /// use tyl_errors::events::ErrorEvent;
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::network("Connection reset");
+    /// let event = error.to_cloudevent("/services/billing", "charge_card").unwrap();
+    /// assert_eq!(event.ty(), "tyl.error.network");
+    ///
+    /// let received = ErrorEvent::from_cloudevent(&event).unwrap();
+    /// assert_eq!(received.error.to_string(), error.to_string());
+    /// assert_eq!(received.context.operation, "charge_card");
+    /// ```
+    pub fn to_cloudevent<S: Into<String>, O: Into<String>>(
+        &self,
+        source: S,
+        subject: O,
+    ) -> TylResult<Event> {
+        let mut context =
+            ErrorContext::new_unenriched(subject.into(), self.category(), self.to_string());
+        self.describe_in(&mut context);
+        build_event(self, &context, source.into())
+    }
+}
+
+impl ErrorEvent {
+    /// Convert this reported error into a CloudEvents 1.0 event, with the
+    /// context's operation as subject. Requires the `cloudevents` feature.
+    ///
+    /// Metadata flagged as sensitive or hidden from clients in problem
+    /// details is left out of the event.
+    ///
+    /// # Errors
+    /// A validation error if `source` is empty.
+    pub fn to_cloudevent<S: Into<String>>(&self, source: S) -> TylResult<Event> {
+        build_event(&self.error, &self.context, source.into())
+    }
+
+    /// Parse an error event produced by [`TylError::to_cloudevent`]. Requires
+    /// the `cloudevents` feature.
+    ///
+    /// The error is restored like [`TylError::from_problem_details`]: as the
+    /// same variant when it carries a plain message, otherwise as a custom
+    /// error with the category of the extension attributes.
+    ///
+    /// # Errors
+    /// A parsing error if the event is not a TYL error event or its data is
+    /// not a serialized [`ErrorContext`].
+    pub fn from_cloudevent(event: &Event) -> TylResult<ErrorEvent> {
+        let code = event
+            .ty()
+            .strip_prefix(CLOUDEVENT_TYPE_PREFIX)
+            .ok_or_else(|| {
+                TylError::parsing(format!("Not a TYL error event: type `{}`", event.ty()))
+            })?;
+        let mut context: ErrorContext = match event.data() {
+            Some(Data::Json(value)) => serde_json::from_value(value.clone()),
+            Some(Data::String(text)) => serde_json::from_str(text),
+            Some(Data::Binary(bytes)) => serde_json::from_slice(bytes),
+            None => return Err(TylError::parsing("Error event without data")),
+        }
+        .map_err(|err| TylError::parsing(format!("Invalid error event data: {err}")))?;
+
+        let category = match event.extension(CLOUDEVENT_CATEGORY_EXTENSION) {
+            Some(ExtensionValue::String(name)) => name.as_str(),
+            _ => "Unknown",
+        };
+        let retriable = matches!(
+            event.extension(CLOUDEVENT_RETRIABLE_EXTENSION),
+            Some(ExtensionValue::Boolean(true))
+        );
+        let message = match event.extension(CLOUDEVENT_MESSAGE_EXTENSION) {
+            Some(ExtensionValue::String(message)) => message.as_str(),
+            _ => &context.message,
+        };
+        let error = restore_error(code, message, category, retriable, |name| {
+            context.get_metadata(name)?.as_str()
+        });
+        context.category = error.category();
+        Ok(ErrorEvent::new(error, context))
+    }
+}
+
+fn build_event(error: &TylError, context: &ErrorContext, source: String) -> TylResult<Event> {
+    let mut published = context.clone();
+    published
+        .metadata
+        .retain(|key, _| !is_client_hidden(key.as_str()) && !context.is_sensitive(key.as_str()));
+    published.sensitive_keys.clear();
+    // Serializing a context of JSON values cannot fail.
+    let data = serde_json::to_value(&published).unwrap_or_default();
+    let mut builder = EventBuilderV10::new();
+    if let Some(message) = error.plain_message() {
+        builder = builder.extension(CLOUDEVENT_MESSAGE_EXTENSION, message);
+    }
+    builder
+        .id(context.error_id.to_string())
+        .source(source)
+        .ty(format!("{CLOUDEVENT_TYPE_PREFIX}{}", error.code()))
+        .subject(context.operation.clone())
+        .time(context.occurred_at)
+        .extension(
+            CLOUDEVENT_CATEGORY_EXTENSION,
+            context.category.category_name(),
+        )
+        .extension(
+            CLOUDEVENT_RETRIABLE_EXTENSION,
+            context.category.is_retriable_for(error),
+        )
+        .data("application/json", data)
+        .build()
+        .map_err(|err| TylError::validation("source", err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+
+    #[test]
+    fn test_error_events_should_roundtrip_through_cloudevents() {
+        // Given: errors of restorable variants and of a custom category
+        let payment = ErrorCategory::custom_fn("CloudEventTestPayment", true, |_| {
+            std::time::Duration::from_millis(250)
+        });
+        let errors = [
+            TylError::validation("email", "Invalid: missing @"),
+            TylError::not_found("invoice", "7"),
+            TylError::timeout("Upstream took 30s"),
+            TylError::business_logic("Card declined", payment.into_classifier()),
+        ];
+
+        for error in errors {
+            // When: sending it as a JSON-encoded event and parsing it back
+            let event = error.to_cloudevent("/services/billing", "charge").unwrap();
+            let json = serde_json::to_string(&event).unwrap();
+            let parsed: Event = serde_json::from_str(&json).unwrap();
+            let received = ErrorEvent::from_cloudevent(&parsed).unwrap();
+
+            // Then: the attributes should follow the spec and the mapping
+            assert_eq!(parsed.ty(), format!("tyl.error.{}", error.code()));
+            assert_eq!(parsed.source(), "/services/billing");
+            assert_eq!(parsed.subject(), Some("charge"));
+            assert_eq!(parsed.id(), received.context.error_id.to_string());
+
+            // And: the error and its category should survive
+            assert_eq!(received.error.to_string(), error.to_string());
+            assert_eq!(received.error.code(), error.code());
+            assert_eq!(
+                received.context.category.category_name(),
+                error.category().category_name()
+            );
+            assert_eq!(received.error.is_retriable(), error.is_retriable());
+        }
+    }
+
+    #[test]
+    fn test_events_should_leave_out_sensitive_metadata() {
+        // Given: an error with a sensitive subject, and a reported context
+        // with sensitive metadata
+        let error = TylError::permission_denied("user:42", "delete", "invoice:7");
+        let context = ErrorContext::new("charge".into(), error.category(), error.to_string())
+            .with_sensitive_metadata("card_number", "4111".into())
+            .with_metadata("amount", 12.into());
+        let reported = ErrorEvent::new(error.clone(), context);
+
+        // When: converting both to events
+        let direct = error.to_cloudevent("/services/billing", "charge").unwrap();
+        let from_report = reported.to_cloudevent("/services/billing").unwrap();
+
+        // Then: sensitive values should not be published
+        let data = |event: &Event| match event.data() {
+            Some(Data::Json(value)) => value["metadata"].clone(),
+            other => panic!("unexpected data {other:?}"),
+        };
+        assert!(data(&direct).get("subject").is_none());
+        assert_eq!(data(&direct)["resource"], "invoice:7");
+        assert!(data(&from_report).get("card_number").is_none());
+        assert_eq!(data(&from_report)["amount"], 12);
+    }
+
+    #[test]
+    fn test_from_cloudevent_should_reject_foreign_events() {
+        // Given: an event of another type
+        let event = EventBuilderV10::new()
+            .id("1")
+            .source("/orders")
+            .ty("com.example.order.created")
+            .build()
+            .unwrap();
+
+        // When/Then: it should not parse as an error event
+        let error = ErrorEvent::from_cloudevent(&event).unwrap_err();
+        assert!(error.to_string().contains("com.example.order.created"));
+        assert!(TylError::internal("x").to_cloudevent("", "op").is_err());
+    }
+}
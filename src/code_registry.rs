@@ -0,0 +1,296 @@
+//! Registry of error codes claimed by TYL modules.
+//!
+//! This module provides [`CodeRegistry`], where modules declare the custom
+//! error codes and code prefixes they use, so that collisions between modules
+//! are caught at startup or in tests instead of surfacing as ambiguous codes in
+//! dashboards and API clients. The codes of built-in errors are reserved.
+
+use crate::error::TylError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// Module name under which the codes of built-in errors are registered.
+pub const BUILTIN_CODE_MODULE: &str = "tyl-errors";
+
+/// Kind of a code registration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CodeKind {
+    /// A single error code.
+    Code,
+    /// Every code starting with the given prefix.
+    Prefix,
+}
+
+/// A code or prefix claimed by a module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CodeRegistration {
+    /// Name of the registering module.
+    pub module: String,
+    /// Claimed code or prefix.
+    pub code: String,
+    /// Whether `code` is a single code or a prefix.
+    pub kind: CodeKind,
+}
+
+impl CodeRegistration {
+    /// Check if two registrations claim overlapping codes.
+    ///
+    /// A module may register codes under its own prefixes.
+    fn conflicts_with(&self, other: &CodeRegistration) -> bool {
+        match (self.kind, other.kind) {
+            (CodeKind::Code, CodeKind::Code) => self.code == other.code,
+            _ if self.module == other.module => false,
+            (CodeKind::Code, CodeKind::Prefix) => self.code.starts_with(&other.code),
+            (CodeKind::Prefix, CodeKind::Code) => other.code.starts_with(&self.code),
+            (CodeKind::Prefix, CodeKind::Prefix) => {
+                self.code.starts_with(&other.code) || other.code.starts_with(&self.code)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CodeRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            CodeKind::Code => write!(f, "code `{}` ({})", self.code, self.module),
+            CodeKind::Prefix => write!(f, "prefix `{}*` ({})", self.code, self.module),
+        }
+    }
+}
+
+/// Two registrations claiming overlapping codes, in registration order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeConflict {
+    /// Earlier registration.
+    pub first: CodeRegistration,
+    /// Later registration.
+    pub second: CodeRegistration,
+}
+
+impl fmt::Display for CodeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} conflicts with {}", self.second, self.first)
+    }
+}
+
+/// Codes and prefixes claimed by modules, checked for collisions.
+///
+/// Registering the same code or prefix twice from one module is a no-op.
+/// Registrations in the [global](Self::global) registry `debug_assert!` that
+/// they do not conflict; standalone registries only collect conflicts for
+/// [`validate`](Self::validate).
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::CodeRegistry;
+///
+/// let registry = CodeRegistry::new();
+/// registry.register_prefix("billing", "billing_");
+/// registry.register_code("billing", "billing_declined");
+/// registry.register_code("orders", "billing_refunded");
+/// registry.register_code("orders", "not_found");
+///
+/// let conflicts = registry.validate().unwrap_err();
+/// assert_eq!(conflicts.len(), 2);
+/// assert_eq!(
+///     conflicts[0].to_string(),
+///     "code `billing_refunded` (orders) conflicts with prefix `billing_*` (billing)"
+/// );
+/// assert_eq!(conflicts[1].first.module, "tyl-errors");
+/// ```
+#[derive(Debug)]
+pub struct CodeRegistry {
+    registrations: Mutex<Vec<CodeRegistration>>,
+    assert_unique: bool,
+}
+
+impl CodeRegistry {
+    /// Create a registry holding only the codes of built-in errors.
+    pub fn new() -> Self {
+        let registry = Self {
+            registrations: Mutex::new(Vec::new()),
+            assert_unique: false,
+        };
+        for error in TylError::builtin_samples() {
+            registry.register(BUILTIN_CODE_MODULE, error.code(), CodeKind::Code);
+        }
+        registry
+    }
+
+    /// Get the process-wide registry.
+    pub fn global() -> &'static CodeRegistry {
+        static GLOBAL: OnceLock<CodeRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(|| CodeRegistry {
+            assert_unique: true,
+            ..CodeRegistry::new()
+        })
+    }
+
+    /// Claim a single error code for a module.
+    pub fn register_code<M: Into<String>, C: Into<String>>(&self, module: M, code: C) {
+        self.register(module, code, CodeKind::Code);
+    }
+
+    /// Claim every code starting with `prefix` for a module.
+    pub fn register_prefix<M: Into<String>, P: Into<String>>(&self, module: M, prefix: P) {
+        self.register(module, prefix, CodeKind::Prefix);
+    }
+
+    fn register<M: Into<String>, C: Into<String>>(&self, module: M, code: C, kind: CodeKind) {
+        let registration = CodeRegistration {
+            module: module.into(),
+            code: code.into(),
+            kind,
+        };
+        let conflict = {
+            let mut registrations = self.registrations.lock().unwrap_or_else(|e| e.into_inner());
+            if registrations.contains(&registration) {
+                return;
+            }
+            let conflict = registrations
+                .iter()
+                .find(|existing| existing.conflicts_with(&registration))
+                .cloned();
+            registrations.push(registration.clone());
+            conflict
+        };
+        // Assert outside the lock, so the registry stays usable after the panic.
+        if let (true, Some(existing)) = (self.assert_unique, conflict) {
+            debug_assert!(false, "{registration} conflicts with {existing}");
+        }
+    }
+
+    /// Get every registration, in registration order.
+    pub fn registrations(&self) -> Vec<CodeRegistration> {
+        self.registrations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Check that no two modules claim overlapping codes.
+    ///
+    /// # Errors
+    /// Every conflicting pair, ordered by the later registration.
+    pub fn validate(&self) -> Result<(), Vec<CodeConflict>> {
+        let registrations = self.registrations.lock().unwrap_or_else(|e| e.into_inner());
+        let mut conflicts = Vec::new();
+        for (index, second) in registrations.iter().enumerate() {
+            for first in &registrations[..index] {
+                if first.conflicts_with(second) {
+                    conflicts.push(CodeConflict {
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+impl Default for CodeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassifierRegistry, ErrorCategory, TylErrorDto};
+    use std::time::Duration;
+
+    #[test]
+    fn test_validate_should_report_overlapping_codes_and_prefixes() {
+        // Given: modules claiming codes and prefixes, some overlapping
+        let registry = CodeRegistry::new();
+        registry.register_prefix("billing", "billing_");
+        registry.register_code("billing", "billing_declined");
+        registry.register_code("billing", "billing_declined");
+        registry.register_prefix("invoices", "billing_inv");
+        registry.register_code("shipping", "parcel_lost");
+        registry.register_code("returns", "parcel_lost");
+
+        // When: validating the registry
+        let conflicts = registry.validate().unwrap_err();
+
+        // Then: each overlapping pair should be reported with both modules
+        let pairs: Vec<(&str, &str)> = conflicts
+            .iter()
+            .map(|c| (c.first.module.as_str(), c.second.module.as_str()))
+            .collect();
+        assert_eq!(pairs, [("billing", "invoices"), ("shipping", "returns")]);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "prefix `billing_inv*` (invoices) conflicts with prefix `billing_*` (billing)"
+        );
+
+        // And: a registry without overlaps should validate
+        let clean = CodeRegistry::new();
+        clean.register_prefix("billing", "billing_");
+        clean.register_code("shipping", "parcel_lost");
+        assert!(clean.validate().is_ok());
+        assert!(clean
+            .registrations()
+            .iter()
+            .any(|r| r.module == BUILTIN_CODE_MODULE && r.code == "not_found"));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "conflicts with code `timeout` (tyl-errors)")]
+    fn test_asserting_registrations_should_assert_uniqueness() {
+        let registry = CodeRegistry {
+            assert_unique: true,
+            ..CodeRegistry::new()
+        };
+        registry.register_code("scheduler", "timeout");
+    }
+
+    #[test]
+    fn test_registered_category_codes_should_be_emitted() {
+        // Given: a category with a registered code
+        let category =
+            ErrorCategory::transient().with_code("code_registry_test", "code_registry_test_busy");
+
+        // When: building an error of it, and one composed further
+        let error = TylError::business_logic("Busy", category.clone().into_classifier());
+        let composed = TylError::business_logic(
+            "Busy",
+            category
+                .with_max_attempts(2)
+                .escalate_after(1)
+                .into_classifier(),
+        );
+
+        // Then: both should carry the registered code
+        assert_eq!(error.code(), "code_registry_test_busy");
+        assert_eq!(composed.code(), "code_registry_test_busy");
+        assert_eq!(error.qualified_code(), "code_registry_test_busy");
+        assert!(CodeRegistry::global().validate().is_ok());
+
+        // And: it should survive the DTO and problem details round trips
+        ClassifierRegistry::global().register(
+            "code_registry_test.busy",
+            ErrorCategory::custom_fn("CodeRegistryTestBusy", true, |_| Duration::ZERO)
+                .with_code("code_registry_test", "code_registry_test_busy"),
+        );
+        let registered = TylError::business_logic(
+            "Busy",
+            ClassifierRegistry::global()
+                .get("code_registry_test.busy")
+                .unwrap()
+                .into_classifier(),
+        );
+        let dto = TylError::try_from(TylErrorDto::from(&registered)).unwrap();
+        assert_eq!(dto.code(), "code_registry_test_busy");
+        let problem = TylError::from_problem_details(&registered.to_problem_details());
+        assert_eq!(problem.code(), "code_registry_test_busy");
+    }
+}
@@ -0,0 +1,173 @@
+//! Collection of failures from multi-threaded pipelines.
+//!
+//! This module provides [`ErrorCollector`], a cloneable handle worker threads
+//! record their failed items into, and [`ErrorDrain`], its receiving end,
+//! which the coordinating thread drains into a [`TylErrorGroup`] or an
+//! [`ErrorReport`] once the workers are done.
+
+use crate::error::TylError;
+use crate::group::{GroupedError, TylErrorGroup};
+use crate::summary::ErrorReport;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Sending end of an error collection, cloned into every worker.
+///
+/// # Example
+/// ```rust
+/// use std::thread;
+/// use tyl_errors::{ErrorCollector, TylError};
+///
+/// let (collector, drain) = ErrorCollector::channel();
+/// let workers: Vec<_> = (0..4)
+///     .map(|worker| {
+///         let collector = collector.clone();
+///         thread::spawn(move || {
+///             let index = worker * 10;
+///             if worker % 2 == 1 {
+///                 collector.push(index, TylError::network("Connection reset"));
+///             }
+///         })
+///     })
+///     .collect();
+/// drop(collector);
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+///
+/// let group = drain.wait();
+/// assert_eq!(group.len(), 2);
+/// assert_eq!(group.errors()[0].index, 10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorCollector {
+    sender: Sender<GroupedError>,
+}
+
+/// Receiving end of an error collection.
+#[derive(Debug)]
+pub struct ErrorDrain {
+    receiver: Receiver<GroupedError>,
+}
+
+impl ErrorCollector {
+    /// Create a collector and the drain receiving its errors.
+    pub fn channel() -> (ErrorCollector, ErrorDrain) {
+        let (sender, receiver) = mpsc::channel();
+        (ErrorCollector { sender }, ErrorDrain { receiver })
+    }
+
+    /// Record the failure of the item at `index`.
+    ///
+    /// Errors recorded after the drain was dropped are discarded.
+    pub fn push(&self, index: usize, error: TylError) {
+        self.send(GroupedError {
+            index,
+            id: None,
+            error,
+        });
+    }
+
+    /// Record the failure of the item at `index` with the given identifier.
+    pub fn push_with_id<S: Into<String>>(&self, index: usize, id: S, error: TylError) {
+        self.send(GroupedError {
+            index,
+            id: Some(id.into()),
+            error,
+        });
+    }
+
+    fn send(&self, error: GroupedError) {
+        // A dropped drain means nobody is interested in the failures anymore.
+        let _ = self.sender.send(error);
+    }
+}
+
+impl ErrorDrain {
+    /// Drain the errors recorded so far without waiting, in batch order.
+    pub fn try_drain(&self) -> TylErrorGroup {
+        into_group(self.receiver.try_iter().collect())
+    }
+
+    /// Wait until every [`ErrorCollector`] clone is dropped and return all
+    /// recorded errors in batch order.
+    pub fn wait(self) -> TylErrorGroup {
+        into_group(self.receiver.iter().collect())
+    }
+
+    /// Wait like [`wait`](Self::wait) and summarize the errors.
+    pub fn wait_report(self) -> ErrorReport {
+        ErrorReport::from_group(&self.wait())
+    }
+}
+
+/// Build a group from errors received in any order.
+fn into_group(mut errors: Vec<GroupedError>) -> TylErrorGroup {
+    errors.sort_by_key(|error| error.index);
+    let mut group = TylErrorGroup::new();
+    for GroupedError { index, id, error } in errors {
+        match id {
+            Some(id) => group.push_with_id(index, id, error),
+            None => group.push(index, error),
+        }
+    }
+    group
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_from_worker_threads_should_be_collected_in_batch_order() {
+        // Given: workers recording the failures of their share of a batch
+        let (collector, drain) = ErrorCollector::channel();
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let collector = collector.clone();
+                std::thread::spawn(move || {
+                    for index in (worker..40).step_by(4) {
+                        if index % 5 == 0 {
+                            collector.push_with_id(
+                                index,
+                                format!("row-{index}"),
+                                TylError::validation("amount", "Negative"),
+                            );
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        // When: the main thread waits for the workers
+        drop(collector);
+        let group = drain.wait();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // Then: every failure should be collected once, ordered by index
+        let indices: Vec<_> = group.errors().iter().map(|e| e.index).collect();
+        assert_eq!(indices, [0, 5, 10, 15, 20, 25, 30, 35]);
+        assert_eq!(group.errors()[1].id.as_deref(), Some("row-5"));
+        assert_eq!(ErrorReport::from_group(&group).total, 8);
+    }
+
+    #[test]
+    fn test_try_drain_should_return_errors_recorded_so_far() {
+        // Given: a collector still in use
+        let (collector, drain) = ErrorCollector::channel();
+        collector.push(3, TylError::database("Deadlock"));
+        collector.push(1, TylError::network("Connection reset"));
+
+        // When: draining without waiting
+        let early = drain.try_drain();
+
+        // Then: the recorded errors should be returned and consumed
+        assert_eq!(early.errors()[0].index, 1);
+        assert_eq!(early.len(), 2);
+        assert!(drain.try_drain().is_empty());
+        collector.push(4, TylError::timeout("Slow"));
+        drop(collector);
+        assert_eq!(drain.wait_report().total, 1);
+    }
+}
@@ -0,0 +1,527 @@
+//! Configuration file errors.
+//!
+//! This module provides [`ConfigLocation`], the file position attached to
+//! [`TylError::Configuration`], and conversions from the errors of common
+//! configuration loaders: `serde_yaml::Error` (`yaml` feature),
+//! `toml::de::Error` (`toml` feature), `config::ConfigError` (`config`
+//! feature) and `figment::Error` (`figment` feature). The offending key,
+//! file, line and column are extracted where the loader reports them and
+//! exposed through [`TylError::metadata`]. Actual values of keys naming
+//! secrets, such as `database.password`, are never recorded.
+
+use serde::{Deserialize, Serialize};
+
+/// Position of a configuration value in its source.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigLocation {
+    /// File (or other origin, such as `Environment`) the value came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based column number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+/// Fragments of configuration keys whose values are secrets.
+const SECRET_KEY_FRAGMENTS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "private_key",
+    "credential",
+];
+
+/// Check whether a configuration key names a secret, e.g. `database.password`
+/// or `STRIPE_API_KEY`.
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace('-', "_");
+    SECRET_KEY_FRAGMENTS
+        .iter()
+        .any(|fragment| key.contains(fragment))
+}
+
+#[cfg(any(
+    feature = "yaml",
+    feature = "toml",
+    feature = "config",
+    feature = "figment"
+))]
+mod loaders {
+    use super::{is_secret_key, ConfigLocation};
+    use crate::error::TylError;
+    use crate::redact::REDACTED;
+
+    /// Hide the actual value of a secret key, in the value and in the message
+    /// quoting it.
+    fn redact_secret(
+        key: Option<&str>,
+        message: String,
+        actual: Option<String>,
+    ) -> (String, Option<String>) {
+        match actual {
+            Some(actual) if key.is_some_and(is_secret_key) => {
+                let message = if actual.is_empty() {
+                    message
+                } else {
+                    message.replace(&actual, REDACTED)
+                };
+                (message, Some(REDACTED.to_string()))
+            }
+            actual => (message, actual),
+        }
+    }
+
+    /// Find the last `line N, column M` (or `line N column M`) position in a
+    /// loader's error message.
+    ///
+    /// Loaders append the position, so the last one is used: values quoted
+    /// earlier in the message may contain the same words.
+    #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+    fn position_in(message: &str) -> (Option<usize>, Option<usize>) {
+        message
+            .rmatch_indices("line ")
+            .find_map(|(start, _)| {
+                let rest = &message[start + "line ".len()..];
+                let (line, rest) = leading_number(rest)?;
+                let rest = rest.strip_prefix(',').unwrap_or(rest);
+                let (column, _) = leading_number(rest.strip_prefix(" column ")?)?;
+                Some((Some(line), Some(column)))
+            })
+            .unwrap_or((None, None))
+    }
+
+    /// Split the decimal number starting `text` from the rest.
+    #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+    fn leading_number(text: &str) -> Option<(usize, &str)> {
+        let end = text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len());
+        Some((text[..end].parse().ok()?, &text[end..]))
+    }
+
+    /// Find the field named in a serde `missing field` message.
+    #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+    fn missing_field(message: &str) -> Option<&str> {
+        let rest = &message[message.find("missing field `")? + "missing field `".len()..];
+        let field = &rest[..rest.find('`')?];
+        (!field.is_empty()).then_some(field)
+    }
+
+    /// Find the actual and expected values of a serde `invalid type` or
+    /// `invalid value` message, such as `invalid type: string "high",
+    /// expected u16 at line 2 column 9`.
+    ///
+    /// The expected value is split at the last `, expected `, since the
+    /// actual value may quote anything; a trailing position is dropped.
+    #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+    fn mismatch(message: &str) -> Option<(String, String)> {
+        let first_line = message.lines().next().unwrap_or_default();
+        let start = ["invalid type: ", "invalid value: "]
+            .iter()
+            .filter_map(|prefix| first_line.find(prefix).map(|index| index + prefix.len()))
+            .min()?;
+        let (actual, expected) = first_line[start..].rsplit_once(", expected ")?;
+        let expected = match expected.rfind(" at line ") {
+            Some(index) if position_in(&expected[index..]).0.is_some() => &expected[..index],
+            _ => expected,
+        };
+        Some((expected.to_string(), actual.to_string()))
+    }
+
+    /// Find the key path serde_yaml prefixes to its messages, e.g. `database.port`
+    /// in `database.port: invalid type ...`.
+    ///
+    /// Keys with whitespace cannot be told apart from the message and are
+    /// not extracted.
+    #[cfg(feature = "yaml")]
+    fn yaml_path(message: &str) -> Option<&str> {
+        let (path, _) = message.split_once(": ")?;
+        (!path.is_empty() && !path.contains(char::is_whitespace)).then_some(path)
+    }
+
+    /// Join a parent key path and a child field.
+    #[cfg(any(feature = "yaml", feature = "figment"))]
+    fn join_key(parent: Option<&str>, field: Option<&str>) -> Option<String> {
+        match (parent, field) {
+            (Some(parent), Some(field)) => Some(format!("{parent}.{field}")),
+            (parent, field) => parent.or(field).map(str::to_string),
+        }
+    }
+
+    /// Build a configuration error from a loader's message, key and position.
+    ///
+    /// Expected and actual values are taken from serde `invalid type`/`invalid
+    /// value` messages; the actual value of a secret key is redacted.
+    #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+    #[track_caller]
+    fn loader_error(
+        message: String,
+        key: Option<String>,
+        file: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> TylError {
+        let (expected, actual) = match mismatch(&message) {
+            Some((expected, actual)) => (Some(expected), Some(actual)),
+            None => (None, None),
+        };
+        let (message, actual) = redact_secret(key.as_deref(), message, actual);
+
+        let location = (file.is_some() || line.is_some() || column.is_some())
+            .then(|| Box::new(ConfigLocation { file, line, column }));
+        TylError::Configuration {
+            message: crate::limits::limit_message(message),
+            key,
+            expected,
+            actual,
+            location,
+            extras: crate::extras::ErrorExtras::new(),
+        }
+    }
+
+    /// Convert YAML deserialization errors to configuration errors.
+    ///
+    /// The key is taken from the path serde_yaml prefixes to its messages (e.g.
+    /// `database.port: invalid type ...`) and from `missing field` messages.
+    #[cfg(feature = "yaml")]
+    impl From<serde_yaml::Error> for TylError {
+        #[track_caller]
+        fn from(err: serde_yaml::Error) -> Self {
+            let message = err.to_string();
+            let key = join_key(yaml_path(&message), missing_field(&message));
+            let location = err.location();
+            loader_error(
+                format!("invalid YAML: {message}"),
+                key,
+                None,
+                location.as_ref().map(|location| location.line()),
+                location.as_ref().map(|location| location.column()),
+            )
+        }
+    }
+
+    /// Convert TOML deserialization errors to configuration errors.
+    ///
+    /// The line and column come from the error report when the document is known
+    /// to the error; the key from `missing field` messages.
+    #[cfg(feature = "toml")]
+    impl From<toml::de::Error> for TylError {
+        #[track_caller]
+        fn from(err: toml::de::Error) -> Self {
+            let report = err.to_string();
+            let (line, column) = position_in(&report);
+            let key = missing_field(err.message()).map(str::to_string);
+            loader_error(
+                format!("invalid TOML: {}", err.message().trim_end()),
+                key,
+                None,
+                line,
+                column,
+            )
+        }
+    }
+
+    /// Convert `config` crate errors to configuration errors.
+    ///
+    /// Missing properties become missing-key errors and type mismatches keep the
+    /// key, expected and actual values; the origin file is recorded as the
+    /// location, with the line and column of parse errors when reported.
+    #[cfg(feature = "config")]
+    impl From<config::ConfigError> for TylError {
+        #[track_caller]
+        fn from(err: config::ConfigError) -> Self {
+            use config::ConfigError;
+
+            match err {
+                ConfigError::NotFound(key) => TylError::config_missing(key),
+                ConfigError::Type {
+                    origin,
+                    unexpected,
+                    expected,
+                    key,
+                } => {
+                    let actual = unexpected.to_string();
+                    let mut error = match key {
+                        Some(key) => TylError::config_key(key, expected, actual),
+                        None => TylError::Configuration {
+                            message: format!("expected {expected}, got {actual}"),
+                            key: None,
+                            expected: Some(expected.to_string()),
+                            actual: Some(actual),
+                            location: None,
+                            extras: crate::extras::ErrorExtras::new(),
+                        },
+                    };
+                    if let Some(origin) = origin {
+                        error = error.with_config_location(origin, None, None);
+                    }
+                    error
+                }
+                ConfigError::FileParse { uri, cause } => {
+                    let message = cause.to_string();
+                    let (line, column) = position_in(&message);
+                    let key = missing_field(&message).map(str::to_string);
+                    loader_error(message, key, uri, line, column)
+                }
+                ConfigError::At { error, origin, key } => {
+                    let mut converted = TylError::from(*error);
+                    if let TylError::Configuration {
+                        message,
+                        key: existing,
+                        actual,
+                        location,
+                        ..
+                    } = &mut converted
+                    {
+                        if existing.is_none() {
+                            *existing = key;
+                        }
+                        (*message, *actual) = redact_secret(
+                            existing.as_deref(),
+                            std::mem::take(message),
+                            actual.take(),
+                        );
+                        if let Some(origin) = origin {
+                            location.get_or_insert_with(Box::default).file = Some(origin);
+                        }
+                    }
+                    converted
+                }
+                other => TylError::configuration(other.to_string()),
+            }
+        }
+    }
+
+    /// Convert figment extraction errors to configuration errors.
+    ///
+    /// The key is the path figment reports, joined with the field of missing
+    /// field errors; the location is the file of the provider the value came
+    /// from, or the provider's name (e.g. `` `APP_` environment variable(s) ``).
+    /// Only the first of several collected errors is converted.
+    #[cfg(feature = "figment")]
+    impl From<figment::Error> for TylError {
+        #[track_caller]
+        fn from(err: figment::Error) -> Self {
+            use figment::error::Kind;
+
+            let path = (!err.path.is_empty()).then(|| err.path.join("."));
+            let origin = err
+                .metadata
+                .as_ref()
+                .map(|metadata| match &metadata.source {
+                    Some(figment::Source::File(file)) => file.display().to_string(),
+                    _ => metadata.name.to_string(),
+                });
+            let (key, expected, actual) = match &err.kind {
+                Kind::MissingField(field) => {
+                    let key = join_key(path.as_deref(), Some(field));
+                    let mut error = TylError::config_missing(key.unwrap_or_default());
+                    if let Some(origin) = origin {
+                        error = error.with_config_location(origin, None, None);
+                    }
+                    return error;
+                }
+                Kind::InvalidType(actual, expected) | Kind::InvalidValue(actual, expected) => {
+                    (path, Some(expected.clone()), Some(actual.to_string()))
+                }
+                _ => (path, None, None),
+            };
+            let message = match &key {
+                Some(key) => format!("{key}: {}", err.kind),
+                None => err.kind.to_string(),
+            };
+            let (message, actual) = redact_secret(key.as_deref(), message, actual);
+            TylError::Configuration {
+                message: crate::limits::limit_message(message),
+                key,
+                expected,
+                actual,
+                location: origin.map(|file| {
+                    Box::new(ConfigLocation {
+                        file: Some(file),
+                        line: None,
+                        column: None,
+                    })
+                }),
+                extras: crate::extras::ErrorExtras::new(),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[cfg(any(feature = "yaml", feature = "toml", feature = "config"))]
+        #[test]
+        fn test_message_helpers_should_extract_positions_and_fields() {
+            assert_eq!(
+                position_in("TOML parse error at line 3, column 9"),
+                (Some(3), Some(9))
+            );
+            assert_eq!(position_in("at line 2 column 5"), (Some(2), Some(5)));
+            assert_eq!(
+                position_in("invalid value: \"line 1, column 2\" at line 4 column 7"),
+                (Some(4), Some(7))
+            );
+            assert_eq!(position_in("no position"), (None, None));
+            assert_eq!(missing_field("missing field `port`"), Some("port"));
+            assert_eq!(
+                mismatch(
+                    "invalid value: string \"a, expected b\", expected u16 at line 2 column 9"
+                ),
+                Some(("u16".to_string(), "string \"a, expected b\"".to_string()))
+            );
+        }
+
+        #[cfg(feature = "yaml")]
+        #[test]
+        fn test_yaml_errors_should_keep_key_and_position() {
+            use serde::Deserialize;
+
+            #[derive(Debug, Deserialize)]
+            struct Database {
+                #[allow(dead_code)]
+                port: u16,
+            }
+            #[derive(Debug, Deserialize)]
+            struct Settings {
+                #[allow(dead_code)]
+                database: Database,
+            }
+
+            let err = serde_yaml::from_str::<Settings>("database:\n  port: high\n").unwrap_err();
+            let error = TylError::from(err);
+
+            let metadata = error.metadata();
+            assert_eq!(error.code(), "configuration_invalid_key");
+            assert_eq!(metadata["config_key"], "database.port");
+            assert_eq!(metadata["expected"], "u16");
+            assert_eq!(metadata["actual"], "string \"high\"");
+            assert_eq!(metadata["config_line"], 2);
+            assert!(metadata.contains_key("config_column"));
+        }
+
+        #[cfg(feature = "yaml")]
+        #[test]
+        fn test_yaml_errors_should_not_record_secret_values() {
+            use serde::Deserialize;
+
+            #[derive(Debug, Deserialize)]
+            struct Database {
+                #[allow(dead_code)]
+                password: u32,
+            }
+            #[derive(Debug, Deserialize)]
+            struct Settings {
+                #[allow(dead_code)]
+                database: Database,
+            }
+
+            let yaml = "database:\n  password: hunter2\n";
+            let error = TylError::from(serde_yaml::from_str::<Settings>(yaml).unwrap_err());
+
+            let metadata = error.metadata();
+            assert_eq!(metadata["config_key"], "database.password");
+            assert_eq!(metadata["actual"], "[REDACTED]");
+            assert!(!error.to_string().contains("hunter2"));
+        }
+
+        #[cfg(feature = "figment")]
+        #[test]
+        fn test_figment_errors_should_keep_key_origin_and_hide_secrets() {
+            use figment::providers::Serialized;
+            use figment::Figment;
+            use serde::Deserialize;
+
+            #[derive(Debug, Deserialize)]
+            struct Server {
+                #[allow(dead_code)]
+                port: u16,
+                #[allow(dead_code)]
+                api_token: u32,
+            }
+            #[derive(Debug, Deserialize)]
+            struct Settings {
+                #[allow(dead_code)]
+                server: Server,
+            }
+
+            let extract =
+                |figment: Figment| TylError::from(figment.extract::<Settings>().unwrap_err());
+            let mistyped = extract(
+                Figment::new()
+                    .merge(Serialized::default("server.port", "high"))
+                    .merge(Serialized::default("server.api_token", 1)),
+            );
+            let secret = extract(
+                Figment::new()
+                    .merge(Serialized::default("server.port", 80))
+                    .merge(Serialized::default("server.api_token", "hunter2")),
+            );
+            let missing = TylError::from(
+                Figment::new()
+                    .merge(Serialized::default("server.port", 80))
+                    .extract::<Settings>()
+                    .unwrap_err(),
+            );
+
+            assert_eq!(mistyped.code(), "configuration_invalid_key");
+            assert_eq!(mistyped.metadata()["config_key"], "server.port");
+            assert_eq!(mistyped.metadata()["actual"], "string \"high\"");
+            assert!(mistyped.metadata().contains_key("config_file"));
+            assert_eq!(secret.metadata()["config_key"], "server.api_token");
+            assert_eq!(secret.metadata()["actual"], "[REDACTED]");
+            assert!(!secret.to_string().contains("hunter2"));
+            assert_eq!(missing.code(), "configuration_missing_key");
+            assert_eq!(missing.metadata()["config_key"], "server.api_token");
+        }
+
+        #[cfg(feature = "toml")]
+        #[test]
+        fn test_toml_errors_should_keep_position_and_missing_key() {
+            use serde::Deserialize;
+
+            #[derive(Debug, Deserialize)]
+            struct Settings {
+                #[allow(dead_code)]
+                port: u16,
+            }
+
+            let invalid = TylError::from(toml::from_str::<Settings>("port = \"x\"\n").unwrap_err());
+            let missing = TylError::from(toml::from_str::<Settings>("host = \"a\"\n").unwrap_err());
+
+            assert_eq!(invalid.metadata()["config_line"], 1);
+            assert_eq!(invalid.metadata()["config_column"], 8);
+            assert_eq!(missing.metadata()["config_key"], "port");
+        }
+
+        #[cfg(feature = "config")]
+        #[test]
+        fn test_config_errors_should_map_missing_and_mistyped_keys() {
+            use config::{Config, ConfigError};
+
+            let settings = Config::builder()
+                .set_override("server.port", "high")
+                .unwrap()
+                .build()
+                .unwrap();
+
+            let missing = TylError::from(settings.get::<String>("server.host").unwrap_err());
+            let mistyped = TylError::from(settings.get::<u16>("server.port").unwrap_err());
+            let frozen = TylError::from(ConfigError::Frozen);
+
+            assert_eq!(missing.code(), "configuration_missing_key");
+            assert_eq!(missing.metadata()["config_key"], "server.host");
+            assert_eq!(mistyped.code(), "configuration_invalid_key");
+            assert_eq!(mistyped.metadata()["config_key"], "server.port");
+            assert_eq!(frozen.code(), "configuration");
+        }
+    }
+}
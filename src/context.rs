@@ -4,16 +4,47 @@
 //! retry attempts, and associated metadata for debugging and monitoring.
 
 use crate::category::ErrorCategory;
-use chrono::{DateTime, Utc};
+use crate::clock::{self, Instant};
+use crate::error::{TylError, TylResult};
+use crate::limits;
+use crate::meta_key::MetaKey;
+use crate::redact::{debug_message, RedactedDebug, SafeDebug, REDACTED};
+use crate::settings::ErrorSettings;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// HTTP header carrying the idempotency key of a request (`Idempotency-Key`).
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Sensitive key masking the [tenant ID](ErrorContext::tenant_id) of a context.
+///
+/// Set by [`ErrorContext::with_sensitive_tenant_id`], or with
+/// [`ErrorContext::mark_sensitive`] for contexts built by enrichers.
+pub const TENANT_ID_KEY: &str = "tenant_id";
+
 /// Context information for error tracking and monitoring.
 ///
 /// Provides rich metadata about error occurrences including operation context,
 /// retry tracking, and arbitrary metadata for debugging and monitoring systems.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `Debug` output masks metadata flagged as sensitive when `TYL_ERROR_REDACT=true`;
+/// use [`ErrorContext::safe_debug`] to force redaction regardless of the setting.
+///
+/// Messages and metadata values are cut to the limits of
+/// [`ErrorSettings`] (`TYL_ERROR_MAX_MESSAGE_LEN`,
+/// `TYL_ERROR_MAX_METADATA_VALUE_LEN`) when set through the constructor and
+/// metadata methods, and again when serialized; truncated values end with
+/// [`TRUNCATION_MARKER`](crate::TRUNCATION_MARKER).
+///
+/// With the `schemars` feature, implements `JsonSchema` describing the
+/// serialized form, for generating models in other languages.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ErrorContext {
     /// Unique identifier for this error occurrence.
     pub error_id: Uuid,
@@ -24,13 +55,64 @@ pub struct ErrorContext {
     #[serde(default = "ErrorCategory::unknown")]
     pub category: ErrorCategory,
     /// Human-readable error message.
+    #[serde(
+        serialize_with = "limits::serialize_message",
+        deserialize_with = "limits::deserialize_message"
+    )]
     pub message: String,
     /// Timestamp when the error occurred.
     pub occurred_at: DateTime<Utc>,
+    /// Monotonic instant when the error occurred, for duration math.
+    ///
+    /// Unaffected by wall-clock adjustments, but only meaningful in the
+    /// process that created the context: it is not serialized, and decoded
+    /// contexts get the instant they were decoded at.
+    #[serde(skip, default = "Instant::now")]
+    pub occurred_at_mono: Instant,
     /// Number of attempts for this operation (starts at 1).
     pub attempt_count: usize,
     /// Additional metadata for debugging and monitoring.
-    pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(
+        serialize_with = "limits::serialize_metadata",
+        deserialize_with = "limits::deserialize_metadata"
+    )]
+    pub metadata: HashMap<MetaKey, serde_json::Value>,
+    /// Metadata keys whose values must be masked in redacted output.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub sensitive_keys: HashSet<MetaKey>,
+    /// Idempotency key of the logical request, shared by all of its retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Tenant the failed operation ran for, in multi-tenant services.
+    ///
+    /// Masked like sensitive metadata when [`TENANT_ID_KEY`] is flagged as sensitive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Service where the error originated, when propagated between services.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_service: Option<String>,
+    /// Team owning the error, used for on-call routing (see
+    /// [`TylError::owner`](crate::TylError::owner)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Number of times the error was forwarded between services (see
+    /// [`TylError::forward`](crate::TylError::forward)).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub hop_count: u32,
+    /// Key-value pairs propagated to downstream services through the
+    /// [`BAGGAGE_HEADER`](crate::BAGGAGE_HEADER) and envelopes, unlike
+    /// metadata, which stays local. Set with
+    /// [`with_baggage`](ErrorContext::with_baggage).
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "crate::baggage::deserialize_baggage"
+    )]
+    pub baggage: BTreeMap<String, String>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 impl ErrorContext {
@@ -42,23 +124,44 @@ impl ErrorContext {
     /// * `message` - Human-readable error message
     ///
     /// # Returns
-    /// A new ErrorContext with generated UUID, current timestamp, and empty metadata.
+    /// A new ErrorContext with generated UUID, current timestamp, and the
+    /// metadata of any registered [enrichers](crate::enrich).
     pub fn new(operation: String, category: ErrorCategory, message: String) -> Self {
+        let mut context = Self::new_unenriched(operation, category, message);
+        crate::enrich::apply(&mut context);
+        context
+    }
+
+    /// Create a new error context without running the enrichers, e.g. for a
+    /// context received from another service.
+    pub(crate) fn new_unenriched(
+        operation: String,
+        category: ErrorCategory,
+        message: String,
+    ) -> Self {
         Self {
             error_id: Uuid::new_v4(),
             operation,
             category,
-            message,
-            occurred_at: Utc::now(),
+            message: limits::limit_message(message),
+            occurred_at: clock::now(),
+            occurred_at_mono: Instant::now(),
             attempt_count: 1,
             metadata: HashMap::new(),
+            sensitive_keys: HashSet::new(),
+            idempotency_key: None,
+            tenant_id: None,
+            origin_service: None,
+            owner: None,
+            hop_count: 0,
+            baggage: BTreeMap::new(),
         }
     }
 
     /// Add metadata to this error context using builder pattern.
     ///
     /// # Arguments
-    /// * `key` - Metadata key
+    /// * `key` - Metadata key; literals are stored without allocating
     /// * `value` - Serializable value to store
     ///
     /// # Returns
@@ -76,11 +179,180 @@ impl ErrorContext {
     /// .with_metadata("endpoint".to_string(), serde_json::json!("/api/users"))
     /// .with_metadata("timeout_ms".to_string(), serde_json::json!(5000));
     /// ```
-    pub fn with_metadata(mut self, key: String, value: serde_json::Value) -> Self {
-        self.metadata.insert(key, value);
+    pub fn with_metadata<K: Into<MetaKey>>(mut self, key: K, value: serde_json::Value) -> Self {
+        self.metadata.insert(key.into(), limits::limit_value(value));
+        self
+    }
+
+    /// Add metadata serialized from a typed value using builder pattern.
+    ///
+    /// # Errors
+    /// A serialization error if the value cannot be represented as JSON
+    /// (e.g. a map with non-string keys).
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use tyl_errors::{ErrorCategory, ErrorContext, TylResult};
+    ///
+    /// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    /// struct Upstream {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn main() -> TylResult<()> {
+    /// let upstream = Upstream { host: "db-1".into(), port: 5432 };
+    /// let context = ErrorContext::new("query".into(), ErrorCategory::network(), "Reset".into())
+    ///     .with_typed_metadata("upstream", &upstream)?;
+    ///
+    /// assert_eq!(context.get_metadata_as::<Upstream>("upstream")?, Some(upstream));
+    /// assert_eq!(context.get_metadata_as::<u16>("missing")?, None);
+    /// assert!(context.get_metadata_as::<u16>("upstream").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_typed_metadata<K, T>(self, key: K, value: &T) -> TylResult<Self>
+    where
+        K: Into<MetaKey>,
+        T: Serialize + ?Sized,
+    {
+        let key = key.into();
+        let value = serde_json::to_value(value)
+            .map_err(|err| TylError::serialization(format!("metadata `{key}`: {err}")))?;
+        Ok(self.with_metadata(key, value))
+    }
+
+    /// Add metadata whose value must never appear in redacted output.
+    ///
+    /// # Arguments
+    /// * `key` - Metadata key
+    /// * `value` - Serializable value to store
+    ///
+    /// # Returns
+    /// Self for method chaining.
+    pub fn with_sensitive_metadata<K: Into<MetaKey>>(
+        mut self,
+        key: K,
+        value: serde_json::Value,
+    ) -> Self {
+        let key = key.into();
+        self.sensitive_keys.insert(key.clone());
+        self.metadata.insert(key, limits::limit_value(value));
+        self
+    }
+
+    /// Flag an existing or future metadata key as sensitive.
+    ///
+    /// # Arguments
+    /// * `key` - Metadata key to mask in redacted output
+    pub fn mark_sensitive<K: Into<MetaKey>>(&mut self, key: K) {
+        self.sensitive_keys.insert(key.into());
+    }
+
+    /// Check if the given metadata key is flagged as sensitive.
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive_keys.contains(key)
+    }
+
+    /// Get a `Debug` view of this context that always redacts.
+    ///
+    /// Sensitive metadata values are masked and long messages truncated even
+    /// when `TYL_ERROR_REDACT` is not set.
+    pub fn safe_debug(&self) -> SafeDebug<'_, Self> {
+        SafeDebug(self)
+    }
+
+    /// Tie this context to a logical request through its idempotency key.
+    ///
+    /// Retries of the same write share the key, so downstream services can
+    /// deduplicate them and reports can be grouped by request.
+    pub fn with_idempotency_key<K: Into<String>>(mut self, key: K) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Attribute this context to a tenant using builder pattern.
+    ///
+    /// Unlike metadata, the tenant ID is exported as a dimension of error
+    /// metrics (diagnostics rates, OpenTelemetry attributes) and can be part of
+    /// [fingerprints](crate::FingerprintOptions::per_tenant).
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, ErrorContext};
+    ///
+    /// let context = ErrorContext::new("checkout".into(), ErrorCategory::network(), "Timeout".into())
+    ///     .with_tenant_id("acme");
+    /// assert_eq!(context.tenant_id.as_deref(), Some("acme"));
+    /// ```
+    pub fn with_tenant_id<T: Into<String>>(mut self, tenant_id: T) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    /// Attribute this context to a tenant whose ID must never appear in
+    /// redacted output, using builder pattern.
+    pub fn with_sensitive_tenant_id<T: Into<String>>(mut self, tenant_id: T) -> Self {
+        self.sensitive_keys.insert(TENANT_ID_KEY.into());
+        self.with_tenant_id(tenant_id)
+    }
+
+    /// Record the service where the error originated using builder pattern.
+    pub fn with_origin_service<S: Into<String>>(mut self, service: S) -> Self {
+        self.origin_service = Some(service.into());
+        self
+    }
+
+    /// Attribute this context to the team owning the error using builder pattern.
+    pub fn with_owner<O: Into<String>>(mut self, owner: O) -> Self {
+        self.owner = Some(owner.into());
         self
     }
 
+    /// Get the tenant ID, or [`REDACTED`] if it is flagged as sensitive.
+    pub fn redacted_tenant_id(&self) -> Option<&str> {
+        let tenant_id = self.tenant_id.as_deref()?;
+        Some(if self.is_sensitive(TENANT_ID_KEY) {
+            REDACTED
+        } else {
+            tenant_id
+        })
+    }
+
+    /// Get the time at which the error occurred in the given time zone.
+    ///
+    /// # Example
+    /// ```rust
+    /// use chrono::FixedOffset;
+    /// use tyl_errors::{ErrorCategory, ErrorContext};
+    ///
+    /// let context = ErrorContext::new("api_call".into(), ErrorCategory::network(), "Timeout".into());
+    /// let cet = FixedOffset::east_opt(3600).unwrap();
+    /// assert_eq!(context.occurred_at_in(&cet), context.occurred_at);
+    /// ```
+    pub fn occurred_at_in<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        self.occurred_at.with_timezone(tz)
+    }
+
+    /// Get the monotonic time elapsed since the error occurred.
+    pub fn elapsed(&self) -> Duration {
+        self.occurred_at_mono.elapsed()
+    }
+
+    /// Get the monotonic time between an earlier context and this one.
+    ///
+    /// Saturates to zero if `earlier` actually occurred later.
+    pub fn duration_since(&self, earlier: &ErrorContext) -> Duration {
+        self.occurred_at_mono
+            .saturating_duration_since(earlier.occurred_at_mono)
+    }
+
+    /// Get the instant at which a retry scheduled `delay` after the error is due.
+    pub fn retry_due_at(&self, delay: Duration) -> Instant {
+        self.occurred_at_mono + delay
+    }
+
     /// Increment the attempt count for retry tracking.
     ///
     /// This should be called each time an operation is retried to maintain
@@ -94,8 +366,8 @@ impl ErrorContext {
     /// # Arguments
     /// * `key` - Metadata key
     /// * `value` - Serializable value to store
-    pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
-        self.metadata.insert(key, value);
+    pub fn add_metadata<K: Into<MetaKey>>(&mut self, key: K, value: serde_json::Value) {
+        self.metadata.insert(key.into(), limits::limit_value(value));
     }
 
     /// Get metadata value by key.
@@ -109,6 +381,24 @@ impl ErrorContext {
         self.metadata.get(key)
     }
 
+    /// Get a metadata value deserialized into `T`.
+    ///
+    /// # Returns
+    /// `None` if the key is absent.
+    ///
+    /// # Errors
+    /// A serialization error (internal) naming the key if the stored value
+    /// does not deserialize into `T`.
+    pub fn get_metadata_as<T: DeserializeOwned>(&self, key: &str) -> TylResult<Option<T>> {
+        self.metadata
+            .get(key)
+            .map(|value| {
+                T::deserialize(value)
+                    .map_err(|err| TylError::serialization(format!("metadata `{key}`: {err}")))
+            })
+            .transpose()
+    }
+
     /// Check if this context has metadata for the given key.
     ///
     /// # Arguments
@@ -123,6 +413,7 @@ impl ErrorContext {
     /// Clear all metadata from this context.
     pub fn clear_metadata(&mut self) {
         self.metadata.clear();
+        self.sensitive_keys.clear();
     }
 
     /// Get the number of metadata entries.
@@ -130,3 +421,117 @@ impl ErrorContext {
         self.metadata.len()
     }
 }
+
+/// Metadata map view that masks sensitive values when redacting.
+struct MetadataDebug<'a> {
+    context: &'a ErrorContext,
+    redact: bool,
+}
+
+impl fmt::Debug for MetadataDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (key, value) in &self.context.metadata {
+            if self.redact && self.context.is_sensitive(key) {
+                map.entry(key, &REDACTED);
+            } else {
+                map.entry(key, value);
+            }
+        }
+        map.finish()
+    }
+}
+
+impl RedactedDebug for ErrorContext {
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>, redact: bool) -> fmt::Result {
+        f.debug_struct("ErrorContext")
+            .field("error_id", &self.error_id)
+            .field("operation", &self.operation)
+            .field("category", &self.category)
+            .field("message", &debug_message(&self.message, redact))
+            .field("occurred_at", &self.occurred_at)
+            .field("attempt_count", &self.attempt_count)
+            .field(
+                "metadata",
+                &MetadataDebug {
+                    context: self,
+                    redact,
+                },
+            )
+            .field("sensitive_keys", &self.sensitive_keys)
+            .field("idempotency_key", &self.idempotency_key)
+            .field(
+                "tenant_id",
+                &if redact {
+                    self.redacted_tenant_id()
+                } else {
+                    self.tenant_id.as_deref()
+                },
+            )
+            .field("origin_service", &self.origin_service)
+            .field("owner", &self.owner)
+            .field("hop_count", &self.hop_count)
+            .field("baggage", &self.baggage)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(f, ErrorSettings::global().redact)
+    }
+}
+
+/// Get the non-empty idempotency key of a header map, if any.
+#[cfg(feature = "http")]
+pub(crate) fn idempotency_key_in(headers: &http::HeaderMap) -> Option<String> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+}
+
+/// Idempotency key propagation through HTTP headers. Requires the `http` feature.
+#[cfg(feature = "http")]
+impl ErrorContext {
+    /// Read the idempotency key from the [`IDEMPOTENCY_KEY_HEADER`] of a request.
+    ///
+    /// Missing, empty and non-ASCII header values leave the key unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, ErrorContext};
+    ///
+    /// let mut headers = http::HeaderMap::new();
+    /// headers.insert("Idempotency-Key", "order-42".parse().unwrap());
+    ///
+    /// let context = ErrorContext::new("orders::create".into(), ErrorCategory::network(), "Timeout".into())
+    ///     .with_idempotency_key_from(&headers);
+    /// assert_eq!(context.idempotency_key.as_deref(), Some("order-42"));
+    ///
+    /// let mut outgoing = http::HeaderMap::new();
+    /// context.propagate_idempotency_key(&mut outgoing);
+    /// assert_eq!(outgoing["idempotency-key"], "order-42");
+    /// ```
+    pub fn with_idempotency_key_from(mut self, headers: &http::HeaderMap) -> Self {
+        if let Some(key) = idempotency_key_in(headers) {
+            self.idempotency_key = Some(key);
+        }
+        self
+    }
+
+    /// Set the [`IDEMPOTENCY_KEY_HEADER`] of an outgoing request to this context's key.
+    ///
+    /// Does nothing without a key, or if the key is not a valid header value.
+    pub fn propagate_idempotency_key(&self, headers: &mut http::HeaderMap) {
+        let value = self
+            .idempotency_key
+            .as_deref()
+            .and_then(|key| http::HeaderValue::from_str(key).ok());
+        if let Some(value) = value {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, value);
+        }
+    }
+}
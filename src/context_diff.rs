@@ -0,0 +1,225 @@
+//! Field-by-field comparison of error contexts.
+//!
+//! This module provides [`ContextDiff`], the result of
+//! [`ErrorContext::diff`], listing the fields and metadata entries in which
+//! two failures of the same operation differ. Identifiers and timestamps are
+//! ignored, since they always differ.
+
+use crate::context::{ErrorContext, TENANT_ID_KEY};
+use crate::redact::REDACTED;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A field with different values in two contexts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextChange {
+    /// Field name, or `metadata.<key>` for metadata entries.
+    pub field: String,
+    /// Value in the first context; `None` if the metadata key is absent.
+    pub left: Option<Value>,
+    /// Value in the second context; `None` if the metadata key is absent.
+    pub right: Option<Value>,
+}
+
+/// Differences between two error contexts, in field order then metadata key order.
+///
+/// Values of metadata flagged as sensitive in either context are replaced by
+/// [`REDACTED`].
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorCategory, ErrorContext};
+///
+/// let first = ErrorContext::new("orders::sync".into(), ErrorCategory::network(), "Timeout".into())
+///     .with_metadata("region", serde_json::json!("eu-west-1"));
+/// let second = ErrorContext::new("orders::sync".into(), ErrorCategory::network(), "Timeout".into())
+///     .with_metadata("region", serde_json::json!("us-east-1"));
+///
+/// let diff = first.diff(&second);
+/// assert_eq!(diff.fields(), ["metadata.region"]);
+/// assert_eq!(diff.to_string(), r#"metadata.region: "eu-west-1" -> "us-east-1""#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextDiff {
+    /// Changed fields.
+    pub changes: Vec<ContextChange>,
+}
+
+impl ContextDiff {
+    /// Check if the contexts are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Get the number of changed fields.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Get the names of the changed fields.
+    pub fn fields(&self) -> Vec<&str> {
+        self.changes
+            .iter()
+            .map(|change| change.field.as_str())
+            .collect()
+    }
+
+    /// Record a change if the values differ.
+    fn compare(&mut self, field: &str, left: Option<Value>, right: Option<Value>) {
+        if left != right {
+            self.changes.push(ContextChange {
+                field: field.to_string(),
+                left,
+                right,
+            });
+        }
+    }
+}
+
+impl fmt::Display for ContextDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no differences");
+        }
+        for (index, change) in self.changes.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}: ", change.field)?;
+            match &change.left {
+                Some(value) => write!(f, "{value}")?,
+                None => f.write_str("<missing>")?,
+            }
+            f.write_str(" -> ")?;
+            match &change.right {
+                Some(value) => write!(f, "{value}")?,
+                None => f.write_str("<missing>")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorContext {
+    /// Compare this context with another failure, ignoring IDs and timestamps.
+    ///
+    /// Compares the operation, category, message, attempt count, idempotency
+    /// key, tenant ID and every metadata entry.
+    pub fn diff(&self, other: &ErrorContext) -> ContextDiff {
+        let mut diff = ContextDiff::default();
+        diff.compare(
+            "operation",
+            Some(json!(self.operation)),
+            Some(json!(other.operation)),
+        );
+        diff.compare(
+            "category",
+            Some(json!(self.category.category_name())),
+            Some(json!(other.category.category_name())),
+        );
+        diff.compare(
+            "message",
+            Some(json!(self.message)),
+            Some(json!(other.message)),
+        );
+        diff.compare(
+            "attempt_count",
+            Some(json!(self.attempt_count)),
+            Some(json!(other.attempt_count)),
+        );
+        diff.compare(
+            "idempotency_key",
+            Some(json!(self.idempotency_key)),
+            Some(json!(other.idempotency_key)),
+        );
+        if self.tenant_id != other.tenant_id {
+            let masked = self.is_sensitive(TENANT_ID_KEY) || other.is_sensitive(TENANT_ID_KEY);
+            let shown = |tenant_id: &Option<String>| match tenant_id {
+                Some(_) if masked => json!(REDACTED),
+                tenant_id => json!(tenant_id),
+            };
+            diff.changes.push(ContextChange {
+                field: "tenant_id".to_string(),
+                left: Some(shown(&self.tenant_id)),
+                right: Some(shown(&other.tenant_id)),
+            });
+        }
+
+        let keys: BTreeSet<&str> = self
+            .metadata
+            .keys()
+            .chain(other.metadata.keys())
+            .map(|key| key.as_str())
+            .collect();
+        for key in keys {
+            let (left, right) = (self.metadata.get(key), other.metadata.get(key));
+            if left == right {
+                continue;
+            }
+            let masked = self.is_sensitive(key) || other.is_sensitive(key);
+            let shown = |value: Option<&Value>| {
+                value.map(|value| {
+                    if masked {
+                        json!(REDACTED)
+                    } else {
+                        value.clone()
+                    }
+                })
+            };
+            // Sensitive values that differ stay listed even though both show as redacted.
+            diff.changes.push(ContextChange {
+                field: format!("metadata.{key}"),
+                left: shown(left),
+                right: shown(right),
+            });
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+
+    #[test]
+    fn test_diff_should_list_changed_fields_and_metadata() {
+        // Given: two failures of one operation with different attempts and metadata
+        let first = ErrorContext::new(
+            "billing::charge".to_string(),
+            ErrorCategory::network(),
+            "Gateway timeout".to_string(),
+        )
+        .with_metadata("amount", json!(100))
+        .with_metadata("currency", json!("EUR"))
+        .with_sensitive_metadata("card", json!("4111"));
+        let mut second = first
+            .clone()
+            .with_metadata("amount", json!(250))
+            .with_metadata("retry_region", json!("b"))
+            .with_sensitive_metadata("card", json!("5500"));
+        second.increment_attempt();
+
+        // When: diffing them
+        let diff = first.diff(&second);
+
+        // Then: only changed values should be listed, sensitive ones masked
+        assert_eq!(
+            diff.fields(),
+            [
+                "attempt_count",
+                "metadata.amount",
+                "metadata.card",
+                "metadata.retry_region"
+            ]
+        );
+        assert_eq!(diff.changes[2].left, Some(json!(REDACTED)));
+        assert_eq!(
+            diff.to_string().lines().last(),
+            Some(r#"metadata.retry_region: <missing> -> "b""#)
+        );
+        assert!(first.diff(&first.clone()).is_empty());
+    }
+}
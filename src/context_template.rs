@@ -0,0 +1,204 @@
+//! Reusable definitions of error contexts for common operations.
+//!
+//! Services tend to copy the same operation names and metadata keys to every
+//! call site of, say, a database query. This module provides
+//! [`ContextTemplate`], registered once at startup under its operation name
+//! and instantiated wherever the operation fails, so the contexts of an
+//! operation share the same name, category and metadata keys.
+
+use crate::category::ErrorCategory;
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use crate::meta_key::MetaKey;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Definition of the contexts of one operation.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ContextTemplate, ErrorCategory, TylError};
+///
+/// ContextTemplate::new("db.query", ErrorCategory::transient())
+///     .with_default("db.system", serde_json::json!("postgresql"))
+///     .with_required_key("db.statement")
+///     .register();
+///
+/// let template = ContextTemplate::get("db.query").unwrap();
+/// let context = template.instantiate_for(&TylError::database("Deadlock detected"))
+///     .with_metadata("db.statement", serde_json::json!("SELECT 1"));
+///
+/// assert_eq!(context.operation, "db.query");
+/// assert_eq!(context.metadata["db.system"], "postgresql");
+/// assert!(template.check(&context).is_ok());
+/// assert!(template.check(&template.instantiate()).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContextTemplate {
+    operation: String,
+    category: ErrorCategory,
+    defaults: HashMap<MetaKey, serde_json::Value>,
+    required_keys: Vec<String>,
+}
+
+fn registry() -> &'static RwLock<HashMap<String, ContextTemplate>> {
+    static TEMPLATES: OnceLock<RwLock<HashMap<String, ContextTemplate>>> = OnceLock::new();
+    TEMPLATES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+impl ContextTemplate {
+    /// Create a template for `operation`, whose errors are expected to be of `category`.
+    pub fn new<O: Into<String>>(operation: O, category: ErrorCategory) -> Self {
+        Self {
+            operation: operation.into(),
+            category,
+            defaults: HashMap::new(),
+            required_keys: Vec::new(),
+        }
+    }
+
+    /// Add a metadata entry set on every instance using builder pattern.
+    pub fn with_default<K: Into<MetaKey>>(mut self, key: K, value: serde_json::Value) -> Self {
+        self.defaults.insert(key.into(), value);
+        self
+    }
+
+    /// Require a metadata key the call site must set, checked by
+    /// [`check`](Self::check), using builder pattern.
+    pub fn with_required_key<K: Into<String>>(mut self, key: K) -> Self {
+        self.required_keys.push(key.into());
+        self
+    }
+
+    /// Get the operation name of this template.
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Get the category errors of this operation are expected to have.
+    pub fn category(&self) -> &ErrorCategory {
+        &self.category
+    }
+
+    /// Register this template process-wide under its operation name,
+    /// replacing the one registered before.
+    pub fn register(self) {
+        registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.operation.clone(), self);
+    }
+
+    /// Get the template registered for `operation`.
+    pub fn get(operation: &str) -> Option<ContextTemplate> {
+        registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(operation)
+            .cloned()
+    }
+
+    /// Create a context of this operation with the expected category, the
+    /// default metadata and an empty message.
+    pub fn instantiate(&self) -> ErrorContext {
+        let mut context =
+            ErrorContext::new(self.operation.clone(), self.category.clone(), String::new());
+        self.apply_defaults(&mut context);
+        context
+    }
+
+    /// Create the context of an error of this operation, as
+    /// [`TylError::to_context`] does, with the default metadata added.
+    ///
+    /// Metadata of the error takes precedence over the defaults.
+    pub fn instantiate_for(&self, error: &TylError) -> ErrorContext {
+        let mut context = error.to_context(self.operation.clone());
+        self.apply_defaults(&mut context);
+        context
+    }
+
+    /// Check that a context follows this template: it has the template's
+    /// operation, expected category and required metadata keys.
+    ///
+    /// # Errors
+    /// An internal error naming the first deviation: contexts are built by
+    /// the service itself, so a deviation is a bug rather than bad input.
+    pub fn check(&self, context: &ErrorContext) -> TylResult<()> {
+        let deviation = |what: String| {
+            TylError::internal(format!(
+                "Context of `{}` deviates from its template: {what}",
+                self.operation
+            ))
+        };
+        if context.operation != self.operation {
+            return Err(deviation(format!(
+                "operation expected `{}`, got `{}`",
+                self.operation, context.operation
+            )));
+        }
+        if context.category != self.category {
+            return Err(deviation(format!(
+                "category expected `{}`, got `{}`",
+                self.category.category_name(),
+                context.category.category_name()
+            )));
+        }
+        match self
+            .required_keys
+            .iter()
+            .find(|key| !context.has_metadata(key))
+        {
+            Some(key) => Err(deviation(format!(
+                "required metadata key `{key}` is missing"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Add the default metadata the context does not already have.
+    fn apply_defaults(&self, context: &mut ErrorContext) {
+        for (key, value) in &self.defaults {
+            if !context.has_metadata(key) {
+                context.add_metadata(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_should_shape_contexts_of_their_operation() {
+        // Given: a registered template for cache lookups
+        ContextTemplate::new("template.test.cache_get", ErrorCategory::network())
+            .with_default("cache.backend", serde_json::json!("redis"))
+            .with_default("input", serde_json::json!("<unset>"))
+            .with_required_key("cache.key")
+            .register();
+        let template = ContextTemplate::get("template.test.cache_get").unwrap();
+
+        // When: instantiating it for a matching and a mismatching error
+        let network = template
+            .instantiate_for(&TylError::network("Connection reset"))
+            .with_metadata("cache.key", serde_json::json!("user:42"));
+        let validation = template
+            .instantiate_for(&TylError::validation("key", "Too long").with_input("user:42:x"));
+
+        // Then: defaults should be added and deviations reported
+        assert_eq!(network.operation, "template.test.cache_get");
+        assert_eq!(network.metadata["cache.backend"], "redis");
+        assert!(template.check(&network).is_ok());
+        let deviation = template.check(&validation).unwrap_err();
+        assert!(deviation.to_string().contains("expected `Network`"));
+        assert_eq!(deviation.code(), "internal");
+        let missing = template.check(&template.instantiate()).unwrap_err();
+        assert!(missing.to_string().contains("`cache.key` is missing"));
+
+        // And: the error's own metadata should win over defaults
+        assert_eq!(network.metadata["input"], "<unset>");
+        assert_eq!(validation.metadata["input"], "user:42:x");
+        assert!(ContextTemplate::get("template.test.unknown").is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Dead-letter records for message consumers.
+//!
+//! This module provides [`DeadLetter`], the record Kafka, SQS and other queue
+//! consumers across TYL publish to their dead-letter queues: the original
+//! message and headers, the [`TylErrorEnvelope`] of the last failure and the
+//! history of delivery attempts. [`DeadLetter::should_park`] applies the
+//! shared policy deciding whether a message is parked for manual handling or
+//! may be redelivered.
+
+use crate::clock;
+use crate::envelope::TylErrorEnvelope;
+use crate::error::{TylError, TylResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Current schema version written by [`DeadLetter::seal`].
+pub const DEAD_LETTER_SCHEMA_VERSION: u32 = 1;
+
+/// One failed delivery of a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    /// Number of the attempt, starting at 1.
+    pub attempt: usize,
+    /// Stable error code of the failure.
+    pub code: String,
+    /// Error message of the failure.
+    pub message: String,
+    /// Timestamp of the failure.
+    pub failed_at: DateTime<Utc>,
+}
+
+/// A message that could not be processed, as published to a dead-letter queue.
+///
+/// The payload is stored as base64 in the wire format, headers in key order.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{DeadLetter, TylError, TylErrorEnvelope};
+///
+/// let error = TylError::validation("amount", "Must be positive");
+/// let mut letter = DeadLetter::new(
+///     b"{\"amount\":-5}".to_vec(),
+///     TylErrorEnvelope::new("payments-consumer", error.clone()),
+/// )
+/// .with_header("topic", "payments");
+/// letter.record_attempt(&error);
+///
+/// // Validation errors will not succeed on redelivery.
+/// assert!(letter.should_park());
+///
+/// let opened = DeadLetter::open(&letter.seal().unwrap()).unwrap();
+/// assert_eq!(opened.payload, letter.payload);
+/// assert_eq!(opened.headers["topic"], "payments");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Version of the dead-letter format.
+    pub schema_version: u32,
+    /// Original message bytes.
+    #[serde(with = "base64_bytes")]
+    pub payload: Vec<u8>,
+    /// Original message headers.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Envelope of the last failure.
+    pub envelope: TylErrorEnvelope,
+    /// Failed deliveries, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<DeliveryAttempt>,
+    /// Timestamp when the record was created.
+    pub dead_lettered_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    /// Create a dead-letter record for a message and its last failure.
+    pub fn new<P: Into<Vec<u8>>>(payload: P, envelope: TylErrorEnvelope) -> Self {
+        Self {
+            schema_version: DEAD_LETTER_SCHEMA_VERSION,
+            payload: payload.into(),
+            headers: BTreeMap::new(),
+            envelope,
+            attempts: Vec::new(),
+            dead_lettered_at: clock::now(),
+        }
+    }
+
+    /// Add an original message header using builder pattern.
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add original message headers using builder pattern.
+    pub fn with_headers<K, V, I>(mut self, headers: I) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.headers
+            .extend(headers.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Append a failed delivery to the attempt history.
+    ///
+    /// The failure becomes the last failure of the envelope, which keeps its
+    /// service but drops the context of the previous failure.
+    pub fn record_attempt(&mut self, error: &TylError) {
+        self.attempts.push(DeliveryAttempt {
+            attempt: self.attempts.len() + 1,
+            code: error.code().to_string(),
+            message: error.to_string(),
+            failed_at: clock::now(),
+        });
+        let service = std::mem::take(&mut self.envelope.service);
+        self.envelope = TylErrorEnvelope::new(service, error.clone());
+    }
+
+    /// Check whether the message should be parked instead of redelivered.
+    ///
+    /// Messages are parked when the last failure was not retriable, or when
+    /// the recorded deliveries used up the retries allowed by the error's
+    /// category and `TYL_ERROR_MAX_RETRIES`.
+    pub fn should_park(&self) -> bool {
+        if !self.envelope.retriable {
+            return true;
+        }
+        let max_retries = TylError::max_retries();
+        let limit = self
+            .envelope
+            .error
+            .category()
+            .max_retries()
+            .map_or(max_retries, |limit| limit.min(max_retries));
+        self.attempts.len() > limit
+    }
+
+    /// Serialize this record into its wire format.
+    pub fn seal(&self) -> TylResult<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Parse a record from its wire format.
+    ///
+    /// # Errors
+    /// Returns a parsing error if the payload is malformed, or a validation
+    /// error if it was written with a newer, unsupported schema version.
+    pub fn open(bytes: &[u8]) -> TylResult<Self> {
+        let letter: Self = serde_json::from_slice(bytes)
+            .map_err(|err| TylError::parsing(format!("Invalid dead letter: {err}")))?;
+
+        if letter.schema_version > DEAD_LETTER_SCHEMA_VERSION {
+            return Err(TylError::validation(
+                "schema_version",
+                format!(
+                    "Unsupported dead letter version {} (max supported {DEAD_LETTER_SCHEMA_VERSION})",
+                    letter.schema_version
+                ),
+            ));
+        }
+
+        Ok(letter)
+    }
+}
+
+/// Standard base64 encoding with padding of the message payload.
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD
+            .decode(encoded)
+            .map_err(|err| D::Error::custom(format!("invalid base64 payload: {err}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_roundtrip_should_preserve_message_and_history() {
+        // Given: a record with binary payload, headers and two attempts
+        let error = TylError::network("Broker unavailable");
+        let mut letter = DeadLetter::new(
+            vec![0u8, 1, 2, 250, 255],
+            TylErrorEnvelope::new("orders-consumer", error.clone()),
+        )
+        .with_headers([("topic", "orders"), ("partition", "3")]);
+        letter.record_attempt(&error);
+        letter.record_attempt(&error);
+
+        // When: sealing and opening it
+        let bytes = letter.seal().unwrap();
+        let opened = DeadLetter::open(&bytes).unwrap();
+
+        // Then: payload, headers and attempts should survive
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["payload"], "AAEC+v8=");
+        assert_eq!(opened.payload, letter.payload);
+        assert_eq!(opened.headers["partition"], "3");
+        assert_eq!(opened.attempts, letter.attempts);
+        assert_eq!(opened.attempts[1].attempt, 2);
+        assert_eq!(opened.envelope.code, "network");
+    }
+
+    #[test]
+    fn test_should_park_should_follow_category_and_attempts() {
+        // Given: a retriable failure
+        let error = TylError::network("Broker unavailable");
+        let mut letter = DeadLetter::new("{}", TylErrorEnvelope::new("orders", error.clone()));
+
+        // When/Then: it should be redelivered until the retries are used up
+        for _ in 0..=TylError::max_retries() {
+            assert!(!letter.should_park());
+            letter.record_attempt(&error);
+        }
+        assert!(letter.should_park());
+
+        // And: non-retriable failures should be parked immediately
+        let invalid = TylError::validation("id", "Missing");
+        assert!(
+            DeadLetter::new("{}", TylErrorEnvelope::new("orders", invalid.clone())).should_park()
+        );
+
+        // And: a later non-retriable delivery should park a retriable first failure
+        let mut letter = DeadLetter::new("{}", TylErrorEnvelope::new("orders", error));
+        letter.record_attempt(&invalid);
+        assert_eq!(letter.envelope.code, "validation");
+        assert_eq!(letter.envelope.service, "orders");
+        assert!(letter.should_park());
+    }
+
+    #[test]
+    fn test_payload_base64_should_handle_every_padding_length() {
+        for payload in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let letter =
+                DeadLetter::new(payload, TylErrorEnvelope::new("s", TylError::internal("x")));
+            let opened = DeadLetter::open(&letter.seal().unwrap()).unwrap();
+            assert_eq!(opened.payload, payload.as_bytes());
+        }
+        let letter = DeadLetter::new("foo", TylErrorEnvelope::new("s", TylError::internal("x")));
+        let sealed = String::from_utf8(letter.seal().unwrap()).unwrap();
+        for corrupt in ["Zm9v!A==", "Zm9", "Z==="] {
+            let json = sealed.replace("\"Zm9v\"", &format!("\"{corrupt}\""));
+            let error = DeadLetter::open(json.as_bytes()).unwrap_err();
+            assert!(error.to_string().contains("base64"), "{error}");
+        }
+    }
+}
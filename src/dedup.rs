@@ -0,0 +1,340 @@
+//! Deduplication of reported errors.
+//!
+//! This module provides [`DedupReporter`], an [`ErrorReporter`] decorator for
+//! error trackers billed or rate limited per event. Errors are grouped by
+//! [fingerprint](crate::TylError::fingerprint): the first occurrence is
+//! forwarded at once, repeats within the window are held back, and a single
+//! summary carrying their count is forwarded when the window ends, on
+//! [`flush`](DedupReporter::flush), on [`shutdown`](crate::shutdown) once
+//! registered as a hook, or when the reporter is dropped.
+
+use crate::clock::Instant;
+use crate::error::TylResult;
+use crate::events::{ErrorEvent, ErrorReporter};
+use crate::log_limit::MAX_TRACKED_FINGERPRINTS;
+use crate::shutdown::ShutdownHook;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Metadata key holding the number of occurrences a summary stands for.
+pub const DUPLICATE_COUNT_KEY: &str = "duplicate_count";
+
+#[derive(Debug)]
+struct Window {
+    duplicates: u64,
+    latest: Option<ErrorEvent>,
+}
+
+/// Open windows, with their opening times oldest first.
+#[derive(Debug, Default)]
+struct Windows {
+    by_fingerprint: HashMap<String, Window>,
+    opened: BinaryHeap<Reverse<(Instant, String)>>,
+}
+
+impl Windows {
+    /// Remove the oldest window.
+    fn pop_oldest(&mut self) -> Option<Window> {
+        let Reverse((_, fingerprint)) = self.opened.pop()?;
+        self.by_fingerprint.remove(&fingerprint)
+    }
+
+    /// Remove the windows opened at or before `deadline`.
+    fn pop_opened_until(&mut self, deadline: Instant) -> Vec<Window> {
+        let mut closed = Vec::new();
+        while self
+            .opened
+            .peek()
+            .is_some_and(|Reverse((opened_at, _))| *opened_at <= deadline)
+        {
+            closed.extend(self.pop_oldest());
+        }
+        closed
+    }
+}
+
+impl Window {
+    /// Take the summary of the held back duplicates, if any.
+    fn summary(self) -> Option<ErrorEvent> {
+        let mut event = self.latest?;
+        event.context.metadata.insert(
+            DUPLICATE_COUNT_KEY.into(),
+            serde_json::json!(self.duplicates),
+        );
+        Some(event)
+    }
+}
+
+/// Reporter decorator suppressing duplicate errors within a window.
+///
+/// The window of a fingerprint opens when an occurrence is forwarded. Repeats
+/// until it closes are not forwarded; the latest of them is forwarded once the
+/// window has closed, with the number of repeats under
+/// [`DUPLICATE_COUNT_KEY`] in its context metadata. Closed windows are
+/// flushed whenever an event is reported and by
+/// [`flush_expired`](Self::flush_expired), which services call on an
+/// interval; [`flush`](Self::flush) and dropping the reporter flush every
+/// window, e.g. on shutdown. At most [`MAX_TRACKED_FINGERPRINTS`] windows
+/// are open at once (see [`with_max_windows`](Self::with_max_windows)):
+/// opening another closes the oldest one early.
+///
+/// # Example
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+/// use tyl_errors::events::{ErrorEvent, ErrorReporter};
+/// use tyl_errors::{DedupReporter, TylError};
+///
+/// let forwarded = Arc::new(Mutex::new(Vec::new()));
+/// let sink = Arc::clone(&forwarded);
+/// let reporter = DedupReporter::new(
+///     move |event: &ErrorEvent| sink.lock().unwrap().push(event.clone()),
+///     Duration::from_secs(60),
+/// );
+///
+/// for _ in 0..5 {
+///     let error = TylError::network("Connection reset");
+///     let context = error.to_context("orders.sync".into());
+///     reporter.report(&ErrorEvent::new(error, context));
+/// }
+/// reporter.flush();
+///
+/// let forwarded = forwarded.lock().unwrap();
+/// assert_eq!(forwarded.len(), 2);
+/// assert_eq!(forwarded[1].context.get_metadata("duplicate_count").unwrap(), 4);
+/// ```
+pub struct DedupReporter<R: ErrorReporter> {
+    inner: R,
+    window: Duration,
+    max_windows: usize,
+    windows: Mutex<Windows>,
+}
+
+impl<R: ErrorReporter> DedupReporter<R> {
+    /// Wrap `inner`, suppressing duplicates within `window`.
+    pub fn new(inner: R, window: Duration) -> Self {
+        Self {
+            inner,
+            window,
+            max_windows: MAX_TRACKED_FINGERPRINTS,
+            windows: Mutex::new(Windows::default()),
+        }
+    }
+
+    /// Keep at most `max` windows open, at least one.
+    pub fn with_max_windows(mut self, max: usize) -> Self {
+        self.max_windows = max.max(1);
+        self
+    }
+
+    /// Get the wrapped reporter.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Forward the summaries of every window, closing them.
+    pub fn flush(&self) {
+        let windows: Vec<Window> = {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            windows.opened.clear();
+            windows
+                .by_fingerprint
+                .drain()
+                .map(|(_, window)| window)
+                .collect()
+        };
+        self.forward_summaries(windows);
+    }
+
+    /// Forward the summaries of the windows that have closed.
+    pub fn flush_expired(&self) {
+        let closed = self.take_closed(Instant::now());
+        self.forward_summaries(closed);
+    }
+
+    fn report_at(&self, event: &ErrorEvent, now: Instant) {
+        let fingerprint = event.error.fingerprint();
+        let (closed, forward) = {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            let mut closed = self.closed_in(&mut windows, now);
+            let forward = match windows.by_fingerprint.get_mut(&fingerprint) {
+                Some(window) => {
+                    window.duplicates += 1;
+                    window.latest = Some(event.clone());
+                    false
+                }
+                None => {
+                    if windows.by_fingerprint.len() >= self.max_windows {
+                        closed.extend(windows.pop_oldest());
+                    }
+                    windows.opened.push(Reverse((now, fingerprint.clone())));
+                    windows.by_fingerprint.insert(
+                        fingerprint,
+                        Window {
+                            duplicates: 0,
+                            latest: None,
+                        },
+                    );
+                    true
+                }
+            };
+            (closed, forward)
+        };
+        self.forward_summaries(closed);
+        if forward {
+            self.inner.report(event);
+        }
+    }
+
+    /// Remove the windows closed at `now`.
+    fn take_closed(&self, now: Instant) -> Vec<Window> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        self.closed_in(&mut windows, now)
+    }
+
+    fn closed_in(&self, windows: &mut Windows, now: Instant) -> Vec<Window> {
+        match now.checked_sub(self.window) {
+            Some(deadline) => windows.pop_opened_until(deadline),
+            None => Vec::new(),
+        }
+    }
+
+    /// Forward summaries outside the lock, so the inner reporter may report errors itself.
+    fn forward_summaries(&self, windows: Vec<Window>) {
+        for summary in windows.into_iter().filter_map(Window::summary) {
+            self.inner.report(&summary);
+        }
+    }
+}
+
+impl<R: ErrorReporter> ErrorReporter for DedupReporter<R> {
+    /// Forward the event unless it repeats an open window.
+    fn report(&self, event: &ErrorEvent) {
+        self.report_at(event, Instant::now());
+    }
+}
+
+impl<R: ErrorReporter> ShutdownHook for DedupReporter<R> {
+    fn on_shutdown(&self, _remaining: Duration) -> TylResult<()> {
+        self.flush();
+        Ok(())
+    }
+}
+
+impl<R: ErrorReporter> Drop for DedupReporter<R> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TylError;
+    use std::sync::Arc;
+
+    fn event(error: TylError) -> ErrorEvent {
+        let context = error.to_context("dedup.test".to_string());
+        ErrorEvent::new(error, context)
+    }
+
+    fn recording() -> (Arc<Mutex<Vec<ErrorEvent>>>, impl ErrorReporter) {
+        let forwarded = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&forwarded);
+        (forwarded, move |event: &ErrorEvent| {
+            sink.lock().unwrap().push(event.clone())
+        })
+    }
+
+    #[test]
+    fn test_duplicates_should_be_summarized_when_the_window_closes() {
+        // Given: a reporter with a one minute window
+        let (forwarded, inner) = recording();
+        let reporter = DedupReporter::new(inner, Duration::from_secs(60));
+        let start = Instant::now();
+
+        // When: one fingerprint repeats three times and another appears once
+        for seconds in [0, 10, 20, 30] {
+            let error = TylError::network(format!("Connection reset after {seconds}ms"));
+            reporter.report_at(&event(error), start + Duration::from_secs(seconds));
+        }
+        reporter.report_at(
+            &event(TylError::validation("email", "Invalid")),
+            start + Duration::from_secs(40),
+        );
+
+        // Then: only the first occurrences should be forwarded so far
+        assert_eq!(forwarded.lock().unwrap().len(), 2);
+
+        // When: an occurrence arrives after the window closed
+        let error = TylError::network("Connection reset after 70ms");
+        reporter.report_at(&event(error), start + Duration::from_secs(70));
+
+        // Then: the summary and the new occurrence should be forwarded
+        let events = forwarded.lock().unwrap().clone();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[2].error.to_string(),
+            "Network error: Connection reset after 30ms"
+        );
+        assert_eq!(
+            events[2].context.get_metadata(DUPLICATE_COUNT_KEY).unwrap(),
+            3
+        );
+        assert!(!events[3].context.has_metadata(DUPLICATE_COUNT_KEY));
+    }
+
+    #[test]
+    fn test_full_reporters_should_close_the_oldest_window() {
+        // Given: a reporter with room for two windows, both holding duplicates
+        let (forwarded, inner) = recording();
+        let reporter = DedupReporter::new(inner, Duration::from_secs(3600)).with_max_windows(2);
+        let start = Instant::now();
+        for (seconds, message) in [(0, "first"), (1, "second"), (2, "first"), (3, "second")] {
+            let error = TylError::internal(message);
+            reporter.report_at(&event(error), start + Duration::from_secs(seconds));
+        }
+
+        // When: a third fingerprint appears, then repeats
+        for seconds in [4, 5] {
+            let error = TylError::timeout("third");
+            reporter.report_at(&event(error), start + Duration::from_secs(seconds));
+        }
+
+        // Then: the oldest window should be summarized and the new one deduplicated
+        let events = forwarded.lock().unwrap().clone();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2].error.to_string(), "Internal error: first");
+        assert_eq!(
+            events[2].context.get_metadata(DUPLICATE_COUNT_KEY).unwrap(),
+            1
+        );
+        assert_eq!(events[3].error.to_string(), "Timeout: third");
+    }
+
+    #[test]
+    fn test_drop_should_flush_pending_summaries() {
+        // Given: a reporter holding back duplicates
+        let (forwarded, inner) = recording();
+        let reporter = DedupReporter::new(inner, Duration::from_secs(3600));
+        for _ in 0..3 {
+            reporter.report(&event(TylError::timeout("Upstream slow")));
+        }
+        reporter.report(&event(TylError::internal("Once")));
+        reporter.flush_expired();
+        assert_eq!(forwarded.lock().unwrap().len(), 2);
+
+        // When: shutting down
+        drop(reporter);
+
+        // Then: only windows with duplicates should produce a summary
+        let events = forwarded.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[2].context.get_metadata(DUPLICATE_COUNT_KEY).unwrap(),
+            2
+        );
+    }
+}
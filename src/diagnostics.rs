@@ -0,0 +1,506 @@
+//! Dev-mode diagnostics feed built on the error event stream.
+//!
+//! Once [`enable`]d, this module keeps a bounded history of reported errors and
+//! per-fingerprint counters, and [`snapshot`] returns a serializable summary,
+//! along with the circuit breakers of [`ResiliencePipeline`]s that are not
+//! closed, suitable for serving from a local debug endpoint or rendering in a
+//! dev TUI.
+//!
+//! [`ResiliencePipeline`]: crate::ResiliencePipeline
+
+use crate::clock;
+use crate::error::TylSeverity;
+use crate::events::{self, ErrorEvent};
+use crate::redact::truncate_message;
+use crate::resilience::{self, CircuitState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+/// Maximum number of errors kept in the history buffer.
+pub const HISTORY_CAPACITY: usize = 256;
+
+/// Number of fingerprints listed in [`DiagnosticsSnapshot::top_fingerprints`].
+pub const TOP_FINGERPRINTS: usize = 10;
+
+/// Maximum number of fingerprints counted; when full, the least recently
+/// seen fingerprint is forgotten.
+pub const MAX_FINGERPRINTS: usize = 1024;
+
+/// Maximum number of tenants counted separately in
+/// [`DiagnosticsSnapshot::rates_by_tenant`].
+pub const MAX_TRACKED_TENANTS: usize = 1024;
+
+/// Tenant ID under which errors of tenants beyond [`MAX_TRACKED_TENANTS`]
+/// are counted together.
+pub const OTHER_TENANTS: &str = "[OTHER]";
+
+/// Summary of one recently reported error.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentError {
+    /// Identifier of the error occurrence.
+    pub error_id: Uuid,
+    /// When the error was reported.
+    pub occurred_at: DateTime<Utc>,
+    /// Operation that failed.
+    pub operation: String,
+    /// Stable error code.
+    pub code: String,
+    /// Error category name.
+    pub category: String,
+    /// Tenant of the failed operation, masked if sensitive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Severity at the time of reporting.
+    pub severity: TylSeverity,
+    /// Error message, truncated for display.
+    pub message: String,
+    /// Fingerprint grouping identical failures.
+    pub fingerprint: String,
+}
+
+/// Occurrence count of one fingerprint since diagnostics were enabled.
+#[derive(Debug, Clone, Serialize)]
+pub struct FingerprintCount {
+    /// The fingerprint.
+    pub fingerprint: String,
+    /// Stable error code of the fingerprinted failure.
+    pub code: String,
+    /// Message of the latest occurrence, truncated for display.
+    pub example_message: String,
+    /// Number of occurrences.
+    pub count: u64,
+    /// When the latest occurrence was reported.
+    pub last_seen: DateTime<Utc>,
+}
+
+/// A circuit breaker that is not closed.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenCircuitBreaker {
+    /// Name given with
+    /// [`ResiliencePipeline::with_named_circuit_breaker`](crate::ResiliencePipeline::with_named_circuit_breaker).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// [`CircuitState::Open`] or [`CircuitState::HalfOpen`].
+    pub state: CircuitState,
+    /// Consecutive failures recorded since the circuit was last closed.
+    pub consecutive_failures: u32,
+}
+
+/// Error rate of one category.
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryRate {
+    /// Error category name.
+    pub category: String,
+    /// Errors reported in the last minute.
+    pub last_minute: u64,
+    /// Errors reported since diagnostics were enabled.
+    pub total: u64,
+}
+
+/// Error rate of one tenant, for per-tenant SLOs.
+///
+/// Tenants flagged as sensitive are counted together under
+/// [`REDACTED`](crate::REDACTED), and tenants first seen once
+/// [`MAX_TRACKED_TENANTS`] are tracked together under [`OTHER_TENANTS`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantRate {
+    /// Tenant ID.
+    pub tenant_id: String,
+    /// Errors reported in the last minute.
+    pub last_minute: u64,
+    /// Errors reported since diagnostics were enabled.
+    pub total: u64,
+}
+
+/// Serializable summary of the error flow in this process.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsSnapshot {
+    /// When the snapshot was taken.
+    pub generated_at: DateTime<Utc>,
+    /// Whether diagnostics collection is enabled.
+    pub enabled: bool,
+    /// Errors reported since diagnostics were enabled.
+    pub total_errors: u64,
+    /// Most recent errors, newest first.
+    pub recent: Vec<RecentError>,
+    /// Most frequent fingerprints, most frequent first.
+    pub top_fingerprints: Vec<FingerprintCount>,
+    /// Error rates per category, highest total first.
+    pub rates_by_category: Vec<CategoryRate>,
+    /// Error rates per tenant, highest total first; errors without a tenant
+    /// are not listed.
+    pub rates_by_tenant: Vec<TenantRate>,
+    /// Circuit breakers of live pipelines that are not closed, by name;
+    /// listed even when diagnostics collection is not enabled.
+    pub open_circuit_breakers: Vec<OpenCircuitBreaker>,
+}
+
+/// Counter of a fingerprint, with when it was last seen.
+struct TrackedFingerprint {
+    count: FingerprintCount,
+    /// Position of the fingerprint in [`Collector::fingerprint_recency`].
+    last_seen: u64,
+}
+
+#[derive(Default)]
+struct Collector {
+    total: u64,
+    history: VecDeque<RecentError>,
+    fingerprints: HashMap<String, TrackedFingerprint>,
+    /// Fingerprints by the error number they were last seen at, oldest first.
+    fingerprint_recency: BTreeMap<u64, String>,
+    category_totals: HashMap<String, u64>,
+    tenant_totals: HashMap<String, u64>,
+}
+
+impl Collector {
+    fn record(&mut self, event: &ErrorEvent) {
+        let message = truncate_message(&event.context.message).into_owned();
+        let fingerprint = event.error.fingerprint();
+        let recent = RecentError {
+            error_id: event.context.error_id,
+            occurred_at: event.context.occurred_at,
+            operation: event.context.operation.clone(),
+            code: event.error.code().to_string(),
+            category: event.error.category().category_name().to_string(),
+            tenant_id: event.context.redacted_tenant_id().map(str::to_string),
+            severity: event.severity,
+            message: message.clone(),
+            fingerprint: fingerprint.clone(),
+        };
+
+        self.total += 1;
+        *self
+            .category_totals
+            .entry(recent.category.clone())
+            .or_insert(0) += 1;
+        if let Some(tenant_id) = &recent.tenant_id {
+            let tenant_id = self.tracked_tenant(tenant_id).to_string();
+            *self.tenant_totals.entry(tenant_id).or_insert(0) += 1;
+        }
+
+        match self.fingerprints.get(&fingerprint) {
+            Some(tracked) => {
+                self.fingerprint_recency.remove(&tracked.last_seen);
+            }
+            None if self.fingerprints.len() >= MAX_FINGERPRINTS => {
+                if let Some((_, oldest)) = self.fingerprint_recency.pop_first() {
+                    self.fingerprints.remove(&oldest);
+                }
+            }
+            None => {}
+        }
+        self.fingerprint_recency
+            .insert(self.total, fingerprint.clone());
+        let tracked = self
+            .fingerprints
+            .entry(fingerprint.clone())
+            .or_insert_with(|| TrackedFingerprint {
+                count: FingerprintCount {
+                    fingerprint,
+                    code: recent.code.clone(),
+                    example_message: String::new(),
+                    count: 0,
+                    last_seen: recent.occurred_at,
+                },
+                last_seen: 0,
+            });
+        tracked.last_seen = self.total;
+        tracked.count.count += 1;
+        tracked.count.example_message = message;
+        tracked.count.last_seen = recent.occurred_at;
+
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(recent);
+    }
+
+    /// Get the tenant ID errors of `tenant_id` are counted under.
+    fn tracked_tenant<'a>(&self, tenant_id: &'a str) -> &'a str {
+        if self.tenant_totals.contains_key(tenant_id)
+            || self.tenant_totals.len() < MAX_TRACKED_TENANTS
+        {
+            tenant_id
+        } else {
+            OTHER_TENANTS
+        }
+    }
+
+    fn snapshot(&self) -> DiagnosticsSnapshot {
+        let now = clock::now();
+        let minute_ago = now - ChronoDuration::minutes(1);
+
+        let mut top_fingerprints: Vec<FingerprintCount> = self
+            .fingerprints
+            .values()
+            .map(|tracked| tracked.count.clone())
+            .collect();
+        top_fingerprints.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+        top_fingerprints.truncate(TOP_FINGERPRINTS);
+
+        // One pass over the history counts the last minute of every category and tenant.
+        let mut category_last_minute: HashMap<&str, u64> = HashMap::new();
+        let mut tenant_last_minute: HashMap<&str, u64> = HashMap::new();
+        for error in self.history.iter().filter(|e| e.occurred_at >= minute_ago) {
+            *category_last_minute.entry(&error.category).or_insert(0) += 1;
+            if let Some(tenant_id) = &error.tenant_id {
+                let tenant_id = if self.tenant_totals.contains_key(tenant_id) {
+                    tenant_id
+                } else {
+                    OTHER_TENANTS
+                };
+                *tenant_last_minute.entry(tenant_id).or_insert(0) += 1;
+            }
+        }
+
+        let mut rates_by_category: Vec<CategoryRate> = self
+            .category_totals
+            .iter()
+            .map(|(category, total)| CategoryRate {
+                category: category.clone(),
+                last_minute: category_last_minute
+                    .get(category.as_str())
+                    .copied()
+                    .unwrap_or(0),
+                total: *total,
+            })
+            .collect();
+        rates_by_category.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| a.category.cmp(&b.category))
+        });
+
+        let mut rates_by_tenant: Vec<TenantRate> = self
+            .tenant_totals
+            .iter()
+            .map(|(tenant_id, total)| TenantRate {
+                tenant_id: tenant_id.clone(),
+                last_minute: tenant_last_minute
+                    .get(tenant_id.as_str())
+                    .copied()
+                    .unwrap_or(0),
+                total: *total,
+            })
+            .collect();
+        rates_by_tenant.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| a.tenant_id.cmp(&b.tenant_id))
+        });
+
+        DiagnosticsSnapshot {
+            generated_at: now,
+            enabled: true,
+            total_errors: self.total,
+            recent: self.history.iter().rev().cloned().collect(),
+            top_fingerprints,
+            rates_by_category,
+            rates_by_tenant,
+            open_circuit_breakers: resilience::open_circuit_breakers(),
+        }
+    }
+}
+
+fn collector() -> &'static OnceLock<Mutex<Collector>> {
+    static COLLECTOR: OnceLock<Mutex<Collector>> = OnceLock::new();
+    &COLLECTOR
+}
+
+/// Start collecting diagnostics from the error event stream.
+///
+/// Idempotent; only errors reported after the first call are collected.
+pub fn enable() {
+    collector().get_or_init(|| {
+        events::subscribe_fn(|event| {
+            if let Some(collector) = collector().get() {
+                collector
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .record(event);
+            }
+        });
+        Mutex::new(Collector::default())
+    });
+}
+
+/// Take a snapshot of the collected diagnostics.
+///
+/// Returns an empty snapshot with `enabled: false` if [`enable`] was never called.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{diagnostics, TylError};
+///
+/// diagnostics::enable();
+/// TylError::network("Upstream timeout").report("catalog.fetch");
+///
+/// let snapshot = diagnostics::snapshot();
+/// assert!(snapshot.recent.iter().any(|e| e.operation == "catalog.fetch"));
+/// let json = serde_json::to_string(&snapshot).unwrap();
+/// assert!(json.contains("top_fingerprints"));
+/// ```
+pub fn snapshot() -> DiagnosticsSnapshot {
+    match collector().get() {
+        Some(collector) => collector
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .snapshot(),
+        None => DiagnosticsSnapshot {
+            generated_at: clock::now(),
+            enabled: false,
+            total_errors: 0,
+            recent: Vec::new(),
+            top_fingerprints: Vec::new(),
+            rates_by_category: Vec::new(),
+            rates_by_tenant: Vec::new(),
+            open_circuit_breakers: resilience::open_circuit_breakers(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TylError;
+
+    #[test]
+    fn test_collector_should_group_by_fingerprint_and_category() {
+        // Given: a collector fed with repeated and distinct errors
+        let mut collector = Collector::default();
+        for id in 0..3 {
+            let error = TylError::database(format!("Deadlock on row {id}"));
+            let context = error.to_context("orders.save".to_string());
+            collector.record(&ErrorEvent::new(error, context));
+        }
+        let error = TylError::validation("email", "invalid");
+        let context = error.to_context("users.create".to_string());
+        collector.record(&ErrorEvent::new(error, context));
+
+        // When: taking a snapshot
+        let snapshot = collector.snapshot();
+
+        // Then: counts should be aggregated
+        assert_eq!(snapshot.total_errors, 4);
+        assert_eq!(snapshot.recent[0].operation, "users.create");
+        assert_eq!(snapshot.top_fingerprints[0].count, 3);
+        assert_eq!(snapshot.rates_by_category[0].category, "Transient");
+        assert_eq!(snapshot.rates_by_category[0].last_minute, 3);
+    }
+
+    #[test]
+    fn test_collector_should_rate_errors_per_tenant() {
+        // Given: errors of two tenants, one of them sensitive, and one without tenant
+        let mut collector = Collector::default();
+        let error = TylError::network("Connection reset");
+        for (tenant, sensitive) in [("acme", false), ("acme", false), ("globex", true)] {
+            let context = error.to_context("checkout".to_string());
+            let context = if sensitive {
+                context.with_sensitive_tenant_id(tenant)
+            } else {
+                context.with_tenant_id(tenant)
+            };
+            collector.record(&ErrorEvent::new(error.clone(), context));
+        }
+        collector.record(&ErrorEvent::new(
+            error.clone(),
+            error.to_context("checkout".to_string()),
+        ));
+
+        // When: taking a snapshot
+        let snapshot = collector.snapshot();
+
+        // Then: each tenant should get its own rate, sensitive ones masked
+        let rates: Vec<(&str, u64)> = snapshot
+            .rates_by_tenant
+            .iter()
+            .map(|rate| (rate.tenant_id.as_str(), rate.total))
+            .collect();
+        assert_eq!(rates, [("acme", 2), ("[REDACTED]", 1)]);
+        assert_eq!(snapshot.rates_by_tenant[0].last_minute, 2);
+        assert_eq!(snapshot.recent[1].tenant_id.as_deref(), Some("[REDACTED]"));
+        assert_eq!(snapshot.recent[0].tenant_id, None);
+    }
+
+    #[test]
+    fn test_tenants_beyond_the_cap_should_be_counted_together() {
+        // Given: errors of more tenants than are tracked
+        let mut collector = Collector::default();
+        let error = TylError::network("Connection reset");
+        for tenant in 0..MAX_TRACKED_TENANTS + 2 {
+            let context = error
+                .to_context("checkout".to_string())
+                .with_tenant_id(format!("tenant-{tenant}"));
+            collector.record(&ErrorEvent::new(error.clone(), context));
+        }
+
+        // When: taking a snapshot
+        let snapshot = collector.snapshot();
+
+        // Then: the last tenants should share one rate
+        assert_eq!(snapshot.rates_by_tenant.len(), MAX_TRACKED_TENANTS + 1);
+        let other = snapshot
+            .rates_by_tenant
+            .iter()
+            .find(|rate| rate.tenant_id == OTHER_TENANTS)
+            .unwrap();
+        assert_eq!((other.total, other.last_minute), (2, 2));
+    }
+
+    #[test]
+    fn test_collector_should_forget_the_least_recently_seen_fingerprint() {
+        // Given: a full collector whose first fingerprint was seen again last
+        let mut collector = Collector::default();
+        let record = |collector: &mut Collector, field: &str| {
+            let error = TylError::validation(field, "invalid");
+            let context = error.to_context("forms.submit".to_string());
+            collector.record(&ErrorEvent::new(error, context));
+        };
+        for field in 0..MAX_FINGERPRINTS {
+            record(&mut collector, &format!("field_{field}"));
+        }
+        record(&mut collector, "field_0");
+
+        // When: a new fingerprint occurs
+        record(&mut collector, "other");
+
+        // Then: the least recently seen fingerprint should be forgotten
+        assert_eq!(collector.fingerprints.len(), MAX_FINGERPRINTS);
+        assert_eq!(collector.fingerprint_recency.len(), MAX_FINGERPRINTS);
+        let counted = |field: &str| {
+            let fingerprint = TylError::validation(field, "invalid").fingerprint();
+            collector
+                .fingerprints
+                .get(&fingerprint)
+                .map(|tracked| tracked.count.count)
+        };
+        assert_eq!(counted("field_0"), Some(2));
+        assert_eq!(counted("field_1"), None);
+        assert_eq!(counted("other"), Some(1));
+        assert_eq!(collector.snapshot().top_fingerprints[0].count, 2);
+    }
+
+    #[test]
+    fn test_history_should_be_bounded() {
+        // Given: more errors than the history capacity
+        let mut collector = Collector::default();
+        for _ in 0..HISTORY_CAPACITY + 5 {
+            let error = TylError::internal("boom");
+            let context = error.to_context("jobs.run".to_string());
+            collector.record(&ErrorEvent::new(error, context));
+        }
+
+        // When/Then: only the newest entries should be kept
+        let snapshot = collector.snapshot();
+        assert_eq!(snapshot.recent.len(), HISTORY_CAPACITY);
+        assert_eq!(snapshot.total_errors, (HISTORY_CAPACITY + 5) as u64);
+    }
+}
@@ -0,0 +1,288 @@
+//! Domain namespaces grouping error codes.
+//!
+//! Error codes form a flat space: `database`, `conflict` and `custom` say
+//! what went wrong but not in which part of the system. This module provides
+//! [`TylDomain`], attached to errors through their category (see
+//! [`ErrorCategory::with_domain`](crate::ErrorCategory::with_domain)) and
+//! used as the prefix of [`TylError::qualified_code`], the code of problem
+//! details, [error stats](crate::ErrorStats) and OpenAPI components, as a
+//! label of the error stats and as a filter of alert rules
+//! (`AlertRule::domains`, `alert` feature). Services add their own domains
+//! with [`TylDomain::register`].
+
+use crate::category::ErrorCategory;
+use crate::error::{TylError, TylResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+/// Part of the system an error belongs to.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorCategory, TylDomain, TylError};
+///
+/// let search = TylDomain::register("search").unwrap();
+/// let category = ErrorCategory::transient().with_domain(search.clone());
+/// let error = TylError::business_logic("Index is rebuilding", category.into_classifier());
+///
+/// assert_eq!(error.domain(), Some(search));
+/// assert_eq!(error.qualified_code(), "search.custom");
+/// assert_eq!(TylError::database("Deadlock").qualified_code(), "storage.database");
+/// assert_eq!("payments".parse::<TylDomain>().unwrap(), TylDomain::Payments);
+/// ```
+///
+/// Deserializing accepts any name, so errors of domains only registered by
+/// the sending service still decode; parsing ([`TylDomain::parse`],
+/// `FromStr`) only accepts built-in and registered domains.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum TylDomain {
+    /// Databases, caches and object stores.
+    Storage,
+    /// Message brokers, queues and event streams.
+    Messaging,
+    /// Authentication and authorization.
+    Auth,
+    /// Payment processing and billing.
+    Payments,
+    /// Domain added by the service with [`TylDomain::register`], or received
+    /// from another service.
+    Custom(CustomDomain),
+}
+
+/// Name of a [`TylDomain::Custom`] domain.
+///
+/// Only built by [`TylDomain::register`], [`TylDomain::parse`] and
+/// deserialization, so it never holds the name of a built-in domain.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CustomDomain(String);
+
+impl CustomDomain {
+    /// Get the domain name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+fn registered() -> &'static RwLock<BTreeSet<String>> {
+    static DOMAINS: OnceLock<RwLock<BTreeSet<String>>> = OnceLock::new();
+    DOMAINS.get_or_init(|| RwLock::new(BTreeSet::new()))
+}
+
+impl TylDomain {
+    /// Built-in domains.
+    pub const BUILTIN: [TylDomain; 4] = [
+        TylDomain::Storage,
+        TylDomain::Messaging,
+        TylDomain::Auth,
+        TylDomain::Payments,
+    ];
+
+    /// Get the name of this domain.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TylDomain::Storage => "storage",
+            TylDomain::Messaging => "messaging",
+            TylDomain::Auth => "auth",
+            TylDomain::Payments => "payments",
+            TylDomain::Custom(name) => name.as_str(),
+        }
+    }
+
+    /// Get the built-in domain with this name.
+    fn builtin(name: &str) -> Option<TylDomain> {
+        Self::BUILTIN.into_iter().find(|d| d.as_str() == name)
+    }
+
+    /// Register a domain of the service, so it can be parsed (e.g. in alert
+    /// rules) and shows up in [`TylDomain::all`].
+    ///
+    /// Registering a built-in name returns the built-in domain.
+    ///
+    /// # Errors
+    /// A validation error on the `domain` field unless the name is lowercase
+    /// `snake_case`: ASCII lowercase letters, digits and underscores,
+    /// starting with a letter.
+    pub fn register<S: Into<String>>(name: S) -> TylResult<TylDomain> {
+        let name = name.into();
+        if let Some(builtin) = Self::builtin(&name) {
+            return Ok(builtin);
+        }
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+        if !valid {
+            return Err(TylError::validation(
+                "domain",
+                "Invalid domain name: expected lowercase snake_case",
+            )
+            .with_input(name));
+        }
+        registered()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.clone());
+        Ok(TylDomain::Custom(CustomDomain(name)))
+    }
+
+    /// Get the built-in or registered domain with this name.
+    pub fn parse(name: &str) -> Option<TylDomain> {
+        if let Some(builtin) = Self::builtin(name) {
+            return Some(builtin);
+        }
+        registered()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(name)
+            .then(|| TylDomain::Custom(CustomDomain(name.to_string())))
+    }
+
+    /// Get the built-in domains followed by the registered ones.
+    pub fn all() -> Vec<TylDomain> {
+        let custom = registered().read().unwrap_or_else(|e| e.into_inner());
+        Self::BUILTIN
+            .into_iter()
+            .chain(
+                custom
+                    .iter()
+                    .cloned()
+                    .map(CustomDomain)
+                    .map(TylDomain::Custom),
+            )
+            .collect()
+    }
+}
+
+impl fmt::Display for TylDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TylDomain {
+    type Err = TylError;
+
+    fn from_str(name: &str) -> TylResult<Self> {
+        Self::parse(name).ok_or_else(|| {
+            TylError::validation("domain", "Unknown domain").with_input(name.to_string())
+        })
+    }
+}
+
+impl From<String> for TylDomain {
+    /// Get the built-in domain with this name, or a custom domain of any
+    /// other name, registered or not.
+    fn from(name: String) -> Self {
+        Self::builtin(&name).unwrap_or(TylDomain::Custom(CustomDomain(name)))
+    }
+}
+
+impl From<TylDomain> for String {
+    fn from(domain: TylDomain) -> Self {
+        match domain {
+            TylDomain::Custom(CustomDomain(name)) => name,
+            builtin => builtin.as_str().to_string(),
+        }
+    }
+}
+
+/// Get the plain [code](TylError::code) of a possibly
+/// [qualified](TylError::qualified_code) one.
+pub(crate) fn unqualified_code(code: &str) -> &str {
+    code.rsplit_once('.').map_or(code, |(_, code)| code)
+}
+
+impl TylError {
+    /// Get the domain this error belongs to.
+    ///
+    /// The domain set on the category (see
+    /// [`ErrorCategory::with_domain`](crate::ErrorCategory::with_domain)),
+    /// otherwise [`TylDomain::Storage`] for database errors and
+    /// [`TylDomain::Auth`] for authentication and permission errors.
+    pub fn domain(&self) -> Option<TylDomain> {
+        self.domain_in(&self.category())
+    }
+
+    /// Get the domain of this error given its already resolved category.
+    pub(crate) fn domain_in(&self, category: &ErrorCategory) -> Option<TylDomain> {
+        if let Some(domain) = category.domain() {
+            return Some(domain.clone());
+        }
+        match self {
+            TylError::Database { .. } => Some(TylDomain::Storage),
+            TylError::Unauthenticated { .. } | TylError::PermissionDenied { .. } => {
+                Some(TylDomain::Auth)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the [code](TylError::code) of this error prefixed with its
+    /// [domain](TylError::domain), e.g. `storage.database`; the plain code
+    /// for errors without a domain.
+    pub fn qualified_code(&self) -> String {
+        self.qualified_code_in(&self.category())
+    }
+
+    /// Get the qualified code of this error given its already resolved category.
+    pub(crate) fn qualified_code_in(&self, category: &ErrorCategory) -> String {
+        match self.domain_in(category) {
+            Some(domain) => format!("{domain}.{}", self.code()),
+            None => self.code().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+
+    #[test]
+    fn test_domains_should_register_parse_and_serialize() {
+        // Given: a registered service domain
+        let ledger = TylDomain::register("domain_test_ledger").unwrap();
+
+        // When/Then: it should parse and serialize like built-in domains
+        assert_eq!(TylDomain::parse("domain_test_ledger"), Some(ledger.clone()));
+        assert_eq!(TylDomain::register("auth").unwrap(), TylDomain::Auth);
+        assert!(TylDomain::register("Ledger").is_err());
+        assert!(TylDomain::register("9lives").is_err());
+        assert!("unregistered".parse::<TylDomain>().is_err());
+        assert!(TylDomain::all().contains(&ledger));
+        let json = serde_json::to_string(&[TylDomain::Messaging, ledger.clone()]).unwrap();
+        assert_eq!(json, r#"["messaging","domain_test_ledger"]"#);
+        let restored: Vec<TylDomain> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, [TylDomain::Messaging, ledger]);
+
+        // And: domains only known to the sender should still deserialize,
+        // and built-in names should never become custom domains
+        let peer: TylDomain = serde_json::from_str(r#""domain_test_peer""#).unwrap();
+        assert_eq!(peer.as_str(), "domain_test_peer");
+        assert!(TylDomain::parse("domain_test_peer").is_none());
+        let storage: TylDomain = serde_json::from_str(r#""storage""#).unwrap();
+        assert_eq!(storage, TylDomain::Storage);
+    }
+
+    #[test]
+    fn test_errors_should_take_the_domain_of_their_category() {
+        // Given: a category attached to the payments domain
+        let declined = ErrorCategory::permanent()
+            .with_domain(TylDomain::Payments)
+            .with_max_attempts(0);
+        let error = TylError::business_logic("Card declined", declined.into_classifier());
+
+        // When/Then: the domain should qualify the error's code
+        assert_eq!(error.domain(), Some(TylDomain::Payments));
+        assert_eq!(error.qualified_code(), "payments.custom");
+        assert_eq!(
+            TylError::permission_denied("bob", "read", "ledger").qualified_code(),
+            "auth.permission_denied"
+        );
+        assert_eq!(TylError::timeout("Slow").qualified_code(), "timeout");
+    }
+}
@@ -0,0 +1,243 @@
+//! Flat error representation for embedding in API responses.
+//!
+//! This module provides [`TylErrorDto`], an alternative to the externally
+//! tagged serde representation of [`TylError`] (`{"NotFound": {...}}`). The
+//! DTO always has the same three members, so it can be embedded with
+//! `#[serde(flatten)]` in response envelopes, and converts back to the
+//! original error without losing information.
+
+use crate::category::restore_category;
+use crate::error::{TylError, TylResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Stable `{"code", "message", "details"}` representation of a [`TylError`].
+///
+/// `details` holds the structured fields of the error variant (plus the
+/// category of custom errors), which is enough to restore the error with
+/// [`TryFrom`]. Being lossless, it includes sensitive fields such as the
+/// subject of a denied permission; prefer [`TylError::to_problem_details`]
+/// for responses to untrusted clients.
+///
+/// With the `schemars` feature, implements `JsonSchema`, so TypeScript or
+/// Python models can be generated from `schemars::schema_for!(TylErrorDto)`.
+///
+/// # Example
+/// ```rust
+/// use serde::Serialize;
+/// use tyl_errors::{TylError, TylErrorDto};
+///
+/// #[derive(Serialize)]
+/// struct ErrorResponse {
+///     request_id: String,
+///     #[serde(flatten)]
+///     error: TylErrorDto,
+/// }
+///
+/// let error = TylError::not_found("user", "42");
+/// let response = ErrorResponse { request_id: "r-1".to_string(), error: (&error).into() };
+/// let json = serde_json::to_value(&response).unwrap();
+///
+/// assert_eq!(json["code"], "not_found");
+/// assert_eq!(json["message"], "Not found: user with id 42");
+/// assert_eq!(json["details"]["resource"], "user");
+///
+/// let restored = TylError::try_from(response.error).unwrap();
+/// assert_eq!(restored.to_string(), error.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TylErrorDto {
+    /// Stable error code (see [`TylError::code`]).
+    pub code: String,
+    /// Human-readable message (the error's `Display` output).
+    pub message: String,
+    /// Structured fields of the error.
+    #[serde(default)]
+    pub details: Map<String, Value>,
+}
+
+impl From<&TylError> for TylErrorDto {
+    fn from(error: &TylError) -> Self {
+        let mut details = match error {
+            TylError::Unknown { variant, raw } => {
+                let mut details = Map::new();
+                details.insert("variant".to_string(), variant.as_str().into());
+                details.insert("raw".to_string(), raw.clone());
+                details
+            }
+            _ => match serde_json::to_value(error) {
+                Ok(Value::Object(tagged)) => match tagged.into_iter().next() {
+                    Some((_, Value::Object(fields))) => fields,
+                    _ => Map::new(),
+                },
+                _ => Map::new(),
+            },
+        };
+
+        if let TylError::Custom { .. } = error {
+            let category = error.category();
+            details.insert("category".to_string(), category.category_name().into());
+            details.insert("retriable".to_string(), category.is_retriable().into());
+        }
+
+        Self {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<TylError> for TylErrorDto {
+    fn from(error: TylError) -> Self {
+        Self::from(&error)
+    }
+}
+
+impl TryFrom<TylErrorDto> for TylError {
+    type Error = TylError;
+
+    /// Restore the error from its DTO.
+    ///
+    /// # Errors
+    /// A parsing error if the code is unknown or the details do not match it.
+    fn try_from(dto: TylErrorDto) -> TylResult<Self> {
+        let TylErrorDto {
+            code, mut details, ..
+        } = dto;
+
+        if code == "unknown" {
+            let variant = match details.remove("variant") {
+                Some(Value::String(variant)) => variant,
+                _ => return Err(TylError::parsing("Unknown error without variant name")),
+            };
+            let raw = details.remove("raw").unwrap_or_default();
+            return Ok(TylError::Unknown { variant, raw });
+        }
+
+        let variant = match code.as_str() {
+            "database" => "Database",
+            "network" => "Network",
+            "validation" => "Validation",
+            "not_found" => "NotFound",
+            "conflict" => "Conflict",
+            "internal" => "Internal",
+            "unauthenticated" => "Unauthenticated",
+            "permission_denied" => "PermissionDenied",
+            "timeout" => "Timeout",
+            "cancelled" => "Cancelled",
+            "configuration" | "configuration_missing_key" | "configuration_invalid_key" => {
+                "Configuration"
+            }
+            "invariant_violated" => "InvariantViolated",
+            "not_implemented" => "NotImplemented",
+            // Custom errors of categories with their own code.
+            _ if code == "custom" || details.contains_key("category") => "Custom",
+            other => return Err(TylError::parsing(format!("Unknown error code '{other}'"))),
+        };
+
+        let category = details.remove("category");
+        let retriable = details.remove("retriable");
+        let mut error: TylError = serde_json::from_value(serde_json::json!({ variant: details }))
+            .map_err(|err| {
+            TylError::parsing(format!("Invalid details for code '{code}': {err}"))
+        })?;
+
+        if let (TylError::Custom { classifier, .. }, Some(Value::String(name))) =
+            (&mut error, category)
+        {
+            let retriable = retriable.and_then(|r| r.as_bool()).unwrap_or(false);
+            *classifier = restore_category(&name, retriable).into_classifier();
+        }
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+    use crate::retry::RetryTelemetry;
+
+    #[test]
+    fn test_dto_should_round_trip_every_variant() {
+        // Given: errors covering plain, structured, custom and wrapped variants
+        let payment =
+            ErrorCategory::custom_fn("DtoTestPayment", true, |_| std::time::Duration::ZERO);
+        let errors = vec![
+            TylError::database("Deadlock"),
+            TylError::validation("email", "Invalid")
+                .with_input("bob@")
+                .with_constraint(crate::ValidationConstraint::format("email")),
+            TylError::permission_denied("user:1", "delete", "doc:2"),
+            TylError::config_key("db.port", "an integer", "http"),
+            TylError::not_implemented_tracked("export", "#42"),
+            TylError::business_logic("Card declined", payment.into_classifier()),
+            TylError::timeout("slow").with_retry_telemetry(RetryTelemetry {
+                attempts: 3,
+                elapsed_ms: 120,
+                delays_ms: vec![40, 80],
+                attempt_records: vec![{
+                    let mut record = crate::AttemptRecord::default();
+                    record.attempt = 1;
+                    record.duration_ms = 30;
+                    record.code = "timeout".to_string();
+                    record.insert("host", "db-1");
+                    record
+                }],
+            }),
+        ];
+
+        for error in errors {
+            // When: converting to JSON through the DTO and back
+            let json = serde_json::to_string(&TylErrorDto::from(&error)).unwrap();
+            let dto: TylErrorDto = serde_json::from_str(&json).unwrap();
+            let restored = TylError::try_from(dto).unwrap();
+
+            // Then: the error should be unchanged
+            assert_eq!(restored.to_string(), error.to_string());
+            assert_eq!(restored.code(), error.code());
+            assert_eq!(restored.metadata(), error.metadata());
+            assert_eq!(
+                restored.category().category_name(),
+                error.category().category_name()
+            );
+            assert_eq!(restored.is_retriable(), error.is_retriable());
+        }
+    }
+
+    #[test]
+    fn test_unknown_codes_should_be_rejected() {
+        let dto = TylErrorDto {
+            code: "teapot".to_string(),
+            message: "I'm a teapot".to_string(),
+            details: Map::new(),
+        };
+        assert_eq!(TylError::try_from(dto).unwrap_err().code(), "validation");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_schemas_should_describe_the_serialized_form() {
+        use crate::context::ErrorContext;
+
+        // Given/When: the schemas of the DTO and the error context
+        let dto = serde_json::to_value(schemars::schema_for!(TylErrorDto)).unwrap();
+        let context = serde_json::to_value(schemars::schema_for!(ErrorContext)).unwrap();
+
+        // Then: they should list the serialized members only
+        assert_eq!(dto["required"], serde_json::json!(["code", "message"]));
+        assert_eq!(dto["properties"]["details"]["type"], "object");
+        let properties = context["properties"].as_object().unwrap();
+        assert!(properties.contains_key("error_id"));
+        assert!(properties.contains_key("idempotency_key"));
+        assert!(!properties.contains_key("category"));
+        assert!(!properties.contains_key("occurred_at_mono"));
+        assert_eq!(
+            context["properties"]["metadata"]["additionalProperties"],
+            serde_json::json!(true)
+        );
+        assert_eq!(context["properties"]["occurred_at"]["format"], "date-time");
+    }
+}
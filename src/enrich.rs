@@ -0,0 +1,194 @@
+//! Process-wide enrichment of error contexts.
+//!
+//! This module keeps a registry of [`ErrorEnricher`]s that run whenever an
+//! [`ErrorContext`] is created, including contexts created by
+//! [`TylError::to_context`](crate::TylError::to_context) and
+//! [`TylError::report`](crate::TylError::report). Platform-level fields such
+//! as hostname, build version or region are registered once at startup
+//! instead of being attached at every call site.
+
+use crate::context::ErrorContext;
+use crate::meta_key::MetaKey;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Callback adding fields to every newly created error context.
+pub trait ErrorEnricher: Send + Sync {
+    /// Add fields to a freshly created context.
+    fn enrich(&self, context: &mut ErrorContext);
+}
+
+impl<F> ErrorEnricher for F
+where
+    F: Fn(&mut ErrorContext) + Send + Sync,
+{
+    fn enrich(&self, context: &mut ErrorContext) {
+        self(context)
+    }
+}
+
+/// Identifier of a registered enricher, used with [`unregister`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnricherId(u64);
+
+static ENRICHER_COUNT: AtomicUsize = AtomicUsize::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static ENRICHING: Cell<bool> = const { Cell::new(false) };
+}
+
+type Registration = (EnricherId, Arc<dyn ErrorEnricher>);
+
+fn enrichers() -> &'static Mutex<Vec<Registration>> {
+    static ENRICHERS: OnceLock<Mutex<Vec<Registration>>> = OnceLock::new();
+    ENRICHERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Register an enricher run for every new error context.
+///
+/// Enrichers run in registration order, before any metadata added by the
+/// caller, so call-site values win over process-wide defaults.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{enrich, ErrorCategory, ErrorContext, TylError};
+///
+/// let id = enrich::register(|context: &mut ErrorContext| {
+///     if context.operation.starts_with("docs::") {
+///         context.add_metadata("region", serde_json::json!("eu-west-1"));
+///     }
+/// });
+///
+/// let context = TylError::network("Reset").to_context("docs::fetch".to_string());
+/// assert_eq!(context.metadata["region"], "eu-west-1");
+/// enrich::unregister(id);
+/// ```
+pub fn register<E: ErrorEnricher + 'static>(enricher: E) -> EnricherId {
+    let id = EnricherId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut enrichers = enrichers().lock().unwrap_or_else(|e| e.into_inner());
+    enrichers.push((id, Arc::new(enricher)));
+    ENRICHER_COUNT.store(enrichers.len(), Ordering::Release);
+    id
+}
+
+/// Register a fixed metadata entry added to every new error context.
+pub fn register_metadata<K: Into<MetaKey>>(key: K, value: serde_json::Value) -> EnricherId {
+    let key = key.into();
+    register(move |context: &mut ErrorContext| {
+        context.add_metadata(key.clone(), value.clone());
+    })
+}
+
+/// Register the team owning the errors of operations starting with `operation_prefix`.
+///
+/// Module paths make good prefixes (e.g. `billing::` or `billing.`). Contexts
+/// already attributed to an owner, by the error itself or an earlier
+/// enricher, are left unchanged.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{enrich, TylError};
+///
+/// let id = enrich::register_owner("inventory.", "warehouse-team");
+/// let context = TylError::database("Deadlock").to_context("inventory.reserve".to_string());
+/// assert_eq!(context.owner.as_deref(), Some("warehouse-team"));
+/// enrich::unregister(id);
+/// ```
+pub fn register_owner<P: Into<String>, O: Into<String>>(
+    operation_prefix: P,
+    owner: O,
+) -> EnricherId {
+    let (prefix, owner) = (operation_prefix.into(), owner.into());
+    register(move |context: &mut ErrorContext| {
+        if context.owner.is_none() && context.operation.starts_with(&prefix) {
+            context.owner = Some(owner.clone());
+        }
+    })
+}
+
+/// Remove a registered enricher.
+///
+/// # Returns
+/// True if the enricher existed.
+pub fn unregister(id: EnricherId) -> bool {
+    let mut enrichers = enrichers().lock().unwrap_or_else(|e| e.into_inner());
+    let before = enrichers.len();
+    enrichers.retain(|(existing, _)| *existing != id);
+    ENRICHER_COUNT.store(enrichers.len(), Ordering::Release);
+    enrichers.len() != before
+}
+
+/// Run all registered enrichers on a new context.
+///
+/// Enrichers are invoked outside the registry lock; contexts created by an
+/// enricher itself are not enriched again.
+pub(crate) fn apply(context: &mut ErrorContext) {
+    if ENRICHER_COUNT.load(Ordering::Acquire) == 0 || ENRICHING.with(Cell::get) {
+        return;
+    }
+
+    let enrichers: Vec<Arc<dyn ErrorEnricher>> = enrichers()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(_, enricher)| Arc::clone(enricher))
+        .collect();
+
+    let _guard = Reentrancy::enter();
+    for enricher in enrichers {
+        enricher.enrich(context);
+    }
+}
+
+/// Marks the current thread as enriching until dropped, even on panic.
+struct Reentrancy;
+
+impl Reentrancy {
+    fn enter() -> Self {
+        ENRICHING.with(|flag| flag.set(true));
+        Reentrancy
+    }
+}
+
+impl Drop for Reentrancy {
+    fn drop(&mut self) {
+        ENRICHING.with(|flag| flag.set(false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TylError;
+
+    #[test]
+    fn test_enrichers_should_run_for_reported_errors_until_unregistered() {
+        // Given: an enricher scoped to test operations
+        let scoped = register(|context: &mut ErrorContext| {
+            if context.operation.starts_with("enrich::test") {
+                context.add_metadata("build", serde_json::json!("1.2.3"));
+                // Contexts created while enriching are not enriched again.
+                let nested = ErrorContext::new(
+                    "enrich::test::nested".to_string(),
+                    context.category.clone(),
+                    String::new(),
+                );
+                assert!(!nested.has_metadata("build"));
+            }
+        });
+
+        // When: reporting errors before and after unregistering
+        let enriched = TylError::timeout("slow").report("enrich::test::report");
+        let other = TylError::timeout("slow").to_context("enrich::other".to_string());
+        assert!(unregister(scoped));
+        let plain = TylError::timeout("slow").report("enrich::test::report");
+
+        // Then: only matching contexts created while registered should be enriched
+        assert_eq!(enriched.metadata["build"], "1.2.3");
+        assert!(!other.has_metadata("build"));
+        assert!(!plain.has_metadata("build"));
+        assert!(!unregister(scoped));
+    }
+}
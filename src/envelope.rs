@@ -0,0 +1,167 @@
+//! Wire format for propagating errors over message queues.
+//!
+//! This module provides the [`TylErrorEnvelope`] type used by dead-letter queues
+//! and async reply channels across TYL services, so every service seals and
+//! opens errors with the same versioned JSON format.
+
+use crate::clock;
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Current schema version written by [`TylErrorEnvelope::seal`].
+pub const ENVELOPE_SCHEMA_VERSION: u32 = 1;
+
+/// Serialized error plus the metadata needed to interpret it on the receiving side.
+///
+/// The category name and retriability are stored explicitly because custom
+/// classifiers cannot be reconstructed after deserialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TylErrorEnvelope {
+    /// Version of the envelope format.
+    pub schema_version: u32,
+    /// Name of the service that produced the error.
+    pub service: String,
+    /// Stable error code (see [`TylError::code`]).
+    pub code: String,
+    /// Name of the error category at the time of sealing.
+    pub category: String,
+    /// Whether the error was retriable at the time of sealing.
+    pub retriable: bool,
+    /// The error itself.
+    pub error: TylError,
+    /// Optional tracking context for the failed operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<ErrorContext>,
+    /// Timestamp when the envelope was created.
+    pub sealed_at: DateTime<Utc>,
+}
+
+impl TylErrorEnvelope {
+    /// Create a new envelope for an error produced by the given service.
+    ///
+    /// # Arguments
+    /// * `service` - Name of the producing service
+    /// * `error` - The error to propagate
+    ///
+    /// # Returns
+    /// An envelope with the current schema version and no context.
+    pub fn new<S: Into<String>>(service: S, error: TylError) -> Self {
+        let category = error.category();
+        Self {
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            service: service.into(),
+            code: error.code().to_string(),
+            category: category.category_name().to_string(),
+            retriable: category.is_retriable(),
+            error,
+            context: None,
+            sealed_at: clock::now(),
+        }
+    }
+
+    /// Attach the tracking context of the failed operation using builder pattern.
+    pub fn with_context(mut self, context: ErrorContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Serialize this envelope into its wire format.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{TylError, TylErrorEnvelope};
+    ///
+    /// let error = TylError::network("Upstream timeout");
+    /// let context = error.to_context("orders.publish".to_string());
+    /// let bytes = TylErrorEnvelope::new("orders-service", error)
+    ///     .with_context(context)
+    ///     .seal()
+    ///     .unwrap();
+    ///
+    /// let envelope = TylErrorEnvelope::open(&bytes).unwrap();
+    /// assert_eq!(envelope.service, "orders-service");
+    /// assert_eq!(envelope.code, "network");
+    /// assert!(envelope.retriable);
+    /// ```
+    pub fn seal(&self) -> TylResult<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Parse an envelope from its wire format.
+    ///
+    /// # Errors
+    /// Returns a validation error if the payload is malformed or was written
+    /// with a newer, unsupported schema version.
+    pub fn open(bytes: &[u8]) -> TylResult<Self> {
+        let envelope: Self = serde_json::from_slice(bytes)
+            .map_err(|err| TylError::parsing(format!("Invalid error envelope: {err}")))?;
+
+        if envelope.schema_version > ENVELOPE_SCHEMA_VERSION {
+            return Err(TylError::validation(
+                "schema_version",
+                format!(
+                    "Unsupported error envelope version {} (max supported {ENVELOPE_SCHEMA_VERSION})",
+                    envelope.schema_version
+                ),
+            ));
+        }
+
+        Ok(envelope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_roundtrip_should_preserve_error_and_context() {
+        // Given: a sealed envelope with context
+        let error = TylError::not_found("order", "42");
+        let context = error
+            .to_context("orders.lookup".to_string())
+            .with_metadata("tenant".to_string(), serde_json::json!("acme"));
+        let bytes = TylErrorEnvelope::new("orders", error)
+            .with_context(context.clone())
+            .seal()
+            .unwrap();
+
+        // When: opening it
+        let envelope = TylErrorEnvelope::open(&bytes).unwrap();
+
+        // Then: everything should survive the round-trip
+        assert_eq!(envelope.schema_version, ENVELOPE_SCHEMA_VERSION);
+        assert_eq!(envelope.category, "Permanent");
+        assert!(!envelope.retriable);
+        assert_eq!(envelope.error.to_string(), "Not found: order with id 42");
+        let opened_context = envelope.context.unwrap();
+        assert_eq!(opened_context.error_id, context.error_id);
+        assert_eq!(opened_context.metadata["tenant"], serde_json::json!("acme"));
+    }
+
+    #[test]
+    fn test_open_should_reject_newer_schema_versions() {
+        // Given: an envelope written by a newer producer
+        let mut envelope = TylErrorEnvelope::new("billing", TylError::internal("boom"));
+        envelope.schema_version = ENVELOPE_SCHEMA_VERSION + 1;
+        let bytes = envelope.seal().unwrap();
+
+        // When: opening it
+        let result = TylErrorEnvelope::open(&bytes);
+
+        // Then: it should be rejected as a validation error
+        assert!(
+            matches!(result, Err(TylError::Validation { field, .. }) if field == "schema_version")
+        );
+    }
+
+    #[test]
+    fn test_open_should_reject_malformed_payloads() {
+        assert!(matches!(
+            TylErrorEnvelope::open(b"not json"),
+            Err(TylError::Validation { .. })
+        ));
+    }
+}
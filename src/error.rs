@@ -3,51 +3,321 @@
 //! This module defines the main TylError enum that represents all error types
 //! in the TYL framework, along with convenient constructor methods.
 
-use crate::category::{default_classifier, ErrorCategory, ErrorClassifier};
+use crate::category::{
+    default_classifier, BuiltinCategory, ErrorCategory, ErrorClassifier, RetryScope,
+};
+use crate::config_error::ConfigLocation;
 use crate::context::ErrorContext;
-use crate::settings::ErrorSettings;
+use crate::exit_status::ProcessExit;
+use crate::extras::{CallerLocation, ErrorExtras};
+use crate::limits::{limit_message, limit_metadata, limit_value, Limited};
+use crate::log_limit::{LogDecision, LogRateLimiter};
+use crate::meta_key::MetaKey;
+use crate::operation::{OPERATION_LOCATION_KEY, OPERATION_PATH_KEY};
+use crate::redact::{debug_message, RedactedDebug, SafeDebug, REDACTED};
+use crate::retry::{RetryHint, RetryTelemetry};
+use crate::settings::{ErrorSettings, LogLevel};
+use crate::suppress::{self, SUPPRESSED_UNTIL_KEY};
+use crate::validation::ValidationConstraint;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Structured fields that identify people or expose internals and must be
+/// redacted in contexts and kept out of client-facing responses.
+pub(crate) const SENSITIVE_FIELDS: &[&str] =
+    &["subject", "stderr_tail", "panic_file", "panic_backtrace"];
+
+/// Check whether a metadata entry must be kept out of client-facing
+/// responses: [`SENSITIVE_FIELDS`], the driver code of database errors
+/// (visible in contexts for dashboards) and every `panic_*` entry.
+pub(crate) fn is_client_hidden(key: &str) -> bool {
+    SENSITIVE_FIELDS.contains(&key)
+        || key == DRIVER_CODE_KEY
+        || key.starts_with("panic_")
+        || key == OPERATION_LOCATION_KEY
+        || key == OPERATION_PATH_KEY
+}
+
+/// Metadata key of the driver-native cause code of database errors, see
+/// [`TylError::with_driver_code`].
+pub const DRIVER_CODE_KEY: &str = "driver_code";
+
 /// Result type alias for TYL framework operations.
 pub type TylResult<T> = Result<T, TylError>;
 
+/// Shared handle to the underlying error that caused a [`TylError`].
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
+/// How serious an error occurrence is for operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TylSeverity {
+    /// Diagnostic noise, only interesting while debugging.
+    Debug,
+    /// Expected outcome worth recording (e.g. cancellations).
+    Info,
+    /// Caller or client problem; the service itself is healthy.
+    Warning,
+    /// Failure of the service or one of its dependencies.
+    Error,
+    /// Failure requiring immediate operator attention.
+    Critical,
+}
+
+impl TylSeverity {
+    /// Get the log level used when logging errors of this severity.
+    pub fn log_level(&self) -> LogLevel {
+        match self {
+            TylSeverity::Debug => LogLevel::Debug,
+            TylSeverity::Info => LogLevel::Info,
+            TylSeverity::Warning => LogLevel::Warn,
+            TylSeverity::Error | TylSeverity::Critical => LogLevel::Error,
+        }
+    }
+
+    /// Get the uppercase name of this severity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TylSeverity::Debug => "DEBUG",
+            TylSeverity::Info => "INFO",
+            TylSeverity::Warning => "WARNING",
+            TylSeverity::Error => "ERROR",
+            TylSeverity::Critical => "CRITICAL",
+        }
+    }
+
+    /// Get the default severity for errors of the given category.
+    pub fn for_category(category: &ErrorCategory) -> Self {
+        let builtin = match category {
+            ErrorCategory::Builtin(builtin) => Some(builtin.clone()),
+            ErrorCategory::Custom(custom) => BuiltinCategory::from_name(custom.category_name()),
+        };
+
+        match builtin {
+            Some(
+                BuiltinCategory::Validation
+                | BuiltinCategory::Authentication
+                | BuiltinCategory::Permanent,
+            ) => TylSeverity::Warning,
+            _ => TylSeverity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for TylSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options of [`TylError::fingerprint_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FingerprintOptions {
+    /// Group occurrences per tenant, so each tenant's failures are counted
+    /// and rate limited separately.
+    pub per_tenant: bool,
+}
+
+impl FingerprintOptions {
+    /// Create options producing the same fingerprint as [`TylError::fingerprint`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include the context's tenant ID in the fingerprint using builder pattern.
+    pub fn per_tenant(mut self, per_tenant: bool) -> Self {
+        self.per_tenant = per_tenant;
+        self
+    }
+}
+
 /// Main error type for the TYL framework.
 ///
 /// Provides a comprehensive set of error variants covering common error scenarios
 /// in hexagonal architecture patterns, with built-in retry logic and error classification.
-#[derive(Error, Debug, Clone, Serialize, Deserialize)]
+///
+/// `Debug` output truncates very long messages when `TYL_ERROR_REDACT=true`;
+/// use [`TylError::safe_debug`] to force redaction regardless of the setting.
+///
+/// Deserialization tolerates variants added by newer versions of this crate:
+/// they become [`TylError::Unknown`], which serializes back to the original
+/// JSON so relaying services do not lose information.
+#[derive(Error, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub enum TylError {
-    #[error("Database error: {message}")]
-    Database { message: String },
+    #[error("Database error: {}", Limited(.message))]
+    Database {
+        message: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Network error: {message}")]
-    Network { message: String },
+    #[error("Network error: {}", Limited(.message))]
+    Network {
+        message: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Validation error: {field}: {message}")]
-    Validation { field: String, message: String },
+    #[error("Validation error: {field}: {}", Limited(.message))]
+    Validation {
+        field: String,
+        message: String,
+        /// The offending input, if recorded.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        input: Option<String>,
+        /// Underlying parse error, if any (not serialized).
+        #[source]
+        #[serde(skip)]
+        source: Option<ErrorSource>,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
     #[error("Not found: {resource} with id {id}")]
-    NotFound { resource: String, id: String },
+    NotFound {
+        resource: String,
+        id: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Conflict: {}", Limited(.message))]
+    Conflict {
+        message: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Internal error: {}", Limited(.message))]
+    Internal {
+        message: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Unauthenticated: {}", Limited(.reason))]
+    Unauthenticated {
+        reason: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Conflict: {message}")]
-    Conflict { message: String },
+    #[error("Permission denied: {action} on {resource}")]
+    PermissionDenied {
+        /// Principal that was denied (sensitive; redacted in contexts).
+        subject: String,
+        /// Action that was attempted.
+        action: String,
+        /// Resource the action targeted.
+        resource: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Timeout: {}", Limited(.message))]
+    Timeout {
+        message: String,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Cancelled: {}", Limited(.message))]
+    Cancelled {
+        message: String,
+        /// Error of the work interrupted by the cancellation, if any (not serialized).
+        #[source]
+        #[serde(skip)]
+        source: Option<ErrorSource>,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Internal error: {message}")]
-    Internal { message: String },
+    #[error("Invariant violated: {name}: {}", InvariantDiff(.expected, .actual))]
+    InvariantViolated {
+        /// Name of the violated invariant.
+        name: String,
+        /// Value the invariant required.
+        expected: serde_json::Value,
+        /// Value actually observed.
+        actual: serde_json::Value,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
+
+    #[error("Configuration error: {}", Limited(.message))]
+    Configuration {
+        message: String,
+        /// Configuration key that failed, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+        /// Description of the expected value, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected: Option<String>,
+        /// The actual value found, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        actual: Option<String>,
+        /// File and position the value came from, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<Box<ConfigLocation>>,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Configuration error: {message}")]
-    Configuration { message: String },
+    #[error(
+        "Feature not implemented: {feature}{}",
+        TrackingSuffix(.tracking_issue, .planned_version)
+    )]
+    NotImplemented {
+        feature: String,
+        /// Link to the issue tracking the implementation, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tracking_issue: Option<String>,
+        /// Version in which the feature is planned to ship, if known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        planned_version: Option<String>,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
+    },
 
-    #[error("Feature not implemented: {feature}")]
-    NotImplemented { feature: String },
+    /// A variant this version does not know, kept as received.
+    #[error("Unknown error variant: {variant}")]
+    Unknown {
+        /// Name of the unrecognized variant.
+        variant: String,
+        /// Raw JSON payload of the variant.
+        raw: serde_json::Value,
+    },
 
-    #[error("Custom error: {message}")]
+    #[error("Custom error: {}", Limited(.message))]
     Custom {
         message: String,
         #[serde(skip)]
         #[serde(default = "default_classifier")]
         classifier: Box<dyn ErrorClassifier>,
+        /// Underlying error, if this error wraps one (not serialized).
+        #[source]
+        #[serde(skip)]
+        source: Option<ErrorSource>,
+        /// Details kept outside the variant's fields, see [`ErrorExtras`].
+        #[serde(default, skip_serializing_if = "ErrorExtras::is_empty")]
+        extras: ErrorExtras,
     },
 }
 
@@ -55,112 +325,632 @@ impl TylError {
     // === Primary Constructors ===
 
     /// Create a database-related error.
+    #[track_caller]
     pub fn database<S: Into<String>>(message: S) -> Self {
         Self::Database {
-            message: message.into(),
+            message: limit_message(message.into()),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a network-related error.
+    #[track_caller]
     pub fn network<S: Into<String>>(message: S) -> Self {
         Self::Network {
-            message: message.into(),
+            message: limit_message(message.into()),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a validation error for a specific field.
+    #[track_caller]
     pub fn validation<F: Into<String>, M: Into<String>>(field: F, message: M) -> Self {
         Self::Validation {
             field: field.into(),
-            message: message.into(),
+            message: limit_message(message.into()),
+            input: None,
+            source: None,
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a "not found" error for a specific resource.
+    #[track_caller]
     pub fn not_found<R: Into<String>, I: Into<String>>(resource: R, id: I) -> Self {
         Self::NotFound {
             resource: resource.into(),
             id: id.into(),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a conflict error (e.g., duplicate resources, constraint violations).
+    #[track_caller]
     pub fn conflict<S: Into<String>>(message: S) -> Self {
         Self::Conflict {
-            message: message.into(),
+            message: limit_message(message.into()),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create an internal system error.
+    #[track_caller]
     pub fn internal<S: Into<String>>(message: S) -> Self {
         Self::Internal {
-            message: message.into(),
+            message: limit_message(message.into()),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create an error for a caller whose identity could not be established.
+    #[track_caller]
+    pub fn unauthenticated<S: Into<String>>(reason: S) -> Self {
+        Self::Unauthenticated {
+            reason: limit_message(reason.into()),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create an error for an authenticated caller lacking a permission.
+    ///
+    /// The subject, action and resource are exposed in [`TylError::metadata`];
+    /// the subject is marked sensitive in contexts created with
+    /// [`TylError::to_context`] and left out of problem details.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::permission_denied("user:42", "delete", "invoice:7");
+    /// assert_eq!(error.to_string(), "Permission denied: delete on invoice:7");
+    /// assert_eq!(error.http_status(), 403);
+    ///
+    /// let context = error.to_context("invoices.delete".to_string());
+    /// assert!(context.is_sensitive("subject"));
+    /// ```
+    #[track_caller]
+    pub fn permission_denied<S, A, R>(subject: S, action: A, resource: R) -> Self
+    where
+        S: Into<String>,
+        A: Into<String>,
+        R: Into<String>,
+    {
+        Self::PermissionDenied {
+            subject: subject.into(),
+            action: action.into(),
+            resource: resource.into(),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create a timeout error (an operation exceeded its deadline).
+    #[track_caller]
+    pub fn timeout<S: Into<String>>(message: S) -> Self {
+        Self::Timeout {
+            message: limit_message(message.into()),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create a cancellation error (an operation was cancelled before completing).
+    #[track_caller]
+    pub fn cancelled<S: Into<String>>(message: S) -> Self {
+        Self::Cancelled {
+            message: limit_message(message.into()),
+            source: None,
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a configuration error.
+    #[track_caller]
     pub fn configuration<S: Into<String>>(message: S) -> Self {
         Self::Configuration {
-            message: message.into(),
+            message: limit_message(message.into()),
+            key: None,
+            expected: None,
+            actual: None,
+            location: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create a configuration error for a key holding an invalid value.
+    ///
+    /// The key, expected and actual values are kept as structured fields and
+    /// copied into the metadata of contexts created with [`TylError::to_context`].
+    /// The actual value of a key naming a secret (containing `password`,
+    /// `secret`, `token`, `api_key`, ...) is replaced with [`REDACTED`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::config_key("database.port", "integer", "\"abc\"");
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Configuration error: database.port: expected integer, got \"abc\""
+    /// );
+    /// assert_eq!(error.code(), "configuration_invalid_key");
+    ///
+    /// let secret = TylError::config_key("database.password", "8+ characters", "\"hunter2\"");
+    /// assert_eq!(secret.metadata()["actual"], "[REDACTED]");
+    /// ```
+    #[track_caller]
+    pub fn config_key<K: Into<String>, E: Into<String>, A: Into<String>>(
+        key: K,
+        expected: E,
+        actual: A,
+    ) -> Self {
+        let (key, expected, mut actual) = (key.into(), expected.into(), actual.into());
+        if crate::config_error::is_secret_key(&key) {
+            actual = REDACTED.to_string();
+        }
+        Self::Configuration {
+            message: limit_message(format!("{key}: expected {expected}, got {actual}")),
+            key: Some(key),
+            expected: Some(expected),
+            actual: Some(actual),
+            location: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create a configuration error for a required key that is missing.
+    #[track_caller]
+    pub fn config_missing<K: Into<String>>(key: K) -> Self {
+        let key = key.into();
+        Self::Configuration {
+            message: limit_message(format!("missing required key {key}")),
+            key: Some(key),
+            expected: None,
+            actual: None,
+            location: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Record where a configuration error's value came from.
+    ///
+    /// The file, line and column are exposed as `config_file`, `config_line`
+    /// and `config_column` in [`TylError::metadata`]. Other variants are
+    /// returned unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::config_missing("database.url").with_config_location("config/app.toml", Some(3), None);
+    /// assert_eq!(error.metadata()["config_file"], "config/app.toml");
+    /// assert_eq!(error.metadata()["config_line"], 3);
+    /// ```
+    pub fn with_config_location<F: Into<String>>(
+        mut self,
+        file: F,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> Self {
+        if let Self::Configuration { location, .. } = &mut self {
+            *location = Some(Box::new(ConfigLocation {
+                file: Some(file.into()),
+                line,
+                column,
+            }));
+        }
+        self
+    }
+
+    /// Create a configuration error for a required environment variable that is not set.
+    ///
+    /// The variable name is exposed as `config_key` in [`TylError::metadata`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::env_missing("DATABASE_URL");
+    /// assert_eq!(error.to_string(), "Configuration error: missing environment variable DATABASE_URL");
+    /// assert_eq!(error.metadata()["config_key"], "DATABASE_URL");
+    /// ```
+    #[track_caller]
+    pub fn env_missing<V: Into<String>>(var: V) -> Self {
+        let var = var.into();
+        Self::Configuration {
+            message: limit_message(format!("missing environment variable {var}")),
+            key: Some(var),
+            expected: None,
+            actual: None,
+            location: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create an error for a violated domain invariant.
+    ///
+    /// The expected and actual values are kept as structured JSON (see
+    /// [`TylError::metadata`]) and the message shows where they differ.
+    /// Values that cannot be serialized are recorded as a description of the
+    /// serialization failure.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_json::json;
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::invariant(
+    ///     "order_total_matches_lines",
+    ///     json!({ "total": 30, "currency": "EUR" }),
+    ///     json!({ "total": 25, "currency": "EUR" }),
+    /// );
+    ///
+    /// assert_eq!(error.code(), "invariant_violated");
+    /// assert_eq!(error.metadata()["actual"]["total"], 25);
+    /// assert!(error.to_string().ends_with("(differs at total: 30 != 25)"));
+    /// ```
+    #[track_caller]
+    pub fn invariant<N, E, A>(name: N, expected: E, actual: A) -> Self
+    where
+        N: Into<String>,
+        E: Serialize,
+        A: Serialize,
+    {
+        let to_value = |value: Result<serde_json::Value, serde_json::Error>| {
+            value.unwrap_or_else(|err| serde_json::json!(format!("<unserializable: {err}>")))
+        };
+        Self::InvariantViolated {
+            name: name.into(),
+            expected: limit_value(to_value(serde_json::to_value(expected))),
+            actual: limit_value(to_value(serde_json::to_value(actual))),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a "not implemented" error for missing features.
+    #[track_caller]
     pub fn not_implemented<S: Into<String>>(feature: S) -> Self {
         Self::NotImplemented {
             feature: feature.into(),
+            tracking_issue: None,
+            planned_version: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create a "not implemented" error pointing clients at the tracking issue.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::not_implemented_tracked(
+    ///     "bulk export",
+    ///     "https://github.com/the-yaml-life/tyl-errors/issues/12",
+    /// )
+    /// .with_planned_version("0.3.0");
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Feature not implemented: bulk export \
+    ///      (tracking: https://github.com/the-yaml-life/tyl-errors/issues/12, planned for 0.3.0)"
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn not_implemented_tracked<F: Into<String>, U: Into<String>>(
+        feature: F,
+        issue_url: U,
+    ) -> Self {
+        Self::NotImplemented {
+            feature: feature.into(),
+            tracking_issue: Some(issue_url.into()),
+            planned_version: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Record the version a missing feature is planned for.
+    ///
+    /// Only affects [`TylError::NotImplemented`] errors; other errors are returned unchanged.
+    pub fn with_planned_version<V: Into<String>>(mut self, version: V) -> Self {
+        if let TylError::NotImplemented {
+            planned_version, ..
+        } = &mut self
+        {
+            *planned_version = Some(version.into());
         }
+        self
+    }
+
+    /// Record the offending input of a validation or parsing error.
+    ///
+    /// The input is exposed as `input` in [`TylError::metadata`]. Only affects
+    /// [`TylError::Validation`] errors; other errors are returned unchanged.
+    /// See [`InputResultExt::with_input`] to convert and record the input of a
+    /// failed parse in one step.
+    pub fn with_input<I: Into<String>>(mut self, value: I) -> Self {
+        if let TylError::Validation { input, .. } = &mut self {
+            *input = Some(limit_message(value.into()));
+        }
+        self
+    }
+
+    /// Mark this error as having happened before the operation had any side
+    /// effect, e.g. because the connection was never established.
+    ///
+    /// Retry executors retry non-idempotent operations only for such errors,
+    /// see [`ErrorClassifier::failed_before_side_effects`]. Has no effect on
+    /// [`TylError::Unknown`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let refused = TylError::network("Connection refused").with_before_side_effects();
+    /// assert!(refused.failed_before_side_effects());
+    /// assert!(!TylError::network("Connection reset by peer").failed_before_side_effects());
+    /// ```
+    pub fn with_before_side_effects(mut self) -> Self {
+        if let Some(extras) = self.extras_mut() {
+            extras.set_before_side_effects();
+        }
+        self
+    }
+
+    /// Check whether this error was marked with
+    /// [`TylError::with_before_side_effects`].
+    pub fn failed_before_side_effects(&self) -> bool {
+        self.extras()
+            .is_some_and(|extras| extras.before_side_effects())
+    }
+
+    /// Record the rule a validation error's input failed.
+    ///
+    /// The constraint is exposed as structured `constraint` in
+    /// [`TylError::metadata`] and problem details. Only affects
+    /// [`TylError::Validation`] errors; other errors are returned unchanged.
+    pub fn with_constraint(mut self, value: ValidationConstraint) -> Self {
+        if let TylError::Validation { extras, .. } = &mut self {
+            extras.set_constraint(value);
+        }
+        self
+    }
+
+    /// Record the driver-native cause code of a database error, such as the
+    /// SQLSTATE of a Postgres error, the errno of a MySQL error or the prefix
+    /// of a Redis error reply.
+    ///
+    /// The code is exposed as `driver_code` in [`TylError::metadata`] and
+    /// contexts, so dashboards can break database errors down by cause; it
+    /// is kept out of problem details. Only affects [`TylError::Database`]
+    /// errors; other errors are returned unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{TylError, DRIVER_CODE_KEY};
+    ///
+    /// let error = TylError::database("duplicate key value violates unique constraint")
+    ///     .with_driver_code("23505");
+    /// assert_eq!(error.driver_code(), Some("23505"));
+    /// assert_eq!(error.metadata()[DRIVER_CODE_KEY], "23505");
+    /// ```
+    pub fn with_driver_code<C: Into<String>>(mut self, code: C) -> Self {
+        if let TylError::Database { extras, .. } = &mut self {
+            extras.set_driver_code(code.into());
+        }
+        self
+    }
+
+    /// Get the driver-native cause code of a database error, if recorded.
+    pub fn driver_code(&self) -> Option<&str> {
+        match self {
+            TylError::Database { extras, .. } => extras.driver_code(),
+            _ => None,
+        }
+    }
+
+    /// Get the rule a validation error's input failed, if recorded.
+    pub fn constraint(&self) -> Option<&ValidationConstraint> {
+        match self {
+            TylError::Validation { extras, .. } => extras.constraint(),
+            _ => None,
+        }
+    }
+
+    /// Attach the attempt telemetry of a retry executor that gave up on this error.
+    ///
+    /// The error is otherwise unchanged; the telemetry is kept in its
+    /// [extras](ErrorExtras) and dropped for [`TylError::Unknown`].
+    pub fn with_retry_telemetry(mut self, telemetry: RetryTelemetry) -> Self {
+        if let Some(extras) = self.extras_mut() {
+            extras.set_retry_telemetry(telemetry);
+        }
+        self
+    }
+
+    /// Get the attempt telemetry if a retry executor gave up on this error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{RetryPolicy, TylError, TylResult};
+    ///
+    /// let policy = RetryPolicy::fast()
+    ///     .with_base_delay(Duration::from_millis(1))
+    ///     .with_max_attempts(2);
+    /// let error = policy
+    ///     .execute(|| -> TylResult<()> { Err(TylError::network("Connection reset")) })
+    ///     .unwrap_err();
+    ///
+    /// let telemetry = error.retry_telemetry().unwrap();
+    /// assert_eq!(telemetry.attempts, 3);
+    /// assert_eq!(telemetry.delays_ms.len(), 2);
+    /// assert!(matches!(error, TylError::Network { .. }));
+    /// assert_eq!(error.to_string(), "Network error: Connection reset");
+    /// ```
+    pub fn retry_telemetry(&self) -> Option<&RetryTelemetry> {
+        self.extras()?.retry_telemetry()
     }
 
     /// Create a custom error with domain-specific classification.
+    #[track_caller]
     pub fn business_logic<S: Into<String>>(
         message: S,
         classifier: Box<dyn ErrorClassifier>,
     ) -> Self {
         Self::Custom {
-            message: message.into(),
+            message: limit_message(message.into()),
             classifier,
+            source: None,
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create an error wrapping a third-party error known to be transient.
+    ///
+    /// The error is classified as [`BuiltinCategory::Transient`](crate::BuiltinCategory)
+    /// and the source is preserved for `std::error::Error::source()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::error::Error;
+    /// use tyl_errors::TylError;
+    ///
+    /// let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset by peer");
+    /// let error = TylError::retriable("Broker connection dropped", io);
+    /// assert!(error.category().is_retriable());
+    /// assert_eq!(error.source().unwrap().to_string(), "reset by peer");
+    /// ```
+    #[track_caller]
+    pub fn retriable<S, E>(message: S, source: E) -> Self
+    where
+        S: Into<String>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Custom {
+            message: limit_message(message.into()),
+            classifier: Box::new(BuiltinCategory::Transient),
+            source: Some(Arc::new(source)),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Create an error wrapping a third-party error known to be permanent.
+    ///
+    /// The error is classified as [`BuiltinCategory::Permanent`](crate::BuiltinCategory)
+    /// and the source is preserved for `std::error::Error::source()`.
+    #[track_caller]
+    pub fn permanent<S, E>(message: S, source: E) -> Self
+    where
+        S: Into<String>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Custom {
+            message: limit_message(message.into()),
+            classifier: Box::new(BuiltinCategory::Permanent),
+            source: Some(Arc::new(source)),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Bridge a foreign error into a `TylError` with the given category.
+    ///
+    /// The source's `Display` output becomes the message (cut to
+    /// `TYL_ERROR_MAX_MESSAGE_LEN`) and the source is preserved for
+    /// `std::error::Error::source()` and [`TylError::downcast_source_ref`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, TylError};
+    ///
+    /// let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out");
+    /// let error = TylError::wrap(io, ErrorCategory::network());
+    ///
+    /// assert_eq!(error.to_string(), "Custom error: read timed out");
+    /// assert_eq!(error.category().category_name(), "Network");
+    /// let io = error.downcast_source_ref::<std::io::Error>().unwrap();
+    /// assert_eq!(io.kind(), std::io::ErrorKind::TimedOut);
+    /// ```
+    #[track_caller]
+    pub fn wrap<E>(source: E, category: ErrorCategory) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Custom {
+            message: limit_message(source.to_string()),
+            classifier: category.into_classifier(),
+            source: Some(Arc::new(source)),
+            extras: ErrorExtras::new(),
+        }
+    }
+
+    /// Get the underlying error as a concrete type, if it is one.
+    pub fn downcast_source_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        match self {
+            TylError::Validation { source, .. } | TylError::Custom { source, .. } => {
+                source.as_deref()?.downcast_ref::<E>()
+            }
+            _ => None,
         }
     }
 
     // === Convenience Constructors ===
 
     /// Create a parsing error (specialized validation error).
+    #[track_caller]
     pub fn parsing<S: Into<String>>(message: S) -> Self {
         Self::Validation {
             field: "parsing".to_string(),
-            message: message.into(),
+            message: limit_message(message.into()),
+            input: None,
+            source: None,
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a serialization error (specialized internal error).
+    #[track_caller]
     pub fn serialization<S: Into<String>>(message: S) -> Self {
         let msg = message.into();
         Self::Internal {
-            message: format!("Serialization error: {msg}"),
+            message: limit_message(format!("Serialization error: {msg}")),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create a connection error (specialized network error).
+    #[track_caller]
     pub fn connection<S: Into<String>>(message: S) -> Self {
         let msg = message.into();
         Self::Network {
-            message: format!("Connection error: {msg}"),
+            message: limit_message(format!("Connection error: {msg}")),
+            extras: ErrorExtras::new(),
         }
     }
 
     /// Create an initialization error (specialized internal error).
+    #[track_caller]
     pub fn initialization<S: Into<String>>(message: S) -> Self {
         let msg = message.into();
         Self::Internal {
-            message: format!("Initialization error: {msg}"),
+            message: limit_message(format!("Initialization error: {msg}")),
+            extras: ErrorExtras::new(),
         }
     }
 
     // === Error Category and Classification ===
 
     /// Get the error category for this error type.
+    ///
+    /// Installed [`ClassificationOverrides`](crate::ClassificationOverrides)
+    /// take precedence over the default classification.
     pub fn category(&self) -> ErrorCategory {
+        crate::overrides::ClassificationOverrides::with_current(|overrides| {
+            overrides.resolve(self, None, None)
+        })
+        .unwrap_or_else(|| self.default_category())
+    }
+
+    /// Get the category of this error ignoring classification overrides.
+    pub(crate) fn default_category(&self) -> ErrorCategory {
         match self {
             TylError::Database { .. } => ErrorCategory::transient(),
             TylError::Network { .. } => ErrorCategory::network(),
@@ -168,15 +958,443 @@ impl TylError {
             TylError::NotFound { .. } => ErrorCategory::permanent(),
             TylError::Conflict { .. } => ErrorCategory::permanent(),
             TylError::Internal { .. } => ErrorCategory::internal(),
+            TylError::Unauthenticated { .. } => ErrorCategory::authentication(),
+            TylError::PermissionDenied { .. } => ErrorCategory::authentication(),
+            TylError::Timeout { .. } => ErrorCategory::transient(),
+            TylError::Cancelled { .. } => ErrorCategory::permanent(),
+            TylError::InvariantViolated { .. } => ErrorCategory::internal(),
             TylError::Configuration { .. } => ErrorCategory::permanent(),
             TylError::NotImplemented { .. } => ErrorCategory::permanent(),
+            TylError::Unknown { .. } => ErrorCategory::unknown(),
             TylError::Custom { classifier, .. } => ErrorCategory::Custom(classifier.clone()),
         }
     }
 
+    /// Get the stable, machine-readable code for this error.
+    ///
+    /// Codes are `snake_case` identifiers intended for API responses, metrics
+    /// labels and snapshot tests; unlike messages they never change between
+    /// occurrences of the same failure. Custom errors have the code of their
+    /// category (see [`ErrorCategory::with_code`]), `custom` by default.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TylError::Database { .. } => "database",
+            TylError::Network { .. } => "network",
+            TylError::Validation { .. } => "validation",
+            TylError::NotFound { .. } => "not_found",
+            TylError::Conflict { .. } => "conflict",
+            TylError::Internal { .. } => "internal",
+            TylError::Unauthenticated { .. } => "unauthenticated",
+            TylError::PermissionDenied { .. } => "permission_denied",
+            TylError::Timeout { .. } => "timeout",
+            TylError::Cancelled { .. } => "cancelled",
+            TylError::InvariantViolated { .. } => "invariant_violated",
+            TylError::Configuration {
+                key: Some(_),
+                expected: Some(_),
+                ..
+            } => "configuration_invalid_key",
+            TylError::Configuration { key: Some(_), .. } => "configuration_missing_key",
+            TylError::Configuration { .. } => "configuration",
+            TylError::NotImplemented { .. } => "not_implemented",
+            TylError::Unknown { .. } => "unknown",
+            TylError::Custom { classifier, .. } => classifier.code().unwrap_or("custom"),
+        }
+    }
+
+    /// One error of every built-in code, plus custom errors of every built-in category.
+    pub(crate) fn builtin_samples() -> Vec<TylError> {
+        let mut samples = vec![
+            TylError::validation("", ""),
+            TylError::not_found("", ""),
+            TylError::conflict(""),
+            TylError::unauthenticated(""),
+            TylError::permission_denied("", "", ""),
+            TylError::cancelled(""),
+            TylError::internal(""),
+            TylError::invariant("", "", ""),
+            TylError::configuration(""),
+            TylError::config_missing(""),
+            TylError::config_key("", "", ""),
+            TylError::Unknown {
+                variant: String::new(),
+                raw: serde_json::Value::Null,
+            },
+            TylError::not_implemented(""),
+            TylError::network(""),
+            TylError::database(""),
+            TylError::timeout(""),
+        ];
+        samples.extend(BuiltinCategory::ALL.into_iter().map(|category| {
+            TylError::business_logic("", ErrorCategory::Builtin(category).into_classifier())
+        }));
+        samples
+    }
+
+    /// Get a stable fingerprint grouping occurrences of the same failure.
+    ///
+    /// The fingerprint hashes the code, category, validated field or missing
+    /// resource, and the message with ids masked: numbers, hex ids (`0x1f`,
+    /// `a3f9c0`) and UUIDs are replaced by `#`, so ids and counters embedded
+    /// in messages do not split groups. It is stable across processes and
+    /// releases of this crate.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let a = TylError::database("Pool exhausted after 30s (17 waiters)");
+    /// let b = TylError::database("Pool exhausted after 31s (4 waiters)");
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// assert_ne!(a.fingerprint(), TylError::network("Pool exhausted").fingerprint());
+    ///
+    /// let c = TylError::not_implemented("Job 0d1e4c2a-95f3-4b8e-a7d6-3c5b2f1e9a80 on 0xdeadbeef");
+    /// let d = TylError::not_implemented("Job f47ac10b-58cc-4372-a567-0e02b2c3d479 on 0x1f");
+    /// assert_eq!(c.fingerprint(), d.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> String {
+        self.fingerprint_of(None)
+    }
+
+    /// Get the fingerprint of an occurrence of this error, as configured by `options`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{FingerprintOptions, TylError};
+    ///
+    /// let error = TylError::database("Pool exhausted");
+    /// let acme = error.to_context("checkout".into()).with_tenant_id("acme");
+    /// let globex = error.to_context("checkout".into()).with_tenant_id("globex");
+    ///
+    /// let per_tenant = FingerprintOptions::new().per_tenant(true);
+    /// assert_ne!(error.fingerprint_with(&acme, per_tenant), error.fingerprint_with(&globex, per_tenant));
+    /// assert_eq!(error.fingerprint_with(&acme, FingerprintOptions::new()), error.fingerprint());
+    /// ```
+    pub fn fingerprint_with(&self, context: &ErrorContext, options: FingerprintOptions) -> String {
+        let tenant_id = options
+            .per_tenant
+            .then_some(context.tenant_id.as_deref())
+            .flatten();
+        self.fingerprint_of(tenant_id)
+    }
+
+    fn fingerprint_of(&self, tenant_id: Option<&str>) -> String {
+        let category = self.category();
+        let discriminator = match self {
+            TylError::Validation { field, .. } => field.as_str(),
+            TylError::NotFound { resource, .. } => resource.as_str(),
+            _ => "",
+        };
+        let message = match self {
+            TylError::NotFound { .. } => String::new(),
+            _ => mask_ids(&self.to_string()),
+        };
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for part in [
+            self.code(),
+            category.category_name(),
+            discriminator,
+            message.as_str(),
+        ]
+        .into_iter()
+        .chain(tenant_id)
+        {
+            for byte in part.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        format!("{hash:016x}")
+    }
+
+    /// Get the name of the enum variant of this error.
+    pub(crate) fn variant_name(&self) -> &'static str {
+        match self {
+            TylError::Database { .. } => "Database",
+            TylError::Network { .. } => "Network",
+            TylError::Validation { .. } => "Validation",
+            TylError::NotFound { .. } => "NotFound",
+            TylError::Conflict { .. } => "Conflict",
+            TylError::Internal { .. } => "Internal",
+            TylError::Unauthenticated { .. } => "Unauthenticated",
+            TylError::PermissionDenied { .. } => "PermissionDenied",
+            TylError::Timeout { .. } => "Timeout",
+            TylError::Cancelled { .. } => "Cancelled",
+            TylError::InvariantViolated { .. } => "InvariantViolated",
+            TylError::Configuration { .. } => "Configuration",
+            TylError::NotImplemented { .. } => "NotImplemented",
+            TylError::Unknown { .. } => "Unknown",
+            TylError::Custom { .. } => "Custom",
+        }
+    }
+
+    /// Check if this error is equivalent to another, ignoring volatile parts.
+    ///
+    /// Compares the variant, code, category, validated field and missing resource,
+    /// but not messages, identifiers, timestamps or retry delays. Useful for
+    /// golden-file tests of error responses; see also
+    /// [`assert_errors_equivalent!`](crate::assert_errors_equivalent).
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let expected = TylError::not_found("user", "<any>");
+    /// let actual = TylError::not_found("user", "9f1c2d4e");
+    /// assert!(actual.similar_to(&expected));
+    /// assert!(!actual.similar_to(&TylError::not_found("order", "9f1c2d4e")));
+    /// ```
+    pub fn similar_to(&self, other: &TylError) -> bool {
+        crate::testing::differences(self, other).is_empty()
+    }
+
+    /// Get where this error was created.
+    ///
+    /// Recorded with `#[track_caller]` by the constructors and `From`
+    /// conversions, so it points at the caller's code: a cheap substitute for
+    /// backtraces in release builds. The location is serialized with the
+    /// error, so deserialized errors keep it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::network("Connection reset");
+    /// let location = error.location().unwrap();
+    /// assert_eq!(location.line(), line!() - 2);
+    /// assert_eq!(
+    ///     error.verbose().to_string(),
+    ///     format!("Network error: Connection reset (at {}:{})", location.file(), location.line())
+    /// );
+    /// ```
+    pub fn location(&self) -> Option<CallerLocation<'_>> {
+        self.extras()?.location()
+    }
+
+    /// Get a `Display` view of this error followed by where it was created,
+    /// e.g. `Network error: Connection reset (at src/client.rs:42)`.
+    pub fn verbose(&self) -> VerboseDisplay<'_> {
+        VerboseDisplay(self)
+    }
+
+    /// Get a `Debug` view of this error that always redacts.
+    ///
+    /// Long messages are truncated even when `TYL_ERROR_REDACT` is not set.
+    pub fn safe_debug(&self) -> SafeDebug<'_, Self> {
+        SafeDebug(self)
+    }
+
+    /// Write the `Display` form of this error into any text sink.
+    ///
+    /// Formats straight into `out` without building an intermediate `String`,
+    /// so log lines and response buffers can be filled during error storms
+    /// without extra allocations.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let mut line = String::from("request failed: ");
+    /// TylError::not_found("user", "42").write_to(&mut line).unwrap();
+    /// assert_eq!(line, "request failed: Not found: user with id 42");
+    /// ```
+    pub fn write_to<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{self}")
+    }
+
+    /// Get the structured fields of this error as metadata entries.
+    ///
+    /// These entries are copied into every context created with [`TylError::to_context`].
+    pub fn metadata(&self) -> HashMap<String, serde_json::Value> {
+        self.metadata_entries()
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect()
+    }
+
+    /// Get the metadata entries of this error keyed as in contexts.
+    pub(crate) fn metadata_entries(&self) -> HashMap<MetaKey, serde_json::Value> {
+        let mut metadata = self.variant_metadata();
+        if let Some(telemetry) = self.retry_telemetry() {
+            metadata.insert("retry_attempts".into(), telemetry.attempts.into());
+            metadata.insert("retry_elapsed_ms".into(), telemetry.elapsed_ms.into());
+            metadata.insert(
+                "retry_delays_ms".into(),
+                serde_json::json!(telemetry.delays_ms),
+            );
+            if !telemetry.attempt_records.is_empty() {
+                metadata.insert(
+                    "retry_attempt_records".into(),
+                    serde_json::json!(telemetry.attempt_records),
+                );
+            }
+        }
+        metadata.extend(self.operation_metadata());
+        metadata
+    }
+
+    /// Get the metadata entries of this error's variant and extras.
+    fn variant_metadata(&self) -> HashMap<MetaKey, serde_json::Value> {
+        if let TylError::InvariantViolated {
+            name,
+            expected,
+            actual,
+            ..
+        } = self
+        {
+            return HashMap::from([
+                ("invariant".into(), serde_json::json!(name)),
+                ("expected".into(), expected.clone()),
+                ("actual".into(), actual.clone()),
+            ]);
+        }
+
+        let fields: Vec<(&'static str, Option<&String>)> = match self {
+            TylError::Configuration {
+                key,
+                expected,
+                actual,
+                location,
+                ..
+            } => vec![
+                ("config_key", key.as_ref()),
+                ("expected", expected.as_ref()),
+                ("actual", actual.as_ref()),
+                (
+                    "config_file",
+                    location
+                        .as_ref()
+                        .and_then(|location| location.file.as_ref()),
+                ),
+            ],
+            TylError::NotImplemented {
+                tracking_issue,
+                planned_version,
+                ..
+            } => vec![
+                ("tracking_issue", tracking_issue.as_ref()),
+                ("planned_version", planned_version.as_ref()),
+            ],
+            TylError::Validation { field, input, .. } => {
+                vec![("field", Some(field)), ("input", input.as_ref())]
+            }
+            TylError::NotFound { resource, id, .. } => {
+                vec![("resource", Some(resource)), ("id", Some(id))]
+            }
+            TylError::Unknown { variant, .. } => vec![("variant", Some(variant))],
+            TylError::PermissionDenied {
+                subject,
+                action,
+                resource,
+                ..
+            } => vec![
+                ("subject", Some(subject)),
+                ("action", Some(action)),
+                ("resource", Some(resource)),
+            ],
+            _ => Vec::new(),
+        };
+
+        let mut metadata: HashMap<MetaKey, serde_json::Value> = fields
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| (MetaKey::from_static(name), serde_json::json!(value)))
+            })
+            .collect();
+        if let TylError::Configuration {
+            location: Some(location),
+            ..
+        } = self
+        {
+            for (name, position) in [
+                ("config_line", location.line),
+                ("config_column", location.column),
+            ] {
+                if let Some(position) = position {
+                    metadata.insert(MetaKey::from_static(name), serde_json::json!(position));
+                }
+            }
+        }
+        if let Some(code) = self.driver_code() {
+            metadata.insert(MetaKey::from_static(DRIVER_CODE_KEY), code.into());
+        }
+        if let Some(constraint) = self.constraint() {
+            metadata.insert(
+                MetaKey::from_static("constraint"),
+                serde_json::json!(constraint),
+            );
+        }
+        if let Some(exit) = self.downcast_source_ref::<ProcessExit>() {
+            let entries = [
+                ("exit_code", exit.code.map(serde_json::Value::from)),
+                ("exit_signal", exit.signal.map(serde_json::Value::from)),
+                ("stderr_tail", Some(exit.stderr_tail.as_str().into())),
+            ];
+            for (name, value) in entries {
+                if let Some(value) = value {
+                    metadata.insert(MetaKey::from_static(name), value);
+                }
+            }
+        }
+        if let Some(panic) = self.panic_details() {
+            let entries = [
+                ("panic_thread", panic.thread.as_deref().map(Into::into)),
+                ("panic_file", panic.file.as_deref().map(Into::into)),
+                ("panic_line", panic.line.map(serde_json::Value::from)),
+                ("panic_column", panic.column.map(serde_json::Value::from)),
+                (
+                    "panic_backtrace",
+                    (!panic.backtrace.is_empty()).then(|| serde_json::json!(panic.backtrace)),
+                ),
+            ];
+            for (name, value) in entries {
+                if let Some(value) = value {
+                    metadata.insert(MetaKey::from_static(name), value);
+                }
+            }
+        }
+        metadata
+    }
+
     /// Convert this error to an ErrorContext for tracking operations.
+    ///
+    /// With `TYL_ERROR_ID_MODE=deterministic`, the error ID is
+    /// [derived](TylError::derive_error_id) from this error and the
+    /// idempotency key set by enrichers instead of random, if one is set. The error's
+    /// [owner](TylError::owner) takes precedence over the one set by
+    /// enrichers. A [suppression](crate::suppress) of its code is recorded
+    /// under [`SUPPRESSED_UNTIL_KEY`](crate::SUPPRESSED_UNTIL_KEY). Building a
+    /// context does not count the error: [`TylError::report`] does.
     pub fn to_context(&self, operation: String) -> ErrorContext {
-        ErrorContext::new(operation, self.category(), self.to_string())
+        let mut context = ErrorContext::new(operation, self.category(), self.to_string());
+        self.describe_in(&mut context);
+        if let Some(until) = self.suppressed_until() {
+            context.add_metadata(SUPPRESSED_UNTIL_KEY, until.to_rfc3339().into());
+        }
+        context.apply_error_id_mode(self);
+        context
+    }
+
+    /// Copy the owner and metadata of this error into a context.
+    pub(crate) fn describe_in(&self, context: &mut ErrorContext) {
+        if let Some(owner) = self.owner() {
+            context.owner = Some(owner);
+        }
+        context
+            .metadata
+            .extend(limit_metadata(self.metadata_entries()));
+        for key in SENSITIVE_FIELDS {
+            if context.has_metadata(key) {
+                context.mark_sensitive(key.to_string());
+            }
+        }
+        if self
+            .retry_telemetry()
+            .is_some_and(RetryTelemetry::has_sensitive_records)
+        {
+            context.mark_sensitive("retry_attempt_records");
+        }
     }
 
     // === Environment-based Configuration ===
@@ -188,9 +1406,11 @@ impl TylError {
         ErrorSettings::global().backtrace_enabled
     }
 
-    /// Get maximum retry attempts from TYL_ERROR_MAX_RETRIES (default: 3).
+    /// Get maximum retry attempts from TYL_ERROR_MAX_RETRIES (default: 3),
+    /// lowered to TYL_ERROR_MAX_ATTEMPTS_CEILING when that is set.
     pub fn max_retries() -> usize {
-        ErrorSettings::global().max_retries
+        let settings = ErrorSettings::global();
+        settings.cap_attempts(settings.max_retries)
     }
 
     /// Check if error logging is enabled via TYL_ERROR_LOG_ERRORS (default: true).
@@ -198,40 +1418,1105 @@ impl TylError {
         ErrorSettings::global().log_errors
     }
 
+    /// Check if error logging is enabled at `level`.
+    pub(crate) fn log_enabled(level: crate::settings::LogLevel) -> bool {
+        Self::log_errors_enabled() && level <= Self::log_level()
+    }
+
     /// Get current log level from TYL_ERROR_LOG_LEVEL (default: INFO).
     pub fn log_level() -> crate::settings::LogLevel {
         ErrorSettings::global().log_level
     }
 
+    /// Check if this error is retriable, letting its classifier inspect the error itself.
+    pub fn is_retriable(&self) -> bool {
+        self.category().is_retriable_for(self)
+    }
+
+    /// Get the team owning this error, used for on-call routing.
+    ///
+    /// Set at construction through the category of custom errors (see
+    /// [`ErrorCategory::with_owner`]). Errors without one are attributed by
+    /// operation when their context is created, with
+    /// [`enrich::register_owner`](crate::enrich::register_owner).
+    pub fn owner(&self) -> Option<String> {
+        self.category().owner().map(str::to_string)
+    }
+
+    /// Get who may retry this error.
+    ///
+    /// [`RetryScope::None`] for errors that are not retriable; otherwise the
+    /// scope set by the category (see [`ErrorCategory::with_retry_scope`]),
+    /// [`RetryScope::Caller`] by default.
+    pub fn retry_scope(&self) -> RetryScope {
+        let category = self.category();
+        if !category.is_retriable_for(self) {
+            return RetryScope::None;
+        }
+        category.retry_scope_for(self).unwrap_or(RetryScope::Caller)
+    }
+
     /// Check if this error should be retried based on attempt count and max retries.
+    ///
+    /// The limit is the lower of [`TylError::max_retries`] and the retries left
+    /// by the category's [`ErrorCategory::max_attempts`].
     pub fn should_retry(&self, attempt: usize) -> bool {
-        self.category().is_retriable() && attempt < Self::max_retries()
+        let category = self.category();
+        let max_retries = category
+            .max_retries()
+            .unwrap_or(usize::MAX)
+            .min(Self::max_retries());
+        category.is_retriable_for(self) && attempt < max_retries
+    }
+
+    /// Get whether, when and by whom this error should be retried after
+    /// `attempt` retries, in one call.
+    ///
+    /// Merges the category's [retriability](TylError::is_retriable) and
+    /// [scope](TylError::retry_scope), the retry limit of
+    /// [`TylError::should_retry`] (category limit, `TYL_ERROR_MAX_RETRIES` and
+    /// `TYL_ERROR_MAX_ATTEMPTS_CEILING`) and the category's delay, never
+    /// shorter than `TYL_ERROR_MIN_DELAY`. `None` when the error should not be
+    /// retried anymore.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{RetryScope, TylError};
+    ///
+    /// let hint = TylError::network("Connection reset").retry_hint(0).unwrap();
+    /// assert_eq!(hint.scope, RetryScope::Caller);
+    /// assert!(hint.delay > Duration::ZERO);
+    /// assert!(TylError::validation("email", "Invalid").retry_hint(0).is_none());
+    /// ```
+    pub fn retry_hint(&self, attempt: usize) -> Option<RetryHint> {
+        self.retry_hint_with(attempt, None)
+    }
+
+    /// Get the [retry hint](TylError::retry_hint) of this error, waiting at
+    /// least as long as the server asked for, e.g. in a `Retry-After` header
+    /// or a received problem's [`retry_after`](crate::ProblemDetails::retry_after).
+    pub fn retry_hint_with(
+        &self,
+        attempt: usize,
+        server_retry_after: Option<Duration>,
+    ) -> Option<RetryHint> {
+        let category = self.category();
+        if !category.is_retriable_for(self) {
+            return None;
+        }
+        let max_retries = category
+            .max_retries()
+            .unwrap_or(usize::MAX)
+            .min(Self::max_retries());
+        if attempt >= max_retries {
+            return None;
+        }
+        let delay = category.retry_delay_for(self, attempt + 1);
+        Some(RetryHint {
+            delay: server_retry_after.map_or(delay, |server| delay.max(server)),
+            scope: category.retry_scope_for(self).unwrap_or(RetryScope::Caller),
+            remaining_attempts: max_retries - attempt,
+        })
+    }
+
+    /// Get the severity of this error.
+    ///
+    /// Client-side failures (validation, missing resources, conflicts) are
+    /// warnings, cancellations are informational, broken configuration is
+    /// critical, and everything else is an error. Custom errors derive their severity from their category.
+    ///
+    /// A category assigning a severity through
+    /// [`ErrorClassifier::severity_for`](crate::ErrorClassifier::severity_for),
+    /// such as an escalated [`EscalatingCategory`](crate::EscalatingCategory),
+    /// takes precedence.
+    ///
+    /// Errors whose code is [suppressed](crate::suppress) are at most
+    /// informational.
+    pub fn severity(&self) -> TylSeverity {
+        self.severity_in(&self.category())
+    }
+
+    /// Get the severity of this error given its already resolved category.
+    pub(crate) fn severity_in(&self, category: &ErrorCategory) -> TylSeverity {
+        let severity = self.unsuppressed_severity(category);
+        if self.is_suppressed() {
+            return suppress::downgrade(severity);
+        }
+        severity
+    }
+
+    fn unsuppressed_severity(&self, category: &ErrorCategory) -> TylSeverity {
+        if let Some(severity) = category.severity_for(self) {
+            return severity;
+        }
+        match self {
+            TylError::Validation { .. }
+            | TylError::NotFound { .. }
+            | TylError::Conflict { .. }
+            | TylError::Unauthenticated { .. }
+            | TylError::PermissionDenied { .. }
+            | TylError::NotImplemented { .. } => TylSeverity::Warning,
+            TylError::Database { .. } | TylError::Network { .. } | TylError::Internal { .. } => {
+                TylSeverity::Error
+            }
+            TylError::Timeout { .. } => TylSeverity::Error,
+            TylError::Cancelled { .. } => TylSeverity::Info,
+            TylError::Configuration { .. } => TylSeverity::Critical,
+            TylError::InvariantViolated { .. } => TylSeverity::Error,
+            TylError::Unknown { .. } => TylSeverity::Error,
+            TylError::Custom { .. } => TylSeverity::for_category(&self.category()),
+        }
+    }
+
+    /// Get the level this error is logged at.
+    ///
+    /// Uses the level configured for the error's category in
+    /// `TYL_ERROR_CATEGORY_LOG_LEVELS` if any, and the level of its
+    /// [severity](TylError::severity) otherwise: validation failures log as
+    /// warnings, cancellations as info and internal failures as errors.
+    /// Errors whose code is [suppressed](crate::suppress) log at their
+    /// downgraded severity's level regardless of the configured one.
+    pub fn effective_log_level(&self) -> LogLevel {
+        self.effective_log_level_with(&ErrorSettings::global())
+    }
+
+    /// Get the level this error is logged at under the given settings.
+    pub fn effective_log_level_with(&self, settings: &ErrorSettings) -> LogLevel {
+        if self.is_suppressed() {
+            return self.severity().log_level();
+        }
+        settings
+            .category_log_level(self.category().category_name())
+            .unwrap_or_else(|| self.severity().log_level())
+    }
+
+    /// Log this error at its [effective level](TylError::effective_log_level)
+    /// if logging is enabled and the level passes `TYL_ERROR_LOG_LEVEL`.
+    pub fn log(&self) {
+        self.log_at(self.effective_log_level());
     }
 
     /// Log error if logging is enabled and meets log level criteria.
+    #[deprecated(
+        note = "use `TylError::log`, which picks the level from the error's severity and category"
+    )]
     pub fn log_if_enabled(&self, level: crate::settings::LogLevel) {
-        if Self::log_errors_enabled() && level <= Self::log_level() {
-            eprintln!(
-                "[{}] {}",
-                match level {
-                    crate::settings::LogLevel::Error => "ERROR",
-                    crate::settings::LogLevel::Warn => "WARN",
-                    crate::settings::LogLevel::Info => "INFO",
-                    crate::settings::LogLevel::Debug => "DEBUG",
-                },
+        self.log_at(level);
+    }
+
+    /// Log error at the given level if logging is enabled and meets log level criteria.
+    ///
+    /// Repeated errors are rate limited by fingerprint (see [`LogRateLimiter`]).
+    pub(crate) fn log_at(&self, level: crate::settings::LogLevel) {
+        if !Self::log_enabled(level) {
+            return;
+        }
+        let limiter = LogRateLimiter::global();
+        match limiter.check(&self.fingerprint()) {
+            LogDecision::Emit { suppressed: 0 } => eprintln!("[{}] {}", level.as_str(), self),
+            LogDecision::Emit { suppressed } => eprintln!(
+                "[{}] {} ({suppressed} similar errors suppressed)",
+                level.as_str(),
                 self
+            ),
+            LogDecision::Suppress => {}
+        }
+        for summary in limiter.take_summaries() {
+            eprintln!(
+                "[{}] {} similar errors suppressed (fingerprint {})",
+                level.as_str(),
+                summary.pending,
+                summary.fingerprint
             );
         }
     }
 }
 
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Replace ids in a message with `#`.
+///
+/// Words of ASCII letters, digits and dashes containing a digit are replaced
+/// whole when they are hex ids or UUIDs, optionally prefixed with `0x`; in
+/// other words, every run of digits is replaced with a single `#`.
+fn mask_ids(text: &str) -> String {
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if !c.is_ascii_alphanumeric() {
+            masked.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        let (word, tail) = rest.split_at(end);
+        let hex = word
+            .strip_prefix("0x")
+            .or_else(|| word.strip_prefix("0X"))
+            .unwrap_or(word);
+        if !word.bytes().any(|b| b.is_ascii_digit()) {
+            masked.push_str(word);
+        } else if hex.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-') {
+            masked.push('#');
+        } else {
+            let mut in_digits = false;
+            for c in word.chars() {
+                if c.is_ascii_digit() {
+                    if !in_digits {
+                        masked.push('#');
+                    }
+                    in_digits = true;
+                } else {
+                    masked.push(c);
+                    in_digits = false;
+                }
+            }
+        }
+        rest = tail;
+    }
+    masked
+}
+
+/// Maximum number of differing paths listed in an invariant violation message.
+const MAX_INVARIANT_DIFFS: usize = 5;
+
+/// Readable comparison of an invariant's expected and actual values.
+struct InvariantDiff<'a>(&'a serde_json::Value, &'a serde_json::Value);
+
+impl fmt::Display for InvariantDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut diffs = Vec::new();
+        collect_diffs(String::new(), Some(self.0), Some(self.1), &mut diffs);
+        write!(f, "expected {}, got {}", self.0, self.1)?;
+        // Scalar mismatches are already fully described by the values.
+        if diffs.iter().all(|(path, _, _)| path.is_empty()) {
+            return Ok(());
+        }
+
+        f.write_str(" (differs at ")?;
+        for (index, (path, expected, actual)) in diffs.iter().take(MAX_INVARIANT_DIFFS).enumerate()
+        {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            let missing = || "<missing>".to_string();
+            write!(
+                f,
+                "{path}: {} != {}",
+                expected.map_or_else(missing, |v| v.to_string()),
+                actual.map_or_else(missing, |v| v.to_string())
+            )?;
+        }
+        if diffs.len() > MAX_INVARIANT_DIFFS {
+            write!(f, "; {} more", diffs.len() - MAX_INVARIANT_DIFFS)?;
+        }
+        f.write_str(")")
+    }
+}
+
+type ValueDiff<'a> = (
+    String,
+    Option<&'a serde_json::Value>,
+    Option<&'a serde_json::Value>,
+);
+
+/// Collect the paths at which two JSON values differ, descending into objects and arrays.
+fn collect_diffs<'a>(
+    path: String,
+    expected: Option<&'a serde_json::Value>,
+    actual: Option<&'a serde_json::Value>,
+    diffs: &mut Vec<ValueDiff<'a>>,
+) {
+    use serde_json::Value;
+
+    let child = |key: &dyn fmt::Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (expected, actual) {
+        (Some(Value::Object(left)), Some(Value::Object(right))) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                collect_diffs(child(key), left.get(key), right.get(key), diffs);
+            }
+        }
+        (Some(Value::Array(left)), Some(Value::Array(right))) => {
+            for index in 0..left.len().max(right.len()) {
+                collect_diffs(child(&index), left.get(index), right.get(index), diffs);
+            }
+        }
+        (left, right) if left != right => diffs.push((path, left, right)),
+        _ => {}
+    }
+}
+
+/// `Display` view of an error with its [location](TylError::location).
+///
+/// Created by [`TylError::verbose`].
+#[derive(Debug, Clone, Copy)]
+pub struct VerboseDisplay<'a>(&'a TylError);
+
+impl fmt::Display for VerboseDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        match self.0.location() {
+            Some(location) => write!(f, " (at {}:{})", location.file(), location.line()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Optional tracking details of a `NotImplemented` error, written straight into the formatter.
+struct TrackingSuffix<'a>(&'a Option<String>, &'a Option<String>);
+
+impl fmt::Display for TrackingSuffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.0, self.1) {
+            (Some(issue), Some(version)) => {
+                write!(f, " (tracking: {issue}, planned for {version})")
+            }
+            (Some(issue), None) => write!(f, " (tracking: {issue})"),
+            (None, Some(version)) => write!(f, " (planned for {version})"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl RedactedDebug for TylError {
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>, redact: bool) -> fmt::Result {
+        match self {
+            TylError::Database {
+                message, extras, ..
+            } => f
+                .debug_struct("Database")
+                .field("message", &debug_message(message, redact))
+                .field("driver_code", &extras.driver_code())
+                .finish(),
+            TylError::Network { message, .. } => f
+                .debug_struct("Network")
+                .field("message", &debug_message(message, redact))
+                .finish(),
+            TylError::Validation {
+                field,
+                message,
+                input,
+                source,
+                extras,
+            } => f
+                .debug_struct("Validation")
+                .field("field", field)
+                .field("message", &debug_message(message, redact))
+                .field("input", &input.as_deref().map(|i| debug_message(i, redact)))
+                .field("constraint", &extras.constraint())
+                .field("source", source)
+                .finish(),
+            TylError::NotFound { resource, id, .. } => f
+                .debug_struct("NotFound")
+                .field("resource", resource)
+                .field("id", id)
+                .finish(),
+            TylError::Conflict { message, .. } => f
+                .debug_struct("Conflict")
+                .field("message", &debug_message(message, redact))
+                .finish(),
+            TylError::Internal { message, .. } => f
+                .debug_struct("Internal")
+                .field("message", &debug_message(message, redact))
+                .finish(),
+            TylError::Unauthenticated { reason, .. } => f
+                .debug_struct("Unauthenticated")
+                .field("reason", &debug_message(reason, redact))
+                .finish(),
+            TylError::PermissionDenied {
+                subject,
+                action,
+                resource,
+                ..
+            } => f
+                .debug_struct("PermissionDenied")
+                .field("subject", &if redact { REDACTED } else { subject.as_str() })
+                .field("action", action)
+                .field("resource", resource)
+                .finish(),
+            TylError::Timeout { message, .. } => f
+                .debug_struct("Timeout")
+                .field("message", &debug_message(message, redact))
+                .finish(),
+            TylError::Cancelled { message, .. } => f
+                .debug_struct("Cancelled")
+                .field("message", &debug_message(message, redact))
+                .finish(),
+            TylError::InvariantViolated {
+                name,
+                expected,
+                actual,
+                ..
+            } => {
+                let (expected, actual): (&dyn fmt::Debug, &dyn fmt::Debug) = if redact {
+                    (&REDACTED, &REDACTED)
+                } else {
+                    (expected, actual)
+                };
+                f.debug_struct("InvariantViolated")
+                    .field("name", name)
+                    .field("expected", expected)
+                    .field("actual", actual)
+                    .finish()
+            }
+            TylError::Configuration {
+                message,
+                key,
+                expected,
+                actual,
+                location,
+                ..
+            } => f
+                .debug_struct("Configuration")
+                .field("message", &debug_message(message, redact))
+                .field("key", key)
+                .field("expected", expected)
+                .field("actual", actual)
+                .field("location", location)
+                .finish(),
+            TylError::NotImplemented {
+                feature,
+                tracking_issue,
+                planned_version,
+                ..
+            } => f
+                .debug_struct("NotImplemented")
+                .field("feature", feature)
+                .field("tracking_issue", tracking_issue)
+                .field("planned_version", planned_version)
+                .finish(),
+            TylError::Unknown { variant, raw } => {
+                let raw: &dyn fmt::Debug = if redact { &REDACTED } else { raw };
+                f.debug_struct("Unknown")
+                    .field("variant", variant)
+                    .field("raw", raw)
+                    .finish()
+            }
+            TylError::Custom {
+                message,
+                classifier,
+                source,
+                ..
+            } => f
+                .debug_struct("Custom")
+                .field("message", &debug_message(message, redact))
+                .field("classifier", classifier)
+                .field("source", source)
+                .finish(),
+        }
+    }
+}
+
+/// Names of the variants known to this version, as they appear in serialized errors.
+const KNOWN_VARIANTS: &[&str] = &[
+    "Database",
+    "Network",
+    "Validation",
+    "NotFound",
+    "Conflict",
+    "Internal",
+    "Unauthenticated",
+    "PermissionDenied",
+    "Timeout",
+    "Cancelled",
+    "InvariantViolated",
+    "Configuration",
+    "NotImplemented",
+    "Custom",
+];
+
+impl Serialize for TylError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        match self {
+            TylError::Unknown {
+                variant,
+                raw: serde_json::Value::Null,
+            } => serializer.serialize_str(variant),
+            TylError::Unknown { variant, raw } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(variant, raw)?;
+                map.end()
+            }
+            known => TylError::serialize(known, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TylError {
+    /// Deserialize an error, turning variants unknown to this version into
+    /// [`TylError::Unknown`].
+    ///
+    /// Human-readable formats are read as a variant name (`"Throttled"`) or a
+    /// single-entry map; other formats, such as bincode, as an enum.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let error = if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ErrorVisitor)?
+        } else {
+            deserializer.deserialize_enum("TylError", KNOWN_VARIANTS, ErrorVisitor)?
+        };
+        Ok(match error {
+            unknown @ TylError::Unknown { .. } => unknown,
+            known => known.limited(),
+        })
+    }
+}
+
+/// Visitor of serialized errors, handing known variants to the derived
+/// implementation.
+struct ErrorVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ErrorVisitor {
+    type Value = TylError;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a TylError variant")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, variant: &str) -> Result<TylError, E> {
+        if KNOWN_VARIANTS.contains(&variant) {
+            // Rejected by the derived implementation: no variant is a unit.
+            return TylError::deserialize(serde::de::value::StrDeserializer::new(variant));
+        }
+        Ok(TylError::Unknown {
+            variant: variant.to_string(),
+            raw: serde_json::Value::Null,
+        })
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<TylError, A::Error> {
+        use serde::de::Error as _;
+
+        let variant: String = map
+            .next_key()?
+            .ok_or_else(|| A::Error::custom("expected a TylError variant, found an empty map"))?;
+        let error = if KNOWN_VARIANTS.contains(&variant.as_str()) {
+            TylError::deserialize(serde::de::value::EnumAccessDeserializer::new(MapVariant {
+                variant: VariantTag::Name(variant),
+                map: &mut map,
+            }))?
+        } else {
+            TylError::Unknown {
+                variant,
+                raw: map.next_value()?,
+            }
+        };
+        if map.next_key::<serde::de::IgnoredAny>()?.is_some() {
+            return Err(A::Error::custom("expected a single TylError variant"));
+        }
+        Ok(error)
+    }
+
+    fn visit_enum<A: serde::de::EnumAccess<'de>>(self, data: A) -> Result<TylError, A::Error> {
+        use serde::de::VariantAccess as _;
+
+        let (variant, access) = data.variant::<VariantTag>()?;
+        match variant {
+            VariantTag::Name(name) if !KNOWN_VARIANTS.contains(&name.as_str()) => {
+                Ok(TylError::Unknown {
+                    variant: name,
+                    raw: access.newtype_variant()?,
+                })
+            }
+            variant => {
+                TylError::deserialize(serde::de::value::EnumAccessDeserializer::new(ReadVariant {
+                    variant,
+                    access,
+                }))
+            }
+        }
+    }
+}
+
+/// Variant of a serialized error, by name or, in compact formats, by index.
+enum VariantTag {
+    Name(String),
+    Index(u64),
+}
+
+impl VariantTag {
+    /// Hand the variant to the identifier seed of the derived implementation.
+    fn identify<'de, S, E>(self, seed: S) -> Result<S::Value, E>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+        E: serde::de::Error,
+    {
+        use serde::de::IntoDeserializer as _;
+
+        match self {
+            VariantTag::Name(name) => seed.deserialize(name.into_deserializer()),
+            VariantTag::Index(index) => seed.deserialize(index.into_deserializer()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VariantTag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TagVisitor {
+            type Value = VariantTag;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a variant name or index")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<VariantTag, E> {
+                Ok(VariantTag::Name(name.to_string()))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, name: &[u8]) -> Result<VariantTag, E> {
+                Ok(VariantTag::Name(String::from_utf8_lossy(name).into_owned()))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, index: u64) -> Result<VariantTag, E> {
+                Ok(VariantTag::Index(index))
+            }
+        }
+
+        deserializer.deserialize_identifier(TagVisitor)
+    }
+}
+
+/// A variant whose tag was already read from an enum, with its payload.
+struct ReadVariant<A> {
+    variant: VariantTag,
+    access: A,
+}
+
+impl<'de, A: serde::de::VariantAccess<'de>> serde::de::EnumAccess<'de> for ReadVariant<A> {
+    type Error = A::Error;
+    type Variant = A;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, A), A::Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        Ok((self.variant.identify(seed)?, self.access))
+    }
+}
+
+/// A variant whose name was already read as the key of a map, with the
+/// payload as its value.
+struct MapVariant<'a, A> {
+    variant: VariantTag,
+    map: &'a mut A,
+}
+
+impl<'de, 'a, A: serde::de::MapAccess<'de>> serde::de::EnumAccess<'de> for MapVariant<'a, A> {
+    type Error = A::Error;
+    type Variant = Self;
+
+    fn variant_seed<S>(mut self, seed: S) -> Result<(S::Value, Self), A::Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        let variant = std::mem::replace(&mut self.variant, VariantTag::Index(0));
+        Ok((variant.identify(seed)?, self))
+    }
+}
+
+impl<'de, 'a, A: serde::de::MapAccess<'de>> serde::de::VariantAccess<'de> for MapVariant<'a, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.map.next_value()
+    }
+
+    fn newtype_variant_seed<S>(self, seed: S) -> Result<S::Value, A::Error>
+    where
+        S: serde::de::DeserializeSeed<'de>,
+    {
+        self.map.next_value_seed(seed)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.map
+            .next_value_seed(PayloadSeed::Tuple { len, visitor })
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.map
+            .next_value_seed(PayloadSeed::Struct { fields, visitor })
+    }
+}
+
+/// Seed reading the payload of a tuple or struct variant from a map value.
+enum PayloadSeed<V> {
+    Tuple {
+        len: usize,
+        visitor: V,
+    },
+    Struct {
+        fields: &'static [&'static str],
+        visitor: V,
+    },
+}
+
+impl<'de, V: serde::de::Visitor<'de>> serde::de::DeserializeSeed<'de> for PayloadSeed<V> {
+    type Value = V::Value;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        match self {
+            PayloadSeed::Tuple { len, visitor } => deserializer.deserialize_tuple(len, visitor),
+            PayloadSeed::Struct { fields, visitor } => {
+                deserializer.deserialize_struct("TylError", fields, visitor)
+            }
+        }
+    }
+}
+
+/// Fields of an error rebuilt from its code by [`TylError::from_code`];
+/// each variant reads the fields it carries.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CodeFields<'a> {
+    pub(crate) message: &'a str,
+    pub(crate) field: &'a str,
+    pub(crate) resource: &'a str,
+    pub(crate) id: &'a str,
+    pub(crate) subject: &'a str,
+    pub(crate) action: &'a str,
+}
+
+impl TylError {
+    /// Get the message of this error without the prefix of its `Display`
+    /// output, for the variants carrying one.
+    pub(crate) fn plain_message(&self) -> Option<&str> {
+        match self {
+            TylError::Database { message, .. }
+            | TylError::Network { message, .. }
+            | TylError::Validation { message, .. }
+            | TylError::Conflict { message, .. }
+            | TylError::Internal { message, .. }
+            | TylError::Timeout { message, .. }
+            | TylError::Cancelled { message, .. }
+            | TylError::Configuration { message, .. }
+            | TylError::Custom { message, .. } => Some(message),
+            TylError::Unauthenticated { reason, .. } => Some(reason),
+            TylError::NotImplemented { feature, .. } => Some(feature),
+            TylError::NotFound { .. }
+            | TylError::PermissionDenied { .. }
+            | TylError::InvariantViolated { .. }
+            | TylError::Unknown { .. } => None,
+        }
+    }
+
+    /// Build the variant of a built-in code from its fields, e.g. for
+    /// injected faults or errors received from other services.
+    ///
+    /// `None` for `custom`, `unknown` and codes that are not built-in.
+    #[track_caller]
+    pub(crate) fn from_code(code: &str, fields: CodeFields<'_>) -> Option<TylError> {
+        let message = fields.message;
+        Some(match code {
+            "database" => TylError::database(message),
+            "network" => TylError::network(message),
+            "timeout" => TylError::timeout(message),
+            "conflict" => TylError::conflict(message),
+            "cancelled" => TylError::cancelled(message),
+            "unauthenticated" => TylError::unauthenticated(message),
+            "internal" => TylError::internal(message),
+            "configuration" => TylError::configuration(message),
+            "not_implemented" => TylError::not_implemented(message),
+            "validation" => TylError::validation(fields.field, message),
+            "not_found" => TylError::not_found(fields.resource, fields.id),
+            "permission_denied" => {
+                TylError::permission_denied(fields.subject, fields.action, fields.resource)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Apply the size limits to the fields of an error that did not go
+    /// through its constructors, e.g. one received from another service.
+    fn limited(mut self) -> Self {
+        match &mut self {
+            TylError::Database { message, .. }
+            | TylError::Network { message, .. }
+            | TylError::Conflict { message, .. }
+            | TylError::Internal { message, .. }
+            | TylError::Timeout { message, .. }
+            | TylError::Cancelled { message, .. }
+            | TylError::Configuration { message, .. }
+            | TylError::Custom { message, .. }
+            | TylError::Unauthenticated {
+                reason: message, ..
+            } => *message = limit_message(std::mem::take(message)),
+            TylError::Validation { message, input, .. } => {
+                *message = limit_message(std::mem::take(message));
+                *input = input.take().map(limit_message);
+            }
+            TylError::InvariantViolated {
+                expected, actual, ..
+            } => {
+                *expected = limit_value(expected.take());
+                *actual = limit_value(actual.take());
+            }
+            TylError::NotFound { .. }
+            | TylError::PermissionDenied { .. }
+            | TylError::NotImplemented { .. }
+            | TylError::Unknown { .. } => {}
+        }
+        self
+    }
+}
+
+impl fmt::Debug for TylError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(f, ErrorSettings::global().redact)
+    }
+}
+
 // === Standard Library Integrations ===
 
 /// Convert serde_json errors to TylError.
 impl From<serde_json::Error> for TylError {
+    #[track_caller]
     fn from(err: serde_json::Error) -> Self {
         Self::Internal {
-            message: format!("JSON serialization error: {err}"),
+            message: limit_message(format!("JSON serialization error: {err}")),
+            extras: ErrorExtras::new(),
         }
     }
 }
+
+/// Build a parsing error that keeps the std error as its source.
+#[track_caller]
+fn parse_failure<E>(what: &str, err: E) -> TylError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    field_failure("parsing", what, err)
+}
+
+/// Build a validation error for `field` that keeps the error as its source.
+#[track_caller]
+fn field_failure<E>(field: &str, what: &str, err: E) -> TylError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    TylError::Validation {
+        field: field.to_string(),
+        message: limit_message(format!("{what}: {err}")),
+        input: None,
+        source: Some(Arc::new(err)),
+        extras: ErrorExtras::new(),
+    }
+}
+
+/// Convert boxed errors (e.g. tower's `BoxError`) to errors.
+///
+/// A boxed `TylError` is unboxed unchanged; any other error is wrapped as an
+/// internal error keeping it as the source.
+impl From<Box<dyn std::error::Error + Send + Sync>> for TylError {
+    #[track_caller]
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match err.downcast::<TylError>() {
+            Ok(error) => *error,
+            Err(err) => Self::Custom {
+                message: limit_message(err.to_string()),
+                classifier: Box::new(BuiltinCategory::Internal),
+                source: Some(Arc::from(err)),
+                extras: ErrorExtras::new(),
+            },
+        }
+    }
+}
+
+/// Convert integer parse errors to parsing validation errors.
+impl From<std::num::ParseIntError> for TylError {
+    #[track_caller]
+    fn from(err: std::num::ParseIntError) -> Self {
+        parse_failure("Invalid integer", err)
+    }
+}
+
+/// Convert float parse errors to parsing validation errors.
+impl From<std::num::ParseFloatError> for TylError {
+    #[track_caller]
+    fn from(err: std::num::ParseFloatError) -> Self {
+        parse_failure("Invalid float", err)
+    }
+}
+
+/// Convert UTF-8 decoding errors to parsing validation errors.
+impl From<std::str::Utf8Error> for TylError {
+    #[track_caller]
+    fn from(err: std::str::Utf8Error) -> Self {
+        parse_failure("Invalid UTF-8", err)
+    }
+}
+
+/// Convert UTF-8 decoding errors to parsing validation errors.
+impl From<std::string::FromUtf8Error> for TylError {
+    #[track_caller]
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        parse_failure("Invalid UTF-8", err)
+    }
+}
+
+/// Convert environment variable lookup errors to configuration errors.
+///
+/// `VarError` does not carry the variable name; use [`TylError::env_missing`]
+/// when the name should be recorded. Values that are not valid unicode are
+/// not recorded, since environment variables often hold secrets.
+impl From<std::env::VarError> for TylError {
+    #[track_caller]
+    fn from(err: std::env::VarError) -> Self {
+        match err {
+            std::env::VarError::NotPresent => {
+                TylError::configuration("environment variable not present")
+            }
+            std::env::VarError::NotUnicode(_) => TylError::Configuration {
+                message: "environment variable is not valid unicode".to_string(),
+                key: None,
+                expected: Some("valid unicode".to_string()),
+                actual: None,
+                location: None,
+                extras: ErrorExtras::new(),
+            },
+        }
+    }
+}
+
+/// Extension method recording the input of failed parses.
+pub trait InputResultExt<T> {
+    /// Convert the error of this result into a [`TylError`] recording the
+    /// offending input, see [`TylError::with_input`].
+    ///
+    /// Parse errors such as `uuid::Error` do not keep their input, so the
+    /// `From` conversions used by `?` cannot record it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{InputResultExt, TylResult};
+    ///
+    /// fn parse_id(raw: &str) -> TylResult<uuid::Uuid> {
+    ///     uuid::Uuid::parse_str(raw).with_input(raw)
+    /// }
+    ///
+    /// let error = parse_id("not-a-uuid").unwrap_err();
+    /// assert_eq!(error.metadata()["input"], "not-a-uuid");
+    /// ```
+    #[track_caller]
+    fn with_input<I: Into<String>>(self, input: I) -> TylResult<T>;
+}
+
+impl<T, E> InputResultExt<T> for Result<T, E>
+where
+    TylError: From<E>,
+{
+    #[track_caller]
+    fn with_input<I: Into<String>>(self, input: I) -> TylResult<T> {
+        // Not `map_err`: closures do not forward the caller's location.
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(TylError::from(error).with_input(input)),
+        }
+    }
+}
+
+/// Convert chrono parse errors to parsing validation errors.
+impl From<chrono::ParseError> for TylError {
+    #[track_caller]
+    fn from(err: chrono::ParseError) -> Self {
+        parse_failure("Invalid timestamp", err)
+    }
+}
+
+/// Convert uuid parse errors to parsing validation errors.
+impl From<uuid::Error> for TylError {
+    #[track_caller]
+    fn from(err: uuid::Error) -> Self {
+        parse_failure("Invalid UUID", err)
+    }
+}
+
+// === Optional Integrations ===
+
+/// Convert URL parse errors to validation errors on the `url` field.
+#[cfg(feature = "url")]
+impl From<url::ParseError> for TylError {
+    #[track_caller]
+    fn from(err: url::ParseError) -> Self {
+        field_failure("url", "Invalid URL", err)
+    }
+}
+
+/// Convert invalid header values to validation errors on the `header` field.
+#[cfg(feature = "http")]
+impl From<http::header::InvalidHeaderValue> for TylError {
+    #[track_caller]
+    fn from(err: http::header::InvalidHeaderValue) -> Self {
+        field_failure("header", "Invalid header value", err)
+    }
+}
+
+/// Convert non-visible-ASCII header values to validation errors on the `header` field.
+#[cfg(feature = "http")]
+impl From<http::header::ToStrError> for TylError {
+    #[track_caller]
+    fn from(err: http::header::ToStrError) -> Self {
+        field_failure("header", "Header value is not visible ASCII", err)
+    }
+}
+
+/// Convert tokio deadline errors to timeout errors.
+#[cfg(feature = "tokio")]
+impl From<tokio::time::error::Elapsed> for TylError {
+    #[track_caller]
+    fn from(err: tokio::time::error::Elapsed) -> Self {
+        Self::Timeout {
+            message: limit_message(err.to_string()),
+            extras: ErrorExtras::new(),
+        }
+    }
+}
+
+/// Convert tokio task join errors to cancellation or internal errors.
+///
+/// Cancelled tasks become [`TylError::Cancelled`]; panicked tasks become
+/// [`TylError::Internal`] carrying the panic message when it is a string.
+#[cfg(feature = "tokio")]
+impl From<tokio::task::JoinError> for TylError {
+    #[track_caller]
+    fn from(err: tokio::task::JoinError) -> Self {
+        if err.is_cancelled() {
+            return Self::Cancelled {
+                message: "Task was cancelled".to_string(),
+                source: None,
+                extras: ErrorExtras::new(),
+            };
+        }
+
+        // The task may have panicked on another worker thread.
+        crate::panic_capture::panic_error("Task panicked", err.into_panic().as_ref(), None)
+    }
+}
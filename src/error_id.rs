@@ -0,0 +1,131 @@
+//! Deterministic error IDs.
+//!
+//! Error IDs are random by default. For pipelines that report idempotently,
+//! this module derives the ID as a UUIDv5 of the error
+//! [fingerprint](TylError::fingerprint) and the idempotency key, so every
+//! retry or replay of the same logical failure reports the same ID. The mode
+//! is selected per context with [`ErrorContext::with_derived_id`] or globally
+//! with `TYL_ERROR_ID_MODE=deterministic`.
+
+use crate::context::ErrorContext;
+use crate::error::TylError;
+use crate::settings::{ErrorIdMode, ErrorSettings};
+use uuid::Uuid;
+
+/// Namespace of deterministic error IDs.
+pub const ERROR_ID_NAMESPACE: Uuid = Uuid::from_u128(0x4e30_94da_a546_525c_9e4f_2fba_decb_e8ad);
+
+impl TylError {
+    /// Derive the deterministic error ID of this failure.
+    ///
+    /// A UUIDv5 in [`ERROR_ID_NAMESPACE`] of the fingerprint and the
+    /// idempotency key of the logical request. Without a key, occurrences of
+    /// the same failure cannot be told apart from unrelated ones, so a
+    /// random UUIDv4 is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let first = TylError::database("Deadlock on row 17");
+    /// let replay = TylError::database("Deadlock on row 17");
+    /// assert_eq!(first.derive_error_id(Some("order-42")), replay.derive_error_id(Some("order-42")));
+    /// assert_ne!(first.derive_error_id(Some("order-42")), first.derive_error_id(Some("order-43")));
+    /// assert_eq!(first.derive_error_id(None).get_version_num(), 4);
+    /// ```
+    pub fn derive_error_id(&self, idempotency_key: Option<&str>) -> Uuid {
+        let Some(key) = idempotency_key else {
+            return Uuid::new_v4();
+        };
+        let mut name = self.fingerprint();
+        name.push('\0');
+        name.push_str(key);
+        Uuid::new_v5(&ERROR_ID_NAMESPACE, name.as_bytes())
+    }
+}
+
+impl ErrorContext {
+    /// Replace the error ID with the one derived from `error` and this
+    /// context's idempotency key, using builder pattern.
+    ///
+    /// Call after setting the idempotency key; without one, the random ID is
+    /// kept. With `TYL_ERROR_ID_MODE=deterministic`, [`TylError::to_context`]
+    /// already derives the ID from the key set by enrichers.
+    pub fn with_derived_id(mut self, error: &TylError) -> Self {
+        if let Some(key) = self.idempotency_key.as_deref() {
+            self.error_id = error.derive_error_id(Some(key));
+        }
+        self
+    }
+
+    /// Derive the error ID from `error` if the global mode is deterministic
+    /// and the context has an idempotency key.
+    pub(crate) fn apply_error_id_mode(&mut self, error: &TylError) {
+        if ErrorSettings::global().error_id_mode == ErrorIdMode::Deterministic {
+            if let Some(key) = self.idempotency_key.as_deref() {
+                self.error_id = error.derive_error_id(Some(key));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_ids_should_identify_the_logical_failure() {
+        // Given: two occurrences of the same failure for one request
+        let error = TylError::network("Connection reset after 1503ms");
+        let replay = TylError::network("Connection reset after 2210ms");
+
+        // When: deriving context IDs from them
+        let first = error
+            .to_context("orders.sync".to_string())
+            .with_idempotency_key("sync-7")
+            .with_derived_id(&error);
+        let second = replay
+            .to_context("orders.sync".to_string())
+            .with_idempotency_key("sync-7")
+            .with_derived_id(&replay);
+        let other_request = error
+            .to_context("orders.sync".to_string())
+            .with_idempotency_key("sync-8")
+            .with_derived_id(&error);
+
+        // Then: the same failure of the same request should share its ID
+        assert_eq!(first.error_id, second.error_id);
+        assert_ne!(first.error_id, other_request.error_id);
+        assert_ne!(
+            first.error_id,
+            TylError::timeout("Connection reset after 1503ms").derive_error_id(Some("sync-7"))
+        );
+        assert_eq!(
+            first.error_id.to_string(),
+            Uuid::new_v5(
+                &ERROR_ID_NAMESPACE,
+                format!("{}\0sync-7", error.fingerprint()).as_bytes()
+            )
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_ids_without_idempotency_key_should_stay_random() {
+        // Given: two occurrences of the same failure without idempotency key
+        let error = TylError::network("Connection reset");
+
+        // When: deriving their IDs
+        let first = error
+            .to_context("orders.sync".to_string())
+            .with_derived_id(&error);
+        let second = error
+            .to_context("orders.sync".to_string())
+            .with_derived_id(&error);
+
+        // Then: they should not be merged into one occurrence
+        assert_ne!(first.error_id, second.error_id);
+        assert_eq!(first.error_id.get_version_num(), 4);
+        assert_ne!(error.derive_error_id(None), error.derive_error_id(None));
+    }
+}
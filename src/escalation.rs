@@ -0,0 +1,187 @@
+//! Escalation of errors that keep failing.
+//!
+//! This module provides [`EscalatingCategory`], a classifier wrapper that
+//! keeps an error retriable for a number of attempts and then treats it as
+//! permanent with a raised severity, so a dependency that never recovers ends
+//! up alerted instead of silently retried.
+
+use crate::category::{ErrorCategory, ErrorClassifier, RetryScope};
+use crate::domain::TylDomain;
+use crate::error::{TylError, TylSeverity};
+use std::time::Duration;
+
+/// Category retriable for the first N retry attempts, permanent afterwards.
+///
+/// Wraps a builtin or custom category. Retry executors stop after `attempts`
+/// retries, and an error is escalated once its
+/// [retry telemetry](TylError::retry_telemetry) shows those retries were
+/// made: it is then no longer retriable and its
+/// [severity](TylError::severity) is raised (to [`TylSeverity::Critical`]
+/// by default).
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{ErrorCategory, EscalatingCategory, RetryPolicy, TylError, TylResult, TylSeverity};
+///
+/// let category = EscalatingCategory::new(ErrorCategory::service_unavailable(), 3);
+/// let policy = RetryPolicy::new().with_base_delay(Duration::from_millis(1));
+///
+/// let result: TylResult<()> = policy.execute(|| {
+///     Err(TylError::business_logic("Ledger unavailable", category.clone().into()))
+/// });
+///
+/// let error = result.unwrap_err();
+/// assert!(category.is_escalated(&error));
+/// assert!(!error.is_retriable());
+/// assert_eq!(error.severity(), TylSeverity::Critical);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EscalatingCategory {
+    inner: Box<dyn ErrorClassifier>,
+    attempts: usize,
+    severity: TylSeverity,
+}
+
+impl EscalatingCategory {
+    /// Wrap a category, escalating its errors after `attempts` retries.
+    pub fn new(category: ErrorCategory, attempts: usize) -> Self {
+        Self {
+            inner: category.into_classifier(),
+            attempts,
+            severity: TylSeverity::Critical,
+        }
+    }
+
+    /// Set the severity of escalated errors.
+    pub fn with_severity(mut self, severity: TylSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Get the number of retries before escalation.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Check if an error has used up its retries.
+    pub fn is_escalated(&self, error: &TylError) -> bool {
+        let attempts = error.retry_telemetry().map_or(1, |t| t.attempts);
+        attempts > self.attempts
+    }
+}
+
+impl ErrorClassifier for EscalatingCategory {
+    fn is_retriable(&self) -> bool {
+        self.attempts > 0 && self.inner.is_retriable()
+    }
+
+    fn retry_delay(&self, attempt: usize) -> Duration {
+        self.inner.retry_delay(attempt)
+    }
+
+    fn category_name(&self) -> &'static str {
+        self.inner.category_name()
+    }
+
+    fn clone_box(&self) -> Box<dyn ErrorClassifier> {
+        Box::new(self.clone())
+    }
+
+    fn is_retriable_for(&self, error: &TylError) -> bool {
+        !self.is_escalated(error) && self.inner.is_retriable_for(error)
+    }
+
+    fn retry_delay_for(&self, error: &TylError, attempt: usize) -> Duration {
+        self.inner.retry_delay_for(error, attempt)
+    }
+
+    fn failed_before_side_effects(&self, error: &TylError) -> bool {
+        self.inner.failed_before_side_effects(error)
+    }
+
+    fn max_attempts(&self) -> Option<usize> {
+        let attempts = self.attempts.saturating_add(1);
+        Some(
+            self.inner
+                .max_attempts()
+                .map_or(attempts, |max| max.min(attempts)),
+        )
+    }
+
+    fn severity_for(&self, error: &TylError) -> Option<TylSeverity> {
+        if self.is_escalated(error) {
+            Some(self.severity)
+        } else {
+            self.inner.severity_for(error)
+        }
+    }
+
+    fn retry_scope_for(&self, error: &TylError) -> Option<RetryScope> {
+        self.inner.retry_scope_for(error)
+    }
+
+    fn owner(&self) -> Option<&str> {
+        self.inner.owner()
+    }
+
+    fn domain(&self) -> Option<&TylDomain> {
+        self.inner.domain()
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        self.inner.code()
+    }
+}
+
+impl From<EscalatingCategory> for ErrorCategory {
+    fn from(category: EscalatingCategory) -> Self {
+        ErrorCategory::Custom(Box::new(category))
+    }
+}
+
+impl From<EscalatingCategory> for Box<dyn ErrorClassifier> {
+    fn from(category: EscalatingCategory) -> Self {
+        Box::new(category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RetryPolicy;
+
+    #[test]
+    fn test_errors_should_escalate_after_the_configured_attempts() {
+        // Given: a custom category escalating after two retries
+        let payment = ErrorCategory::custom_fn("Payment", true, |_| Duration::from_millis(1));
+        let category = EscalatingCategory::new(payment, 2);
+        let policy = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false);
+
+        // When: an always-failing operation is retried
+        let mut calls = 0;
+        let error = policy
+            .execute(|| -> crate::TylResult<()> {
+                calls += 1;
+                Err(TylError::business_logic(
+                    "Declined",
+                    category.clone().into(),
+                ))
+            })
+            .unwrap_err();
+
+        // Then: it should stop after two retries and escalate
+        assert_eq!(calls, 3);
+        assert!(category.is_escalated(&error));
+        assert!(!error.is_retriable());
+        assert_eq!(error.severity(), TylSeverity::Critical);
+        assert_eq!(error.category().category_name(), "Payment");
+
+        // And: a single failure should stay retriable with its own severity
+        let first = TylError::business_logic("Declined", category.into());
+        assert!(first.is_retriable());
+        assert_eq!(first.severity(), TylSeverity::Error);
+    }
+}
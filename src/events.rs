@@ -0,0 +1,293 @@
+//! In-process stream of reported error events.
+//!
+//! This module lets sidecar components (metrics exporters, dev tools, TUI
+//! dashboards) observe every error reported with [`TylError::report`] without
+//! patching call sites. Subscribers receive events either through a channel
+//! ([`subscribe`]), a callback ([`subscribe_fn`]) or an [`ErrorReporter`]
+//! ([`install`]).
+
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylSeverity};
+use crate::stats::ErrorStats;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// A reported error together with its tracking context and severity.
+#[derive(Debug, Clone)]
+pub struct ErrorEvent {
+    /// The reported error.
+    pub error: TylError,
+    /// Tracking context of the failed operation.
+    pub context: ErrorContext,
+    /// Severity of the error at the time of reporting.
+    pub severity: TylSeverity,
+}
+
+impl ErrorEvent {
+    /// Create an event for an error and its context, using the error's severity.
+    pub fn new(error: TylError, context: ErrorContext) -> Self {
+        let severity = error.severity();
+        Self {
+            error,
+            context,
+            severity,
+        }
+    }
+}
+
+/// Identifier of a callback subscription, used with [`unsubscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Callback = Arc<dyn Fn(&ErrorEvent) + Send + Sync>;
+
+enum Subscriber {
+    /// Sender of a [`Subscription`], with its count of dropped events.
+    Channel(SyncSender<ErrorEvent>, Arc<AtomicU64>),
+    Callback(SubscriptionId, Callback),
+}
+
+/// Maximum number of events queued for a [`Subscription`].
+pub const SUBSCRIPTION_CAPACITY: usize = 1024;
+
+static SUBSCRIBER_COUNT: AtomicUsize = AtomicUsize::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn subscribers() -> &'static Mutex<Vec<Subscriber>> {
+    static SUBSCRIBERS: OnceLock<Mutex<Vec<Subscriber>>> = OnceLock::new();
+    SUBSCRIBERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn register(subscriber: Subscriber) {
+    let mut subscribers = subscribers().lock().unwrap_or_else(|e| e.into_inner());
+    subscribers.push(subscriber);
+    SUBSCRIBER_COUNT.store(subscribers.len(), Ordering::Release);
+}
+
+/// Channel receiving every reported error event.
+///
+/// At most [`SUBSCRIPTION_CAPACITY`] events are queued; events published
+/// while the queue is full are dropped and counted by
+/// [`Subscription::dropped`], so a subscriber that stops reading cannot grow
+/// memory without bound. Dropping the subscription unsubscribes it on the
+/// next published event.
+pub struct Subscription {
+    receiver: Receiver<ErrorEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// Get the next event if one is already queued.
+    pub fn try_recv(&self) -> Option<ErrorEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ErrorEvent> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Drain all currently queued events.
+    pub fn drain(&self) -> Vec<ErrorEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Get the number of events dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Subscribe to reported error events through a channel.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{events, TylError};
+///
+/// let subscription = events::subscribe();
+/// TylError::database("Connection refused").report("orders.save");
+///
+/// let event = subscription.try_recv().unwrap();
+/// assert_eq!(event.context.operation, "orders.save");
+/// assert_eq!(event.error.code(), "database");
+/// ```
+pub fn subscribe() -> Subscription {
+    let (sender, receiver) = mpsc::sync_channel(SUBSCRIPTION_CAPACITY);
+    let dropped = Arc::new(AtomicU64::new(0));
+    register(Subscriber::Channel(sender, Arc::clone(&dropped)));
+    Subscription { receiver, dropped }
+}
+
+/// Subscribe to reported error events with a callback.
+///
+/// The callback runs synchronously on the reporting thread and must be cheap.
+pub fn subscribe_fn<F>(callback: F) -> SubscriptionId
+where
+    F: Fn(&ErrorEvent) + Send + Sync + 'static,
+{
+    let id = SubscriptionId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    register(Subscriber::Callback(id, Arc::new(callback)));
+    id
+}
+
+/// Destination of reported error events (error tracker, log pipeline, metrics).
+///
+/// Reporters can be decorated, e.g. with [`DedupReporter`](crate::DedupReporter).
+pub trait ErrorReporter: Send + Sync {
+    /// Report one error event.
+    fn report(&self, event: &ErrorEvent);
+}
+
+impl<F> ErrorReporter for F
+where
+    F: Fn(&ErrorEvent) + Send + Sync,
+{
+    fn report(&self, event: &ErrorEvent) {
+        self(event)
+    }
+}
+
+/// Forward every error reported with [`TylError::report`] to a reporter.
+///
+/// Returns the callback subscription, which can be removed with [`unsubscribe`].
+pub fn install<R: ErrorReporter + 'static>(reporter: Arc<R>) -> SubscriptionId {
+    subscribe_fn(move |event| reporter.report(event))
+}
+
+/// Remove a callback subscription.
+///
+/// # Returns
+/// True if the subscription existed.
+pub fn unsubscribe(id: SubscriptionId) -> bool {
+    let mut subscribers = subscribers().lock().unwrap_or_else(|e| e.into_inner());
+    let before = subscribers.len();
+    subscribers.retain(|s| !matches!(s, Subscriber::Callback(existing, _) if *existing == id));
+    SUBSCRIBER_COUNT.store(subscribers.len(), Ordering::Release);
+    subscribers.len() != before
+}
+
+/// Publish an event to all subscribers.
+///
+/// Publishing is a no-op when nobody is subscribed. Callbacks are invoked
+/// outside the registry lock, so they may report errors themselves.
+pub fn publish(event: ErrorEvent) {
+    if SUBSCRIBER_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+
+    let callbacks: Vec<Callback> = {
+        let mut subscribers = subscribers().lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.retain(|subscriber| match subscriber {
+            Subscriber::Channel(sender, dropped) => match sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            },
+            Subscriber::Callback(..) => true,
+        });
+        SUBSCRIBER_COUNT.store(subscribers.len(), Ordering::Release);
+        subscribers
+            .iter()
+            .filter_map(|subscriber| match subscriber {
+                Subscriber::Callback(_, callback) => Some(Arc::clone(callback)),
+                Subscriber::Channel(..) => None,
+            })
+            .collect()
+    };
+
+    for callback in callbacks {
+        callback(&event);
+    }
+}
+
+impl TylError {
+    /// Report this error to in-process subscribers.
+    ///
+    /// Creates a context for the failed operation (see [`TylError::to_context`]),
+    /// publishes an [`ErrorEvent`] and returns the context for further use.
+    pub fn report<S: Into<String>>(&self, operation: S) -> ErrorContext {
+        let context = self.to_context(operation.into());
+        self.report_context(&context);
+        context
+    }
+
+    /// Report this error with an existing context to in-process subscribers.
+    ///
+    /// The error is counted once in the [global stats](crate::ErrorStats::global).
+    pub fn report_context(&self, context: &ErrorContext) {
+        ErrorStats::global().record_context(self, context);
+        publish(ErrorEvent::new(self.clone(), context.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribe_should_receive_reported_errors() {
+        // Given: a channel subscription
+        let subscription = subscribe();
+
+        // When: reporting an error
+        let context = TylError::validation("email", "invalid").report("events.test.channel");
+
+        // Then: the subscriber should receive the event
+        let event = subscription
+            .drain()
+            .into_iter()
+            .find(|e| e.context.operation == "events.test.channel")
+            .unwrap();
+        assert_eq!(event.context.error_id, context.error_id);
+        assert_eq!(event.severity, TylSeverity::Warning);
+    }
+
+    #[test]
+    fn test_full_subscriptions_should_drop_and_count_events() {
+        // Given: a subscription whose queue is full
+        let subscription = subscribe();
+        let error = TylError::internal("boom");
+        for _ in 0..SUBSCRIPTION_CAPACITY {
+            publish(ErrorEvent::new(
+                error.clone(),
+                error.to_context("events.test.full".to_string()),
+            ));
+        }
+
+        // When: publishing one more event
+        publish(ErrorEvent::new(
+            error.clone(),
+            error.to_context("events.test.full".to_string()),
+        ));
+
+        // Then: it should be dropped and counted without blocking
+        assert!(subscription.dropped() >= 1);
+        assert_eq!(subscription.drain().len(), SUBSCRIPTION_CAPACITY);
+    }
+
+    #[test]
+    fn test_subscribe_fn_should_stop_after_unsubscribe() {
+        // Given: a counting callback subscription
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let id = subscribe_fn(move |event| {
+            if event.context.operation == "events.test.callback" {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // When: reporting before and after unsubscribing
+        TylError::internal("boom").report("events.test.callback");
+        assert!(unsubscribe(id));
+        TylError::internal("boom").report("events.test.callback");
+
+        // Then: only the first report should be observed
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(!unsubscribe(id));
+    }
+}
@@ -0,0 +1,362 @@
+//! Subprocess exit statuses.
+//!
+//! TYL workers orchestrate subprocesses. This module classifies the exit
+//! status of a failed child into an error with a suitable category (out of
+//! memory kills, timeouts, temporary failures, crashes), keeping the exit
+//! code, signal and the tail of its stderr as [`ProcessExit`] source. In the
+//! other direction, [`TylError::exit_code`] gives workers a `sysexits(3)`
+//! style status to exit with, which the parent classifies back.
+
+use crate::category::{BuiltinCategory, ErrorCategory};
+use crate::error::TylError;
+use crate::extras::ErrorExtras;
+use crate::redact::REDACTED;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::ExitStatus;
+use std::sync::Arc;
+
+/// Maximum number of characters of stderr kept, counted from the end.
+pub const STDERR_TAIL_MAX_LEN: usize = 2048;
+
+/// Exit code of workers failing on invalid input (`EX_DATAERR`).
+pub const EXIT_DATA_ERROR: u8 = 65;
+/// Exit code of workers failing on an internal error (`EX_SOFTWARE`).
+pub const EXIT_SOFTWARE: u8 = 70;
+/// Exit code of workers failing temporarily (`EX_TEMPFAIL`).
+pub const EXIT_TEMPORARY_FAILURE: u8 = 75;
+/// Exit code of workers lacking permissions (`EX_NOPERM`).
+pub const EXIT_NO_PERMISSION: u8 = 77;
+/// Exit code of workers with an invalid configuration (`EX_CONFIG`).
+pub const EXIT_CONFIG: u8 = 78;
+/// Exit code of workers that were cancelled (`128 + SIGINT`).
+pub const EXIT_CANCELLED: u8 = 130;
+
+/// Exit code of `timeout(1)` when the command timed out.
+const EXIT_TIMED_OUT: i32 = 124;
+
+const SIGINT: i32 = 2;
+const SIGKILL: i32 = 9;
+const SIGALRM: i32 = 14;
+const SIGTERM: i32 = 15;
+
+/// How a subprocess failed, kept as the source of the error built by
+/// [`TylError::from_exit_status`].
+///
+/// Exposed as `exit_code`, `exit_signal` and `stderr_tail` in
+/// [`TylError::metadata`]; the stderr tail is sensitive and is not included
+/// in problem details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessExit {
+    /// Exit code, if the process exited.
+    pub code: Option<i32>,
+    /// Signal that terminated the process, if any (Unix only).
+    pub signal: Option<i32>,
+    /// Last lines of stderr, truncated and with secrets redacted.
+    pub stderr_tail: String,
+}
+
+impl fmt::Display for ProcessExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.code, self.signal) {
+            (_, Some(signal)) => write!(f, "killed by signal {signal}"),
+            (Some(code), None) => write!(f, "exited with code {code}"),
+            (None, None) => f.write_str("terminated"),
+        }
+    }
+}
+
+impl std::error::Error for ProcessExit {}
+
+impl TylError {
+    /// Classify the exit status of a subprocess, or `None` if it succeeded.
+    ///
+    /// | Exit | Category |
+    /// |------|----------|
+    /// | `SIGKILL`, code 137 (likely out of memory) | `ResourceExhaustion` |
+    /// | `SIGALRM`, code 124 (`timeout(1)`), code 75 (`EX_TEMPFAIL`) | `Transient` |
+    /// | `SIGINT`, `SIGTERM`, codes 130 and 143 | [`TylError::Cancelled`] |
+    /// | code 65 (`EX_DATAERR`) | `Validation` |
+    /// | code 77 (`EX_NOPERM`) | `Authentication` |
+    /// | code 78 (`EX_CONFIG`) | `Permanent` |
+    /// | other codes and signals | `Internal` |
+    ///
+    /// Values of `password=`, `token:`, `Bearer ...`-like secrets in
+    /// `stderr_tail` are replaced by `[REDACTED]`, and the last
+    /// [`STDERR_TAIL_MAX_LEN`] characters of the result are kept in the
+    /// [`ProcessExit`] source. Cancellations carry no source: the exit is
+    /// only described in their message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::process::Command;
+    /// use tyl_errors::TylError;
+    ///
+    /// # #[cfg(unix)] {
+    /// let output = Command::new("sh")
+    ///     .args(["-c", "echo 'token=abc123 upstream busy' >&2; exit 75"])
+    ///     .output()
+    ///     .unwrap();
+    /// let stderr = String::from_utf8_lossy(&output.stderr);
+    /// let error = TylError::from_exit_status(output.status, &stderr).unwrap();
+    ///
+    /// assert!(error.is_retriable());
+    /// assert_eq!(error.metadata()["exit_code"], 75);
+    /// assert_eq!(error.metadata()["stderr_tail"], "token=[REDACTED] upstream busy\n");
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn from_exit_status(status: ExitStatus, stderr_tail: &str) -> Option<TylError> {
+        if status.success() {
+            return None;
+        }
+        Some(Self::from_exit(
+            status.code(),
+            exit_signal(&status),
+            stderr_tail,
+        ))
+    }
+
+    /// Get the `sysexits(3)` style code a worker failing with this error
+    /// should exit with, e.g. through `std::process::ExitCode::from`.
+    ///
+    /// Configuration errors exit with [`EXIT_CONFIG`], cancellations with
+    /// [`EXIT_CANCELLED`], validation errors with [`EXIT_DATA_ERROR`],
+    /// authentication errors with [`EXIT_NO_PERMISSION`] and other retriable
+    /// errors with [`EXIT_TEMPORARY_FAILURE`]; everything else exits with
+    /// [`EXIT_SOFTWARE`]. [`TylError::from_exit_status`] classifies these
+    /// codes back into the same categories.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            TylError::Configuration { .. } => return EXIT_CONFIG,
+            TylError::Cancelled { .. } => return EXIT_CANCELLED,
+            _ => {}
+        }
+        match self.category() {
+            ErrorCategory::Builtin(BuiltinCategory::Validation) => EXIT_DATA_ERROR,
+            ErrorCategory::Builtin(BuiltinCategory::Authentication) => EXIT_NO_PERMISSION,
+            category if category.is_retriable_for(self) => EXIT_TEMPORARY_FAILURE,
+            _ => EXIT_SOFTWARE,
+        }
+    }
+
+    #[track_caller]
+    fn from_exit(code: Option<i32>, signal: Option<i32>, stderr_tail: &str) -> TylError {
+        let exit = ProcessExit {
+            code,
+            signal,
+            stderr_tail: tail(&redact_secrets(stderr_tail)),
+        };
+        let (category, reason) = match (code, signal) {
+            (_, Some(SIGINT | SIGTERM)) | (Some(130 | 143), None) => {
+                return TylError::cancelled(format!("Subprocess was cancelled ({exit})"));
+            }
+            (_, Some(SIGKILL)) | (Some(137), None) => (
+                BuiltinCategory::ResourceExhaustion,
+                "was killed, likely out of memory",
+            ),
+            (_, Some(SIGALRM)) | (Some(EXIT_TIMED_OUT), None) => {
+                (BuiltinCategory::Transient, "timed out")
+            }
+            (_, Some(_)) => (BuiltinCategory::Internal, "crashed"),
+            (Some(code), None) => match u8::try_from(code) {
+                Ok(EXIT_TEMPORARY_FAILURE) => (BuiltinCategory::Transient, "failed temporarily"),
+                Ok(EXIT_DATA_ERROR) => (BuiltinCategory::Validation, "rejected its input"),
+                Ok(EXIT_NO_PERMISSION) => (BuiltinCategory::Authentication, "lacked permission"),
+                Ok(EXIT_CONFIG) => (BuiltinCategory::Permanent, "is misconfigured"),
+                _ => (BuiltinCategory::Internal, "failed"),
+            },
+            (None, None) => (BuiltinCategory::Internal, "failed"),
+        };
+        TylError::Custom {
+            message: format!("Subprocess {reason} ({exit})"),
+            classifier: Box::new(category),
+            source: Some(Arc::new(exit)),
+            extras: ErrorExtras::new(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(status)
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Keep the last [`STDERR_TAIL_MAX_LEN`] characters of `stderr`.
+fn tail(stderr: &str) -> String {
+    let count = stderr.chars().count();
+    if count <= STDERR_TAIL_MAX_LEN {
+        return stderr.to_string();
+    }
+    let skipped: String = stderr.chars().skip(count - STDERR_TAIL_MAX_LEN).collect();
+    format!("…{skipped}")
+}
+
+/// Words naming credentials, whose assigned values are redacted.
+const SECRET_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "pwd",
+    "secret",
+    "token",
+    "apikey",
+    "api_key",
+    "api-key",
+    "accesskey",
+    "access_key",
+    "access-key",
+];
+
+/// Replace the values of credentials-like assignments (`password=...`,
+/// `token: ...`) and bearer tokens.
+fn redact_secrets(text: &str) -> String {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(is_word) {
+        redacted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let mut end = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+        // `api-key` and `access-key` span a dash.
+        if let Some(next) = rest[end..].strip_prefix('-') {
+            let joined = end + 1 + next.find(|c| !is_word(c)).unwrap_or(next.len());
+            if SECRET_WORDS.contains(&rest[..joined].to_ascii_lowercase().as_str()) {
+                end = joined;
+            }
+        }
+        let (word, tail) = rest.split_at(end);
+        redacted.push_str(word);
+        rest = tail;
+
+        let word = word.to_ascii_lowercase();
+        let value_start = if SECRET_WORDS.contains(&word.as_str()) {
+            tail.trim_start()
+                .strip_prefix(|c| c == ':' || c == '=')
+                .map(|value| tail.len() - value.trim_start().len())
+        } else if word == "bearer" && tail.starts_with(char::is_whitespace) {
+            Some(tail.len() - tail.trim_start().len())
+        } else {
+            None
+        };
+        if let Some(value_start) = value_start {
+            let value = &tail[value_start..];
+            let value_end = value
+                .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '&'))
+                .unwrap_or(value.len());
+            if value_end > 0 {
+                redacted.push_str(&tail[..value_start]);
+                redacted.push_str(REDACTED);
+                rest = &value[value_end..];
+            }
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exits_should_be_classified_by_code_and_signal() {
+        // Given: exits of an out of memory kill, a timeout and a crash
+        let cases = [
+            (None, Some(SIGKILL), "ResourceExhaustion", true),
+            (Some(137), None, "ResourceExhaustion", true),
+            (Some(124), None, "Transient", true),
+            (Some(65), None, "Validation", false),
+            (None, Some(11), "Internal", false),
+            (Some(1), None, "Internal", false),
+        ];
+
+        for (code, signal, category, retriable) in cases {
+            // When: classifying the exit
+            let error = TylError::from_exit(code, signal, "");
+
+            // Then: it should get the expected category and exit details
+            assert_eq!(error.category().category_name(), category, "{error}");
+            assert_eq!(error.is_retriable(), retriable, "{error}");
+            let exit = error.downcast_source_ref::<ProcessExit>().unwrap();
+            assert_eq!((exit.code, exit.signal), (code, signal));
+        }
+        assert_eq!(
+            TylError::from_exit(None, Some(SIGKILL), "").to_string(),
+            "Custom error: Subprocess was killed, likely out of memory (killed by signal 9)"
+        );
+
+        // And: interrupted and terminated exits should be cancellations
+        for (code, signal) in [
+            (None, Some(SIGTERM)),
+            (None, Some(SIGINT)),
+            (Some(130), None),
+        ] {
+            let error = TylError::from_exit(code, signal, "");
+            assert_eq!(error.code(), "cancelled", "{error}");
+            assert!(!error.is_retriable());
+        }
+    }
+
+    #[test]
+    fn test_exit_codes_should_classify_back_into_the_same_category() {
+        for error in [
+            TylError::validation("input", "Malformed CSV"),
+            TylError::database("Deadlock"),
+            TylError::unauthenticated("Token expired"),
+            TylError::internal("Bug"),
+        ] {
+            let restored = TylError::from_exit(Some(i32::from(error.exit_code())), None, "");
+            assert_eq!(
+                restored.category().category_name(),
+                error.category().category_name(),
+                "{error}"
+            );
+        }
+        assert_eq!(
+            TylError::configuration("Missing DSN").exit_code(),
+            EXIT_CONFIG
+        );
+        assert_eq!(TylError::cancelled("Shutdown").exit_code(), EXIT_CANCELLED);
+        let restored = TylError::from_exit(Some(i32::from(EXIT_CANCELLED)), None, "");
+        assert_eq!(restored.exit_code(), EXIT_CANCELLED);
+    }
+
+    #[test]
+    fn test_stderr_tail_should_be_truncated_redacted_and_sensitive() {
+        // Given: a long stderr ending with secrets
+        let stderr = format!(
+            "{}\nconnecting with password=hunter2, Authorization: Bearer eyJ.abc\n",
+            "x".repeat(3000)
+        );
+
+        // When: classifying a failed exit with it
+        let error = TylError::from_exit(Some(1), None, &stderr);
+
+        // Then: the tail should be cut from the start and secrets masked
+        let metadata = error.metadata();
+        let tail = metadata["stderr_tail"].as_str().unwrap();
+        assert!(tail.starts_with('…'));
+        assert!(!tail.contains(&"x".repeat(STDERR_TAIL_MAX_LEN)));
+        assert!(tail.ends_with("password=[REDACTED], Authorization: Bearer [REDACTED]\n"));
+        assert_eq!(metadata["exit_code"], 1);
+
+        // And: a secret longer than the tail should be redacted before the cut
+        let stderr = format!("token={}\n", "s".repeat(3000));
+        let error = TylError::from_exit(Some(1), None, &stderr);
+        assert_eq!(error.metadata()["stderr_tail"], "token=[REDACTED]\n");
+        assert_eq!(
+            redact_secrets("API-Key: k1 x-token = t2&pwd:p3 tokens=4 bearer"),
+            "API-Key: [REDACTED] x-token = [REDACTED]&pwd:[REDACTED] tokens=4 bearer"
+        );
+
+        // And: it should not reach problem details
+        let problem = error.to_problem_details();
+        assert!(!problem.extensions.contains_key("stderr_tail"));
+        assert_eq!(problem.extensions["exit_code"], 1);
+    }
+}
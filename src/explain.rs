@@ -0,0 +1,319 @@
+//! Explanations of retry decisions.
+//!
+//! This module provides [`RetryDecision`], a structured account of whether an
+//! error is retried at a given attempt, which rule decided it and how long the
+//! next attempt is delayed. Retry executors log their decisions at debug
+//! level, so operators can answer "why was this retried?" from the logs.
+
+use crate::error::TylError;
+use crate::overrides::ClassificationOverrides;
+use crate::retry::RetryPolicy;
+use crate::settings::ErrorSettings;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// What decided a retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RetryDecisionSource {
+    /// The error's category (its classifier, or the default classification).
+    Classifier,
+    /// A [classification override](crate::ClassificationOverrides) remapping the category.
+    Override,
+    /// The `TYL_ERROR_MAX_RETRIES` or `TYL_ERROR_MAX_ATTEMPTS_CEILING` environment setting.
+    Env,
+    /// The limits or idempotency of a [`RetryPolicy`].
+    Policy,
+}
+
+impl RetryDecisionSource {
+    /// Get the lowercase name of the source.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryDecisionSource::Classifier => "classifier",
+            RetryDecisionSource::Override => "override",
+            RetryDecisionSource::Env => "env",
+            RetryDecisionSource::Policy => "policy",
+        }
+    }
+}
+
+/// Whether an error is retried at an attempt, and why.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{RetryDecisionSource, TylError};
+///
+/// let decision = TylError::network("Connection reset").explain_retry(0);
+/// assert!(decision.retriable);
+/// assert_eq!(decision.source, RetryDecisionSource::Classifier);
+/// assert!(decision.chosen_delay.is_some());
+///
+/// let decision = TylError::validation("email", "Invalid").explain_retry(0);
+/// assert!(!decision.retriable);
+/// assert_eq!(decision.reason, "category `Validation` is not retriable");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryDecision {
+    /// Whether another attempt is made.
+    pub retriable: bool,
+    /// Human-readable explanation of the decision.
+    pub reason: String,
+    /// Rule that decided.
+    pub source: RetryDecisionSource,
+    /// Name of the error's category.
+    pub category: String,
+    /// Number of retries already made.
+    pub attempt: usize,
+    /// Delay before the next attempt, when retried.
+    pub chosen_delay: Option<Duration>,
+    /// Limits the decision was made under.
+    pub policy: String,
+}
+
+impl fmt::Display for RetryDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.chosen_delay {
+            Some(delay) if self.retriable => write!(f, "retry in {delay:?}")?,
+            _ => f.write_str("no retry")?,
+        }
+        write!(
+            f,
+            " after {} retries: {} ({}; {})",
+            self.attempt,
+            self.reason,
+            self.source.as_str(),
+            self.policy
+        )
+    }
+}
+
+/// Outcome of the checks shared by errors and retry policies.
+struct Verdict {
+    retriable: bool,
+    reason: String,
+    source: RetryDecisionSource,
+}
+
+impl TylError {
+    /// Explain whether this error is retried at `attempt` (0-based), as
+    /// decided by [`TylError::should_retry`].
+    pub fn explain_retry(&self, attempt: usize) -> RetryDecision {
+        let max_retries = Self::max_retries();
+        let verdict = self.category_verdict(attempt).unwrap_or_else(|| {
+            if attempt >= max_retries {
+                Verdict {
+                    retriable: false,
+                    reason: format!("{} allows {max_retries} retries", limit_variable()),
+                    source: RetryDecisionSource::Env,
+                }
+            } else {
+                self.retriable_verdict(attempt, max_retries)
+            }
+        });
+        let category = self.category();
+        let chosen_delay = verdict
+            .retriable
+            .then(|| category.retry_delay_for(self, attempt + 1));
+        RetryDecision {
+            retriable: verdict.retriable,
+            reason: verdict.reason,
+            source: verdict.source,
+            category: category.category_name().to_string(),
+            attempt,
+            chosen_delay,
+            policy: format!("TYL_ERROR_MAX_RETRIES={max_retries}"),
+        }
+    }
+
+    /// Explain why the category refuses a retry, if it does.
+    fn category_verdict(&self, attempt: usize) -> Option<Verdict> {
+        let category = self.category();
+        let name = category.category_name();
+        if !category.is_retriable_for(self) {
+            return Some(Verdict {
+                retriable: false,
+                reason: format!("category `{name}` is not retriable"),
+                source: self.classification_source(),
+            });
+        }
+        match category.max_attempts() {
+            Some(limit) if attempt + 1 >= limit => Some(Verdict {
+                retriable: false,
+                reason: format!("category `{name}` allows {limit} attempts"),
+                source: self.classification_source(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn retriable_verdict(&self, attempt: usize, max_retries: usize) -> Verdict {
+        Verdict {
+            retriable: true,
+            reason: format!(
+                "category `{}` is retriable (retry {} of {max_retries})",
+                self.category().category_name(),
+                attempt + 1
+            ),
+            source: self.classification_source(),
+        }
+    }
+
+    /// Get whether the category comes from an override or the classifier.
+    fn classification_source(&self) -> RetryDecisionSource {
+        if ClassificationOverrides::with_current(|overrides| {
+            overrides.resolve(self, None, None).is_some()
+        }) {
+            RetryDecisionSource::Override
+        } else {
+            RetryDecisionSource::Classifier
+        }
+    }
+}
+
+/// Get the environment variable setting the error-level retry limit.
+fn limit_variable() -> &'static str {
+    let settings = ErrorSettings::global();
+    if settings.cap_attempts(settings.max_retries) < settings.max_retries {
+        "TYL_ERROR_MAX_ATTEMPTS_CEILING"
+    } else {
+        "TYL_ERROR_MAX_RETRIES"
+    }
+}
+
+impl RetryPolicy {
+    /// Explain whether this policy's executors retry `error` after `retries`
+    /// retries, and with which delay.
+    ///
+    /// The [retry budget](crate::RetryBudget) is not consulted.
+    pub fn explain_retry(&self, error: &TylError, retries: usize) -> RetryDecision {
+        let verdict = error.category_verdict(retries).unwrap_or_else(|| {
+            if !self.should_retry(retries) {
+                let limit = ErrorSettings::global().cap_attempts(self.max_attempts);
+                if limit < self.max_attempts {
+                    Verdict {
+                        retriable: false,
+                        reason: format!("TYL_ERROR_MAX_ATTEMPTS_CEILING allows {limit} retries"),
+                        source: RetryDecisionSource::Env,
+                    }
+                } else {
+                    Verdict {
+                        retriable: false,
+                        reason: format!("policy allows {limit} retries"),
+                        source: RetryDecisionSource::Policy,
+                    }
+                }
+            } else if !self.in_scope(error) {
+                Verdict {
+                    retriable: false,
+                    reason: format!(
+                        "error may only be retried at {} scope, policy runs at {} scope",
+                        error.retry_scope(),
+                        self.retry_scope()
+                    ),
+                    source: RetryDecisionSource::Policy,
+                }
+            } else if !self.may_repeat(error) {
+                Verdict {
+                    retriable: false,
+                    reason: "operation is not idempotent and may have had side effects".to_string(),
+                    source: RetryDecisionSource::Policy,
+                }
+            } else {
+                error.retriable_verdict(retries, self.max_attempts)
+            }
+        });
+        RetryDecision {
+            retriable: verdict.retriable,
+            reason: verdict.reason,
+            source: verdict.source,
+            category: error.category().category_name().to_string(),
+            attempt: retries,
+            chosen_delay: verdict.retriable.then(|| self.calculate_delay(retries + 1)),
+            policy: format!(
+                "max_attempts={}, idempotency={:?}",
+                self.max_attempts,
+                self.idempotency()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::{ErrorCategory, RetryScope};
+    use crate::retry::Idempotency;
+
+    #[test]
+    fn test_policy_decisions_should_name_the_deciding_rule() {
+        // Given: a policy for a non-idempotent operation
+        let policy = RetryPolicy::new()
+            .with_max_attempts(2)
+            .with_base_delay(Duration::from_millis(10))
+            .with_jitter(false)
+            .with_idempotency(Idempotency::NonIdempotent);
+
+        // When: explaining decisions for several errors and attempts
+        let refused_error = TylError::network("Connection refused").with_before_side_effects();
+        let refused = policy.explain_retry(&refused_error, 0);
+        let reset = policy.explain_retry(&TylError::network("Connection reset"), 0);
+        let exhausted = policy.explain_retry(&refused_error, 2);
+        let capped = TylError::business_logic(
+            "Declined",
+            ErrorCategory::transient()
+                .with_max_attempts(2)
+                .into_classifier(),
+        );
+
+        // Then: each decision should carry its reason, source and delay
+        assert!(refused.retriable);
+        assert_eq!(refused.chosen_delay, Some(Duration::from_millis(10)));
+        assert_eq!(
+            refused.reason,
+            "category `Network` is retriable (retry 1 of 2)"
+        );
+        assert!(!reset.retriable);
+        assert_eq!(reset.source, RetryDecisionSource::Policy);
+        assert_eq!(reset.chosen_delay, None);
+        assert_eq!(exhausted.reason, "policy allows 2 retries");
+        assert_eq!(
+            policy.explain_retry(&capped, 1).reason,
+            "category `Transient` allows 2 attempts"
+        );
+        assert_eq!(
+            refused.to_string(),
+            "retry in 10ms after 0 retries: category `Network` is retriable (retry 1 of 2) \
+             (classifier; max_attempts=2, idempotency=NonIdempotent)"
+        );
+    }
+
+    #[test]
+    fn test_retry_scope_should_restrict_who_retries() {
+        // Given: an error only infrastructure may retry, narrowed by a combinator
+        let category = ErrorCategory::network()
+            .with_retry_scope(RetryScope::Caller)
+            .and_then(ErrorCategory::transient().with_retry_scope(RetryScope::Infrastructure));
+        let error = TylError::business_logic("Sidecar reset", category.into_classifier());
+
+        // When: explaining the decisions of caller and infrastructure policies
+        let caller = RetryPolicy::new().explain_retry(&error, 0);
+        let infrastructure = RetryPolicy::new()
+            .with_retry_scope(RetryScope::Infrastructure)
+            .explain_retry(&error, 0);
+
+        // Then: only the infrastructure policy should retry it
+        assert_eq!(error.retry_scope(), RetryScope::Infrastructure);
+        assert!(!caller.retriable);
+        assert_eq!(caller.source, RetryDecisionSource::Policy);
+        assert_eq!(
+            caller.reason,
+            "error may only be retried at infrastructure scope, policy runs at caller scope"
+        );
+        assert!(infrastructure.retriable);
+        assert_eq!(
+            TylError::validation("email", "Invalid").retry_scope(),
+            RetryScope::None
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Details recorded alongside the fields of an error variant.
+//!
+//! Every [`TylError`] variant except `Unknown` carries an [`ErrorExtras`]
+//! holding what the crate records about an error beyond its variant's own
+//! fields, such as where the error was created. Keeping these details in one
+//! opaque field lets them grow without changing the shape of the public
+//! variants. They are serialized with the error under `extras` and read
+//! through accessors of [`TylError`] such as [`TylError::location`].
+
+use crate::error::TylError;
+use crate::operation::OperationFrame;
+use crate::panic_capture::PanicDetails;
+use crate::retry::RetryTelemetry;
+use crate::validation::ValidationConstraint;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::panic::Location;
+
+/// Details of an error kept outside the fields of its variant.
+///
+/// Build variants with [`ErrorExtras::new`], which records the caller's
+/// location, or [`ErrorExtras::default`].
+#[derive(Debug, Clone, Default)]
+pub struct ErrorExtras {
+    /// Where the error was created, when created in this process.
+    caller: Option<&'static Location<'static>>,
+    /// Everything else, boxed to keep errors small.
+    details: Option<Box<Details>>,
+}
+
+/// Details of [`ErrorExtras`] that are rarely set, and their wire format.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Details {
+    /// Where a deserialized error was created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caller: Option<OwnedLocation>,
+    /// Where the panic the error was converted from happened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    panic: Option<PanicDetails>,
+    /// Driver-native cause code of a database error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    driver_code: Option<String>,
+    /// Rule the input of a validation error failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    constraint: Option<ValidationConstraint>,
+    /// Attempts a retry executor made before giving up on the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry: Option<RetryTelemetry>,
+    /// Operations attached to the error, innermost first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    operations: Vec<OperationFrame>,
+    /// Whether the error happened before the operation had side effects.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    before_side_effects: bool,
+}
+
+impl Details {
+    fn is_empty(&self) -> bool {
+        self.caller.is_none()
+            && self.panic.is_none()
+            && self.driver_code.is_none()
+            && self.constraint.is_none()
+            && self.retry.is_none()
+            && self.operations.is_empty()
+            && !self.before_side_effects
+    }
+}
+
+/// A creation location received with a serialized error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OwnedLocation {
+    file: String,
+    line: u32,
+}
+
+impl ErrorExtras {
+    /// Create extras recording the caller's location.
+    #[track_caller]
+    pub fn new() -> Self {
+        Self {
+            caller: Some(Location::caller()),
+            details: None,
+        }
+    }
+
+    /// Check whether no details are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.caller.is_none() && self.details.is_none()
+    }
+
+    /// Get where the error was created.
+    pub(crate) fn location(&self) -> Option<CallerLocation<'_>> {
+        if let Some(caller) = self.caller {
+            return Some(CallerLocation {
+                file: caller.file(),
+                line: caller.line(),
+            });
+        }
+        let restored = self.details()?.caller.as_ref()?;
+        Some(CallerLocation {
+            file: &restored.file,
+            line: restored.line,
+        })
+    }
+
+    /// Get the details of the panic the error was converted from.
+    pub(crate) fn panic(&self) -> Option<&PanicDetails> {
+        self.details()?.panic.as_ref()
+    }
+
+    /// Record the details of the panic the error was converted from.
+    pub(crate) fn set_panic(&mut self, panic: PanicDetails) {
+        self.details_mut().panic = Some(panic);
+    }
+
+    /// Get the driver-native cause code of a database error.
+    pub(crate) fn driver_code(&self) -> Option<&str> {
+        self.details()?.driver_code.as_deref()
+    }
+
+    /// Record the driver-native cause code of a database error.
+    pub(crate) fn set_driver_code(&mut self, code: String) {
+        self.details_mut().driver_code = Some(code);
+    }
+
+    /// Get the rule the input of a validation error failed.
+    pub(crate) fn constraint(&self) -> Option<&ValidationConstraint> {
+        self.details()?.constraint.as_ref()
+    }
+
+    /// Record the rule the input of a validation error failed.
+    pub(crate) fn set_constraint(&mut self, constraint: ValidationConstraint) {
+        self.details_mut().constraint = Some(constraint);
+    }
+
+    /// Get the attempts a retry executor made before giving up on the error.
+    pub(crate) fn retry_telemetry(&self) -> Option<&RetryTelemetry> {
+        self.details()?.retry.as_ref()
+    }
+
+    /// Record the attempts a retry executor made before giving up on the error.
+    pub(crate) fn set_retry_telemetry(&mut self, telemetry: RetryTelemetry) {
+        self.details_mut().retry = Some(telemetry);
+    }
+
+    /// Get the operations attached to the error, innermost first.
+    pub(crate) fn operations(&self) -> &[OperationFrame] {
+        self.details().map_or(&[], |details| &details.operations)
+    }
+
+    /// Record an operation enclosing those already attached to the error.
+    pub(crate) fn push_operation(&mut self, frame: OperationFrame) {
+        self.details_mut().operations.push(frame);
+    }
+
+    /// Check whether the error happened before the operation had side effects.
+    pub(crate) fn before_side_effects(&self) -> bool {
+        self.details()
+            .is_some_and(|details| details.before_side_effects)
+    }
+
+    /// Record that the error happened before the operation had side effects.
+    pub(crate) fn set_before_side_effects(&mut self) {
+        self.details_mut().before_side_effects = true;
+    }
+
+    fn details(&self) -> Option<&Details> {
+        self.details.as_deref()
+    }
+
+    fn details_mut(&mut self) -> &mut Details {
+        self.details.get_or_insert_with(Box::default)
+    }
+}
+
+impl Serialize for ErrorExtras {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut details = self.details().cloned().unwrap_or_default();
+        if let Some(caller) = self.caller {
+            details.caller = Some(OwnedLocation {
+                file: caller.file().to_string(),
+                line: caller.line(),
+            });
+        }
+        details.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorExtras {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let details = Details::deserialize(deserializer)?;
+        Ok(Self {
+            caller: None,
+            details: (!details.is_empty()).then(|| Box::new(details)),
+        })
+    }
+}
+
+/// Where an error was created, see [`TylError::location`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallerLocation<'a> {
+    file: &'a str,
+    line: u32,
+}
+
+impl<'a> CallerLocation<'a> {
+    /// Get the source file the error was created in.
+    pub fn file(&self) -> &'a str {
+        self.file
+    }
+
+    /// Get the line the error was created on.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+}
+
+impl fmt::Display for CallerLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+impl TylError {
+    /// Get the extras of this error, `None` for [`TylError::Unknown`].
+    pub(crate) fn extras(&self) -> Option<&ErrorExtras> {
+        match self {
+            TylError::Database { extras, .. }
+            | TylError::Network { extras, .. }
+            | TylError::Validation { extras, .. }
+            | TylError::NotFound { extras, .. }
+            | TylError::Conflict { extras, .. }
+            | TylError::Internal { extras, .. }
+            | TylError::Unauthenticated { extras, .. }
+            | TylError::PermissionDenied { extras, .. }
+            | TylError::Timeout { extras, .. }
+            | TylError::Cancelled { extras, .. }
+            | TylError::InvariantViolated { extras, .. }
+            | TylError::Configuration { extras, .. }
+            | TylError::NotImplemented { extras, .. }
+            | TylError::Custom { extras, .. } => Some(extras),
+            TylError::Unknown { .. } => None,
+        }
+    }
+
+    /// Get the extras of this error mutably, `None` for [`TylError::Unknown`].
+    pub(crate) fn extras_mut(&mut self) -> Option<&mut ErrorExtras> {
+        match self {
+            TylError::Database { extras, .. }
+            | TylError::Network { extras, .. }
+            | TylError::Validation { extras, .. }
+            | TylError::NotFound { extras, .. }
+            | TylError::Conflict { extras, .. }
+            | TylError::Internal { extras, .. }
+            | TylError::Unauthenticated { extras, .. }
+            | TylError::PermissionDenied { extras, .. }
+            | TylError::Timeout { extras, .. }
+            | TylError::Cancelled { extras, .. }
+            | TylError::InvariantViolated { extras, .. }
+            | TylError::Configuration { extras, .. }
+            | TylError::NotImplemented { extras, .. }
+            | TylError::Custom { extras, .. } => Some(extras),
+            TylError::Unknown { .. } => None,
+        }
+    }
+}
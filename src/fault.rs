@@ -0,0 +1,143 @@
+//! Fault injection driven by feature flags.
+//!
+//! Resilience code paths (retries, fallbacks, circuit breakers, error pages)
+//! are rarely exercised until production fails. This module lets services
+//! call [`inject_fault`] at the start of an operation and answer it from a
+//! [`FaultFlagSource`] backed by their feature-flag provider, so failures can
+//! be switched on for one tenant or operation in staging without a redeploy.
+//! Nothing is injected until a source is installed.
+
+use crate::error::{CodeFields, TylError, TylResult};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Operation and tenant a fault flag is evaluated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultTarget<'a> {
+    /// Name of the operation about to run.
+    pub operation: &'a str,
+    /// Tenant the operation runs for, if known.
+    pub tenant_id: Option<&'a str>,
+}
+
+/// Feature-flag provider deciding which faults to inject.
+pub trait FaultFlagSource: Send + Sync {
+    /// Get the code of the error to inject for `target` (e.g. `"timeout"`),
+    /// or `None` when its flag is off.
+    fn fault_for(&self, target: &FaultTarget<'_>) -> Option<String>;
+}
+
+impl<F> FaultFlagSource for F
+where
+    F: Fn(&FaultTarget<'_>) -> Option<String> + Send + Sync,
+{
+    fn fault_for(&self, target: &FaultTarget<'_>) -> Option<String> {
+        self(target)
+    }
+}
+
+fn installed() -> &'static RwLock<Option<Arc<dyn FaultFlagSource>>> {
+    static SOURCE: OnceLock<RwLock<Option<Arc<dyn FaultFlagSource>>>> = OnceLock::new();
+    SOURCE.get_or_init(|| RwLock::new(None))
+}
+
+/// Install the process-wide fault flag source, replacing the current one.
+pub fn install_fault_flag_source<S: FaultFlagSource + 'static>(source: Arc<S>) {
+    *installed().write().unwrap_or_else(|e| e.into_inner()) = Some(source);
+}
+
+/// Remove the process-wide fault flag source, disabling injection.
+pub fn clear_fault_flag_source() {
+    *installed().write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Fail with an injected error if the installed [`FaultFlagSource`] flags
+/// this operation and tenant.
+///
+/// The injected error has the flagged code (unknown codes inject an internal
+/// error) and its message, or its ID for not-found and permission errors,
+/// marks it as injected, so it can be told apart from real failures in logs.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+/// use tyl_errors::{inject_fault, install_fault_flag_source, FaultTarget};
+///
+/// install_fault_flag_source(Arc::new(|target: &FaultTarget<'_>| {
+///     (target.tenant_id == Some("acme") && target.operation == "orders.create")
+///         .then(|| "timeout".to_string())
+/// }));
+///
+/// let injected = inject_fault("orders.create", Some("acme")).unwrap_err();
+/// assert_eq!(injected.code(), "timeout");
+/// assert!(inject_fault("orders.create", Some("globex")).is_ok());
+/// ```
+pub fn inject_fault(operation: &str, tenant_id: Option<&str>) -> TylResult<()> {
+    let source = match installed()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        Some(source) => Arc::clone(source),
+        None => return Ok(()),
+    };
+    let target = FaultTarget {
+        operation,
+        tenant_id,
+    };
+    match source.fault_for(&target) {
+        Some(code) => Err(injected_error(&code, operation)),
+        None => Ok(()),
+    }
+}
+
+/// Build the injected error of `code` for `operation`.
+fn injected_error(code: &str, operation: &str) -> TylError {
+    let message = format!("Injected fault in {operation}");
+    let fields = CodeFields {
+        message: &message,
+        field: "injected_fault",
+        resource: operation,
+        id: "injected_fault",
+        subject: "injected_fault",
+        action: "call",
+    };
+    TylError::from_code(code, fields).unwrap_or_else(|| TylError::internal(message.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_should_only_hit_flagged_targets() {
+        // Given: no source installed
+        clear_fault_flag_source();
+        assert!(inject_fault("fault.test.charge", Some("acme")).is_ok());
+
+        // When: installing a source flagging one operation of one tenant
+        install_fault_flag_source(Arc::new(|target: &FaultTarget<'_>| {
+            match (target.operation, target.tenant_id) {
+                ("fault.test.charge", Some("acme")) => Some("network".to_string()),
+                ("fault.test.refund", _) => Some("no_such_code".to_string()),
+                _ => None,
+            }
+        }));
+
+        // Then: only flagged targets should fail, with the flagged code
+        let injected = inject_fault("fault.test.charge", Some("acme")).unwrap_err();
+        assert_eq!(
+            injected.to_string(),
+            "Network error: Injected fault in fault.test.charge"
+        );
+        assert!(injected.is_retriable());
+        assert!(inject_fault("fault.test.charge", Some("globex")).is_ok());
+        assert!(inject_fault("fault.test.charge", None).is_ok());
+        assert_eq!(
+            inject_fault("fault.test.refund", None).unwrap_err().code(),
+            "internal"
+        );
+
+        clear_fault_flag_source();
+        assert!(inject_fault("fault.test.charge", Some("acme")).is_ok());
+    }
+}
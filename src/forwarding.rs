@@ -0,0 +1,162 @@
+//! Guard against errors bouncing between services.
+//!
+//! This module counts how many times an error is forwarded from one service
+//! to another in its [`ErrorContext`]. Once the count exceeds
+//! `TYL_ERROR_MAX_HOPS`, the forwarded error is replaced with a permanent
+//! internal error recording the loop, so two services relaying each other's
+//! failures cannot keep retrying and re-publishing them forever.
+
+use crate::category::ErrorCategory;
+use crate::context::ErrorContext;
+use crate::envelope::TylErrorEnvelope;
+use crate::error::TylError;
+use crate::limits;
+use crate::settings::ErrorSettings;
+use serde_json::json;
+
+/// Metadata key recording a forwarding loop on the context of the replacement error.
+pub const FORWARDING_LOOP_KEY: &str = "forwarding_loop";
+
+impl TylError {
+    /// Record that this error is forwarded by `service` to another service.
+    ///
+    /// Increments the context's hop count. Once it exceeds
+    /// `TYL_ERROR_MAX_HOPS`, returns a non-retriable internal error instead,
+    /// with the origin, hop count and original code recorded under
+    /// [`FORWARDING_LOOP_KEY`] in the context; errors already replaced are
+    /// forwarded unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{TylError, FORWARDING_LOOP_KEY};
+    ///
+    /// let mut context = TylError::network("Connection reset")
+    ///     .to_context("orders.sync".into())
+    ///     .with_origin_service("inventory");
+    /// let mut error = TylError::network("Connection reset");
+    /// for hop in 0..20 {
+    ///     let service = if hop % 2 == 0 { "orders" } else { "billing" };
+    ///     error = error.forward(&mut context, service);
+    /// }
+    ///
+    /// assert_eq!(error.code(), "internal");
+    /// assert!(!error.is_retriable());
+    /// assert_eq!(context.get_metadata(FORWARDING_LOOP_KEY).unwrap()["origin_service"], "inventory");
+    /// ```
+    pub fn forward<S: Into<String>>(self, context: &mut ErrorContext, service: S) -> TylError {
+        self.forward_with_max(context, service.into(), ErrorSettings::global().max_hops)
+    }
+
+    fn forward_with_max(
+        self,
+        context: &mut ErrorContext,
+        service: String,
+        max_hops: u32,
+    ) -> TylError {
+        context.hop_count = context.hop_count.saturating_add(1);
+        if max_hops == 0
+            || context.hop_count <= max_hops
+            || context.has_metadata(FORWARDING_LOOP_KEY)
+        {
+            return self;
+        }
+
+        let origin = context
+            .origin_service
+            .as_deref()
+            .unwrap_or("unknown service");
+        let error = TylError::internal(format!(
+            "Forwarding loop: error from {origin} forwarded {} times (max {max_hops}): {self}",
+            context.hop_count
+        ));
+        context.metadata.insert(
+            FORWARDING_LOOP_KEY.into(),
+            json!({
+                "origin_service": context.origin_service,
+                "hop_count": context.hop_count,
+                "max_hops": max_hops,
+                "service": service,
+                "code": self.code(),
+            }),
+        );
+        context.category = ErrorCategory::internal();
+        context.message = limits::limit_message(error.to_string());
+        error
+    }
+}
+
+impl TylErrorEnvelope {
+    /// Re-seal this envelope for forwarding by `service`, applying the hop
+    /// limit of [`TylError::forward`].
+    ///
+    /// The envelope's producer becomes the context's origin service if none
+    /// was recorded; envelopes without context get one.
+    pub fn forward<S: Into<String>>(self, service: S) -> TylErrorEnvelope {
+        let service = service.into();
+        let mut context = match self.context {
+            Some(context) => context,
+            None => self.error.to_context(self.service.clone()),
+        };
+        if context.origin_service.is_none() {
+            context.origin_service = Some(self.service);
+        }
+        let error = self.error.forward(&mut context, service.clone());
+        TylErrorEnvelope::new(service, error).with_context(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_should_replace_errors_past_the_hop_limit() {
+        // Given: a retriable error bouncing between two services
+        let mut context = TylError::timeout("Upstream slow")
+            .to_context("orders.sync".to_string())
+            .with_origin_service("inventory");
+        let mut error = TylError::timeout("Upstream slow");
+
+        // When: forwarding it up to the limit, then once more
+        for _ in 0..3 {
+            error = error.forward_with_max(&mut context, "orders".to_string(), 3);
+            assert_eq!(error.code(), "timeout");
+        }
+        error = error.forward_with_max(&mut context, "billing".to_string(), 3);
+
+        // Then: it should become a permanent internal error recording the loop
+        assert_eq!(error.code(), "internal");
+        assert!(!error.is_retriable());
+        assert_eq!(
+            error.to_string(),
+            "Internal error: Forwarding loop: error from inventory forwarded 4 times (max 3): \
+             Timeout: Upstream slow"
+        );
+        let record = context.get_metadata(FORWARDING_LOOP_KEY).unwrap();
+        assert_eq!(record["service"], "billing");
+        assert_eq!(record["code"], "timeout");
+        assert_eq!(context.category.category_name(), "Internal");
+
+        // And: further hops should not wrap it again
+        let message = error.to_string();
+        error = error.forward_with_max(&mut context, "orders".to_string(), 3);
+        assert_eq!(error.to_string(), message);
+        assert_eq!(context.hop_count, 5);
+    }
+
+    #[test]
+    fn test_envelope_forward_should_count_hops_from_the_producer() {
+        // Given: an envelope produced by the inventory service
+        let envelope = TylErrorEnvelope::new("inventory", TylError::network("reset"));
+
+        // When: forwarding it twice
+        let envelope = envelope.forward("orders").forward("billing");
+
+        // Then: the context should carry the origin and hop count
+        let context = envelope.context.unwrap();
+        assert_eq!(envelope.service, "billing");
+        assert_eq!(context.origin_service.as_deref(), Some("inventory"));
+        assert_eq!(context.hop_count, 2);
+        assert!(envelope.retriable);
+    }
+}
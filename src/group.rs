@@ -0,0 +1,272 @@
+//! Aggregation of failures from batch operations.
+//!
+//! This module provides [`TylErrorGroup`], which collects the failed items of a
+//! batch together with their position (and optional identifier), and
+//! [`partition_results`] / [`TylResultExt::collect_errors`] for splitting batch
+//! results into successes and such a group.
+
+use crate::category::ErrorCategory;
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// One failed item of a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedError {
+    /// Position of the item in the batch.
+    pub index: usize,
+    /// Identifier of the item, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The item's error.
+    pub error: TylError,
+}
+
+/// Aggregate of the failures of a batch operation.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{partition_results, TylError};
+///
+/// let results = vec![
+///     Ok(1),
+///     Err(TylError::database("Deadlock")),
+///     Ok(3),
+///     Err(TylError::network("Connection reset")),
+/// ];
+///
+/// let (values, failures) = partition_results(results);
+/// assert_eq!(values, vec![1, 3]);
+/// assert_eq!(failures.len(), 2);
+/// assert_eq!(failures.errors()[1].index, 3);
+/// assert!(failures.is_retriable());
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TylErrorGroup {
+    errors: Vec<GroupedError>,
+}
+
+impl TylErrorGroup {
+    /// Create an empty group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the failure of the item at `index`.
+    pub fn push(&mut self, index: usize, error: TylError) {
+        self.errors.push(GroupedError {
+            index,
+            id: None,
+            error,
+        });
+    }
+
+    /// Record the failure of the item at `index` with the given identifier.
+    pub fn push_with_id<S: Into<String>>(&mut self, index: usize, id: S, error: TylError) {
+        self.errors.push(GroupedError {
+            index,
+            id: Some(id.into()),
+            error,
+        });
+    }
+
+    /// Get the recorded failures in batch order.
+    pub fn errors(&self) -> &[GroupedError] {
+        &self.errors
+    }
+
+    /// Consume the group, returning the recorded failures.
+    pub fn into_errors(self) -> Vec<GroupedError> {
+        self.errors
+    }
+
+    /// Get the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Check if no failure was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Get the category of the whole group.
+    ///
+    /// If all failures share a category, that category is returned. Mixed
+    /// groups are permanent if any failure is non-retriable, and transient
+    /// otherwise. Empty groups are of unknown category.
+    pub fn category(&self) -> ErrorCategory {
+        let Some(first) = self.errors.first().map(|e| e.error.category()) else {
+            return ErrorCategory::unknown();
+        };
+
+        let uniform = self
+            .errors
+            .iter()
+            .all(|e| e.error.category().category_name() == first.category_name());
+        if uniform {
+            first
+        } else if self.is_retriable() {
+            ErrorCategory::transient()
+        } else {
+            ErrorCategory::permanent()
+        }
+    }
+
+    /// Check if retrying the whole batch may succeed.
+    ///
+    /// True only if the group is non-empty and every failure is retriable.
+    pub fn is_retriable(&self) -> bool {
+        !self.errors.is_empty() && self.errors.iter().all(|e| e.error.is_retriable())
+    }
+
+    /// Convert the group into a single error, or `None` if it is empty.
+    ///
+    /// A group with one failure yields that error; larger groups yield a
+    /// custom error of the group's [`category`](Self::category).
+    pub fn to_error(&self) -> Option<TylError> {
+        match self.errors.as_slice() {
+            [] => None,
+            [only] => Some(only.error.clone()),
+            _ => Some(TylError::business_logic(
+                self.to_string(),
+                self.category().into_classifier(),
+            )),
+        }
+    }
+
+    /// Create one tracking context per failure, with `batch_index` and
+    /// `batch_id` metadata identifying the item.
+    pub fn to_contexts(&self, operation: &str) -> Vec<ErrorContext> {
+        self.errors
+            .iter()
+            .map(|item| {
+                let mut context = item.error.to_context(operation.to_string());
+                context.add_metadata("batch_index".to_string(), serde_json::json!(item.index));
+                if let Some(id) = &item.id {
+                    context.add_metadata("batch_id".to_string(), serde_json::json!(id));
+                }
+                context
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TylErrorGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.first() {
+            None => write!(f, "no batch items failed"),
+            Some(first) => write!(
+                f,
+                "{} batch item(s) failed; first at index {}: {}",
+                self.errors.len(),
+                first.index,
+                first.error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TylErrorGroup {}
+
+/// Split batch results into the successful values and a group of failures.
+///
+/// Failures are recorded with their position in the iterator.
+pub fn partition_results<T, I>(results: I) -> (Vec<T>, TylErrorGroup)
+where
+    I: IntoIterator<Item = TylResult<T>>,
+{
+    let mut values = Vec::new();
+    let mut group = TylErrorGroup::new();
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => group.push(index, error),
+        }
+    }
+    (values, group)
+}
+
+/// Split keyed batch results into the successful items and a group of failures.
+///
+/// Failures are recorded with their position and key.
+pub fn partition_keyed_results<K, T, I>(results: I) -> (Vec<(K, T)>, TylErrorGroup)
+where
+    K: fmt::Display,
+    I: IntoIterator<Item = (K, TylResult<T>)>,
+{
+    let mut values = Vec::new();
+    let mut group = TylErrorGroup::new();
+    for (index, (key, result)) in results.into_iter().enumerate() {
+        match result {
+            Ok(value) => values.push((key, value)),
+            Err(error) => group.push_with_id(index, key.to_string(), error),
+        }
+    }
+    (values, group)
+}
+
+/// Extension methods for iterators of [`TylResult`]s.
+pub trait TylResultExt<T>: Iterator<Item = TylResult<T>> + Sized {
+    /// Process every result, collecting the values and the failures.
+    ///
+    /// Equivalent to [`partition_results`].
+    fn collect_errors(self) -> (Vec<T>, TylErrorGroup) {
+        partition_results(self)
+    }
+}
+
+impl<T, I> TylResultExt<T> for I where I: Iterator<Item = TylResult<T>> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_errors_should_keep_indices_and_classify_batch() {
+        // Given: a batch with one transient and one permanent failure
+        let results = ["1", "x", "3", "4"].iter().map(|raw| match *raw {
+            "x" => Err(TylError::validation("amount", "not a number")),
+            "4" => Err(TylError::database("Deadlock")),
+            raw => Ok(raw.len()),
+        });
+
+        // When: collecting the errors
+        let (values, group) = results.collect_errors();
+
+        // Then: the batch should not be retriable as a whole
+        assert_eq!(values, vec![1, 1]);
+        assert_eq!(group.errors()[0].index, 1);
+        assert!(!group.is_retriable());
+        assert_eq!(group.category().category_name(), "Permanent");
+        assert!(group
+            .to_error()
+            .unwrap()
+            .to_string()
+            .contains("2 batch item(s)"));
+    }
+
+    #[test]
+    fn test_keyed_partition_should_record_ids_in_contexts() {
+        // Given: keyed results with a single transient failure
+        let results = vec![
+            ("order-1", Ok(())),
+            ("order-2", Err(TylError::network("Upstream timeout"))),
+        ];
+
+        // When: partitioning and creating contexts
+        let (values, group) = partition_keyed_results(results);
+        let contexts = group.to_contexts("orders.sync");
+
+        // Then: the failure should be identified and the batch retriable
+        assert_eq!(values.len(), 1);
+        assert!(group.is_retriable());
+        assert_eq!(group.category().category_name(), "Network");
+        assert_eq!(
+            contexts[0].get_metadata("batch_id"),
+            Some(&serde_json::json!("order-2"))
+        );
+        assert_eq!(group.to_error().unwrap().code(), "network");
+    }
+}
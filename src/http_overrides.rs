@@ -0,0 +1,241 @@
+//! Operator-controlled overrides of HTTP status mapping.
+//!
+//! This module provides [`HttpMappingOverrides`], a table of rules that remap
+//! errors to a different HTTP status per product (for example "answer
+//! `not_found` with 410 Gone for soft-deleted resources"). The installed
+//! table is consulted by [`TylError::http_status`], and so by problem details
+//! and the web integrations, before the default mapping; it is loaded lazily
+//! from `TYL_ERROR_HTTP_MAPPING_OVERRIDES` or installed explicitly with
+//! [`HttpMappingOverrides::install`].
+
+use crate::error::{TylError, TylResult};
+use crate::snapshot::{Snapshot, SnapshotCache};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Environment variable holding a JSON HTTP mapping table.
+pub const HTTP_MAPPING_OVERRIDES_ENV: &str = "TYL_ERROR_HTTP_MAPPING_OVERRIDES";
+
+/// One mapping rule; every condition that is set must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpMappingOverride {
+    /// Error code to match (see [`TylError::code`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Error variant to match (e.g. `NotFound`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    /// Category name to match (e.g. `Permanent`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// HTTP status to use instead.
+    pub status: u16,
+}
+
+impl HttpMappingOverride {
+    fn matches(&self, error: &TylError) -> bool {
+        self.code.as_deref().map_or(true, |c| c == error.code())
+            && self
+                .variant
+                .as_deref()
+                .map_or(true, |v| v == error.variant_name())
+            && self
+                .category
+                .as_deref()
+                .map_or(true, |c| c == error.category().category_name())
+    }
+}
+
+/// Ordered table of HTTP status overrides; the first matching rule wins.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{HttpMappingOverrides, TylError};
+///
+/// let overrides = HttpMappingOverrides::from_json(r#"{
+///     "rules": [{ "code": "not_found", "status": 410 }]
+/// }"#).unwrap();
+///
+/// assert_eq!(overrides.resolve(&TylError::not_found("user", "42")), Some(410));
+/// assert_eq!(overrides.resolve(&TylError::conflict("Version mismatch")), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpMappingOverrides {
+    /// Rules evaluated in order.
+    #[serde(default)]
+    pub rules: Vec<HttpMappingOverride>,
+}
+
+impl HttpMappingOverrides {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule using builder pattern.
+    pub fn with_rule(mut self, rule: HttpMappingOverride) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Map errors with `code` to `status` using builder pattern.
+    pub fn with_code<C: Into<String>>(self, code: C, status: u16) -> Self {
+        self.with_rule(HttpMappingOverride {
+            code: Some(code.into()),
+            status,
+            ..HttpMappingOverride::default()
+        })
+    }
+
+    /// Check that every rule has a condition and maps to an error status.
+    pub fn validate(&self) -> TylResult<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if rule.code.is_none() && rule.variant.is_none() && rule.category.is_none() {
+                return Err(TylError::config_key(
+                    format!("rules[{index}]"),
+                    "a code, variant or category to match",
+                    "no condition",
+                ));
+            }
+            if !(400..=599).contains(&rule.status) {
+                return Err(TylError::config_key(
+                    format!("rules[{index}].status"),
+                    "an HTTP error status (400-599)",
+                    rule.status.to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load and validate a table from JSON.
+    pub fn from_json(json: &str) -> TylResult<Self> {
+        let overrides: Self = serde_json::from_str(json).map_err(|err| {
+            TylError::configuration(format!("Invalid HTTP mapping overrides: {err}"))
+        })?;
+        overrides.validate()?;
+        Ok(overrides)
+    }
+
+    /// Load and validate a table from YAML. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> TylResult<Self> {
+        let overrides: Self = serde_yaml::from_str(yaml).map_err(|err| {
+            TylError::configuration(format!("Invalid HTTP mapping overrides: {err}"))
+        })?;
+        overrides.validate()?;
+        Ok(overrides)
+    }
+
+    /// Load a table from [`HTTP_MAPPING_OVERRIDES_ENV`], if set.
+    pub fn from_env() -> TylResult<Option<Self>> {
+        match std::env::var(HTTP_MAPPING_OVERRIDES_ENV) {
+            Ok(json) => Self::from_json(&json).map(Some),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Find the overriding status for an error, if a rule matches.
+    pub fn resolve(&self, error: &TylError) -> Option<u16> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(error))
+            .map(|rule| rule.status)
+    }
+
+    /// Install this table process-wide, replacing the current one.
+    pub fn install(self) {
+        INSTALLED.replace(self);
+    }
+
+    /// Remove all process-wide overrides.
+    pub fn clear() {
+        Self::new().install();
+    }
+
+    /// Get the process-wide table.
+    pub fn current() -> Arc<HttpMappingOverrides> {
+        INSTALLED.current()
+    }
+
+    /// Run `f` with the process-wide table, without locking it.
+    pub(crate) fn with_current<R>(f: impl FnOnce(&HttpMappingOverrides) -> R) -> R {
+        INSTALLED.with(f)
+    }
+}
+
+thread_local! {
+    static CACHE: SnapshotCache<HttpMappingOverrides> = const { SnapshotCache::new(None) };
+}
+
+/// The process-wide table, loaded from the environment on first use.
+static INSTALLED: Snapshot<HttpMappingOverrides> = Snapshot::new(
+    || {
+        HttpMappingOverrides::from_env()
+            .unwrap_or_else(|error| {
+                error.log();
+                None
+            })
+            .unwrap_or_default()
+    },
+    &CACHE,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+    use std::time::Duration;
+
+    #[test]
+    fn test_installed_overrides_should_change_http_status() {
+        // Given: an override for a test-only custom category
+        let gone = ErrorCategory::custom_fn("HttpOverridesTestGone", false, |_| Duration::ZERO);
+        let error = TylError::business_logic("Order was deleted", gone.into_classifier());
+        let previous = HttpMappingOverrides::current();
+        let mut table = (*previous).clone();
+        table.rules.push(HttpMappingOverride {
+            variant: Some("Custom".to_string()),
+            category: Some("HttpOverridesTestGone".to_string()),
+            status: 410,
+            ..HttpMappingOverride::default()
+        });
+        table.install();
+
+        // When/Then: the status and problem details should use it
+        assert_eq!(error.http_status(), 410);
+        assert_eq!(error.to_problem_details().status, 410);
+        assert_eq!(error.to_problem_details().title, "Gone");
+        assert_eq!(TylError::not_found("user", "42").http_status(), 404);
+
+        // And: reinstalling the previous table should take effect at once
+        (*previous).clone().install();
+        assert_eq!(error.http_status(), 500);
+    }
+
+    #[test]
+    fn test_validate_should_reject_unconditional_and_non_error_rules() {
+        let error = HttpMappingOverrides::from_json(r#"{"rules": [{"status": 410}]}"#).unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[0]");
+
+        let error = HttpMappingOverrides::new()
+            .with_code("not_found", 410)
+            .with_code("conflict", 200)
+            .validate()
+            .unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[1].status");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_from_yaml_should_load_rules() {
+        let overrides =
+            HttpMappingOverrides::from_yaml("rules:\n  - variant: NotFound\n    status: 410\n")
+                .unwrap();
+        assert_eq!(
+            overrides.resolve(&TylError::not_found("doc", "7")),
+            Some(410)
+        );
+    }
+}
@@ -14,6 +14,10 @@
 //! - **Extensible**: Custom error categories without modifying core library
 //! - **Serialization**: Full serde support for all error types
 //! - **Zero Configuration**: Works out of the box with sensible defaults
+//! - **Safe Debugging**: Opt-in redaction of sensitive metadata in `Debug` output
+//! - **Error Events**: In-process subscription to every reported error
+//! - **Audit Events**: Structured audit records for security-relevant failures
+//! - **Context Enrichment**: Process-wide fields added to every error context
 //!
 //! ## Environment Variables
 //!
@@ -25,6 +29,21 @@
 //! | `TYL_ERROR_MAX_RETRIES` | `3` | Maximum retry attempts for retriable errors |
 //! | `TYL_ERROR_LOG_ERRORS` | `true` | Log errors to stderr (`true`/`false`) |
 //! | `TYL_ERROR_LOG_LEVEL` | `INFO` | Log level (`ERROR`/`WARN`/`INFO`/`DEBUG`) |
+//! | `TYL_ERROR_REDACT` | `false` | Redact sensitive data in `Debug` output (`true`/`false`) |
+//! | `TYL_ERROR_CLASSIFICATION_OVERRIDES` | - | JSON table of [`ClassificationOverrides`] |
+//! | `TYL_ERROR_HTTP_MAPPING_OVERRIDES` | - | JSON table of [`HttpMappingOverrides`] |
+//! | `TYL_ERROR_MAX_MESSAGE_LEN` | `16384` | Maximum message length in bytes (`0` = unlimited) |
+//! | `TYL_ERROR_MAX_METADATA_VALUE_LEN` | `4096` | Maximum serialized metadata value size in bytes (`0` = unlimited) |
+//! | `TYL_ERROR_CATEGORY_LOG_LEVELS` | - | Per-category log levels, e.g. `Validation=DEBUG,Network=WARN` |
+//! | `TYL_ERROR_MAX_RETRIES_PER_MINUTE` | `0` | Retries per minute shared by every retry policy in the process (`0` = unlimited) |
+//! | `TYL_ERROR_LOG_BURST` | `5` | Log lines per error fingerprint before rate limiting (`0` = unlimited) |
+//! | `TYL_ERROR_LOG_INTERVAL_SECS` | `60` | Once rate limited, at most one log line per fingerprint per interval |
+//! | `TYL_ERROR_MIN_DELAY` | `0` | Minimum delay between retries in milliseconds, enforced on every category and policy |
+//! | `TYL_ERROR_MAX_ATTEMPTS_CEILING` | `0` | Maximum retries allowed by any category, policy or queue (`0` = no ceiling) |
+//! | `TYL_ERROR_MAX_HOPS` | `8` | Maximum times an error may be forwarded between services (`0` = unlimited) |
+//! | `TYL_ERROR_ID_MODE` | `random` | Error ID generation (`random` UUIDv4 or `deterministic` UUIDv5) |
+//! | `TYL_ERROR_BAGGAGE_KEYS` | - | Comma-separated baggage keys propagated between services (unset = any key) |
+//! | `TYL_ERROR_MAX_BAGGAGE_LEN` | `2048` | Maximum encoded size of the `baggage` header in bytes (`0` = unlimited) |
 //! | `RUST_BACKTRACE` | - | Standard Rust backtrace (overrides TYL_ERROR_BACKTRACE) |
 //!
 //! **Example:**
@@ -59,8 +78,8 @@
 //!     }
 //! }
 //!
-//! // Conditional logging based on environment configuration
-//! error.log_if_enabled(tyl_errors::LogLevel::Error);
+//! // Conditional logging at the level of the error's severity (or category override)
+//! error.log();
 //!
 //! // Manual logging check
 //! if TylError::log_errors_enabled() && TylError::log_level() >= tyl_errors::LogLevel::Info {
@@ -95,18 +114,195 @@
 //! ```
 
 // Module declarations
+#[cfg(feature = "alert")]
+mod alert;
+pub mod audit;
+mod baggage;
+mod cancel;
+#[cfg(feature = "yaml")]
+mod catalog;
+#[cfg(feature = "tower")]
+mod catch_layer;
 mod category;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod classifier_registry;
+mod clock;
+#[cfg(feature = "cloudevents")]
+mod cloudevent;
+mod code_registry;
+mod collector;
+mod config_error;
 mod context;
+mod context_diff;
+mod context_template;
+mod dead_letter;
+mod dedup;
+pub mod diagnostics;
+mod domain;
+mod dto;
+pub mod enrich;
+mod envelope;
 mod error;
+mod error_id;
+mod escalation;
+pub mod events;
+mod exit_status;
+mod explain;
+mod extras;
+mod fault;
+mod forwarding;
+mod group;
+mod http_overrides;
+mod limits;
+mod log_limit;
+mod loose_json;
+mod meta_key;
+#[cfg(feature = "schemars")]
+mod openapi;
+mod operation;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod overrides;
+mod panic_capture;
+mod problem;
+mod profile;
+mod queue;
+mod redact;
+mod resilience;
 mod retry;
+#[cfg(any(feature = "backon", feature = "futures-retry"))]
+mod retry_adapters;
+mod retry_budget;
+#[cfg(feature = "salvo")]
+mod salvo_writer;
 mod settings;
+mod shutdown;
+mod snapshot;
+mod static_error;
+mod stats;
+mod summary;
+mod suppress;
+pub mod testing;
+mod translate;
+mod validation;
+#[cfg(feature = "warp")]
+pub mod warp_reject;
 
 // Re-export main types and traits
-pub use category::{BuiltinCategory, ErrorCategory, ErrorClassifier};
-pub use context::ErrorContext;
-pub use error::{TylError, TylResult};
-pub use retry::{RetryPolicy, RetryResult, RetryableError};
-pub use settings::{ErrorSettings, LogLevel};
+#[cfg(feature = "alert")]
+pub use alert::{AlertRouter, AlertRouterConfig, AlertRule, Route};
+pub use baggage::BAGGAGE_HEADER;
+pub use cancel::{ShouldCancel, CANCEL_POLL_INTERVAL};
+#[cfg(feature = "yaml")]
+pub use catalog::{describe_code, CodeDescription};
+#[cfg(feature = "tower")]
+pub use catch_layer::{CatchFuture, TylCatch, TylCatchLayer};
+pub use category::{BuiltinCategory, ErrorCategory, ErrorClassifier, FnClassifier, RetryScope};
+pub use classifier_registry::ClassifierRegistry;
+#[cfg(feature = "cloudevents")]
+pub use cloudevent::{
+    CLOUDEVENT_CATEGORY_EXTENSION, CLOUDEVENT_MESSAGE_EXTENSION, CLOUDEVENT_RETRIABLE_EXTENSION,
+    CLOUDEVENT_TYPE_PREFIX,
+};
+pub use code_registry::{
+    CodeConflict, CodeKind, CodeRegistration, CodeRegistry, BUILTIN_CODE_MODULE,
+};
+pub use collector::{ErrorCollector, ErrorDrain};
+pub use config_error::ConfigLocation;
+pub use context::{ErrorContext, IDEMPOTENCY_KEY_HEADER, TENANT_ID_KEY};
+pub use context_diff::{ContextChange, ContextDiff};
+pub use context_template::ContextTemplate;
+pub use dead_letter::{DeadLetter, DeliveryAttempt, DEAD_LETTER_SCHEMA_VERSION};
+pub use dedup::{DedupReporter, DUPLICATE_COUNT_KEY};
+pub use domain::{CustomDomain, TylDomain};
+pub use dto::TylErrorDto;
+pub use envelope::{TylErrorEnvelope, ENVELOPE_SCHEMA_VERSION};
+pub use error::{
+    ErrorSource, FingerprintOptions, InputResultExt, TylError, TylResult, TylSeverity,
+    VerboseDisplay, DRIVER_CODE_KEY,
+};
+pub use error_id::ERROR_ID_NAMESPACE;
+pub use escalation::EscalatingCategory;
+pub use exit_status::{
+    ProcessExit, EXIT_CANCELLED, EXIT_CONFIG, EXIT_DATA_ERROR, EXIT_NO_PERMISSION, EXIT_SOFTWARE,
+    EXIT_TEMPORARY_FAILURE, STDERR_TAIL_MAX_LEN,
+};
+pub use explain::{RetryDecision, RetryDecisionSource};
+pub use extras::{CallerLocation, ErrorExtras};
+pub use fault::{
+    clear_fault_flag_source, inject_fault, install_fault_flag_source, FaultFlagSource, FaultTarget,
+};
+pub use forwarding::FORWARDING_LOOP_KEY;
+pub use group::{
+    partition_keyed_results, partition_results, GroupedError, TylErrorGroup, TylResultExt,
+};
+pub use http_overrides::{HttpMappingOverride, HttpMappingOverrides, HTTP_MAPPING_OVERRIDES_ENV};
+pub use limits::{
+    is_truncated, DEFAULT_MAX_BAGGAGE_LEN, DEFAULT_MAX_MESSAGE_LEN, DEFAULT_MAX_METADATA_VALUE_LEN,
+    TRUNCATION_MARKER,
+};
+pub use log_limit::{LogCount, LogDecision, LogRateLimiter, MAX_TRACKED_FINGERPRINTS};
+pub use loose_json::{
+    LooseField, LooseJsonAliases, LOOSE_JSON_MAX_DEPTH, LOOSE_JSON_MAX_PAYLOAD_LEN,
+};
+pub use meta_key::{MetaKey, MAX_INTERNED_KEYS};
+#[cfg(feature = "schemars")]
+pub use openapi::{
+    OpenApiErrors, DEFAULT_OPENAPI_STATUSES, OPENAPI_CODE_SCHEMA, OPENAPI_PROBLEM_SCHEMA,
+};
+pub use operation::{
+    OperationFrame, OperationName, OperationResultExt, MAX_OPERATION_FRAMES,
+    OPERATION_LOCATION_KEY, OPERATION_PATH_KEY, OPERATION_SEPARATOR,
+};
+#[cfg(feature = "otel")]
+pub use otel::OtelLogExporter;
+pub use overrides::{
+    ClassificationOverride, ClassificationOverrides, CLASSIFICATION_OVERRIDES_ENV,
+};
+pub use panic_capture::{install_panic_hook, PanicDetails, MAX_BACKTRACE_LINES};
+pub use problem::{
+    MultiStatusBody, ProblemDetails, MULTI_STATUS, PROBLEM_CODE, PROBLEM_ERROR_ID,
+    PROBLEM_JSON_CONTENT_TYPE, PROBLEM_TYL_CATEGORY, PROBLEM_TYL_ITEM_ID, PROBLEM_TYL_ITEM_INDEX,
+    PROBLEM_TYL_MESSAGE, PROBLEM_TYL_RETRIABLE, PROBLEM_TYL_RETRY_AFTER_MS,
+    PROBLEM_TYL_RETRY_SCOPE, RETRY_SCOPE_HEADER, SERVER_ERROR_DETAIL,
+};
+pub use profile::Profile;
+pub use queue::{
+    BoxedRetryOperation, InMemoryRetryStore, QueuedRetry, RetryQueue, RetryStore, StoredRetry,
+};
+pub use redact::{RedactedDebug, SafeDebug, REDACTED, SAFE_DEBUG_MAX_MESSAGE_LEN};
+pub use resilience::{
+    CircuitBreakerConfig, CircuitState, ResilienceConfig, ResiliencePipeline, RetryPolicyConfig,
+};
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use retry::GlooSleeper;
+#[cfg(feature = "tokio")]
+pub use retry::TokioSleeper;
+pub use retry::{
+    AttemptObserver, AttemptRecord, Idempotency, JitterKind, RetryHint, RetryPolicy, RetryResult,
+    RetrySchedule, RetryTelemetry, RetryableError, SleepFuture, Sleeper,
+};
+#[cfg(feature = "backon")]
+pub use retry_adapters::PolicyBackoff;
+pub use retry_budget::RetryBudget;
+pub use settings::{
+    validate_configuration, ErrorIdMode, ErrorSettings, LogLevel, SettingsOverride,
+    DEFAULT_LOG_BURST, DEFAULT_LOG_INTERVAL, DEFAULT_MAX_HOPS,
+};
+pub use shutdown::{
+    register_shutdown_hook, remove_shutdown_hook, shutdown, ShutdownHook, ShutdownHookId,
+    ShutdownHooks, ShutdownReport,
+};
+pub use static_error::StaticTylError;
+pub use stats::{ErrorStats, ErrorStatsSnapshot};
+pub use summary::{CodeSummary, ErrorReport, DEFAULT_REPORT_EXAMPLES, DEFAULT_REPORT_ITEMS};
+pub use suppress::{
+    active_suppressions, lift_suppressions, suppress, Suppression, Suppressions,
+    SuppressionsOverride, SUPPRESSED_UNTIL_KEY,
+};
+pub use translate::{ErrorTranslator, TranslatedError, Translation};
+pub use validation::{FieldViolation, TylValidationErrors, ValidationConstraint};
 
 #[cfg(test)]
 mod tests {
@@ -154,10 +350,12 @@ mod tests {
                 TylError::NotFound {
                     resource: r1,
                     id: i1,
+                    ..
                 },
                 TylError::NotFound {
                     resource: r2,
                     id: i2,
+                    ..
                 },
             ) => {
                 assert_eq!(r1, r2);
@@ -259,6 +457,34 @@ mod tests {
         assert_eq!(context.metadata["timeout_ms"], serde_json::json!(5000));
     }
 
+    #[test]
+    fn test_error_context_typed_metadata_should_roundtrip() {
+        // Given: a context with typed metadata
+        let hosts = vec!["db-1".to_string(), "db-2".to_string()];
+        let context = ErrorContext::new(
+            "query".to_string(),
+            ErrorCategory::transient(),
+            "Failover".to_string(),
+        )
+        .with_typed_metadata("hosts", &hosts)
+        .unwrap();
+
+        // When/Then: values should deserialize into their type, or fail naming the key
+        assert_eq!(
+            context.get_metadata_as::<Vec<String>>("hosts").unwrap(),
+            Some(hosts)
+        );
+        assert_eq!(context.get_metadata_as::<u32>("absent").unwrap(), None);
+        let error = context.get_metadata_as::<u32>("hosts").unwrap_err();
+        assert_eq!(error.code(), "internal");
+        assert_eq!(error.http_status(), 500);
+        assert!(error.to_string().contains("metadata `hosts`"));
+
+        // And: values that are not valid JSON should be rejected
+        let keyed: std::collections::HashMap<(u8, u8), u8> = [((1, 2), 3)].into();
+        assert!(context.with_typed_metadata("keyed", &keyed).is_err());
+    }
+
     #[test]
     fn test_error_context_retry_tracking_should_increment() {
         // Given: error context
@@ -276,6 +502,37 @@ mod tests {
         assert_eq!(context.attempt_count, 2);
     }
 
+    #[test]
+    fn test_error_context_should_measure_durations_monotonically() {
+        use std::time::Duration;
+
+        // Given: a context and a copy occurring 20ms later, after the wall
+        // clock was set back an hour
+        let first = ErrorContext::new(
+            "sync".to_string(),
+            ErrorCategory::transient(),
+            "Busy".to_string(),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        let mut later = first.clone();
+        later.occurred_at_mono += Duration::from_millis(20);
+        later.occurred_at = first.occurred_at - chrono::Duration::hours(1);
+
+        // When/Then: durations should come from the monotonic instant
+        assert_eq!(later.duration_since(&first), Duration::from_millis(20));
+        assert_eq!(first.duration_since(&later), Duration::ZERO);
+        assert!(first.elapsed() >= Duration::from_millis(5));
+        assert_eq!(
+            first.retry_due_at(Duration::from_secs(1)) - first.occurred_at_mono,
+            Duration::from_secs(1)
+        );
+
+        // And: the instant should not be serialized
+        let json = serde_json::to_value(&first).unwrap();
+        assert!(json.get("occurred_at_mono").is_none());
+        assert!(serde_json::from_value::<ErrorContext>(json).is_ok());
+    }
+
     #[test]
     fn test_custom_error_category_should_be_extensible() {
         // Given: a custom domain-specific error category
@@ -353,4 +610,492 @@ mod tests {
         assert!(!category.is_retriable());
         assert_eq!(category.category_name(), "BusinessLogic");
     }
+
+    #[test]
+    fn test_config_errors_should_expose_structured_fields() {
+        // Given: structured configuration errors
+        let invalid = TylError::config_key("server.port", "integer", "\"http\"");
+        let missing = TylError::config_missing("DATABASE_URL");
+
+        // When: converting them to contexts
+        let invalid_context = invalid.to_context("load_config".to_string());
+        let missing_context = missing.to_context("load_config".to_string());
+
+        // Then: fields should appear in display, codes and metadata
+        assert_eq!(
+            missing.to_string(),
+            "Configuration error: missing required key DATABASE_URL"
+        );
+        assert_eq!(invalid.code(), "configuration_invalid_key");
+        assert_eq!(missing.code(), "configuration_missing_key");
+        assert_eq!(TylError::configuration("bad").code(), "configuration");
+        assert_eq!(
+            invalid_context.metadata["config_key"],
+            serde_json::json!("server.port")
+        );
+        assert_eq!(
+            invalid_context.metadata["expected"],
+            serde_json::json!("integer")
+        );
+        assert_eq!(missing_context.metadata_count(), 1);
+    }
+
+    #[test]
+    fn test_retriable_and_permanent_should_preserve_source() {
+        // Given: a third-party error
+        use std::error::Error;
+        let io = || std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out");
+
+        // When: wrapping it with an explicit classification
+        let retriable = TylError::retriable("Cache read failed", io());
+        let permanent = TylError::permanent("Corrupted cache entry", io());
+
+        // Then: category should follow the constructor and the source be kept
+        assert!(retriable.category().is_retriable());
+        assert_eq!(retriable.category().category_name(), "Transient");
+        assert!(!permanent.category().is_retriable());
+        assert_eq!(permanent.category().category_name(), "Permanent");
+        assert_eq!(retriable.source().unwrap().to_string(), "read timed out");
+        assert!(TylError::internal("no source").source().is_none());
+    }
+
+    #[test]
+    fn test_std_parse_errors_should_convert_with_source() {
+        // Given: functions using `?` on std parsing results
+        use std::error::Error;
+        fn parse_port(raw: &str) -> TylResult<u16> {
+            Ok(raw.parse::<u16>()?)
+        }
+        fn decode(bytes: Vec<u8>) -> TylResult<String> {
+            Ok(String::from_utf8(bytes)?)
+        }
+
+        // When: parsing invalid input
+        let port_error = parse_port("http").unwrap_err();
+        let utf8_error = decode(vec![0xff, 0xfe]).unwrap_err();
+        let float_error = TylError::from("1.2.3".parse::<f64>().unwrap_err());
+
+        // Then: validation errors carrying the source should be produced
+        assert!(matches!(&port_error, TylError::Validation { field, .. } if field == "parsing"));
+        assert!(port_error.to_string().contains("Invalid integer"));
+        assert!(port_error.source().is_some());
+        assert!(utf8_error.to_string().contains("Invalid UTF-8"));
+        assert_eq!(float_error.code(), "validation");
+        assert!(!float_error.category().is_retriable());
+    }
+
+    #[test]
+    fn test_chrono_and_uuid_errors_should_convert_to_parsing_errors() {
+        // Given: functions using `?` on chrono and uuid parsing
+        fn parse_at(raw: &str) -> TylResult<chrono::DateTime<chrono::FixedOffset>> {
+            Ok(chrono::DateTime::parse_from_rfc3339(raw)?)
+        }
+        fn parse_id(raw: &str) -> TylResult<uuid::Uuid> {
+            uuid::Uuid::parse_str(raw).with_input(raw)
+        }
+
+        // When: parsing invalid input
+        let time_error = parse_at("yesterday").unwrap_err();
+        let id_error = parse_id("1234").unwrap_err();
+
+        // Then: parsing errors should be produced, with the input when recorded
+        assert!(time_error.to_string().contains("Invalid timestamp"));
+        assert!(!time_error.metadata().contains_key("input"));
+        assert_eq!(id_error.code(), "validation");
+        assert_eq!(id_error.metadata()["input"], "1234");
+        assert_eq!(
+            id_error
+                .to_context("ids.parse".to_string())
+                .get_metadata("input"),
+            Some(&serde_json::json!("1234"))
+        );
+    }
+
+    #[test]
+    fn test_database_errors_should_keep_their_driver_code() {
+        // Given: a database error carrying its SQLSTATE, exhausted after retries
+        let error = TylError::database("could not serialize access").with_driver_code("40001");
+        let exhausted = error
+            .clone()
+            .with_retry_telemetry(RetryTelemetry::default());
+
+        // When: serializing it and creating its context and problem details
+        let json = serde_json::to_value(&error).unwrap();
+        let restored: TylError = serde_json::from_value(json.clone()).unwrap();
+        let context = exhausted.to_context("orders.save".to_string());
+
+        // Then: the code should survive and be exposed as metadata
+        assert_eq!(json["Database"]["extras"]["driver_code"], "40001");
+        assert_eq!(restored.driver_code(), Some("40001"));
+        assert_eq!(exhausted.driver_code(), Some("40001"));
+        assert_eq!(
+            context.get_metadata(DRIVER_CODE_KEY),
+            Some(&serde_json::json!("40001"))
+        );
+        assert!(!error
+            .to_problem_details()
+            .extensions
+            .contains_key(DRIVER_CODE_KEY));
+        assert_eq!(TylError::database("down").driver_code(), None);
+        assert_eq!(
+            TylError::network("down")
+                .with_driver_code("x")
+                .driver_code(),
+            None
+        );
+        let legacy: TylError = serde_json::from_str(r#"{"Database":{"message":"down"}}"#).unwrap();
+        assert!(legacy.metadata().is_empty());
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_url_parse_errors_should_convert_to_url_validation_errors() {
+        fn parse(raw: &str) -> TylResult<url::Url> {
+            Ok(url::Url::parse(raw)?)
+        }
+
+        let error = parse("not a url").unwrap_err();
+        assert!(matches!(&error, TylError::Validation { field, .. } if field == "url"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_header_errors_should_convert_to_header_validation_errors() {
+        fn header(raw: &str) -> TylResult<http::HeaderValue> {
+            Ok(http::HeaderValue::from_str(raw)?)
+        }
+        fn read(value: &http::HeaderValue) -> TylResult<&str> {
+            Ok(value.to_str()?)
+        }
+
+        let invalid = header("line\nbreak").unwrap_err();
+        let opaque = read(&http::HeaderValue::from_bytes(&[0xfa]).unwrap()).unwrap_err();
+        assert!(matches!(&invalid, TylError::Validation { field, .. } if field == "header"));
+        assert!(matches!(&opaque, TylError::Validation { field, .. } if field == "header"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_tokio_errors_should_convert_to_timeout_and_cancelled() {
+        // Given: an elapsed deadline, a cancelled task and a panicked task
+        let elapsed = tokio::time::timeout(
+            std::time::Duration::from_millis(1),
+            tokio::time::sleep(std::time::Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+        let aborted = tokio::spawn(tokio::time::sleep(std::time::Duration::from_secs(5)));
+        aborted.abort();
+        let cancelled = aborted.await.unwrap_err();
+        let panicked = tokio::spawn(async { panic!("worker exploded") })
+            .await
+            .unwrap_err();
+
+        // When: converting them
+        let timeout = TylError::from(elapsed);
+        let cancelled = TylError::from(cancelled);
+        let panicked = TylError::from(panicked);
+
+        // Then: each should map to the matching variant
+        assert_eq!(timeout.code(), "timeout");
+        assert!(timeout.category().is_retriable());
+        assert_eq!(cancelled.code(), "cancelled");
+        assert!(!cancelled.category().is_retriable());
+        assert_eq!(panicked.code(), "internal");
+        assert!(panicked.to_string().contains("worker exploded"));
+    }
+
+    #[test]
+    fn test_env_errors_should_become_configuration_errors() {
+        // Given: a lookup of an unset variable
+        fn read(var: &str) -> TylResult<String> {
+            Ok(std::env::var(var)?)
+        }
+
+        // When: reading it with `?` and with the named helper
+        let error = read("TYL_ERRORS_TEST_UNSET_VARIABLE").unwrap_err();
+        let named = TylError::env_missing("TYL_ERRORS_TEST_UNSET_VARIABLE");
+
+        // Then: both should be configuration errors, the named one structured
+        assert_eq!(error.code(), "configuration");
+        assert_eq!(named.code(), "configuration_missing_key");
+        assert_eq!(
+            named.metadata()["config_key"],
+            "TYL_ERRORS_TEST_UNSET_VARIABLE"
+        );
+
+        // And: values that are not valid unicode should not be recorded
+        let secret = std::ffi::OsString::from("hunter2");
+        let invalid = TylError::from(std::env::VarError::NotUnicode(secret));
+        assert_eq!(invalid.metadata()["expected"], "valid unicode");
+        assert!(!invalid.metadata().contains_key("actual"));
+        assert!(!serde_json::to_string(&invalid).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_auth_errors_should_map_to_401_403_and_hide_subject() {
+        // Given: authentication and authorization failures
+        let unauthenticated = TylError::unauthenticated("token expired");
+        let denied = TylError::permission_denied("alice@example.com", "refund", "order:9");
+
+        // When: converting them for HTTP responses and audit contexts
+        let problem = denied.to_problem_details();
+        let context = denied.to_context("orders.refund".to_string());
+
+        // Then: statuses should follow the failure and the subject stay internal
+        assert_eq!(unauthenticated.http_status(), 401);
+        assert_eq!(unauthenticated.category().category_name(), "Authentication");
+        assert_eq!(problem.status, 403);
+        assert_eq!(problem.extensions["action"], "refund");
+        assert!(!problem.extensions.contains_key("subject"));
+        assert!(context.is_sensitive("subject"));
+        assert!(!format!("{:?}", context.safe_debug()).contains("alice@example.com"));
+        assert!(!format!("{:?}", denied.safe_debug()).contains("alice@example.com"));
+    }
+
+    #[test]
+    fn test_sensitive_tenant_ids_should_be_masked_but_kept_distinct() {
+        // Given: contexts of two tenants, one of them sensitive
+        let error = TylError::database("Pool exhausted");
+        let public = error
+            .to_context("checkout".to_string())
+            .with_tenant_id("acme");
+        let hidden = error
+            .to_context("checkout".to_string())
+            .with_sensitive_tenant_id("globex");
+
+        // When: rendering, archiving and comparing them
+        let debug = format!("{:?}", hidden.safe_debug());
+        let archived = hidden.serialize_with_profile(Profile::Minimal).unwrap();
+        let diff = public.diff(&hidden);
+
+        // Then: the sensitive tenant should never appear
+        assert!(!debug.contains("globex"));
+        assert!(archived.get("tenant_id").is_none());
+        assert_eq!(
+            public.serialize_with_profile(Profile::Minimal).unwrap()["tenant_id"],
+            "acme"
+        );
+        assert_eq!(diff.changes[0].field, "tenant_id");
+        assert_eq!(diff.changes[0].left, Some(serde_json::json!("[REDACTED]")));
+
+        // And: it should still be a dimension of full serialization and fingerprints
+        let json = serde_json::to_value(&hidden).unwrap();
+        assert_eq!(json["tenant_id"], "globex");
+        let per_tenant = FingerprintOptions::new().per_tenant(true);
+        assert_ne!(
+            error.fingerprint_with(&public, per_tenant),
+            error.fingerprint_with(&hidden, per_tenant)
+        );
+    }
+
+    #[test]
+    fn test_wrap_should_bridge_foreign_errors() {
+        // Given: a foreign error from a client library
+        #[derive(Debug)]
+        struct ClientError(u16);
+        impl std::fmt::Display for ClientError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "upstream returned {}", self.0)
+            }
+        }
+        impl std::error::Error for ClientError {}
+
+        // When: wrapping it with a category
+        let error = TylError::wrap(ClientError(503), ErrorCategory::service_unavailable());
+
+        // Then: message, category and the typed source should be available
+        assert_eq!(error.to_string(), "Custom error: upstream returned 503");
+        assert!(error.is_retriable());
+        assert_eq!(error.downcast_source_ref::<ClientError>().unwrap().0, 503);
+        assert!(error.downcast_source_ref::<std::io::Error>().is_none());
+        assert!(TylError::network("down")
+            .downcast_source_ref::<ClientError>()
+            .is_none());
+    }
+
+    #[test]
+    fn test_unknown_variants_should_deserialize_and_round_trip() {
+        // Given: an error variant from a newer version of the crate
+        let json = r#"{"RateLimited":{"message":"Slow down","retry_after_ms":1500}}"#;
+
+        // When: deserializing it
+        let error: TylError = serde_json::from_str(json).unwrap();
+
+        // Then: it should become an Unknown error that serializes unchanged
+        match &error {
+            TylError::Unknown { variant, raw } => {
+                assert_eq!(variant, "RateLimited");
+                assert_eq!(raw["retry_after_ms"], 1500);
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+        assert_eq!(error.code(), "unknown");
+        assert_eq!(error.http_status(), 500);
+        assert_eq!(serde_json::to_string(&error).unwrap(), json);
+        let dto = TylErrorDto::from(&error);
+        assert_eq!(
+            TylError::try_from(dto).unwrap().to_string(),
+            error.to_string()
+        );
+
+        // And: malformed known variants should still be rejected
+        assert!(serde_json::from_str::<TylError>(r#"{"NotFound":{"id":1}}"#).is_err());
+        let unit: TylError = serde_json::from_str(r#""Throttled""#).unwrap();
+        assert_eq!(serde_json::to_string(&unit).unwrap(), r#""Throttled""#);
+    }
+
+    #[test]
+    fn test_invariant_should_keep_structured_values_and_show_diff() {
+        // Given: an invariant comparing two nested values
+        let error = TylError::invariant(
+            "stock_never_negative",
+            serde_json::json!({"sku": "A-1", "levels": [3, 4], "unit": "pcs"}),
+            serde_json::json!({"sku": "A-1", "levels": [3, -2], "warehouse": "b"}),
+        );
+
+        // When: inspecting the error
+        let message = error.to_string();
+        let metadata = error.metadata();
+
+        // Then: the values should stay structured and the message list differing paths
+        assert_eq!(metadata["invariant"], "stock_never_negative");
+        assert_eq!(metadata["actual"]["levels"][1], -2);
+        assert!(message.starts_with("Invariant violated: stock_never_negative: expected {"));
+        assert!(message.ends_with(
+            "(differs at levels.1: 4 != -2; unit: \"pcs\" != <missing>; warehouse: <missing> != \"b\")"
+        ));
+        assert_eq!(error.code(), "invariant_violated");
+        assert_eq!(error.http_status(), 500);
+        assert!(!error.is_retriable());
+
+        // And: scalar values should be shown without a path list
+        let scalar = TylError::invariant("balance_matches", 100, 90);
+        assert_eq!(
+            scalar.to_string(),
+            "Invariant violated: balance_matches: expected 100, got 90"
+        );
+        let restored = TylError::try_from(TylErrorDto::from(&scalar)).unwrap();
+        assert_eq!(restored.metadata(), scalar.metadata());
+    }
+
+    #[test]
+    fn test_oversized_messages_and_metadata_should_be_truncated() {
+        // Given: a wrapped error embedding a huge payload, under default limits
+        let _settings = ErrorSettings::override_scope(ErrorSettings::default());
+        let payload = "x".repeat(DEFAULT_MAX_MESSAGE_LEN * 4);
+        let source = std::io::Error::new(std::io::ErrorKind::InvalidData, payload.clone());
+        let error = TylError::wrap(source, ErrorCategory::permanent());
+
+        // When: building and serializing a context for it
+        let mut context = error
+            .to_context("limits::test::ingest".to_string())
+            .with_metadata("body", serde_json::json!(payload));
+        context.message = payload.clone();
+        let json = serde_json::to_value(&context).unwrap();
+
+        // Then: every copy should be capped and flagged as truncated
+        assert!(error.to_string().len() < DEFAULT_MAX_MESSAGE_LEN + 64);
+        assert!(is_truncated(&error.to_string()));
+        let body = context.metadata["body"].as_str().unwrap();
+        assert!(body.len() < DEFAULT_MAX_METADATA_VALUE_LEN + 32);
+        assert!(is_truncated(body));
+        let message = json["message"].as_str().unwrap();
+        assert!(message.len() < DEFAULT_MAX_MESSAGE_LEN + 32);
+        assert!(message.ends_with(&format!("{TRUNCATION_MARKER} 49152 bytes]")));
+        let connection = TylError::connection(payload.as_str()).to_string();
+        assert!(connection.len() < DEFAULT_MAX_MESSAGE_LEN + 64);
+        assert!(is_truncated(&connection));
+        assert!(is_truncated(
+            &TylError::config_missing(payload.as_str()).to_string()
+        ));
+        assert!(!is_truncated(&TylError::network("Reset").to_string()));
+    }
+
+    #[test]
+    fn test_boxed_errors_should_unbox_tyl_errors_and_wrap_others() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(TylError::timeout("slow"));
+        assert_eq!(TylError::from(boxed).code(), "timeout");
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "connection refused".into();
+        let error = TylError::from(boxed);
+        assert_eq!(error.category().category_name(), "Internal");
+        assert_eq!(
+            std::error::Error::source(&error).unwrap().to_string(),
+            "connection refused"
+        );
+    }
+
+    #[test]
+    fn test_errors_should_record_where_they_were_created() {
+        // Given: an error built by a constructor and one converted with `?`
+        let built = TylError::validation("email", "Malformed");
+        let built_line = line!() - 1;
+        let parse = || -> TylResult<u32> { Ok("x".parse::<u32>()?) };
+        let converted_line = line!() - 1;
+        let converted = parse().unwrap_err();
+
+        // When: inspecting, displaying and serializing them
+        let json = serde_json::to_value(&built).unwrap();
+        let restored: TylError = serde_json::from_value(json.clone()).unwrap();
+        let exhausted = built.clone().with_retry_telemetry(Default::default());
+
+        // Then: each should point at the caller's code
+        let location = built.location().unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), built_line));
+        assert_eq!(converted.location().unwrap().line(), converted_line);
+        assert_eq!(
+            built.verbose().to_string(),
+            format!(
+                "Validation error: email: Malformed (at {}:{built_line})",
+                file!()
+            )
+        );
+        assert_eq!(json["Validation"]["extras"]["caller"]["line"], built_line);
+        assert_eq!(exhausted.location(), Some(location));
+
+        // And: deserialized errors should keep where they were created
+        assert_eq!(restored.location(), Some(location));
+        assert_eq!(restored.verbose().to_string(), built.verbose().to_string());
+        assert_eq!(serde_json::to_value(&restored).unwrap(), json);
+    }
+
+    #[test]
+    fn test_severity_should_rank_errors() {
+        // Given/When/Then: severities should follow the error kind
+        assert_eq!(
+            TylError::validation("f", "m").severity(),
+            TylSeverity::Warning
+        );
+        assert_eq!(TylError::database("down").severity(), TylSeverity::Error);
+        assert_eq!(
+            TylError::config_missing("DATABASE_URL").severity(),
+            TylSeverity::Critical
+        );
+        assert!(TylSeverity::Critical > TylSeverity::Warning);
+    }
+
+    #[test]
+    fn test_effective_log_level_should_follow_severity_and_category_overrides() {
+        // Given: settings routing network errors to warn
+        let settings = ErrorSettings::default().with_category_log_level("network", LogLevel::Warn);
+
+        // When/Then: levels should come from severity unless overridden
+        let level = |error: TylError| error.effective_log_level_with(&settings);
+        assert_eq!(level(TylError::validation("f", "m")), LogLevel::Warn);
+        assert_eq!(level(TylError::internal("bug")), LogLevel::Error);
+        assert_eq!(level(TylError::cancelled("shutdown")), LogLevel::Info);
+        assert_eq!(level(TylError::network("reset")), LogLevel::Warn);
+    }
+
+    #[test]
+    fn test_custom_errors_should_derive_severity_from_category() {
+        let rejected =
+            TylError::business_logic("rejected", ErrorCategory::validation().into_classifier());
+        let flaky = TylError::business_logic("flaky", ErrorCategory::transient().into_classifier());
+
+        assert_eq!(rejected.severity(), TylSeverity::Warning);
+        assert_eq!(flaky.severity(), TylSeverity::Error);
+        assert_eq!(TylSeverity::Warning.log_level(), LogLevel::Warn);
+    }
 }
@@ -0,0 +1,217 @@
+//! Size limits for error messages and metadata.
+//!
+//! Wrapped errors sometimes embed whole request or response bodies in their
+//! message, which then ends up in every log line and serialized context. The
+//! helpers in this module cut such values down to the limits configured in
+//! [`ErrorSettings`] and append [`TRUNCATION_MARKER`] so readers can tell the
+//! value was shortened.
+
+use crate::meta_key::MetaKey;
+use crate::settings::ErrorSettings;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Default maximum message length in bytes (`TYL_ERROR_MAX_MESSAGE_LEN`).
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 16 * 1024;
+
+/// Default maximum serialized size of a metadata value in bytes
+/// (`TYL_ERROR_MAX_METADATA_VALUE_LEN`).
+pub const DEFAULT_MAX_METADATA_VALUE_LEN: usize = 4 * 1024;
+
+/// Default maximum encoded size of the `baggage` header in bytes
+/// (`TYL_ERROR_MAX_BAGGAGE_LEN`).
+pub const DEFAULT_MAX_BAGGAGE_LEN: usize = 2 * 1024;
+
+/// Text inserted where a value was cut, followed by the number of bytes dropped.
+pub const TRUNCATION_MARKER: &str = "…[truncated";
+
+/// Cut `text` to at most `max_len` bytes (on a char boundary), appending the marker.
+///
+/// A limit of zero disables truncation.
+fn truncate(text: &str, max_len: usize) -> Cow<'_, str> {
+    if max_len == 0 || text.len() <= max_len {
+        return Cow::Borrowed(text);
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!(
+        "{}{TRUNCATION_MARKER} {} bytes]",
+        &text[..end],
+        text.len() - end
+    ))
+}
+
+/// Apply the global message limit to an owned message.
+pub(crate) fn limit_message(message: String) -> String {
+    match truncate(&message, ErrorSettings::global().max_message_len) {
+        Cow::Borrowed(_) => message,
+        Cow::Owned(truncated) => truncated,
+    }
+}
+
+/// Apply the global metadata value limit.
+///
+/// Oversized strings are truncated in place; other oversized values are
+/// replaced by their truncated JSON text.
+pub(crate) fn limit_value(value: Value) -> Value {
+    limit_value_to(value, ErrorSettings::global().max_metadata_value_len)
+}
+
+fn limit_value_to(value: Value, max_len: usize) -> Value {
+    if max_len == 0 {
+        return value;
+    }
+    match value {
+        Value::String(text) => match truncate(&text, max_len) {
+            Cow::Borrowed(_) => Value::String(text),
+            Cow::Owned(truncated) => Value::String(truncated),
+        },
+        Value::Array(_) | Value::Object(_) => {
+            let json = value.to_string();
+            match truncate(&json, max_len) {
+                Cow::Borrowed(_) => value,
+                Cow::Owned(truncated) => Value::String(truncated),
+            }
+        }
+        scalar => scalar,
+    }
+}
+
+/// Check whether a string was shortened by these limits.
+pub fn is_truncated(text: &str) -> bool {
+    text.contains(TRUNCATION_MARKER)
+}
+
+/// Displays a text with the global message limit applied.
+pub(crate) struct Limited<'a>(pub(crate) &'a str);
+
+impl fmt::Display for Limited<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&truncate(self.0, ErrorSettings::global().max_message_len))
+    }
+}
+
+/// Deserialize a message with the global limit applied.
+pub(crate) fn deserialize_message<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    String::deserialize(deserializer).map(limit_message)
+}
+
+/// Deserialize metadata with the global value limit applied.
+pub(crate) fn deserialize_metadata<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<MetaKey, Value>, D::Error> {
+    let metadata = HashMap::<MetaKey, Value>::deserialize(deserializer)?;
+    Ok(limit_metadata(metadata))
+}
+
+/// Apply the global metadata value limit to every entry.
+pub(crate) fn limit_metadata<K>(metadata: HashMap<K, Value>) -> HashMap<K, Value>
+where
+    K: Eq + std::hash::Hash,
+{
+    let max_len = ErrorSettings::global().max_metadata_value_len;
+    metadata
+        .into_iter()
+        .map(|(key, value)| (key, limit_value_to(value, max_len)))
+        .collect()
+}
+
+/// Serialize a message with the global limit applied.
+pub(crate) fn serialize_message<S: Serializer>(
+    message: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    truncate(message, ErrorSettings::global().max_message_len).serialize(serializer)
+}
+
+/// Serialize metadata with the global value limit applied.
+pub(crate) fn serialize_metadata<S: Serializer>(
+    metadata: &HashMap<MetaKey, Value>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let max_len = ErrorSettings::global().max_metadata_value_len;
+    serializer.collect_map(
+        metadata
+            .iter()
+            .map(|(key, value)| (key, limit_value_to(value.clone(), max_len))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorContext, TylError};
+
+    #[test]
+    fn test_truncate_should_respect_char_boundaries_and_mark_output() {
+        // Given: a message with multi-byte characters over the limit
+        let message = "é".repeat(10);
+
+        // When: truncating to an odd byte count
+        let truncated = truncate(&message, 5);
+
+        // Then: the cut should land on a char boundary and report the dropped bytes
+        assert_eq!(truncated, "éé…[truncated 16 bytes]");
+        assert!(is_truncated(&truncated));
+        assert_eq!(truncate(&message, 0), message);
+        assert_eq!(truncate("short", 5), "short");
+    }
+
+    #[test]
+    fn test_oversized_structured_values_should_become_truncated_json() {
+        let value = serde_json::json!({"body": "x".repeat(64)});
+        let limited = limit_value_to(value, 16);
+        assert_eq!(limited, "{\"body\":\"xxxxxxx…[truncated 59 bytes]");
+        assert_eq!(
+            limit_value_to(serde_json::json!(12345), 2),
+            serde_json::json!(12345)
+        );
+    }
+
+    #[test]
+    fn test_limits_should_apply_wherever_errors_and_contexts_are_built() {
+        // Given: limits below the defaults, above what other tests build
+        let _settings = ErrorSettings::override_scope(ErrorSettings {
+            max_message_len: 12 * 1024,
+            max_metadata_value_len: 4000,
+            ..ErrorSettings::default()
+        });
+        let long = "x".repeat(64 * 1024);
+
+        // When: building errors through constructors, literals and deserialization
+        let constructed = TylError::network(long.as_str());
+        let literal = TylError::Internal {
+            message: long.clone(),
+            extras: Default::default(),
+        };
+        let json = serde_json::json!({ "Database": { "message": long } });
+        let deserialized: TylError = serde_json::from_value(json).unwrap();
+        let invariant = TylError::invariant("balance", long.as_str(), 0);
+
+        // Then: messages and metadata should all be cut
+        assert!(is_truncated(&constructed.to_string()));
+        assert!(is_truncated(&literal.to_string()));
+        assert!(is_truncated(&deserialized.to_string()));
+        let context = invariant.to_context("ledger.check".to_string());
+        assert!(is_truncated(
+            context.get_metadata("expected").unwrap().as_str().unwrap()
+        ));
+
+        // And: deserialized contexts should be limited as well
+        let mut json = serde_json::to_value(&context).unwrap();
+        json["message"] = long.clone().into();
+        json["metadata"]["expected"] = long.clone().into();
+        let decoded: ErrorContext = serde_json::from_value(json).unwrap();
+        assert!(is_truncated(&decoded.message));
+        assert!(is_truncated(
+            decoded.get_metadata("expected").unwrap().as_str().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+//! Rate limiting of repeated error log lines.
+//!
+//! This module provides [`LogRateLimiter`], consulted by the built-in stderr
+//! logging before every line. Errors are grouped by
+//! [fingerprint](crate::TylError::fingerprint): the first few occurrences are
+//! logged, then at most one line per interval carrying the number of
+//! occurrences suppressed since the previous line, so an error storm cannot
+//! flood the logs while aggregate counts are still recorded. Occurrences
+//! suppressed after the last line of a fingerprint are reported by
+//! [`LogRateLimiter::take_summaries`] once the interval has passed.
+
+use crate::clock::Instant;
+use crate::settings::ErrorSettings;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Maximum number of fingerprints tracked by a [`LogRateLimiter`].
+///
+/// When full, the least recently seen fingerprint is forgotten; its pending
+/// suppressed occurrences are reported by [`LogRateLimiter::take_summaries`].
+pub const MAX_TRACKED_FINGERPRINTS: usize = 4096;
+
+/// Outcome of [`LogRateLimiter::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogDecision {
+    /// Log the line, mentioning the occurrences suppressed since the last one.
+    Emit {
+        /// Occurrences suppressed since the previous line.
+        suppressed: u64,
+    },
+    /// Drop the line.
+    Suppress,
+}
+
+/// Occurrence counts of one fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogCount {
+    /// Error fingerprint.
+    pub fingerprint: String,
+    /// Occurrences seen, logged or not.
+    pub total: u64,
+    /// Occurrences suppressed and not yet reported in a log line.
+    pub pending: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    total: u64,
+    pending: u64,
+    last_emitted: Instant,
+    /// Position of the fingerprint in [`Entries::recency`].
+    last_seen: u64,
+}
+
+/// Fingerprints tracked by a [`LogRateLimiter`], in least-recently-seen order.
+#[derive(Debug, Default)]
+struct Entries {
+    by_fingerprint: HashMap<String, Entry>,
+    /// Fingerprints by the tick they were last seen at, oldest first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    /// When the pending occurrences of fingerprints are due as summaries.
+    due: BinaryHeap<Reverse<(Instant, String)>>,
+    /// Pending occurrences of forgotten fingerprints, not yet reported.
+    evicted: Vec<LogCount>,
+}
+
+/// Per-fingerprint limit on error log lines.
+///
+/// The first `burst` occurrences of a fingerprint are logged, then at most
+/// one per `interval`. A burst of `0` disables the limit.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{LogDecision, LogRateLimiter};
+///
+/// let limiter = LogRateLimiter::new(2, Duration::from_secs(60));
+/// assert_eq!(limiter.check("a1b2"), LogDecision::Emit { suppressed: 0 });
+/// assert_eq!(limiter.check("a1b2"), LogDecision::Emit { suppressed: 0 });
+/// assert_eq!(limiter.check("a1b2"), LogDecision::Suppress);
+/// assert_eq!(limiter.counts()[0].total, 3);
+/// ```
+#[derive(Debug)]
+pub struct LogRateLimiter {
+    burst: u64,
+    interval: Duration,
+    entries: Mutex<Entries>,
+}
+
+impl LogRateLimiter {
+    /// Create a limiter logging `burst` occurrences, then one per `interval`.
+    pub fn new(burst: u64, interval: Duration) -> Self {
+        Self {
+            burst,
+            interval,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Create a limiter that logs every occurrence.
+    pub fn unlimited() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    /// Get the process-wide limiter used by [`TylError::log`](crate::TylError::log).
+    ///
+    /// Configured with `TYL_ERROR_LOG_BURST` and `TYL_ERROR_LOG_INTERVAL_SECS`.
+    pub fn global() -> Arc<LogRateLimiter> {
+        static GLOBAL: OnceLock<Arc<LogRateLimiter>> = OnceLock::new();
+        Arc::clone(GLOBAL.get_or_init(|| {
+            let settings = ErrorSettings::global();
+            Arc::new(LogRateLimiter::new(
+                settings.log_burst,
+                settings.log_interval,
+            ))
+        }))
+    }
+
+    /// Record an occurrence of `fingerprint` and decide whether to log it.
+    pub fn check(&self, fingerprint: &str) -> LogDecision {
+        self.check_at(fingerprint, Instant::now())
+    }
+
+    /// Get the occurrence counts of every tracked fingerprint, most frequent first.
+    pub fn counts(&self) -> Vec<LogCount> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut counts: Vec<LogCount> = entries
+            .by_fingerprint
+            .iter()
+            .map(|(fingerprint, entry)| LogCount {
+                fingerprint: fingerprint.clone(),
+                total: entry.total,
+                pending: entry.pending,
+            })
+            .collect();
+        counts.sort_by(|left, right| {
+            right
+                .total
+                .cmp(&left.total)
+                .then_with(|| left.fingerprint.cmp(&right.fingerprint))
+        });
+        counts
+    }
+
+    /// Take the occurrences suppressed and not yet reported, resetting them.
+    ///
+    /// Called on [`shutdown`](crate::shutdown) so the last suppressed
+    /// occurrences still reach the logs.
+    pub fn take_pending(&self) -> Vec<LogCount> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.due.clear();
+        let mut pending = std::mem::take(&mut entries.evicted);
+        pending.extend(
+            entries
+                .by_fingerprint
+                .iter_mut()
+                .filter(|(_, entry)| entry.pending > 0)
+                .map(|(fingerprint, entry)| LogCount {
+                    fingerprint: fingerprint.clone(),
+                    total: entry.total,
+                    pending: std::mem::take(&mut entry.pending),
+                }),
+        );
+        pending.sort_by(|left, right| left.fingerprint.cmp(&right.fingerprint));
+        pending
+    }
+
+    /// Take the occurrences suppressed at least one interval ago and not yet
+    /// reported, resetting them.
+    ///
+    /// Without this, occurrences suppressed after the last line of a
+    /// fingerprint would only be reported when it occurs again. Also returns
+    /// the pending occurrences of fingerprints forgotten to stay within
+    /// [`MAX_TRACKED_FINGERPRINTS`].
+    pub fn take_summaries(&self) -> Vec<LogCount> {
+        self.take_summaries_at(Instant::now())
+    }
+
+    fn take_summaries_at(&self, now: Instant) -> Vec<LogCount> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Entries {
+            by_fingerprint,
+            due,
+            evicted,
+            ..
+        } = &mut *entries;
+        let mut summaries = std::mem::take(evicted);
+        while let Some(Reverse((deadline, _))) = due.peek() {
+            if *deadline > now {
+                break;
+            }
+            let Some(Reverse((_, fingerprint))) = due.pop() else {
+                break;
+            };
+            let Some(entry) = by_fingerprint.get_mut(&fingerprint) else {
+                continue;
+            };
+            if entry.pending == 0 {
+                continue;
+            }
+            let deadline = entry.last_emitted + self.interval;
+            if deadline > now {
+                // Logged again since; its new occurrences are due later.
+                due.push(Reverse((deadline, fingerprint)));
+                continue;
+            }
+            summaries.push(LogCount {
+                fingerprint,
+                total: entry.total,
+                pending: std::mem::take(&mut entry.pending),
+            });
+            entry.last_emitted = now;
+        }
+        summaries
+    }
+
+    fn check_at(&self, fingerprint: &str, now: Instant) -> LogDecision {
+        if self.burst == 0 {
+            return LogDecision::Emit { suppressed: 0 };
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entries = &mut *entries;
+        entries.tick += 1;
+        let tick = entries.tick;
+        match entries.by_fingerprint.get_mut(fingerprint) {
+            Some(entry) => {
+                entries.recency.remove(&entry.last_seen);
+                entry.last_seen = tick;
+            }
+            None => {
+                if entries.by_fingerprint.len() >= MAX_TRACKED_FINGERPRINTS {
+                    entries.evict_oldest();
+                }
+                entries.by_fingerprint.insert(
+                    fingerprint.to_string(),
+                    Entry {
+                        total: 0,
+                        pending: 0,
+                        last_emitted: now,
+                        last_seen: tick,
+                    },
+                );
+            }
+        }
+        entries.recency.insert(tick, fingerprint.to_string());
+
+        let Some(entry) = entries.by_fingerprint.get_mut(fingerprint) else {
+            return LogDecision::Emit { suppressed: 0 };
+        };
+        entry.total += 1;
+        if entry.total <= self.burst
+            || now.saturating_duration_since(entry.last_emitted) >= self.interval
+        {
+            let suppressed = std::mem::take(&mut entry.pending);
+            entry.last_emitted = now;
+            LogDecision::Emit { suppressed }
+        } else {
+            entry.pending += 1;
+            if entry.pending == 1 {
+                let deadline = entry.last_emitted + self.interval;
+                entries
+                    .due
+                    .push(Reverse((deadline, fingerprint.to_string())));
+            }
+            LogDecision::Suppress
+        }
+    }
+}
+
+impl Entries {
+    /// Forget the least recently seen fingerprint, keeping its pending
+    /// occurrences for the next summaries.
+    fn evict_oldest(&mut self) {
+        let Some((_, fingerprint)) = self.recency.pop_first() else {
+            return;
+        };
+        if let Some(entry) = self.by_fingerprint.remove(&fingerprint) {
+            if entry.pending > 0 {
+                self.evicted.push(LogCount {
+                    fingerprint,
+                    total: entry.total,
+                    pending: entry.pending,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_should_log_burst_then_one_summary_per_interval() {
+        // Given: a limiter logging three lines, then one per minute
+        let limiter = LogRateLimiter::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+
+        // When: one fingerprint occurs ten times within a minute
+        let decisions: Vec<LogDecision> = (0..10)
+            .map(|second| limiter.check_at("storm", start + Duration::from_secs(second)))
+            .collect();
+
+        // Then: only the burst should be logged
+        let emitted = decisions
+            .iter()
+            .filter(|decision| **decision != LogDecision::Suppress)
+            .count();
+        assert_eq!(emitted, 3);
+
+        // And: the next line after the interval should report the suppressed ones
+        assert_eq!(
+            limiter.check_at("storm", start + Duration::from_secs(62)),
+            LogDecision::Emit { suppressed: 7 }
+        );
+        assert_eq!(
+            limiter.check_at("storm", start + Duration::from_secs(63)),
+            LogDecision::Suppress
+        );
+        assert_eq!(
+            limiter.check_at("other", start + Duration::from_secs(63)),
+            LogDecision::Emit { suppressed: 0 }
+        );
+        let counts = limiter.counts();
+        assert_eq!((counts[0].total, counts[0].pending), (12, 1));
+        let pending = limiter.take_pending();
+        assert_eq!((pending.len(), pending[0].pending), (1, 1));
+        assert!(limiter.take_pending().is_empty());
+        assert_eq!(
+            LogRateLimiter::unlimited().check("storm"),
+            LogDecision::Emit { suppressed: 0 }
+        );
+    }
+
+    #[test]
+    fn test_limiter_should_summarize_occurrences_of_quiet_fingerprints() {
+        // Given: a fingerprint suppressed twice, then not seen again
+        let limiter = LogRateLimiter::new(1, Duration::from_secs(60));
+        let start = Instant::now();
+        for second in 0..3 {
+            limiter.check_at("storm", start + Duration::from_secs(second));
+        }
+
+        // When: taking summaries before and after the interval
+        let early = limiter.take_summaries_at(start + Duration::from_secs(30));
+        let due = limiter.take_summaries_at(start + Duration::from_secs(61));
+
+        // Then: the suppressed occurrences should be reported once, when due
+        assert!(early.is_empty());
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].fingerprint.as_str(), due[0].pending), ("storm", 2));
+        assert!(limiter
+            .take_summaries_at(start + Duration::from_secs(200))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_limiter_should_forget_the_least_recently_seen_fingerprint() {
+        // Given: a full limiter whose first fingerprint has pending occurrences
+        let limiter = LogRateLimiter::new(1, Duration::from_secs(60));
+        let start = Instant::now();
+        limiter.check_at("first", start);
+        limiter.check_at("first", start);
+        for index in 1..MAX_TRACKED_FINGERPRINTS {
+            limiter.check_at(&format!("fp-{index}"), start);
+        }
+        limiter.check_at("fp-1", start);
+
+        // When: a new fingerprint occurs
+        limiter.check_at("new", start);
+
+        // Then: the least recently seen one should be forgotten and summarized
+        let counts = limiter.counts();
+        assert_eq!(counts.len(), MAX_TRACKED_FINGERPRINTS);
+        assert!(counts.iter().all(|count| count.fingerprint != "first"));
+        assert!(counts.iter().any(|count| count.fingerprint == "fp-1"));
+        let summaries = limiter.take_summaries_at(start);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(
+            (summaries[0].fingerprint.as_str(), summaries[0].pending),
+            ("first", 1)
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Best-effort parsing of error payloads from services outside TYL.
+//!
+//! Services not using this crate answer with ad-hoc shapes such as
+//! `{"error": "not_found", "message": "..."}`, `{"code": 404, "msg": "..."}`
+//! or `{"error": {"type": "InvalidArgument", "field": "email"}}`. This module
+//! provides [`TylError::from_loose_json`], which recognizes the common field
+//! names and error kinds of such payloads and maps them to the matching
+//! variant, and [`LooseJsonAliases`] to teach it the names of a specific
+//! service.
+
+use crate::error::{CodeFields, TylError};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Maximum number of nested `error` objects descended into.
+pub const LOOSE_JSON_MAX_DEPTH: usize = 8;
+
+/// Maximum number of bytes of a payload kept as the message of an error
+/// that has none.
+pub const LOOSE_JSON_MAX_PAYLOAD_LEN: usize = 1024;
+
+/// Role of a payload field read by [`TylError::from_loose_json`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LooseField {
+    /// Error kind or code (`"not_found"`, `"InvalidArgument"`, `404`).
+    Kind,
+    /// Human-readable message.
+    Message,
+    /// Name of the invalid field of validation errors.
+    Field,
+    /// Type of the missing or protected resource.
+    Resource,
+    /// Identifier of the missing resource.
+    Id,
+    /// HTTP status code, used when the kind is not recognized.
+    Status,
+}
+
+impl LooseField {
+    /// Field names recognized by default, in lookup order.
+    fn default_names(&self) -> &'static [&'static str] {
+        match self {
+            LooseField::Kind => &["error", "code", "kind", "type", "error_code", "errorCode"],
+            LooseField::Message => &[
+                "message",
+                "msg",
+                "detail",
+                "error_description",
+                "description",
+                "reason",
+            ],
+            LooseField::Field => &["field", "param", "parameter", "path"],
+            LooseField::Resource => &["resource", "entity", "resource_type"],
+            LooseField::Id => &["id", "resource_id", "key"],
+            LooseField::Status => &["status", "status_code", "statusCode", "http_status"],
+        }
+    }
+}
+
+/// Error kinds recognized by default (normalized, see [`normalize_kind`]) and their codes.
+const DEFAULT_KINDS: &[(&str, &str)] = &[
+    ("not_found", "not_found"),
+    ("notfound", "not_found"),
+    ("missing", "not_found"),
+    ("no_such_resource", "not_found"),
+    ("validation", "validation"),
+    ("invalid", "validation"),
+    ("invalid_argument", "validation"),
+    ("invalid_request", "validation"),
+    ("bad_request", "validation"),
+    ("unprocessable_entity", "validation"),
+    ("conflict", "conflict"),
+    ("already_exists", "conflict"),
+    ("duplicate", "conflict"),
+    ("unauthenticated", "unauthenticated"),
+    ("unauthorized", "unauthenticated"),
+    ("invalid_token", "unauthenticated"),
+    ("permission_denied", "permission_denied"),
+    ("forbidden", "permission_denied"),
+    ("access_denied", "permission_denied"),
+    ("timeout", "timeout"),
+    ("timed_out", "timeout"),
+    ("deadline_exceeded", "timeout"),
+    ("network", "network"),
+    ("unavailable", "network"),
+    ("service_unavailable", "network"),
+    ("bad_gateway", "network"),
+    ("connection_refused", "network"),
+    ("database", "database"),
+    ("db", "database"),
+    ("cancelled", "cancelled"),
+    ("canceled", "cancelled"),
+    ("not_implemented", "not_implemented"),
+    ("unimplemented", "not_implemented"),
+    ("configuration", "configuration"),
+    ("internal", "internal"),
+    ("server", "internal"),
+    ("unknown", "internal"),
+];
+
+/// Field names and error kinds recognized by [`TylError::from_loose_json_with`].
+///
+/// The defaults cover common conventions; aliases added for a service are
+/// looked up before them.
+///
+/// # Example
+/// ```rust
+/// use serde_json::json;
+/// use tyl_errors::{LooseField, LooseJsonAliases, TylError};
+///
+/// let aliases = LooseJsonAliases::new()
+///     .with_field_alias(LooseField::Kind, "errorType")
+///     .with_field_alias(LooseField::Message, "humanMessage")
+///     .with_kind_alias("ENTITY_MISSING", "not_found");
+///
+/// let payload = json!({
+///     "errorType": "ENTITY_MISSING",
+///     "humanMessage": "no such order",
+///     "entity": "order",
+///     "id": "42",
+/// });
+/// let error = TylError::from_loose_json_with(&payload, &aliases);
+/// assert_eq!(error.to_string(), "Not found: order with id 42");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LooseJsonAliases {
+    fields: HashMap<LooseField, Vec<String>>,
+    kinds: HashMap<String, &'static str>,
+}
+
+impl LooseJsonAliases {
+    /// Create aliases recognizing only the default names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recognize `name` as a field holding `role`.
+    pub fn with_field_alias<N: Into<String>>(mut self, role: LooseField, name: N) -> Self {
+        self.fields.entry(role).or_default().push(name.into());
+        self
+    }
+
+    /// Map the error kind `kind` to the built-in error `code` (e.g. `"not_found"`).
+    ///
+    /// Kinds are matched case-insensitively, ignoring separators and a
+    /// trailing `Error`/`Exception`. Unknown codes map to internal errors.
+    pub fn with_kind_alias<K: AsRef<str>>(mut self, kind: K, code: &str) -> Self {
+        let code = DEFAULT_KINDS
+            .iter()
+            .map(|(_, code)| *code)
+            .find(|known| *known == code)
+            .unwrap_or("internal");
+        self.kinds.insert(normalize_kind(kind.as_ref()), code);
+        self
+    }
+
+    /// Get the value of the first field holding `role` with a usable value:
+    /// a string or number, or an object for the kind.
+    fn lookup<'a>(
+        &self,
+        object: &'a serde_json::Map<String, Value>,
+        role: LooseField,
+    ) -> Option<&'a Value> {
+        let custom = self
+            .fields
+            .get(&role)
+            .into_iter()
+            .flatten()
+            .map(String::as_str);
+        custom
+            .chain(role.default_names().iter().copied())
+            .find_map(|name| {
+                object.get(name).filter(|value| match value {
+                    Value::String(_) | Value::Number(_) => true,
+                    Value::Object(_) => role == LooseField::Kind,
+                    _ => false,
+                })
+            })
+    }
+
+    /// Get the text of the first present field holding `role`.
+    fn text(&self, object: &serde_json::Map<String, Value>, role: LooseField) -> Option<String> {
+        match self.lookup(object, role)? {
+            Value::String(text) => Some(text.clone()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Map a kind to a built-in code.
+    fn code_of(&self, kind: &str) -> Option<&'static str> {
+        let kind = normalize_kind(kind);
+        if let Some(code) = self.kinds.get(&kind) {
+            return Some(code);
+        }
+        DEFAULT_KINDS
+            .iter()
+            .find(|(alias, _)| *alias == kind)
+            .map(|(_, code)| *code)
+    }
+
+    /// Parse a payload, descending into a kind field holding an object up
+    /// to [`LOOSE_JSON_MAX_DEPTH`] times.
+    #[track_caller]
+    fn parse(&self, value: &Value, depth: usize) -> TylError {
+        let object = match value {
+            Value::Object(object) => object,
+            Value::String(message) => return TylError::internal(message.as_str()),
+            other => return TylError::internal(payload_message(other)),
+        };
+        let mut code = None;
+        let mut status = self.lookup(object, LooseField::Status).and_then(status_of);
+        match self.lookup(object, LooseField::Kind) {
+            Some(nested @ Value::Object(_)) if depth < LOOSE_JSON_MAX_DEPTH => {
+                return self.parse(nested, depth + 1)
+            }
+            Some(Value::String(kind)) => code = self.code_of(kind),
+            Some(number @ Value::Number(_)) => status = status.or_else(|| status_of(number)),
+            _ => {}
+        }
+        let code = code
+            .or_else(|| status.and_then(status_code))
+            .unwrap_or("internal");
+        self.build(code, object, value)
+    }
+
+    /// Build the error of `code` from the payload's fields.
+    #[track_caller]
+    fn build(
+        &self,
+        code: &str,
+        object: &serde_json::Map<String, Value>,
+        value: &Value,
+    ) -> TylError {
+        let text = |role| self.text(object, role);
+        let message = text(LooseField::Message).unwrap_or_else(|| payload_message(value));
+        let field = text(LooseField::Field);
+        let resource = text(LooseField::Resource);
+        let id = text(LooseField::Id);
+        let fields = CodeFields {
+            message: &message,
+            field: field.as_deref().unwrap_or("request"),
+            resource: resource.as_deref().unwrap_or("resource"),
+            id: id.as_deref().unwrap_or("unknown"),
+            subject: "",
+            action: "access",
+        };
+        TylError::from_code(code, fields).unwrap_or_else(|| TylError::internal(message.as_str()))
+    }
+}
+
+/// Normalize an error kind: `NotFoundError`, `not-found` and `NOT_FOUND` all become `not_found`.
+fn normalize_kind(kind: &str) -> String {
+    let mut normalized = String::with_capacity(kind.len() + 4);
+    let mut previous_lower = false;
+    for c in kind.trim().chars() {
+        if c.is_uppercase() && previous_lower {
+            normalized.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        match c {
+            '-' | ' ' | '.' => normalized.push('_'),
+            _ => normalized.extend(c.to_lowercase()),
+        }
+    }
+    for suffix in ["_error", "_exception"] {
+        if let Some(stripped) = normalized.strip_suffix(suffix) {
+            normalized.truncate(stripped.len());
+        }
+    }
+    normalized
+}
+
+/// Serialize a payload kept as a message, cut after
+/// [`LOOSE_JSON_MAX_PAYLOAD_LEN`] bytes.
+fn payload_message(value: &Value) -> String {
+    struct Bounded(Vec<u8>);
+
+    impl Write for Bounded {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            let room = LOOSE_JSON_MAX_PAYLOAD_LEN - self.0.len();
+            if room == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            let taken = bytes.len().min(room);
+            self.0.extend_from_slice(&bytes[..taken]);
+            Ok(taken)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut bounded = Bounded(Vec::new());
+    let complete = serde_json::to_writer(&mut bounded, value).is_ok();
+    let mut message = String::from_utf8_lossy(&bounded.0).into_owned();
+    if !complete {
+        // A multi-byte character cut in half decodes as a replacement character.
+        message.truncate(message.trim_end_matches('\u{FFFD}').len());
+        message.push('…');
+    }
+    message
+}
+
+/// Read an HTTP status from a number or numeric string.
+fn status_of(value: &Value) -> Option<u16> {
+    match value {
+        Value::Number(number) => number.as_u64()?.try_into().ok(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Map an HTTP status to a built-in code.
+fn status_code(status: u16) -> Option<&'static str> {
+    Some(match status {
+        400 | 422 => "validation",
+        401 => "unauthenticated",
+        403 => "permission_denied",
+        404 | 410 => "not_found",
+        409 => "conflict",
+        408 | 504 => "timeout",
+        501 => "not_implemented",
+        502 | 503 => "network",
+        500..=599 => "internal",
+        _ => return None,
+    })
+}
+
+impl TylError {
+    /// Map a loosely structured error payload to the matching error.
+    ///
+    /// Recognizes common field names (`error`, `code`, `message`, `msg`,
+    /// `field`, `status`, ...) and kinds (`not_found`, `InvalidArgument`,
+    /// `FORBIDDEN`, ...); an `error` field holding an object is read instead
+    /// of the payload, up to [`LOOSE_JSON_MAX_DEPTH`] levels deep. Fields
+    /// holding a value of the wrong type, such as `"error": true`, are
+    /// skipped. When no kind is recognized, the HTTP status decides;
+    /// otherwise the payload becomes an internal error with its message, or
+    /// the payload itself, cut after [`LOOSE_JSON_MAX_PAYLOAD_LEN`] bytes, as
+    /// message. Not-found and permission errors keep their resource and ID
+    /// fields, as these variants carry no message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_json::json;
+    /// use tyl_errors::TylError;
+    ///
+    /// let error = TylError::from_loose_json(&json!({"error": "not_found", "resource": "user", "id": 7}));
+    /// assert_eq!(error.to_string(), "Not found: user with id 7");
+    ///
+    /// let error = TylError::from_loose_json(&json!({
+    ///     "error": {"type": "InvalidArgumentError", "field": "email", "msg": "is malformed"}
+    /// }));
+    /// assert_eq!(error.to_string(), "Validation error: email: is malformed");
+    ///
+    /// let error = TylError::from_loose_json(&json!({"code": 503, "message": "try later"}));
+    /// assert!(error.is_retriable());
+    /// ```
+    #[track_caller]
+    pub fn from_loose_json(value: &Value) -> TylError {
+        Self::from_loose_json_with(value, &LooseJsonAliases::default())
+    }
+
+    /// Map a loosely structured error payload, recognizing `aliases` as well
+    /// as the default names. See [`TylError::from_loose_json`].
+    #[track_caller]
+    pub fn from_loose_json_with(value: &Value, aliases: &LooseJsonAliases) -> TylError {
+        aliases.parse(value, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_common_shapes_should_map_to_variants() {
+        // Given: payloads in the shapes of several foreign services
+        let cases = [
+            (
+                json!({"error": "not_found", "message": "gone"}),
+                "not_found",
+            ),
+            (
+                json!({"error": "FORBIDDEN", "resource": "invoice"}),
+                "permission_denied",
+            ),
+            (
+                json!({"code": "already-exists", "msg": "taken"}),
+                "conflict",
+            ),
+            (json!({"kind": "DeadlineExceeded"}), "timeout"),
+            (
+                json!({"type": "AuthenticationError", "status": 401}),
+                "unauthenticated",
+            ),
+            (json!({"error": "Teapot", "status": "409"}), "conflict"),
+            (json!({"statusCode": 502}), "network"),
+            (
+                json!({"error": {"code": "UNIMPLEMENTED", "message": "soon"}}),
+                "not_implemented",
+            ),
+            (json!({"oops": true}), "internal"),
+            (json!("plain text"), "internal"),
+            (json!({"error": true, "code": "NOT_FOUND"}), "not_found"),
+            (json!({"message": ["a"], "error": "conflict"}), "conflict"),
+        ];
+
+        // When/Then: each payload should map to the expected code
+        for (payload, code) in cases {
+            assert_eq!(
+                TylError::from_loose_json(&payload).code(),
+                code,
+                "{payload}"
+            );
+        }
+
+        // And: fields should be carried over, or the payload kept as message
+        let error = TylError::from_loose_json(
+            &json!({"error": "bad_request", "param": "age", "detail": "negative"}),
+        );
+        assert_eq!(error.to_string(), "Validation error: age: negative");
+        let error = TylError::from_loose_json(&json!({"oops": true}));
+        assert_eq!(error.to_string(), r#"Internal error: {"oops":true}"#);
+    }
+
+    #[test]
+    fn test_hostile_payloads_should_be_bounded() {
+        // Given: a deeply nested payload and a huge one without a message
+        let mut nested = json!({"error": "not_found"});
+        for _ in 0..1000 {
+            nested = json!({ "error": nested });
+        }
+        let huge = json!({ "data": "é".repeat(10_000) });
+
+        // When: parsing them
+        let nested = TylError::from_loose_json(&nested);
+        let huge = TylError::from_loose_json(&huge);
+
+        // Then: the nesting should stop at the depth limit
+        assert_eq!(nested.code(), "internal");
+        assert!(nested.to_string().len() <= LOOSE_JSON_MAX_PAYLOAD_LEN + 64);
+
+        // And: the payload kept as message should be cut
+        let message = huge.to_string();
+        assert!(message.len() <= LOOSE_JSON_MAX_PAYLOAD_LEN + 64);
+        assert!(message.ends_with("é…"));
+    }
+
+    #[test]
+    fn test_aliases_should_take_precedence_over_defaults() {
+        // Given: a service naming its fields and kinds differently
+        let aliases = LooseJsonAliases::new()
+            .with_field_alias(LooseField::Kind, "failure")
+            .with_kind_alias("QuotaHit", "network")
+            .with_kind_alias("Oddity", "no_such_code");
+
+        // When/Then: the aliases should be recognized
+        let error = TylError::from_loose_json_with(
+            &json!({"failure": "quota-hit", "error": "x"}),
+            &aliases,
+        );
+        assert_eq!(error.code(), "network");
+        let error = TylError::from_loose_json_with(&json!({"failure": "Oddity"}), &aliases);
+        assert_eq!(error.code(), "internal");
+        assert_eq!(normalize_kind("NotFoundException"), "not_found");
+    }
+}
@@ -0,0 +1,306 @@
+//! Interned metadata keys.
+//!
+//! This module provides [`MetaKey`], the key type of [`ErrorContext`](crate::ErrorContext)
+//! metadata. Literal keys are stored as `&'static str` without allocating,
+//! and keys built at runtime are interned so that repeated keys such as
+//! `"endpoint"` or `"user_id"` share a single allocation.
+//!
+//! The intern table is a fixed-size, insert-only open-addressing table of
+//! write-once slots: looking a key up takes no lock, so interning does not
+//! serialize threads enriching errors concurrently.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Maximum number of distinct runtime keys kept in the intern table.
+///
+/// Keys beyond this limit are still accepted but allocated individually, so
+/// unbounded key sets (e.g. keys embedding identifiers) cannot grow the table
+/// without limit.
+pub const MAX_INTERNED_KEYS: usize = 4096;
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+/// Metadata key that is cheap to create and clone.
+///
+/// Compares, hashes and borrows as `str`, so maps keyed by `MetaKey` can be
+/// queried with plain string slices.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorCategory, ErrorContext, MetaKey};
+///
+/// const ENDPOINT: MetaKey = MetaKey::from_static("endpoint");
+///
+/// let context = ErrorContext::new("api::users::get".to_string(), ErrorCategory::network(), "Timeout".to_string())
+///     .with_metadata(ENDPOINT, serde_json::json!("/api/users"))
+///     .with_metadata(format!("{}_ms", "timeout"), serde_json::json!(5000));
+///
+/// assert_eq!(context.metadata["endpoint"], "/api/users");
+/// assert!(context.has_metadata("timeout_ms"));
+/// assert_eq!(MetaKey::from("user_id".to_string()), "user_id");
+/// ```
+#[derive(Clone)]
+pub struct MetaKey(Repr);
+
+impl MetaKey {
+    /// Create a key from a literal without allocating.
+    pub const fn from_static(key: &'static str) -> Self {
+        Self(Repr::Static(key))
+    }
+
+    /// Create a key from a runtime string, reusing an interned copy if one exists.
+    pub fn new(key: &str) -> Self {
+        Self(Repr::Shared(InternTable::global().intern(key)))
+    }
+
+    /// Get the key as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Static(key) => key,
+            Repr::Shared(key) => key,
+        }
+    }
+}
+
+/// Intern table holding up to [`MAX_INTERNED_KEYS`] keys in twice as many
+/// slots, so probe sequences stay short.
+struct InternTable {
+    hasher: RandomState,
+    slots: Box<[OnceLock<Arc<str>>]>,
+    len: AtomicUsize,
+}
+
+impl InternTable {
+    fn global() -> &'static InternTable {
+        static TABLE: OnceLock<InternTable> = OnceLock::new();
+        TABLE.get_or_init(|| InternTable {
+            hasher: RandomState::new(),
+            slots: (0..MAX_INTERNED_KEYS * 2)
+                .map(|_| OnceLock::new())
+                .collect(),
+            len: AtomicUsize::new(0),
+        })
+    }
+
+    /// Get the interned copy of `key`, interning it if there is room.
+    fn intern(&self, key: &str) -> Arc<str> {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        let start = hasher.finish() as usize;
+        for probe in 0..self.slots.len() {
+            let slot = &self.slots[(start + probe) % self.slots.len()];
+            let interned = match slot.get() {
+                Some(interned) => interned,
+                None => {
+                    if self.len.fetch_add(1, Ordering::Relaxed) >= MAX_INTERNED_KEYS {
+                        self.len.fetch_sub(1, Ordering::Relaxed);
+                        break;
+                    }
+                    let mut inserted = false;
+                    let interned = slot.get_or_init(|| {
+                        inserted = true;
+                        Arc::from(key)
+                    });
+                    if !inserted {
+                        // Another thread filled the slot first.
+                        self.len.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    interned
+                }
+            };
+            if &**interned == key {
+                return Arc::clone(interned);
+            }
+        }
+        Arc::from(key)
+    }
+}
+
+impl Deref for MetaKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for MetaKey {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for MetaKey {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for MetaKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for MetaKey {}
+
+impl PartialEq<str> for MetaKey {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for MetaKey {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for MetaKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MetaKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for MetaKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must hash like `str` to honour the `Borrow<str>` contract.
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for MetaKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for MetaKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&'static str> for MetaKey {
+    fn from(key: &'static str) -> Self {
+        Self::from_static(key)
+    }
+}
+
+impl From<String> for MetaKey {
+    fn from(key: String) -> Self {
+        Self::new(&key)
+    }
+}
+
+impl From<&String> for MetaKey {
+    fn from(key: &String) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<MetaKey> for String {
+    fn from(key: MetaKey) -> Self {
+        key.as_str().to_string()
+    }
+}
+
+impl Serialize for MetaKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MetaKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Self::new(&key))
+    }
+}
+
+/// Schema of a plain string, matching the serialized form.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for MetaKey {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "MetaKey".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        String::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_runtime_keys_should_share_one_allocation() {
+        // Given: the same key built twice at runtime
+        let first = MetaKey::from(String::from("meta_key_test_endpoint"));
+        let second = MetaKey::new("meta_key_test_endpoint");
+
+        // When/Then: both should point at the same interned string
+        assert!(std::ptr::eq(first.as_str(), second.as_str()));
+        assert_eq!(first, MetaKey::from_static("meta_key_test_endpoint"));
+    }
+
+    #[test]
+    fn test_interning_should_agree_across_threads() {
+        // Given: threads interning the same runtime keys concurrently
+        let keys: Vec<Vec<MetaKey>> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    (0..64)
+                        .map(|index| MetaKey::new(&format!("meta_key_test_thread_{index}")))
+                        .collect()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        // When/Then: every thread should get the same allocation per key
+        for thread_keys in &keys[1..] {
+            for (key, first) in thread_keys.iter().zip(&keys[0]) {
+                assert!(std::ptr::eq(key.as_str(), first.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_maps_should_be_queryable_by_str() {
+        let mut map = HashMap::new();
+        map.insert(MetaKey::from("user_id"), 1);
+        map.insert(MetaKey::new("tenant"), 2);
+        assert_eq!(map["user_id"], 1);
+        assert_eq!(map.get("tenant"), Some(&2));
+        assert_eq!(
+            serde_json::to_string(&map.keys().min()).unwrap(),
+            "\"tenant\""
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! OpenAPI components for error responses.
+//!
+//! This module provides [`OpenApiErrors`], a generator of OpenAPI
+//! `components.schemas` and `components.responses` entries describing the
+//! problem details bodies produced by [`TylError::to_problem_details`], with
+//! the error codes each status can carry. Services merge the output into
+//! their specs instead of describing TYL errors by hand.
+//!
+//! Requires the `schemars` feature: the problem details schema is derived
+//! from [`ProblemDetails`](crate::ProblemDetails) and its extension members.
+
+use crate::error::TylError;
+use crate::problem::{status_title, ProblemDetails, PROBLEM_JSON_CONTENT_TYPE};
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::Schema;
+use serde_json::{json, Map, Value};
+
+/// Statuses described by [`OpenApiErrors::new`].
+pub const DEFAULT_OPENAPI_STATUSES: [u16; 6] = [400, 404, 409, 422, 429, 500];
+
+/// Name of the problem details schema in `components.schemas`.
+pub const OPENAPI_PROBLEM_SCHEMA: &str = "TylProblemDetails";
+
+/// Name of the error code enum schema in `components.schemas`.
+pub const OPENAPI_CODE_SCHEMA: &str = "TylErrorCode";
+
+/// Generator of OpenAPI components for error responses.
+///
+/// Knows the [qualified code](TylError::qualified_code) and status of every
+/// built-in error; codes of custom errors are added with
+/// [`with_code`](Self::with_code). Responses are named after
+/// the status reason phrase (`BadRequest`, `TooManyRequests`, ...) and
+/// reference the [`OPENAPI_PROBLEM_SCHEMA`] schema, narrowing `status` and
+/// `code` to the values possible for that status.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::OpenApiErrors;
+///
+/// let components = OpenApiErrors::new()
+///     .with_code("payment_declined", 422)
+///     .to_components();
+///
+/// let codes = &components["schemas"]["TylErrorCode"]["enum"];
+/// assert!(codes.as_array().unwrap().contains(&"payment_declined".into()));
+/// let not_found = &components["responses"]["NotFound"];
+/// assert_eq!(not_found["description"], "Not Found");
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenApiErrors {
+    statuses: Vec<u16>,
+    codes: Vec<(String, u16)>,
+}
+
+impl OpenApiErrors {
+    /// Create a generator for the [default statuses](DEFAULT_OPENAPI_STATUSES)
+    /// and the built-in error codes.
+    pub fn new() -> Self {
+        let mut codes: Vec<(String, u16)> = Vec::new();
+        for error in TylError::builtin_samples() {
+            let entry = (error.qualified_code(), error.http_status());
+            if !codes.contains(&entry) {
+                codes.push(entry);
+            }
+        }
+        Self {
+            statuses: DEFAULT_OPENAPI_STATUSES.to_vec(),
+            codes,
+        }
+    }
+
+    /// Describe another status using builder pattern.
+    pub fn with_status(mut self, status: u16) -> Self {
+        if !self.statuses.contains(&status) {
+            self.statuses.push(status);
+        }
+        self
+    }
+
+    /// Add a service-specific error code returned with `status` using builder pattern.
+    ///
+    /// The status is described as well.
+    pub fn with_code<C: Into<String>>(mut self, code: C, status: u16) -> Self {
+        let entry = (code.into(), status);
+        if !self.codes.contains(&entry) {
+            self.codes.push(entry);
+        }
+        self.with_status(status)
+    }
+
+    /// Get the described statuses.
+    pub fn statuses(&self) -> &[u16] {
+        &self.statuses
+    }
+
+    /// Get the codes returned with a status, in registration order.
+    pub fn codes_for(&self, status: u16) -> Vec<&str> {
+        self.codes
+            .iter()
+            .filter(|(_, code_status)| *code_status == status)
+            .map(|(code, _)| code.as_str())
+            .collect()
+    }
+
+    /// Get every known code, without duplicates.
+    pub fn codes(&self) -> Vec<&str> {
+        let mut codes: Vec<&str> = Vec::new();
+        for (code, _) in &self.codes {
+            if !codes.contains(&code.as_str()) {
+                codes.push(code);
+            }
+        }
+        codes
+    }
+
+    /// Build the `components` object, with `schemas` and `responses` members.
+    pub fn to_components(&self) -> Value {
+        let mut schemas = Map::new();
+        schemas.insert(
+            OPENAPI_CODE_SCHEMA.to_string(),
+            json!({
+                "type": "string",
+                "description": "Stable machine-readable error code.",
+                "enum": self.codes(),
+            }),
+        );
+        schemas.insert(OPENAPI_PROBLEM_SCHEMA.to_string(), problem_schema());
+
+        let mut responses = Map::new();
+        for &status in &self.statuses {
+            responses.insert(response_name(status), self.response(status));
+        }
+
+        json!({ "schemas": schemas, "responses": responses })
+    }
+
+    /// Build the response object of one status.
+    fn response(&self, status: u16) -> Value {
+        let mut properties = Map::new();
+        properties.insert("status".to_string(), json!({ "enum": [status] }));
+        let codes = self.codes_for(status);
+        if !codes.is_empty() {
+            properties.insert("code".to_string(), json!({ "enum": codes }));
+        }
+        json!({
+            "description": status_title(status),
+            "content": {
+                PROBLEM_JSON_CONTENT_TYPE: {
+                    "schema": {
+                        "allOf": [
+                            { "$ref": format!("#/components/schemas/{OPENAPI_PROBLEM_SCHEMA}") },
+                            { "type": "object", "properties": properties },
+                        ],
+                    },
+                },
+            },
+        })
+    }
+}
+
+impl Default for OpenApiErrors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Response name for a status: its reason phrase without spaces.
+fn response_name(status: u16) -> String {
+    match status_title(status) {
+        "Error" => format!("Error{status}"),
+        title => title.split([' ', '-']).collect(),
+    }
+}
+
+/// Problem details produced by [`TylError::to_problem_details`], with the
+/// extension members it sets; only used to derive [`problem_schema`].
+#[allow(dead_code)]
+#[derive(schemars::JsonSchema)]
+#[schemars(
+    rename = "TylProblemDetails",
+    description = "RFC 9457 problem details for a TYL error."
+)]
+struct ProblemSchema {
+    #[serde(flatten)]
+    problem: ProblemDetails,
+    /// Stable machine-readable error code.
+    #[schemars(schema_with = "code_schema")]
+    code: String,
+    /// Message of client errors, without the prefix of `detail`.
+    tyl_message: Option<String>,
+    /// Error category name.
+    tyl_category: Option<String>,
+    /// Whether the request may be retried.
+    tyl_retriable: Option<bool>,
+    /// Suggested delay before retrying, in milliseconds.
+    tyl_retry_after_ms: Option<u64>,
+    /// ID of the reported error occurrence.
+    error_id: Option<uuid::Uuid>,
+}
+
+/// Schema of the `code` member: a reference to the code enum.
+fn code_schema(_: &mut SchemaGenerator) -> Schema {
+    schemars::json_schema!({ "$ref": format!("#/components/schemas/{OPENAPI_CODE_SCHEMA}") })
+}
+
+/// Schema of the problem details produced by [`TylError::to_problem_details`],
+/// derived from [`ProblemDetails`].
+fn problem_schema() -> Value {
+    let schema = SchemaSettings::openapi3()
+        .with(|settings| settings.meta_schema = None)
+        .into_generator()
+        .into_root_schema_for::<ProblemSchema>();
+    schema.to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::{
+        PROBLEM_CODE, PROBLEM_ERROR_ID, PROBLEM_TYL_CATEGORY, PROBLEM_TYL_MESSAGE,
+        PROBLEM_TYL_RETRIABLE, PROBLEM_TYL_RETRY_AFTER_MS,
+    };
+
+    #[test]
+    fn test_components_should_describe_codes_per_status() {
+        // Given: the default generator with a service-specific code
+        let errors = OpenApiErrors::new().with_code("payment_declined", 402);
+
+        // When: building the components
+        let components = errors.to_components();
+
+        // Then: each status should list the codes it can carry
+        assert_eq!(errors.codes_for(400), ["validation", "custom"]);
+        assert_eq!(errors.codes_for(429), ["custom"]);
+        assert!(errors.codes_for(500).contains(&"configuration_missing_key"));
+        assert_eq!(errors.statuses(), [400, 404, 409, 422, 429, 500, 402]);
+
+        let responses = components["responses"].as_object().unwrap();
+        assert_eq!(
+            responses.keys().collect::<Vec<_>>(),
+            [
+                "BadRequest",
+                "Conflict",
+                "Error402",
+                "InternalServerError",
+                "NotFound",
+                "TooManyRequests",
+                "UnprocessableContent"
+            ]
+        );
+        let schema = &responses["NotFound"]["content"][PROBLEM_JSON_CONTENT_TYPE]["schema"];
+        assert_eq!(
+            schema["allOf"][0]["$ref"],
+            "#/components/schemas/TylProblemDetails"
+        );
+        assert_eq!(
+            schema["allOf"][1]["properties"]["code"]["enum"],
+            json!(["not_found"])
+        );
+        assert_eq!(
+            schema["allOf"][1]["properties"]["status"]["enum"],
+            json!([404])
+        );
+
+        // And: the code enum should cover every code once
+        let codes = components["schemas"][OPENAPI_CODE_SCHEMA]["enum"]
+            .as_array()
+            .unwrap();
+        assert_eq!(codes.iter().filter(|code| *code == "custom").count(), 1);
+        assert!(codes.contains(&json!("payment_declined")));
+    }
+
+    #[test]
+    fn test_problem_schema_should_accept_generated_problems() {
+        // Given: a problem details body
+        let problem =
+            serde_json::to_value(TylError::not_found("user", "42").to_problem_details()).unwrap();
+
+        // When: deriving the schema
+        let schema = problem_schema();
+
+        // Then: the problem should have every required member of the schema
+        for member in schema["required"].as_array().unwrap() {
+            assert!(problem.get(member.as_str().unwrap()).is_some(), "{member}");
+        }
+
+        // And: every extension member should be described
+        for member in [
+            PROBLEM_CODE,
+            PROBLEM_TYL_MESSAGE,
+            PROBLEM_TYL_CATEGORY,
+            PROBLEM_TYL_RETRIABLE,
+            PROBLEM_TYL_RETRY_AFTER_MS,
+            PROBLEM_ERROR_ID,
+        ] {
+            assert!(schema["properties"].get(member).is_some(), "{member}");
+        }
+        assert_eq!(
+            schema["properties"][PROBLEM_CODE]["allOf"][0]["$ref"],
+            "#/components/schemas/TylErrorCode"
+        );
+        assert_eq!(schema["additionalProperties"], true);
+    }
+}
@@ -0,0 +1,401 @@
+//! Structured operation names.
+//!
+//! This module provides [`OperationName`], a validated `service::module::action`
+//! identifier for the operation recorded in an [`ErrorContext`]. Declaring
+//! operations as constants keeps labels consistent across services, so
+//! dashboards grouping errors by operation do not split on spelling variants.
+//!
+//! [`OperationResultExt::with_operation`] attaches the operation to a failed
+//! result inline, recording it on the error; [`TylError::operation_context`]
+//! builds the error's context from it.
+
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use crate::limits::limit_value;
+use crate::meta_key::MetaKey;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::panic::Location;
+use std::str::FromStr;
+
+/// Separator between the segments of an operation name.
+pub const OPERATION_SEPARATOR: &str = "::";
+
+/// Metadata key of the `file:line` where the innermost operation of an error
+/// was attached with [`OperationResultExt::with_operation`].
+pub const OPERATION_LOCATION_KEY: &str = "operation_location";
+
+/// Metadata key of the enclosing operations attached to an error, innermost
+/// first, each with its `operation` and `location`.
+pub const OPERATION_PATH_KEY: &str = "operation_path";
+
+/// Validated operation name of the form `service::module::action`.
+///
+/// A name has at least two segments (`service::action`); everything between
+/// the first and the last segment is the module path. Segments are lowercase
+/// `snake_case`: ASCII lowercase letters, digits and underscores, starting
+/// with a letter.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{OperationName, TylError};
+///
+/// const CREATE_INVOICE: OperationName = OperationName::from_static("billing::invoices::create");
+///
+/// assert_eq!(CREATE_INVOICE.service(), "billing");
+/// assert_eq!(CREATE_INVOICE.module(), Some("invoices"));
+/// assert_eq!(CREATE_INVOICE.action(), "create");
+///
+/// let context = TylError::database("Deadlock").report(CREATE_INVOICE);
+/// assert_eq!(context.operation_name(), Some(CREATE_INVOICE));
+/// assert!("Billing.createInvoice".parse::<OperationName>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct OperationName(Cow<'static, str>);
+
+impl OperationName {
+    /// Validate and create an operation name.
+    ///
+    /// # Errors
+    /// A validation error on the `operation` field if the name is malformed.
+    pub fn new<S: Into<String>>(name: S) -> TylResult<Self> {
+        let name = name.into();
+        match check(name.as_bytes()) {
+            Ok(()) => Ok(Self(Cow::Owned(name))),
+            Err(problem) => Err(TylError::validation(
+                "operation",
+                format!("Invalid operation name: {problem}"),
+            )
+            .with_input(name)),
+        }
+    }
+
+    /// Create an operation name from a literal, for use in constants.
+    ///
+    /// # Panics
+    /// If the name is malformed; in a `const` item this fails compilation.
+    pub const fn from_static(name: &'static str) -> Self {
+        if check(name.as_bytes()).is_err() {
+            panic!("invalid operation name");
+        }
+        Self(Cow::Borrowed(name))
+    }
+
+    /// Build an operation name from its segments.
+    pub fn from_parts(service: &str, module: Option<&str>, action: &str) -> TylResult<Self> {
+        match module {
+            Some(module) => Self::new(format!("{service}::{module}::{action}")),
+            None => Self::new(format!("{service}::{action}")),
+        }
+    }
+
+    /// Get the full name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the service segment.
+    pub fn service(&self) -> &str {
+        self.0
+            .split_once(OPERATION_SEPARATOR)
+            .map_or(self.as_str(), |(service, _)| service)
+    }
+
+    /// Get the module path between the service and the action, if any.
+    pub fn module(&self) -> Option<&str> {
+        let (_, rest) = self.0.split_once(OPERATION_SEPARATOR)?;
+        rest.rsplit_once(OPERATION_SEPARATOR)
+            .map(|(module, _)| module)
+    }
+
+    /// Get the action segment.
+    pub fn action(&self) -> &str {
+        self.0
+            .rsplit_once(OPERATION_SEPARATOR)
+            .map_or(self.as_str(), |(_, action)| action)
+    }
+}
+
+/// Check the structure of an operation name.
+const fn check(name: &[u8]) -> Result<(), &'static str> {
+    let mut segments = 1;
+    let mut segment_start = true;
+    let mut i = 0;
+    while i < name.len() {
+        let byte = name[i];
+        if byte == b':' {
+            if i + 1 >= name.len() || name[i + 1] != b':' {
+                return Err("segments must be separated by `::`");
+            }
+            if segment_start {
+                return Err("segments must not be empty");
+            }
+            segments += 1;
+            segment_start = true;
+            i += 2;
+            continue;
+        }
+        if segment_start && !byte.is_ascii_lowercase() {
+            return Err("segments must start with a lowercase letter");
+        }
+        if !(byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'_') {
+            return Err("segments may only contain lowercase letters, digits and `_`");
+        }
+        segment_start = false;
+        i += 1;
+    }
+    if segment_start {
+        return Err("segments must not be empty");
+    }
+    if segments < 2 {
+        return Err("expected at least `service::action`");
+    }
+    Ok(())
+}
+
+impl fmt::Display for OperationName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for OperationName {
+    type Err = TylError;
+
+    fn from_str(name: &str) -> TylResult<Self> {
+        Self::new(name)
+    }
+}
+
+impl TryFrom<String> for OperationName {
+    type Error = TylError;
+
+    fn try_from(name: String) -> TylResult<Self> {
+        Self::new(name)
+    }
+}
+
+impl From<OperationName> for String {
+    fn from(name: OperationName) -> Self {
+        name.0.into_owned()
+    }
+}
+
+impl AsRef<str> for OperationName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ErrorContext {
+    /// Get the operation of this context as a structured name, if it is one.
+    pub fn operation_name(&self) -> Option<OperationName> {
+        OperationName::new(self.operation.as_str()).ok()
+    }
+}
+
+/// Maximum number of operations recorded on an error by
+/// [`OperationResultExt::with_operation`]; enclosing operations beyond it are
+/// dropped.
+pub const MAX_OPERATION_FRAMES: usize = 32;
+
+/// An operation attached to an error, and where it was attached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationFrame {
+    operation: String,
+    location: String,
+}
+
+impl OperationFrame {
+    /// Get the name of the operation.
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Get the `file:line` where the operation was attached.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+impl TylError {
+    /// Get the operations attached with [`OperationResultExt::with_operation`],
+    /// innermost first.
+    pub fn operations(&self) -> &[OperationFrame] {
+        self.extras().map_or(&[], |extras| extras.operations())
+    }
+
+    /// Build the context of the innermost operation attached to this error.
+    ///
+    /// The context is built with [`TylError::to_context`], so the call site
+    /// of the innermost operation is under [`OPERATION_LOCATION_KEY`] and the
+    /// enclosing operations under [`OPERATION_PATH_KEY`]. `None` if no
+    /// operation was attached.
+    pub fn operation_context(&self) -> Option<ErrorContext> {
+        let innermost = self.operations().first()?;
+        Some(self.to_context(innermost.operation.clone()))
+    }
+
+    /// Get the metadata entries of the attached operations.
+    pub(crate) fn operation_metadata(&self) -> Vec<(MetaKey, serde_json::Value)> {
+        let Some((innermost, enclosing)) = self.operations().split_first() else {
+            return Vec::new();
+        };
+        let mut entries = vec![(
+            MetaKey::from_static(OPERATION_LOCATION_KEY),
+            innermost.location.as_str().into(),
+        )];
+        if !enclosing.is_empty() {
+            entries.push((
+                MetaKey::from_static(OPERATION_PATH_KEY),
+                limit_value(serde_json::json!(enclosing)),
+            ));
+        }
+        entries
+    }
+}
+
+/// Extension methods attaching operations to results.
+pub trait OperationResultExt<T> {
+    /// Attach the operation that failed, and where it was attached, to the
+    /// error of this result.
+    ///
+    /// The first call records the innermost operation; later calls record
+    /// the enclosing ones, up to [`MAX_OPERATION_FRAMES`]. The error is
+    /// otherwise unchanged; its context is only built when asked for with
+    /// [`TylError::operation_context`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{OperationResultExt, TylError, TylResult};
+    ///
+    /// fn find_user(id: &str) -> TylResult<String> {
+    ///     Err(TylError::not_found("user", id))
+    /// }
+    ///
+    /// fn get_profile(id: &str) -> TylResult<String> {
+    ///     let user = find_user(id).with_operation("users::repo::find_user")?;
+    ///     Ok(format!("profile of {user}"))
+    /// }
+    ///
+    /// let error = get_profile("42").with_operation("users::api::get_profile").unwrap_err();
+    /// assert_eq!(error.to_string(), "Not found: user with id 42");
+    /// let context = error.operation_context().unwrap();
+    /// assert_eq!(context.operation, "users::repo::find_user");
+    /// assert_eq!(
+    ///     context.metadata["operation_path"][0]["operation"],
+    ///     "users::api::get_profile"
+    /// );
+    /// ```
+    #[track_caller]
+    fn with_operation<S: Into<String>>(self, operation: S) -> TylResult<T>;
+}
+
+impl<T> OperationResultExt<T> for TylResult<T> {
+    #[track_caller]
+    fn with_operation<S: Into<String>>(self, operation: S) -> TylResult<T> {
+        let location = Location::caller();
+        self.map_err(|mut error| {
+            if let Some(extras) = error.extras_mut() {
+                if extras.operations().len() < MAX_OPERATION_FRAMES {
+                    extras.push_operation(OperationFrame {
+                        operation: operation.into(),
+                        location: format!("{}:{}", location.file(), location.line()),
+                    });
+                }
+            }
+            error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_should_reject_malformed_names() {
+        // Given/When/Then: names breaking the convention should be rejected
+        for name in [
+            "billing",
+            "billing.invoices.create",
+            "Billing::create",
+            "billing::::create",
+            "billing::create::",
+            "billing:create",
+            "billing::1st",
+        ] {
+            let error = OperationName::new(name).unwrap_err();
+            assert_eq!(error.metadata()["input"], name, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_segments_should_split_service_module_and_action() {
+        let name = OperationName::from_parts("users", Some("profile::avatar"), "upload").unwrap();
+        assert_eq!(name.service(), "users");
+        assert_eq!(name.module(), Some("profile::avatar"));
+        assert_eq!(name.action(), "upload");
+
+        let short: OperationName = serde_json::from_str("\"users::delete\"").unwrap();
+        assert_eq!(short.module(), None);
+        assert_eq!(serde_json::to_string(&short).unwrap(), "\"users::delete\"");
+        assert!(serde_json::from_str::<OperationName>("\"users\"").is_err());
+    }
+
+    #[test]
+    fn test_with_operation_should_record_operations_and_call_sites() {
+        // Given: a failing lookup wrapped by two enclosing operations
+        let inner_line = line!() + 2;
+        let error = Err::<(), _>(TylError::database("Deadlock"))
+            .with_operation(OperationName::from_static("users::repo::find_user"))
+            .with_operation("users::api::get_profile")
+            .unwrap_err();
+
+        // When: building the context of the innermost operation
+        let context = error.operation_context().unwrap();
+
+        // Then: the innermost operation and every call site should be kept
+        assert_eq!(context.operation, "users::repo::find_user");
+        assert_eq!(context.category.category_name(), "Transient");
+        assert_eq!(
+            context.metadata[OPERATION_LOCATION_KEY],
+            format!("{}:{inner_line}", file!())
+        );
+        let path = context.metadata[OPERATION_PATH_KEY].as_array().unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0]["operation"], "users::api::get_profile");
+        assert_eq!(
+            path[0]["location"],
+            format!("{}:{}", file!(), inner_line + 1)
+        );
+
+        // And: the error should be unchanged, and keep its operations on the wire
+        assert!(matches!(error, TylError::Database { .. }));
+        let restored: TylError =
+            serde_json::from_value(serde_json::to_value(&error).unwrap()).unwrap();
+        assert_eq!(restored.operations(), error.operations());
+        assert!(TylError::internal("Bug").operation_context().is_none());
+        assert!(Ok::<_, TylError>(1).with_operation("users::noop").is_ok());
+    }
+
+    #[test]
+    fn test_operation_frames_should_be_bounded() {
+        // Given: an error passed through more operations than are kept
+        let mut result = Err::<(), _>(TylError::network("Connection reset"));
+        for depth in 0..MAX_OPERATION_FRAMES + 8 {
+            result = result.with_operation(format!("svc::layer_{depth}"));
+        }
+
+        // Then: only the innermost operations should be recorded
+        let error = result.unwrap_err();
+        assert_eq!(error.operations().len(), MAX_OPERATION_FRAMES);
+        assert_eq!(error.operations()[0].operation(), "svc::layer_0");
+        assert!(!error
+            .to_problem_details()
+            .extensions
+            .contains_key(OPERATION_PATH_KEY));
+    }
+}
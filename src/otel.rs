@@ -0,0 +1,197 @@
+//! Export of error contexts as OpenTelemetry log records.
+//!
+//! This module (enabled by the `otel` feature) converts [`ErrorContext`]s into
+//! OTLP `LogRecord`s: the severity comes from [`TylSeverity`], the body is the
+//! error message and the context metadata becomes record attributes. Records
+//! are emitted through any OpenTelemetry [`Logger`]; batching and transport
+//! are left to the SDK logger provider (e.g. a `BatchLogProcessor` with an
+//! OTLP exporter).
+
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylSeverity};
+use crate::events::{self, SubscriptionId};
+use crate::redact::REDACTED;
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, Severity};
+use opentelemetry::Key;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// Event name set on every exported record.
+pub const OTEL_EVENT_NAME: &str = "tyl.error";
+
+/// Writes error contexts to an OpenTelemetry logger.
+///
+/// Sensitive metadata (see [`ErrorContext::is_sensitive`]) is always exported
+/// masked, since log pipelines leave the process.
+///
+/// # Example
+/// ```rust
+/// use opentelemetry::logs::LoggerProvider as _;
+/// use opentelemetry_sdk::logs::{InMemoryLogExporter, SdkLoggerProvider};
+/// use tyl_errors::{OtelLogExporter, TylError};
+///
+/// let exporter = InMemoryLogExporter::default();
+/// let provider = SdkLoggerProvider::builder()
+///     .with_simple_exporter(exporter.clone())
+///     .build();
+/// let otel = OtelLogExporter::new(provider.logger("orders"));
+///
+/// let error = TylError::database("Deadlock");
+/// otel.export_error(&error, &error.to_context("orders::checkout::pay".to_string()));
+///
+/// let logs = exporter.get_emitted_logs().unwrap();
+/// assert_eq!(logs[0].record.severity_text(), Some("ERROR"));
+/// ```
+#[derive(Debug)]
+pub struct OtelLogExporter<L: Logger> {
+    logger: L,
+}
+
+impl<L: Logger> OtelLogExporter<L> {
+    /// Create an exporter writing to the given logger.
+    pub fn new(logger: L) -> Self {
+        Self { logger }
+    }
+
+    /// Emit a log record for an error and its context, using the error's severity.
+    pub fn export_error(&self, error: &TylError, context: &ErrorContext) {
+        let mut record = self.record(context, error.severity());
+        record.add_attribute("tyl.error.code", error.code());
+        self.logger.emit(record);
+    }
+
+    /// Emit a log record for a context with an explicit severity.
+    pub fn export(&self, context: &ErrorContext, severity: TylSeverity) {
+        let record = self.record(context, severity);
+        self.logger.emit(record);
+    }
+
+    fn record(&self, context: &ErrorContext, severity: TylSeverity) -> L::LogRecord {
+        let mut record = self.logger.create_log_record();
+        record.set_event_name(OTEL_EVENT_NAME);
+        record.set_timestamp(SystemTime::from(context.occurred_at));
+        record.set_observed_timestamp(SystemTime::from(crate::clock::now()));
+        record.set_severity_number(otel_severity(severity));
+        record.set_severity_text(severity.as_str());
+        record.set_body(AnyValue::from(context.message.clone()));
+
+        record.add_attribute("tyl.error.id", context.error_id.to_string());
+        record.add_attribute("tyl.error.operation", context.operation.clone());
+        record.add_attribute(
+            "tyl.error.category",
+            context.category.category_name().to_string(),
+        );
+        record.add_attribute("tyl.error.retriable", context.category.is_retriable());
+        record.add_attribute(
+            "tyl.error.attempt_count",
+            i64::try_from(context.attempt_count).unwrap_or(i64::MAX),
+        );
+        if let Some(key) = &context.idempotency_key {
+            record.add_attribute("tyl.error.idempotency_key", key.clone());
+        }
+        if let Some(tenant_id) = context.redacted_tenant_id() {
+            record.add_attribute("tyl.error.tenant_id", tenant_id.to_string());
+        }
+        for (key, value) in &context.metadata {
+            let value = if context.is_sensitive(key) {
+                AnyValue::from(REDACTED)
+            } else {
+                any_value(value)
+            };
+            record.add_attribute(Key::new(key.to_string()), value);
+        }
+        record
+    }
+}
+
+/// Export every error reported with [`TylError::report`] to an OpenTelemetry logger.
+///
+/// Returns the event stream subscription, which can be removed with
+/// [`events::unsubscribe`].
+pub fn install<L>(logger: L) -> SubscriptionId
+where
+    L: Logger + Send + Sync + 'static,
+{
+    let exporter = OtelLogExporter::new(logger);
+    events::subscribe_fn(move |event| exporter.export_error(&event.error, &event.context))
+}
+
+/// Map a TYL severity to the OpenTelemetry severity number.
+fn otel_severity(severity: TylSeverity) -> Severity {
+    match severity {
+        TylSeverity::Debug => Severity::Debug,
+        TylSeverity::Info => Severity::Info,
+        TylSeverity::Warning => Severity::Warn,
+        TylSeverity::Error => Severity::Error,
+        TylSeverity::Critical => Severity::Fatal,
+    }
+}
+
+/// Convert a JSON metadata value to an OpenTelemetry attribute value.
+fn any_value(value: &serde_json::Value) -> AnyValue {
+    match value {
+        serde_json::Value::Null => AnyValue::from(""),
+        serde_json::Value::Bool(flag) => AnyValue::from(*flag),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(int) => AnyValue::from(int),
+            None => AnyValue::from(number.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(text) => AnyValue::from(text.clone()),
+        serde_json::Value::Array(items) => {
+            AnyValue::ListAny(Box::new(items.iter().map(any_value).collect()))
+        }
+        serde_json::Value::Object(entries) => AnyValue::Map(Box::new(
+            entries
+                .iter()
+                .map(|(key, value)| (Key::new(key.clone()), any_value(value)))
+                .collect::<HashMap<_, _>>(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::logs::LoggerProvider as _;
+    use opentelemetry_sdk::logs::{InMemoryLogExporter, SdkLoggerProvider};
+
+    #[test]
+    fn test_export_should_map_severity_body_and_metadata() {
+        // Given: an exporter backed by an in-memory OpenTelemetry pipeline
+        let memory = InMemoryLogExporter::default();
+        let provider = SdkLoggerProvider::builder()
+            .with_simple_exporter(memory.clone())
+            .build();
+        let exporter = OtelLogExporter::new(provider.logger("tyl-errors-test"));
+        let error = TylError::config_missing("DATABASE_URL");
+        let context = error
+            .to_context("app::boot::connect".to_string())
+            .with_metadata("attempt", serde_json::json!(2))
+            .with_sensitive_metadata("password", serde_json::json!("hunter2"));
+
+        // When: exporting the error
+        exporter.export_error(&error, &context);
+
+        // Then: the record should carry severity, body and attributes
+        let logs = memory.get_emitted_logs().unwrap();
+        let record = &logs[0].record;
+        assert_eq!(record.severity_number(), Some(Severity::Fatal));
+        assert_eq!(record.event_name(), Some(OTEL_EVENT_NAME));
+        assert_eq!(
+            record.body(),
+            Some(&AnyValue::from(context.message.clone()))
+        );
+        let attribute = |name: &str| {
+            record
+                .attributes_iter()
+                .find(|(key, _)| key.as_str() == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(attribute("attempt"), Some(AnyValue::from(2i64)));
+        assert_eq!(attribute("password"), Some(AnyValue::from(REDACTED)));
+        assert_eq!(
+            attribute("tyl.error.code"),
+            Some(AnyValue::from("configuration_missing_key"))
+        );
+    }
+}
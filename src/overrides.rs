@@ -0,0 +1,279 @@
+//! Operator-controlled overrides of error classification.
+//!
+//! This module provides [`ClassificationOverrides`], a table of rules that
+//! remap errors to a different builtin category without a deploy (for example
+//! "treat ServiceUnavailable from the billing dependency as permanent"). The
+//! installed table is consulted by [`TylError::category`] before the default
+//! classification; it is loaded lazily from `TYL_ERROR_CLASSIFICATION_OVERRIDES`
+//! or installed explicitly with [`ClassificationOverrides::install`].
+
+use crate::category::{BuiltinCategory, ErrorCategory};
+use crate::error::{TylError, TylResult};
+use crate::snapshot::{Snapshot, SnapshotCache};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Environment variable holding a JSON override table.
+pub const CLASSIFICATION_OVERRIDES_ENV: &str = "TYL_ERROR_CLASSIFICATION_OVERRIDES";
+
+/// One override rule; every condition that is set must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassificationOverride {
+    /// Error code to match (see [`TylError::code`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Default category name to match (e.g. `ServiceUnavailable`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_category: Option<String>,
+    /// Operation to match; a trailing `*` matches any suffix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    /// Dependency (service) the error came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// Builtin category to use instead.
+    pub category: String,
+}
+
+impl ClassificationOverride {
+    fn matches(
+        &self,
+        code: &str,
+        default_category: &str,
+        operation: Option<&str>,
+        service: Option<&str>,
+    ) -> bool {
+        let operation_matches = match (self.operation.as_deref(), operation) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(pattern), Some(operation)) => match pattern.strip_suffix('*') {
+                Some(prefix) => operation.starts_with(prefix),
+                None => pattern == operation,
+            },
+        };
+
+        self.code.as_deref().map_or(true, |c| c == code)
+            && self
+                .from_category
+                .as_deref()
+                .map_or(true, |c| c == default_category)
+            && operation_matches
+            && self.service.as_deref().map_or(true, |s| service == Some(s))
+    }
+}
+
+/// Ordered table of classification overrides; the first matching rule wins.
+///
+/// Rules constrained by operation or service only apply where that
+/// information is known, i.e. through [`TylError::category_in`].
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ClassificationOverrides, TylError};
+///
+/// let overrides = ClassificationOverrides::from_json(r#"{
+///     "rules": [
+///         { "from_category": "Transient", "service": "billing", "category": "Permanent" }
+///     ]
+/// }"#).unwrap();
+///
+/// let error = TylError::database("Deadlock");
+/// let category = overrides.resolve(&error, None, Some("billing")).unwrap();
+/// assert_eq!(category.category_name(), "Permanent");
+/// assert!(overrides.resolve(&error, None, Some("search")).is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassificationOverrides {
+    /// Rules evaluated in order.
+    #[serde(default)]
+    pub rules: Vec<ClassificationOverride>,
+}
+
+impl ClassificationOverrides {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule using builder pattern.
+    pub fn with_rule(mut self, rule: ClassificationOverride) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Check that every rule targets a builtin category.
+    pub fn validate(&self) -> TylResult<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if BuiltinCategory::from_name(&rule.category).is_none() {
+                return Err(TylError::config_key(
+                    format!("rules[{index}].category"),
+                    "a builtin category name",
+                    rule.category.as_str(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load and validate a table from JSON.
+    pub fn from_json(json: &str) -> TylResult<Self> {
+        let overrides: Self = serde_json::from_str(json).map_err(|err| {
+            TylError::configuration(format!("Invalid classification overrides: {err}"))
+        })?;
+        overrides.validate()?;
+        Ok(overrides)
+    }
+
+    /// Load and validate a table from YAML. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> TylResult<Self> {
+        let overrides: Self = serde_yaml::from_str(yaml).map_err(|err| {
+            TylError::configuration(format!("Invalid classification overrides: {err}"))
+        })?;
+        overrides.validate()?;
+        Ok(overrides)
+    }
+
+    /// Load a table from [`CLASSIFICATION_OVERRIDES_ENV`], if set.
+    pub fn from_env() -> TylResult<Option<Self>> {
+        match std::env::var(CLASSIFICATION_OVERRIDES_ENV) {
+            Ok(json) => Self::from_json(&json).map(Some),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Find the overriding category for an error, if a rule matches.
+    pub fn resolve(
+        &self,
+        error: &TylError,
+        operation: Option<&str>,
+        service: Option<&str>,
+    ) -> Option<ErrorCategory> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let default_category = error.default_category();
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.matches(
+                    error.code(),
+                    default_category.category_name(),
+                    operation,
+                    service,
+                )
+            })
+            .and_then(|rule| BuiltinCategory::from_name(&rule.category))
+            .map(ErrorCategory::Builtin)
+    }
+
+    /// Install this table process-wide, replacing the current one.
+    pub fn install(self) {
+        INSTALLED.replace(self);
+    }
+
+    /// Remove all process-wide overrides.
+    pub fn clear() {
+        Self::new().install();
+    }
+
+    /// Get the process-wide table.
+    pub fn current() -> Arc<ClassificationOverrides> {
+        INSTALLED.current()
+    }
+
+    /// Run `f` with the process-wide table, without locking it.
+    pub(crate) fn with_current<R>(f: impl FnOnce(&ClassificationOverrides) -> R) -> R {
+        INSTALLED.with(f)
+    }
+}
+
+thread_local! {
+    static CACHE: SnapshotCache<ClassificationOverrides> = const { SnapshotCache::new(None) };
+}
+
+/// The process-wide table, loaded from the environment on first use.
+static INSTALLED: Snapshot<ClassificationOverrides> = Snapshot::new(
+    || {
+        ClassificationOverrides::from_env()
+            .unwrap_or_else(|error| {
+                error.log();
+                None
+            })
+            .unwrap_or_default()
+    },
+    &CACHE,
+);
+
+impl TylError {
+    /// Get the category of this error for a given operation and dependency.
+    ///
+    /// Like [`TylError::category`], but also applies installed overrides
+    /// constrained by operation or service.
+    pub fn category_in(&self, operation: Option<&str>, service: Option<&str>) -> ErrorCategory {
+        ClassificationOverrides::with_current(|overrides| {
+            overrides.resolve(self, operation, service)
+        })
+        .unwrap_or_else(|| self.default_category())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_installed_overrides_should_change_category() {
+        // Given: overrides for a test-only custom category and operation
+        let flaky = ErrorCategory::custom_fn("OverridesTestFlaky", true, |_| Duration::ZERO);
+        let error = TylError::business_logic("flaky", flaky.into_classifier());
+        let timeout = TylError::timeout("slow");
+        let previous = ClassificationOverrides::current();
+        let mut table = (*previous).clone();
+        table.rules.push(ClassificationOverride {
+            from_category: Some("OverridesTestFlaky".to_string()),
+            category: "Permanent".to_string(),
+            ..ClassificationOverride::default()
+        });
+        table.rules.push(ClassificationOverride {
+            code: Some("timeout".to_string()),
+            operation: Some("overrides.test.*".to_string()),
+            category: "Permanent".to_string(),
+            ..ClassificationOverride::default()
+        });
+        table.install();
+
+        // When/Then: the overrides should apply where they match
+        assert_eq!(error.category().category_name(), "Permanent");
+        assert!(!error.is_retriable());
+        assert!(timeout.category().is_retriable());
+        assert!(!timeout
+            .category_in(Some("overrides.test.fetch"), None)
+            .is_retriable());
+
+        // And: reinstalling the previous table should take effect at once
+        (*previous).clone().install();
+        assert_eq!(error.category().category_name(), "OverridesTestFlaky");
+    }
+
+    #[test]
+    fn test_validate_should_reject_unknown_categories() {
+        let error = ClassificationOverrides::from_json(
+            r#"{"rules": [{"code": "network", "category": "Sometimes"}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "rules[0].category");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_from_yaml_should_load_rules() {
+        let overrides = ClassificationOverrides::from_yaml(
+            "rules:\n  - from_category: ServiceUnavailable\n    service: billing\n    category: Permanent\n",
+        )
+        .unwrap();
+        assert_eq!(overrides.rules[0].service.as_deref(), Some("billing"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Structured capture of panics.
+//!
+//! A panic converted into an error used to keep only its message, flattened
+//! into the error's. This module records on which thread and where a panic
+//! happened, and optionally its backtrace, as [`PanicDetails`] kept in the
+//! [extras](crate::ErrorExtras) of the internal error built by
+//! [`TylError::catch_panic`], the `tower` catch layer and tokio join errors.
+//! The details are serialized with the error and exposed as `panic_*`
+//! entries of [`TylError::metadata`], so panics can be debugged from error
+//! reports alone; they are never included in problem details.
+//!
+//! The location and backtrace of a panic are only known to the panic hook:
+//! they are captured once [`install_panic_hook`] has been called, and only
+//! attached to errors caught on the panicking thread.
+
+use crate::error::{TylError, TylResult};
+use crate::extras::ErrorExtras;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, UnwindSafe};
+use std::sync::Once;
+use std::thread;
+
+/// Maximum number of backtrace lines kept in [`PanicDetails`].
+pub const MAX_BACKTRACE_LINES: usize = 128;
+
+/// Where a panic converted into an error happened.
+///
+/// Exposed as `panic_thread`, `panic_file`, `panic_line`, `panic_column`
+/// and `panic_backtrace` in [`TylError::metadata`]; none of them are
+/// included in problem details.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicDetails {
+    /// Name of the panicking thread, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    /// Source file of the panic, if captured by the hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Line of the panic, if captured by the hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Column of the panic, if captured by the hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// Backtrace lines, captured by the hook when backtraces are enabled
+    /// (`TYL_ERROR_BACKTRACE`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backtrace: Vec<String>,
+}
+
+thread_local! {
+    /// Number of panics seen by the hook on this thread.
+    static PANICS: Cell<u64> = const { Cell::new(0) };
+    /// Last panic seen by the hook on this thread, with its number.
+    static LAST_PANIC: RefCell<Option<(u64, PanicDetails)>> = const { RefCell::new(None) };
+}
+
+/// Install a panic hook capturing the location and backtrace of panics.
+///
+/// The previous hook still runs after the capture, so panics are printed as
+/// before. Installing the hook more than once has no effect.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location();
+            let details = PanicDetails {
+                thread: thread::current().name().map(str::to_string),
+                file: location.map(|location| location.file().to_string()),
+                line: location.map(|location| location.line()),
+                column: location.map(|location| location.column()),
+                backtrace: if TylError::backtrace_enabled() {
+                    backtrace_lines(&Backtrace::force_capture())
+                } else {
+                    Vec::new()
+                },
+            };
+            // The thread-locals are gone when a thread panics while exiting.
+            if let Ok(number) = PANICS.try_with(|panics| {
+                panics.set(panics.get() + 1);
+                panics.get()
+            }) {
+                let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some((number, details)));
+            }
+            previous(info);
+        }));
+    });
+}
+
+impl TylError {
+    /// Run `f`, converting a panic into an internal error.
+    ///
+    /// The error carries the panic message and its [`PanicDetails`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{install_panic_hook, TylError};
+    ///
+    /// install_panic_hook();
+    /// let error = TylError::catch_panic(|| -> u32 { panic!("index out of bounds") }).unwrap_err();
+    ///
+    /// assert_eq!(error.to_string(), "Internal error: Panicked: index out of bounds");
+    /// assert!(error.panic_details().unwrap().line.is_some());
+    /// assert!(error.metadata().contains_key("panic_line"));
+    /// ```
+    #[track_caller]
+    pub fn catch_panic<F, R>(f: F) -> TylResult<R>
+    where
+        F: FnOnce() -> R + UnwindSafe,
+    {
+        // Not `map_err`: closures do not forward the caller's location.
+        match catch_unwind(f) {
+            Ok(value) => Ok(value),
+            Err(panic) => Err(panic.into_error("Panicked")),
+        }
+    }
+
+    /// Convert the payload of a panic caught on this thread, e.g. with
+    /// `std::panic::catch_unwind`, into an internal error.
+    ///
+    /// Only the thread is recorded: use [`TylError::catch_panic`] to also
+    /// record the location and backtrace captured by the hook.
+    #[track_caller]
+    pub fn from_panic(payload: Box<dyn Any + Send>) -> TylError {
+        panic_error("Panicked", payload.as_ref(), Some(current_thread()))
+    }
+
+    /// Get the details of the panic this error was converted from.
+    pub fn panic_details(&self) -> Option<&PanicDetails> {
+        self.extras()?.panic()
+    }
+}
+
+/// A panic caught by [`catch_unwind`], with the details the hook captured.
+pub(crate) struct CaughtPanic {
+    payload: Box<dyn Any + Send>,
+    details: PanicDetails,
+}
+
+impl CaughtPanic {
+    /// Build the internal error of this panic, prefixing its message with `what`.
+    #[track_caller]
+    pub(crate) fn into_error(self, what: &str) -> TylError {
+        panic_error(what, self.payload.as_ref(), Some(self.details))
+    }
+}
+
+/// Run `f`, catching a panic together with the details captured by the hook.
+///
+/// Captures are numbered, so only a panic raised while `f` ran is used;
+/// without one (no hook installed), the details record the thread only.
+pub(crate) fn catch_unwind<F, R>(f: F) -> Result<R, CaughtPanic>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    let before = PANICS.try_with(Cell::get).unwrap_or_default();
+    panic::catch_unwind(f).map_err(|payload| {
+        let captured = LAST_PANIC
+            .try_with(|last| last.borrow_mut().take())
+            .ok()
+            .flatten()
+            .filter(|(number, _)| *number > before)
+            .map(|(_, details)| details);
+        CaughtPanic {
+            payload,
+            details: captured.unwrap_or_else(current_thread),
+        }
+    })
+}
+
+/// Build the internal error of a panic, prefixing its message with `what`.
+#[track_caller]
+pub(crate) fn panic_error(
+    what: &str,
+    payload: &(dyn Any + Send),
+    details: Option<PanicDetails>,
+) -> TylError {
+    let message = payload_message(payload);
+    let mut extras = ErrorExtras::new();
+    if let Some(details) = details {
+        extras.set_panic(details);
+    }
+    TylError::Internal {
+        message: crate::limits::limit_message(format!("{what}: {message}")),
+        extras,
+    }
+}
+
+/// Details of a panic on the current thread, without location or backtrace.
+fn current_thread() -> PanicDetails {
+    PanicDetails {
+        thread: thread::current().name().map(str::to_string),
+        ..PanicDetails::default()
+    }
+}
+
+/// Get the message of a panic payload.
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Split a backtrace into its first [`MAX_BACKTRACE_LINES`] non-empty lines.
+fn backtrace_lines(backtrace: &Backtrace) -> Vec<String> {
+    backtrace
+        .to_string()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(MAX_BACKTRACE_LINES)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ErrorSettings;
+
+    #[test]
+    fn test_caught_panics_should_keep_thread_location_and_backtrace() {
+        // Given: the hook installed
+        install_panic_hook();
+
+        // When: a named thread with backtraces enabled panics inside catch_panic
+        let _settings = ErrorSettings::override_scope(ErrorSettings {
+            backtrace_enabled: true,
+            ..ErrorSettings::default()
+        });
+        let (error, line) = thread::Builder::new()
+            .name("panic-capture-test".to_string())
+            .spawn(|| {
+                let line = line!() + 1;
+                let result = TylError::catch_panic(|| panic!("boom {}", 42));
+                (result.unwrap_err(), line)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        // Then: the panic details should be structured metadata
+        let details = error.panic_details().unwrap();
+        assert_eq!(details.thread.as_deref(), Some("panic-capture-test"));
+        assert!(details
+            .file
+            .as_deref()
+            .unwrap()
+            .ends_with("panic_capture.rs"));
+        assert_eq!(details.line, Some(line));
+        assert!(!details.backtrace.is_empty());
+        let metadata = error.metadata();
+        assert_eq!(metadata["panic_thread"], "panic-capture-test");
+        assert_eq!(metadata["panic_line"], line);
+        assert!(metadata["panic_backtrace"].is_array());
+        assert_eq!(error.code(), "internal");
+        assert_eq!(error.to_string(), "Internal error: Panicked: boom 42");
+
+        // And: serialized error reports should carry them
+        let json = serde_json::to_string(&error).unwrap();
+        let restored: TylError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.panic_details(), Some(details));
+        let problem = error.to_problem_details();
+        assert!(problem
+            .extensions
+            .keys()
+            .all(|key| !key.starts_with("panic_")));
+    }
+
+    #[test]
+    fn test_stale_captures_should_not_be_attached_to_other_panics() {
+        // Given: captures left by panics caught elsewhere on this thread,
+        // one with the same message as the next panic
+        install_panic_hook();
+        let _ = panic::catch_unwind(|| panic!("first"));
+        let _ = panic::catch_unwind(|| panic!("second"));
+
+        // When: converting a payload, and one caught on another thread
+        let converted = TylError::from_panic(Box::new("second"));
+        let joined = panic_error("Task panicked", &"third", None);
+
+        // Then: the other panics' locations should not be used
+        assert_eq!(converted.to_string(), "Internal error: Panicked: second");
+        assert_eq!(converted.panic_details().unwrap().line, None);
+        assert_eq!(joined.panic_details(), None);
+        assert_eq!(TylError::internal("Bug").panic_details(), None);
+        let caught = TylError::catch_panic(|| panic!("fourth")).unwrap_err();
+        assert!(caught.panic_details().unwrap().line.is_some());
+    }
+}
@@ -0,0 +1,719 @@
+//! HTTP status mapping and RFC 9457 problem details.
+//!
+//! This module maps TylError variants to HTTP status codes and converts errors
+//! into `application/problem+json` bodies, so HTTP adapters across TYL services
+//! report errors to clients in the same shape.
+
+use crate::category::{restore_category, BuiltinCategory, ErrorCategory, RetryScope};
+use crate::domain::unqualified_code;
+use crate::error::{is_client_hidden, CodeFields, TylError};
+use crate::group::TylErrorGroup;
+use crate::http_overrides::HttpMappingOverrides;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Media type of serialized [`ProblemDetails`].
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Extension member holding the error code, [qualified](TylError::qualified_code)
+/// with the error's domain.
+pub const PROBLEM_CODE: &str = "code";
+
+/// Extension member holding the message of client errors (4xx) without the
+/// prefix of its `Display` output, to restore the error from.
+pub const PROBLEM_TYL_MESSAGE: &str = "tyl_message";
+
+/// Extension member holding the category name.
+pub const PROBLEM_TYL_CATEGORY: &str = "tyl_category";
+
+/// Extension member holding whether the request may be retried.
+pub const PROBLEM_TYL_RETRIABLE: &str = "tyl_retriable";
+
+/// Extension member holding the suggested delay before a retry, in milliseconds.
+pub const PROBLEM_TYL_RETRY_AFTER_MS: &str = "tyl_retry_after_ms";
+
+/// Extension member holding who may retry the request (see [`RetryScope`]).
+pub const PROBLEM_TYL_RETRY_SCOPE: &str = "tyl_retry_scope";
+
+/// Response header holding who may retry the request, set by the tower integration.
+pub const RETRY_SCOPE_HEADER: &str = "x-tyl-retry-scope";
+
+/// Extension member holding the position of a failed item of a bulk request.
+pub const PROBLEM_TYL_ITEM_INDEX: &str = "tyl_item_index";
+
+/// Extension member holding the identifier of a failed item of a bulk request.
+pub const PROBLEM_TYL_ITEM_ID: &str = "tyl_item_id";
+
+/// HTTP status of responses to partially failed bulk requests.
+pub const MULTI_STATUS: u16 = 207;
+
+/// Extension member holding the ID of the reported error occurrence.
+pub const PROBLEM_ERROR_ID: &str = "error_id";
+
+/// `detail` of problem details for server errors (5xx), whose messages
+/// may expose internals such as panic messages or SQL.
+pub const SERVER_ERROR_DETAIL: &str = "The server failed to process the request";
+
+/// RFC 9457 problem details object.
+///
+/// Extension members are flattened into the top-level JSON object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProblemDetails {
+    /// URI reference identifying the problem type (`urn:tyl:error:<qualified code>`).
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short, human-readable summary of the problem type.
+    pub title: String,
+    /// HTTP status code.
+    pub status: u16,
+    /// Human-readable explanation specific to this occurrence.
+    pub detail: String,
+    /// URI reference identifying this specific occurrence, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Additional extension members.
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProblemDetails {
+    /// Set the occurrence URI using builder pattern.
+    pub fn with_instance<S: Into<String>>(mut self, instance: S) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Add an extension member using builder pattern.
+    pub fn with_extension<K: Into<String>>(mut self, key: K, value: serde_json::Value) -> Self {
+        self.extensions.insert(key.into(), value);
+        self
+    }
+
+    /// Set the `error_id` member using builder pattern.
+    pub fn with_error_id(self, error_id: Uuid) -> Self {
+        self.with_extension(PROBLEM_ERROR_ID, serde_json::json!(error_id))
+    }
+
+    /// Get the position of the failed bulk item from the `tyl_item_index` member.
+    pub fn item_index(&self) -> Option<usize> {
+        self.extensions
+            .get(PROBLEM_TYL_ITEM_INDEX)?
+            .as_u64()?
+            .try_into()
+            .ok()
+    }
+
+    /// Get the identifier of the failed bulk item from the `tyl_item_id` member.
+    pub fn item_id(&self) -> Option<&str> {
+        self.extensions.get(PROBLEM_TYL_ITEM_ID)?.as_str()
+    }
+
+    /// Get the error code from the `code` member, qualified with the
+    /// error's domain, e.g. `storage.database`.
+    pub fn code(&self) -> Option<&str> {
+        self.extensions.get(PROBLEM_CODE)?.as_str()
+    }
+
+    /// Get the ID of the error occurrence from the `error_id` member.
+    pub fn error_id(&self) -> Option<Uuid> {
+        self.extensions
+            .get(PROBLEM_ERROR_ID)?
+            .as_str()?
+            .parse()
+            .ok()
+    }
+
+    /// Check if the request may be retried, from the `tyl_retriable` member.
+    pub fn is_retriable(&self) -> Option<bool> {
+        self.extensions.get(PROBLEM_TYL_RETRIABLE)?.as_bool()
+    }
+
+    /// Get the suggested delay before a retry from the `tyl_retry_after_ms` member.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.extensions
+            .get(PROBLEM_TYL_RETRY_AFTER_MS)?
+            .as_u64()
+            .map(Duration::from_millis)
+    }
+
+    /// Get who may retry the request from the `tyl_retry_scope` member.
+    pub fn retry_scope(&self) -> Option<RetryScope> {
+        RetryScope::parse(self.extensions.get(PROBLEM_TYL_RETRY_SCOPE)?.as_str()?)
+    }
+
+    /// Get the `Retry-After` header value for this problem, if retriable by
+    /// the caller.
+    ///
+    /// Used by the warp, salvo and tower integrations. Problems only
+    /// infrastructure may retry get no header.
+    pub fn retry_after_header(&self) -> Option<String> {
+        if self.retry_scope() == Some(RetryScope::Infrastructure) {
+            return None;
+        }
+        self.retry_after().map(delay_seconds)
+    }
+}
+
+/// Format a delay as `Retry-After` delay-seconds, rounded up.
+fn delay_seconds(delay: Duration) -> String {
+    let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+    seconds.to_string()
+}
+
+impl TylError {
+    /// Get the HTTP status code that best represents this error.
+    ///
+    /// Custom errors are mapped through their category. The installed
+    /// [`HttpMappingOverrides`] take precedence.
+    pub fn http_status(&self) -> u16 {
+        HttpMappingOverrides::with_current(|overrides| overrides.resolve(self))
+            .unwrap_or_else(|| self.default_http_status())
+    }
+
+    /// Get the HTTP status code of this error ignoring mapping overrides.
+    fn default_http_status(&self) -> u16 {
+        match self {
+            TylError::Database { .. } => 503,
+            TylError::Network { .. } => 502,
+            TylError::Validation { .. } => 400,
+            TylError::NotFound { .. } => 404,
+            TylError::Conflict { .. } => 409,
+            TylError::Internal { .. } => 500,
+            TylError::Unauthenticated { .. } => 401,
+            TylError::PermissionDenied { .. } => 403,
+            TylError::Timeout { .. } => 504,
+            TylError::Cancelled { .. } => 499,
+            TylError::InvariantViolated { .. } => 500,
+            TylError::Configuration { .. } => 500,
+            TylError::NotImplemented { .. } => 501,
+            TylError::Unknown { .. } => 500,
+            TylError::Custom { .. } => category_http_status(&self.category()),
+        }
+    }
+
+    /// Get the delay a client should wait before retrying, if retriable.
+    ///
+    /// The category's delay for the next attempt: the first retry, or the
+    /// one after the attempts recorded in the error's
+    /// [retry telemetry](TylError::retry_telemetry).
+    pub fn retry_after(&self) -> Option<Duration> {
+        let category = self.category();
+        if !category.is_retriable_for(self) {
+            return None;
+        }
+        let attempt = self.retry_telemetry().map_or(1, |t| t.attempts);
+        Some(category.retry_delay_for(self, attempt))
+    }
+
+    /// Get the value of the HTTP `Retry-After` header for this error.
+    ///
+    /// Whole seconds (rounded up) of [`TylError::retry_after`]; `None` for
+    /// errors that should not be retried or that only infrastructure may
+    /// retry (see [`TylError::retry_scope`]). Framework integrations set the
+    /// header from this value.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// assert!(TylError::network("Connection reset").retry_after_header().is_some());
+    /// assert_eq!(TylError::validation("email", "Invalid").retry_after_header(), None);
+    /// ```
+    pub fn retry_after_header(&self) -> Option<String> {
+        if self.retry_scope() != RetryScope::Caller {
+            return None;
+        }
+        self.retry_after().map(delay_seconds)
+    }
+
+    /// Convert this error into an RFC 9457 problem details object.
+    ///
+    /// The error code and structured fields (see [`TylError::metadata`]) are
+    /// included as extension members, except sensitive ones such as the
+    /// subject of a denied permission. Server errors (5xx) get the generic
+    /// [`SERVER_ERROR_DETAIL`] instead of their message. The `code`,
+    /// `tyl_category`, `tyl_retriable` and `tyl_retry_scope` members, plus
+    /// `tyl_retry_after_ms` for retriable errors and `tyl_message` for client
+    /// errors, let clients restore the error with
+    /// [`TylError::from_problem_details`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let problem = TylError::not_found("user", "42").to_problem_details();
+    /// assert_eq!(problem.status, 404);
+    /// assert_eq!(problem.problem_type, "urn:tyl:error:not_found");
+    /// assert_eq!(problem.extensions["code"], "not_found");
+    /// assert_eq!(problem.extensions["tyl_category"], "Permanent");
+    /// assert_eq!(problem.is_retriable(), Some(false));
+    /// ```
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let status = self.http_status();
+        let category = self.category();
+        let retriable = category.is_retriable_for(self);
+        let mut extensions = serde_json::Map::new();
+        let code = self.qualified_code_in(&category);
+        extensions.insert(PROBLEM_CODE.to_string(), serde_json::json!(code));
+        extensions.insert(
+            PROBLEM_TYL_CATEGORY.to_string(),
+            serde_json::json!(category.category_name()),
+        );
+        extensions.insert(
+            PROBLEM_TYL_RETRIABLE.to_string(),
+            serde_json::json!(retriable),
+        );
+        extensions.insert(
+            PROBLEM_TYL_RETRY_SCOPE.to_string(),
+            serde_json::json!(self.retry_scope()),
+        );
+        if let Some(delay) = self.retry_after() {
+            extensions.insert(
+                PROBLEM_TYL_RETRY_AFTER_MS.to_string(),
+                serde_json::json!(delay.as_millis() as u64),
+            );
+        }
+        if let Some(message) = self.plain_message().filter(|_| status < 500) {
+            extensions.insert(PROBLEM_TYL_MESSAGE.to_string(), serde_json::json!(message));
+        }
+        extensions.extend(
+            self.metadata()
+                .into_iter()
+                .filter(|(key, _)| !is_client_hidden(key)),
+        );
+
+        ProblemDetails {
+            problem_type: format!("urn:tyl:error:{code}"),
+            title: status_title(status).to_string(),
+            status,
+            detail: self.client_detail(),
+            instance: None,
+            extensions,
+        }
+    }
+
+    /// Get the message of this error safe to send to clients: its `Display`
+    /// output, or [`SERVER_ERROR_DETAIL`] for server errors (5xx).
+    pub(crate) fn client_detail(&self) -> String {
+        if self.http_status() >= 500 {
+            SERVER_ERROR_DETAIL.to_string()
+        } else {
+            self.to_string()
+        }
+    }
+
+    /// Restore an error from problem details received from a TYL service.
+    ///
+    /// Errors carrying a plain message (database, network, timeout, ...) and
+    /// validation, not-found and permission errors are restored as the same
+    /// variant from the structured members: `tyl_message`, or the `detail`
+    /// of server errors, and the variant's fields (`field`, `resource`,
+    /// `id`, ...). Other problems become custom errors with that message and
+    /// the category and retriability of the `tyl_category` and
+    /// `tyl_retriable` members, so retry decisions match the server's.
+    /// Problems only infrastructure may retry become custom errors whose
+    /// category keeps that [`RetryScope`], whatever their code, so the
+    /// client does not retry them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::TylError;
+    ///
+    /// let sent = TylError::conflict("Email already registered");
+    /// let json = serde_json::to_string(&sent.to_problem_details()).unwrap();
+    ///
+    /// let received = TylError::from_problem_details(&serde_json::from_str(&json).unwrap());
+    /// assert_eq!(received.to_string(), sent.to_string());
+    /// assert_eq!(received.code(), "conflict");
+    /// assert!(!received.is_retriable());
+    ///
+    /// // Server errors keep their code and retriability, not their message.
+    /// let sent = TylError::database("Deadlock detected");
+    /// let problem = sent.to_problem_details();
+    /// assert_eq!(problem.code(), Some("storage.database"));
+    /// let received = TylError::from_problem_details(&problem);
+    /// assert_eq!(received.code(), "database");
+    /// assert!(received.is_retriable());
+    /// ```
+    #[track_caller]
+    pub fn from_problem_details(problem: &ProblemDetails) -> TylError {
+        let code = problem.code().map_or("custom", unqualified_code);
+        let member = |name: &str| problem.extensions.get(name)?.as_str();
+        let message = member(PROBLEM_TYL_MESSAGE).unwrap_or(&problem.detail);
+        let category = member(PROBLEM_TYL_CATEGORY).unwrap_or("Unknown");
+        if problem.retry_scope() == Some(RetryScope::Infrastructure) {
+            let category =
+                restore_category(category, true).with_retry_scope(RetryScope::Infrastructure);
+            return TylError::business_logic(message, category.into_classifier());
+        }
+        restore_error(
+            code,
+            message,
+            category,
+            problem.is_retriable().unwrap_or(false),
+            member,
+        )
+    }
+}
+
+/// Body of the response to a bulk request, listing the failed items.
+///
+/// Sent with [`status`](Self::status): `207 Multi-Status` when some items
+/// failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiStatusBody {
+    /// Number of items processed successfully.
+    pub succeeded: usize,
+    /// Number of failed items.
+    pub failed: usize,
+    /// Problem details of each failed item, tagged with its index and ID.
+    pub errors: Vec<ProblemDetails>,
+}
+
+impl MultiStatusBody {
+    /// Get the HTTP status of the response: 200 without failures, otherwise
+    /// [`MULTI_STATUS`].
+    pub fn status(&self) -> u16 {
+        if self.failed == 0 {
+            200
+        } else {
+            MULTI_STATUS
+        }
+    }
+}
+
+impl TylErrorGroup {
+    /// Convert every failed item into problem details.
+    ///
+    /// Each object carries the `tyl_item_index` member and, for items with an
+    /// identifier, `tyl_item_id`.
+    pub fn to_problem_details(&self) -> Vec<ProblemDetails> {
+        self.errors()
+            .iter()
+            .map(|failure| {
+                let problem = failure
+                    .error
+                    .to_problem_details()
+                    .with_extension(PROBLEM_TYL_ITEM_INDEX, serde_json::json!(failure.index));
+                match &failure.id {
+                    Some(id) => problem.with_extension(PROBLEM_TYL_ITEM_ID, serde_json::json!(id)),
+                    None => problem,
+                }
+            })
+            .collect()
+    }
+
+    /// Build the response body of a bulk request of `total_items` items.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{partition_keyed_results, TylError};
+    ///
+    /// let results = vec![
+    ///     ("sku-1", Ok(())),
+    ///     ("sku-2", Err(TylError::validation("quantity", "must be positive"))),
+    ///     ("sku-3", Ok(())),
+    /// ];
+    /// let (_, failures) = partition_keyed_results(results);
+    ///
+    /// let body = failures.to_multi_status(3);
+    /// assert_eq!(body.status(), 207);
+    /// assert_eq!(body.succeeded, 2);
+    /// assert_eq!(body.errors[0].status, 400);
+    /// assert_eq!(body.errors[0].item_index(), Some(1));
+    /// assert_eq!(body.errors[0].item_id(), Some("sku-2"));
+    /// let json = serde_json::to_string(&body).unwrap();
+    /// ```
+    pub fn to_multi_status(&self, total_items: usize) -> MultiStatusBody {
+        MultiStatusBody {
+            succeeded: total_items.saturating_sub(self.len()),
+            failed: self.len(),
+            errors: self.to_problem_details(),
+        }
+    }
+}
+
+/// Restore an error from its code, message and category.
+///
+/// `member` looks up the string fields of the error (`field`, `resource`,
+/// `subject`, ...) sent alongside it.
+#[track_caller]
+pub(crate) fn restore_error<'a>(
+    code: &str,
+    message: &str,
+    category: &str,
+    retriable: bool,
+    member: impl Fn(&str) -> Option<&'a str>,
+) -> TylError {
+    if let Some(error) = restore_variant(code, message, member) {
+        return error;
+    }
+    TylError::business_logic(
+        message,
+        restore_category(category, retriable).into_classifier(),
+    )
+}
+
+/// Restore the variant of a code from the error's message and fields;
+/// `None` if a field the variant requires is missing.
+#[track_caller]
+fn restore_variant<'a>(
+    code: &str,
+    message: &str,
+    member: impl Fn(&str) -> Option<&'a str>,
+) -> Option<TylError> {
+    let required: &[&str] = match code {
+        "validation" => &["field"],
+        "not_found" => &["resource", "id"],
+        _ => &[],
+    };
+    if required.iter().any(|name| member(name).is_none()) {
+        return None;
+    }
+    let member = |name: &str| member(name).unwrap_or_default();
+    let fields = CodeFields {
+        message,
+        field: member("field"),
+        resource: member("resource"),
+        id: member("id"),
+        subject: member("subject"),
+        action: member("action"),
+    };
+    TylError::from_code(code, fields)
+}
+
+/// Map an error category to an HTTP status code.
+///
+/// Custom classifiers reporting a built-in category name are mapped like that
+/// built-in category; other custom categories map to 503 or 500 depending on
+/// their retriability.
+fn category_http_status(category: &ErrorCategory) -> u16 {
+    let builtin = match category {
+        ErrorCategory::Builtin(builtin) => Some(builtin.clone()),
+        ErrorCategory::Custom(custom) => BuiltinCategory::from_name(custom.category_name()),
+    };
+
+    match builtin {
+        Some(BuiltinCategory::Transient | BuiltinCategory::ServiceUnavailable) => 503,
+        Some(BuiltinCategory::ResourceExhaustion) => 429,
+        Some(BuiltinCategory::Network) => 502,
+        Some(BuiltinCategory::Authentication) => 401,
+        Some(BuiltinCategory::Validation) => 400,
+        Some(BuiltinCategory::Permanent) => 422,
+        Some(BuiltinCategory::Internal | BuiltinCategory::Unknown) => 500,
+        None if category.is_retriable() => 503,
+        None => 500,
+    }
+}
+
+/// Get the standard reason phrase for the status codes used by this crate.
+pub(crate) fn status_title(status: u16) -> &'static str {
+    match status {
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        410 => "Gone",
+        422 => "Unprocessable Content",
+        429 => "Too Many Requests",
+        499 => "Client Closed Request",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_should_map_variants() {
+        // Given/When/Then: each variant should map to its HTTP status
+        assert_eq!(TylError::validation("f", "m").http_status(), 400);
+        assert_eq!(TylError::not_found("r", "i").http_status(), 404);
+        assert_eq!(TylError::conflict("dup").http_status(), 409);
+        assert_eq!(TylError::not_implemented("x").http_status(), 501);
+        assert_eq!(TylError::network("x").http_status(), 502);
+        assert_eq!(
+            TylError::business_logic("auth", ErrorCategory::authentication().into_classifier())
+                .http_status(),
+            401
+        );
+    }
+
+    #[test]
+    fn test_problem_details_should_include_tracking_metadata() {
+        // Given: a tracked not-implemented error
+        let error = TylError::not_implemented_tracked("exports", "https://example.com/issues/7")
+            .with_planned_version("2.0");
+
+        // When: converting to problem details
+        let problem = error.to_problem_details();
+        let json = serde_json::to_value(&problem).unwrap();
+
+        // Then: tracking info should be exposed as extension members
+        assert_eq!(json["type"], "urn:tyl:error:not_implemented");
+        assert_eq!(json["status"], 501);
+        assert_eq!(json["title"], "Not Implemented");
+        assert_eq!(json["tracking_issue"], "https://example.com/issues/7");
+        assert_eq!(json["planned_version"], "2.0");
+        assert!(json.get("instance").is_none());
+    }
+
+    #[test]
+    fn test_problem_details_should_roundtrip_through_json() {
+        // Given: problem details with an instance
+        let problem = TylError::conflict("duplicate email")
+            .to_problem_details()
+            .with_instance("/users/42");
+
+        // When: serializing and deserializing
+        let json = serde_json::to_string(&problem).unwrap();
+        let parsed: ProblemDetails = serde_json::from_str(&json).unwrap();
+
+        // Then: it should be unchanged
+        assert_eq!(parsed, problem);
+    }
+
+    #[test]
+    fn test_problem_details_should_restore_errors_on_the_client() {
+        // Given: errors of restorable variants and of a custom category
+        let payment = ErrorCategory::custom_fn("ProblemTestPayment", true, |_| {
+            std::time::Duration::from_millis(250)
+        });
+        let errors = [
+            TylError::validation("email", "Invalid: missing @"),
+            TylError::not_found("user", "42"),
+            TylError::timeout("Upstream took 30s"),
+            TylError::business_logic("Card declined", payment.into_classifier()),
+        ];
+
+        for error in errors {
+            // When: sending it as problem details and restoring it
+            let json = serde_json::to_string(&error.to_problem_details()).unwrap();
+            let problem: ProblemDetails = serde_json::from_str(&json).unwrap();
+            let restored = TylError::from_problem_details(&problem);
+
+            // Then: code, category and retriability should survive, and the
+            // message unless it is a server error
+            if error.http_status() < 500 {
+                assert_eq!(restored.to_string(), error.to_string());
+            } else {
+                assert_eq!(problem.detail, SERVER_ERROR_DETAIL);
+            }
+            assert_eq!(restored.code(), error.code());
+            assert_eq!(
+                restored.category().category_name(),
+                error.category().category_name()
+            );
+            assert_eq!(restored.is_retriable(), error.is_retriable());
+        }
+
+        // And: other variants should keep their category as custom errors
+        let invariant = TylError::invariant("balance", 0, -5).to_problem_details();
+        let restored = TylError::from_problem_details(&invariant);
+        assert_eq!(restored.category().category_name(), "Internal");
+        assert_eq!(
+            restored.to_string(),
+            format!("Custom error: {}", invariant.detail)
+        );
+
+        // And: retry hints and error IDs should be readable from the members
+        let id = uuid::Uuid::new_v4();
+        let problem = TylError::network("reset")
+            .to_problem_details()
+            .with_error_id(id);
+        assert_eq!(problem.error_id(), Some(id));
+        assert_eq!(problem.code(), Some("network"));
+        assert!(problem.retry_after().is_some());
+        assert_eq!(
+            TylError::not_found("u", "1")
+                .to_problem_details()
+                .retry_after(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_retry_after_header_should_round_up_to_whole_seconds() {
+        // Given: a retriable category waiting 1.2 seconds
+        let slow = ErrorCategory::custom_fn("ProblemTestSlow", true, |_| {
+            std::time::Duration::from_millis(1200)
+        });
+        let error = TylError::business_logic("Busy", slow.into_classifier());
+
+        // When/Then: the header should carry the delay rounded up
+        assert_eq!(error.retry_after_header().as_deref(), Some("2"));
+        assert_eq!(
+            error.to_problem_details().retry_after_header().as_deref(),
+            Some("2")
+        );
+        assert_eq!(delay_seconds(Duration::from_secs(3)), "3");
+
+        // And: errors that should not be retried should have no header
+        assert_eq!(TylError::conflict("dup").retry_after_header(), None);
+    }
+
+    #[test]
+    fn test_group_should_become_tagged_problem_details() {
+        // Given: a batch with failures with and without identifiers
+        let mut group = TylErrorGroup::new();
+        group.push(0, TylError::network("Connection reset"));
+        group.push_with_id(2, "order-3", TylError::not_found("order", "3"));
+
+        // When: building the multi-status body
+        let body = group.to_multi_status(5);
+        let json = serde_json::to_value(&body).unwrap();
+
+        // Then: each failure should be tagged with its item
+        assert_eq!(body.status(), MULTI_STATUS);
+        assert_eq!((body.succeeded, body.failed), (3, 2));
+        assert_eq!(json["errors"][0][PROBLEM_TYL_ITEM_INDEX], 0);
+        assert!(json["errors"][0].get(PROBLEM_TYL_ITEM_ID).is_none());
+        assert_eq!(json["errors"][1][PROBLEM_TYL_ITEM_ID], "order-3");
+        assert_eq!(json["errors"][1]["status"], 404);
+        let restored: MultiStatusBody = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, body);
+        assert_eq!(TylErrorGroup::new().to_multi_status(4).status(), 200);
+    }
+
+    #[test]
+    fn test_infrastructure_scope_should_survive_the_wire() {
+        // Given: a network error only infrastructure may retry
+        let category = ErrorCategory::network().with_retry_scope(RetryScope::Infrastructure);
+        let error = TylError::business_logic("Sidecar reset", category.into_classifier());
+
+        // When: sending it to a client
+        let problem = error.to_problem_details();
+        let json = serde_json::to_string(&problem).unwrap();
+        let received: ProblemDetails = serde_json::from_str(&json).unwrap();
+
+        // Then: the scope should be sent without a Retry-After header
+        assert_eq!(
+            received.extensions[PROBLEM_TYL_RETRY_SCOPE],
+            "infrastructure"
+        );
+        assert_eq!(received.retry_scope(), Some(RetryScope::Infrastructure));
+        assert!(received.retry_after().is_some());
+        assert_eq!(received.retry_after_header(), None);
+        assert_eq!(error.retry_after_header(), None);
+
+        // And: the restored error should keep its scope and category
+        let restored = TylError::from_problem_details(&received);
+        assert_eq!(restored.retry_scope(), RetryScope::Infrastructure);
+        assert_eq!(restored.category().category_name(), "Network");
+        assert_eq!(
+            TylError::validation("email", "Invalid")
+                .to_problem_details()
+                .retry_scope(),
+            Some(RetryScope::None)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Serialization profiles controlling how much of an error is persisted.
+//!
+//! This module provides the [`Profile`] used with
+//! [`TylError::serialize_with_profile`] and
+//! [`ErrorContext::serialize_with_profile`]. The minimal profile keeps only
+//! codes, categories, fingerprints and timings, so long-term error archives do
+//! not retain personal data carried in messages or metadata values.
+
+use crate::context::{ErrorContext, TENANT_ID_KEY};
+use crate::error::{TylError, TylResult};
+use crate::meta_key::MetaKey;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// How much information a serialized error or context keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Profile {
+    /// The regular serde representation, including messages and metadata.
+    #[default]
+    Full,
+    /// Codes, categories, fingerprints and timings only; free text and
+    /// metadata values are stripped (metadata keys are kept).
+    Minimal,
+}
+
+impl TylError {
+    /// Serialize this error according to a profile.
+    ///
+    /// With [`Profile::Minimal`] the result contains the variant, code,
+    /// category, retriability and fingerprint only.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{Profile, TylError};
+    ///
+    /// let error = TylError::validation("email", "jane.doe@example.com is not allowed");
+    /// let archived = error.serialize_with_profile(Profile::Minimal).unwrap();
+    ///
+    /// assert_eq!(archived["code"], "validation");
+    /// assert_eq!(archived["fingerprint"], error.fingerprint());
+    /// assert!(!archived.to_string().contains("jane.doe"));
+    /// ```
+    pub fn serialize_with_profile(&self, profile: Profile) -> TylResult<serde_json::Value> {
+        match profile {
+            Profile::Full => Ok(serde_json::to_value(self)?),
+            Profile::Minimal => Ok(json!({
+                "variant": self.variant_name(),
+                "code": self.code(),
+                "category": self.category().category_name(),
+                "retriable": self.is_retriable(),
+                "fingerprint": self.fingerprint(),
+            })),
+        }
+    }
+}
+
+impl ErrorContext {
+    /// Serialize this context according to a profile.
+    ///
+    /// With [`Profile::Minimal`] the message is dropped and only the metadata
+    /// keys are kept; identifiers, operation, category and timings are preserved,
+    /// as is the tenant ID unless it is flagged as sensitive.
+    pub fn serialize_with_profile(&self, profile: Profile) -> TylResult<serde_json::Value> {
+        match profile {
+            Profile::Full => Ok(serde_json::to_value(self)?),
+            Profile::Minimal => {
+                let mut metadata_keys: Vec<&MetaKey> = self.metadata.keys().collect();
+                metadata_keys.sort();
+                let mut minimal = json!({
+                    "error_id": self.error_id,
+                    "operation": self.operation,
+                    "category": self.category.category_name(),
+                    "retriable": self.category.is_retriable(),
+                    "occurred_at": self.occurred_at,
+                    "attempt_count": self.attempt_count,
+                    "metadata_keys": metadata_keys,
+                });
+                if let Some(key) = &self.idempotency_key {
+                    minimal["idempotency_key"] = json!(key);
+                }
+                if !self.is_sensitive(TENANT_ID_KEY) {
+                    if let Some(tenant_id) = &self.tenant_id {
+                        minimal["tenant_id"] = json!(tenant_id);
+                    }
+                }
+                Ok(minimal)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_context_should_drop_message_and_metadata_values() {
+        // Given: a context carrying personal data
+        let context = TylError::permission_denied("jane@example.com", "read", "file:1")
+            .to_context("files.read".to_string())
+            .with_metadata("ip".to_string(), json!("203.0.113.9"));
+
+        // When: serializing with the minimal profile
+        let archived = context.serialize_with_profile(Profile::Minimal).unwrap();
+
+        // Then: only non-personal fields should remain
+        let text = archived.to_string();
+        assert!(!text.contains("jane@example.com"));
+        assert!(!text.contains("203.0.113.9"));
+        assert_eq!(archived["operation"], "files.read");
+        assert_eq!(
+            archived["metadata_keys"],
+            json!(["action", "ip", "resource", "subject"])
+        );
+    }
+
+    #[test]
+    fn test_full_profile_should_match_serde_representation() {
+        let error = TylError::not_found("user", "42");
+        assert_eq!(
+            error.serialize_with_profile(Profile::Full).unwrap(),
+            serde_json::to_value(&error).unwrap()
+        );
+    }
+}
@@ -0,0 +1,598 @@
+//! Deferred retry queue for background retry patterns.
+//!
+//! This module provides [`RetryQueue`], which parks failed work items (messages
+//! or boxed operations) until their category's retry delay has elapsed, so
+//! outbox relays and webhook redelivery loops can poll [`RetryQueue::drain_due`]
+//! instead of sleeping inline. Queues can write through to a [`RetryStore`] so
+//! deferred retries survive process restarts.
+
+use crate::category::restore_category;
+use crate::clock::{self, Instant};
+use crate::context::ErrorContext;
+use crate::error::{TylError, TylResult};
+use crate::settings::{ErrorSettings, LogLevel};
+use crate::shutdown::ShutdownHook;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// A deferred operation stored in a [`RetryQueue`].
+pub type BoxedRetryOperation = Box<dyn FnMut() -> TylResult<()> + Send>;
+
+/// A failed work item waiting in a [`RetryQueue`].
+#[derive(Debug)]
+pub struct QueuedRetry<T> {
+    /// Identifier of the queue entry, stable across requeues and restarts.
+    pub id: Uuid,
+    /// The work item to retry.
+    pub item: T,
+    /// The error of the last failed attempt.
+    pub error: TylError,
+    /// Number of retries scheduled so far, including this one (1-based).
+    pub attempt: usize,
+    /// Priority among items due at the same time; higher runs first.
+    pub priority: u8,
+    /// When the item becomes due.
+    pub due_at: Instant,
+}
+
+impl<T: Clone> QueuedRetry<T> {
+    /// Convert this entry into its persistable form.
+    pub fn to_stored(&self) -> StoredRetry<T> {
+        let remaining = self.due_at.saturating_duration_since(Instant::now());
+        let remaining = chrono::Duration::from_std(remaining).unwrap_or(chrono::Duration::zero());
+        StoredRetry {
+            id: self.id,
+            item: self.item.clone(),
+            category: self.error.category().category_name().to_string(),
+            retriable: self.error.is_retriable(),
+            error: self.error.clone(),
+            attempt: self.attempt,
+            priority: self.priority,
+            due_at: clock::now() + remaining,
+        }
+    }
+}
+
+/// Persistable form of a [`QueuedRetry`].
+///
+/// The due time is stored as wall-clock time, and the error's category name and
+/// retriability are recorded so custom categories survive the round-trip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRetry<T> {
+    /// Identifier of the queue entry.
+    pub id: Uuid,
+    /// The work item to retry.
+    pub item: T,
+    /// The error of the last failed attempt.
+    pub error: TylError,
+    /// Category name of the error.
+    pub category: String,
+    /// Whether the error was retriable.
+    pub retriable: bool,
+    /// Number of retries scheduled so far (1-based).
+    pub attempt: usize,
+    /// Priority among due items; higher runs first.
+    pub priority: u8,
+    /// When the item becomes due.
+    pub due_at: DateTime<Utc>,
+}
+
+impl<T> StoredRetry<T> {
+    /// Convert this stored entry back into a queue entry.
+    pub fn into_queued(self) -> QueuedRetry<T> {
+        let mut error = self.error;
+        if let TylError::Custom { classifier, .. } = &mut error {
+            *classifier = restore_category(&self.category, self.retriable).into_classifier();
+        }
+        let remaining = (self.due_at - clock::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+        QueuedRetry {
+            id: self.id,
+            item: self.item,
+            error,
+            attempt: self.attempt,
+            priority: self.priority,
+            due_at: Instant::now() + remaining,
+        }
+    }
+}
+
+/// Storage backend for [`RetryQueue`] entries.
+///
+/// Queues write entries through to their store when they are scheduled or
+/// requeued, and remove them once completed or rejected, so an entry stays
+/// stored while it is being processed (at-least-once redelivery).
+pub trait RetryStore<T>: Send {
+    /// Insert or replace the entry with the same id.
+    fn save(&mut self, retry: &QueuedRetry<T>) -> TylResult<()>;
+
+    /// Remove the entry with the given id, if present.
+    fn remove(&mut self, id: Uuid) -> TylResult<()>;
+
+    /// Load all stored entries.
+    fn load(&mut self) -> TylResult<Vec<StoredRetry<T>>>;
+}
+
+/// Default store keeping entries only in the queue's own memory.
+///
+/// Nothing survives a restart; plug in a persistent [`RetryStore`] for that.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InMemoryRetryStore;
+
+impl<T> RetryStore<T> for InMemoryRetryStore {
+    fn save(&mut self, _retry: &QueuedRetry<T>) -> TylResult<()> {
+        Ok(())
+    }
+
+    fn remove(&mut self, _id: Uuid) -> TylResult<()> {
+        Ok(())
+    }
+
+    fn load(&mut self) -> TylResult<Vec<StoredRetry<T>>> {
+        Ok(Vec::new())
+    }
+}
+
+struct Scheduled<T> {
+    sequence: u64,
+    retry: QueuedRetry<T>,
+}
+
+impl<T> Scheduled<T> {
+    fn key(&self) -> (Reverse<Instant>, u8, Reverse<u64>) {
+        (
+            Reverse(self.retry.due_at),
+            self.retry.priority,
+            Reverse(self.sequence),
+        )
+    }
+}
+
+impl<T> PartialEq for Scheduled<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T> Eq for Scheduled<T> {}
+
+impl<T> PartialOrd for Scheduled<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Scheduled<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Priority queue of failed work items ordered by their next attempt time.
+///
+/// Items are only accepted while their error is retriable and the retry limit
+/// has not been reached; the next attempt time follows the error category's
+/// retry delay. Due items are returned by descending priority, earliest first
+/// within the same priority.
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use tyl_errors::{RetryQueue, TylError};
+///
+/// let mut queue = RetryQueue::new();
+/// queue.push("webhook-1", TylError::network("Connection reset")).unwrap();
+///
+/// // Permanent failures are handed back instead of being queued.
+/// let rejected = queue.push("webhook-2", TylError::validation("url", "invalid")).unwrap_err();
+/// assert_eq!(rejected.item, "webhook-2");
+///
+/// let due = queue.drain_due_at(Instant::now() + Duration::from_secs(60));
+/// assert_eq!(due[0].item, "webhook-1");
+/// assert!(queue.is_empty());
+/// ```
+pub struct RetryQueue<T> {
+    heap: BinaryHeap<Scheduled<T>>,
+    next_sequence: u64,
+    max_attempts: usize,
+    store: Box<dyn RetryStore<T>>,
+}
+
+impl<T> Default for RetryQueue<T> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            next_sequence: 0,
+            max_attempts: TylError::max_retries(),
+            store: Box::new(InMemoryRetryStore),
+        }
+    }
+}
+
+impl<T> RetryQueue<T> {
+    /// Create an empty queue limited to [`TylError::max_retries`] attempts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a queue backed by a store, restoring the entries it holds.
+    ///
+    /// Store failures after loading are logged (see [`TylError::log`])
+    /// and do not affect the in-memory queue.
+    pub fn with_store<S: RetryStore<T> + 'static>(mut store: S) -> TylResult<Self> {
+        let stored = store.load()?;
+        let mut queue = Self {
+            store: Box::new(store),
+            ..Self::default()
+        };
+        for retry in stored {
+            queue.enqueue(retry.into_queued());
+        }
+        Ok(queue)
+    }
+
+    /// Set the maximum number of retries per item using builder pattern.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Queue a first retry of a failed item.
+    ///
+    /// # Returns
+    /// When the item becomes due, or the rejected entry if the error is not
+    /// retriable or no retries are allowed.
+    pub fn push(&mut self, item: T, error: TylError) -> Result<Instant, Box<QueuedRetry<T>>> {
+        self.schedule(Uuid::new_v4(), item, error, 0, 0)
+    }
+
+    /// Queue a first retry of a failed item with the given priority.
+    pub fn push_with_priority(
+        &mut self,
+        item: T,
+        error: TylError,
+        priority: u8,
+    ) -> Result<Instant, Box<QueuedRetry<T>>> {
+        self.schedule(Uuid::new_v4(), item, error, 0, priority)
+    }
+
+    /// Queue a retry of an item whose failure was tracked in `context`.
+    ///
+    /// The retries already made are taken from the context's attempt count,
+    /// and the category's retry delay runs from the context's monotonic
+    /// [`occurred_at_mono`](ErrorContext::occurred_at_mono) instead of from
+    /// now, so time spent before queueing counts towards it.
+    pub fn push_with_context(
+        &mut self,
+        item: T,
+        error: TylError,
+        context: &ErrorContext,
+    ) -> Result<Instant, Box<QueuedRetry<T>>> {
+        let retries = context.attempt_count.saturating_sub(1);
+        self.schedule_from(
+            context.occurred_at_mono,
+            Uuid::new_v4(),
+            item,
+            error,
+            retries,
+            0,
+        )
+    }
+
+    /// Queue another retry of an item whose retry failed again.
+    ///
+    /// # Returns
+    /// When the item becomes due, or the rejected entry if the new error is
+    /// not retriable or the retry limit has been reached.
+    pub fn requeue(
+        &mut self,
+        retry: QueuedRetry<T>,
+        error: TylError,
+    ) -> Result<Instant, Box<QueuedRetry<T>>> {
+        let id = retry.id;
+        let result = self.schedule(id, retry.item, error, retry.attempt, retry.priority);
+        if result.is_err() {
+            self.persist(|store| store.remove(id));
+        }
+        result
+    }
+
+    /// Mark a drained entry as done, removing it from the store.
+    pub fn complete(&mut self, retry: &QueuedRetry<T>) {
+        let id = retry.id;
+        self.persist(|store| store.remove(id));
+    }
+
+    /// Remove and return all items due now.
+    ///
+    /// Drained entries stay in the store until they are [`requeue`](Self::requeue)d
+    /// or [`complete`](Self::complete)d.
+    pub fn drain_due(&mut self) -> Vec<QueuedRetry<T>> {
+        self.drain_due_at(Instant::now())
+    }
+
+    /// Remove and return all items due at `now`.
+    pub fn drain_due_at(&mut self, now: Instant) -> Vec<QueuedRetry<T>> {
+        let mut due = Vec::new();
+        while self
+            .heap
+            .peek()
+            .is_some_and(|scheduled| scheduled.retry.due_at <= now)
+        {
+            if let Some(scheduled) = self.heap.pop() {
+                due.push(scheduled.retry);
+            }
+        }
+        due.sort_by_key(|retry| Reverse(retry.priority));
+        due
+    }
+
+    /// Get when the earliest queued item becomes due.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.heap.peek().map(|scheduled| scheduled.retry.due_at)
+    }
+
+    /// Get the number of queued items.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Check if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    fn schedule(
+        &mut self,
+        id: Uuid,
+        item: T,
+        error: TylError,
+        retries: usize,
+        priority: u8,
+    ) -> Result<Instant, Box<QueuedRetry<T>>> {
+        self.schedule_from(Instant::now(), id, item, error, retries, priority)
+    }
+
+    fn schedule_from(
+        &mut self,
+        failed_at: Instant,
+        id: Uuid,
+        item: T,
+        error: TylError,
+        retries: usize,
+        priority: u8,
+    ) -> Result<Instant, Box<QueuedRetry<T>>> {
+        let attempt = retries + 1;
+        let due_at = failed_at + error.category().retry_delay_for(&error, attempt);
+        let retry = QueuedRetry {
+            id,
+            item,
+            error,
+            attempt,
+            priority,
+            due_at,
+        };
+        let category_limit = retry.error.category().max_retries().unwrap_or(usize::MAX);
+        let max_attempts = ErrorSettings::global().cap_attempts(self.max_attempts);
+        if !retry.error.is_retriable() || retries >= max_attempts.min(category_limit) {
+            return Err(Box::new(retry));
+        }
+
+        self.persist(|store| store.save(&retry));
+        self.enqueue(retry);
+        Ok(due_at)
+    }
+
+    /// Write every queued entry to the store again.
+    ///
+    /// Entries are written through when scheduled; this retries writes that
+    /// failed, e.g. before the process terminates.
+    ///
+    /// # Returns
+    /// The number of entries written, or the first store error.
+    pub fn persist_pending(&mut self) -> TylResult<usize> {
+        for scheduled in self.heap.iter() {
+            self.store.save(&scheduled.retry)?;
+        }
+        Ok(self.heap.len())
+    }
+
+    fn enqueue(&mut self, retry: QueuedRetry<T>) {
+        self.heap.push(Scheduled {
+            sequence: self.next_sequence,
+            retry,
+        });
+        self.next_sequence += 1;
+    }
+
+    fn persist<F>(&mut self, operation: F)
+    where
+        F: FnOnce(&mut dyn RetryStore<T>) -> TylResult<()>,
+    {
+        if let Err(error) = operation(self.store.as_mut()) {
+            error.log_at(LogLevel::Error);
+        }
+    }
+}
+
+impl<T: Send + 'static> RetryQueue<T> {
+    /// Create a [`ShutdownHook`] writing the queue's entries to its store on
+    /// [`shutdown`](crate::shutdown), see [`RetryQueue::persist_pending`].
+    pub fn shutdown_hook(queue: &Arc<Mutex<Self>>) -> Arc<impl ShutdownHook> {
+        let queue = Arc::clone(queue);
+        Arc::new(move |_: Duration| {
+            queue
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .persist_pending()
+                .map(|_| ())
+        })
+    }
+}
+
+impl RetryQueue<BoxedRetryOperation> {
+    /// Run every due operation, requeueing the ones that fail again.
+    ///
+    /// # Returns
+    /// The number of operations that succeeded and the entries that were
+    /// dropped because their last failure was permanent or out of retries.
+    pub fn run_due(&mut self) -> (usize, Vec<QueuedRetry<BoxedRetryOperation>>) {
+        let mut succeeded = 0;
+        let mut dropped = Vec::new();
+        for mut retry in self.drain_due() {
+            match (retry.item)() {
+                Ok(()) => {
+                    self.complete(&retry);
+                    succeeded += 1;
+                }
+                Err(error) => {
+                    if let Err(rejected) = self.requeue(retry, error) {
+                        dropped.push(*rejected);
+                    }
+                }
+            }
+        }
+        (succeeded, dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::ErrorCategory;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn flaky(message: &str) -> TylError {
+        let category = ErrorCategory::custom_fn("Flaky", true, |_| Duration::ZERO);
+        TylError::business_logic(message, category.into_classifier())
+    }
+
+    #[test]
+    fn test_drain_due_should_order_by_priority() {
+        // Given: due items with different priorities
+        let mut queue = RetryQueue::new();
+        queue.push("low", flaky("busy")).unwrap();
+        queue.push_with_priority("high", flaky("busy"), 9).unwrap();
+
+        // When: draining due items
+        let due = queue.drain_due();
+
+        // Then: the high-priority item should come first
+        assert_eq!(due[0].item, "high");
+        assert_eq!(due[1].item, "low");
+        assert!(queue.next_due().is_none());
+    }
+
+    #[test]
+    fn test_push_should_delay_by_category() {
+        // Given: a transient database failure
+        let mut queue = RetryQueue::new();
+        let due_at = queue.push("row-1", TylError::database("Deadlock")).unwrap();
+
+        // When/Then: it should not be due immediately
+        assert!(due_at > Instant::now());
+        assert_eq!(queue.next_due(), Some(due_at));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_push_with_context_should_delay_from_the_failure() {
+        // Given: a database failure tracked on its second attempt, whose wall
+        // clock timestamp was set back an hour
+        let error = TylError::database("Deadlock");
+        let mut context = error.to_context("orders.save".to_string());
+        context.increment_attempt();
+        context.occurred_at -= chrono::Duration::hours(1);
+        let mut queue = RetryQueue::new();
+
+        // When: queueing its retry
+        let due_at = queue
+            .push_with_context("row-1", error.clone(), &context)
+            .unwrap();
+
+        // Then: the delay of the second retry should run from the failure
+        let delay = error.category().retry_delay_for(&error, 2);
+        assert_eq!(due_at, context.retry_due_at(delay));
+        assert_eq!(queue.drain_due().len(), 0);
+        assert_eq!(queue.drain_due_at(due_at)[0].attempt, 2);
+    }
+
+    /// Store persisting entries as JSON, shared between queue instances.
+    #[derive(Clone, Default)]
+    struct JsonStore(Arc<Mutex<HashMap<Uuid, String>>>);
+
+    impl RetryStore<String> for JsonStore {
+        fn save(&mut self, retry: &QueuedRetry<String>) -> TylResult<()> {
+            let json = serde_json::to_string(&retry.to_stored())?;
+            self.0.lock().unwrap().insert(retry.id, json);
+            Ok(())
+        }
+
+        fn remove(&mut self, id: Uuid) -> TylResult<()> {
+            self.0.lock().unwrap().remove(&id);
+            Ok(())
+        }
+
+        fn load(&mut self) -> TylResult<Vec<StoredRetry<String>>> {
+            let entries = self.0.lock().unwrap();
+            entries
+                .values()
+                .map(|json| Ok(serde_json::from_str(json)?))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_with_store_should_restore_entries_after_restart() {
+        // Given: a queue writing through to a store
+        let store = JsonStore::default();
+        let mut queue = RetryQueue::with_store(store.clone()).unwrap();
+        queue.push("order-7".to_string(), flaky("down")).unwrap();
+        drop(queue);
+
+        // When: a new queue is created from the same store
+        let mut restored = RetryQueue::with_store(store.clone()).unwrap();
+        let retry = restored.drain_due().pop().unwrap();
+
+        // Then: the entry and its custom category should be restored
+        assert_eq!(retry.item, "order-7");
+        assert_eq!(retry.error.category().category_name(), "Flaky");
+        assert!(retry.error.is_retriable());
+
+        // And: persisting pending entries should rewrite lost writes
+        let mut pending = RetryQueue::with_store(store.clone()).unwrap();
+        store.0.lock().unwrap().clear();
+        assert_eq!(pending.persist_pending().unwrap(), 1);
+        assert_eq!(store.0.lock().unwrap().len(), 1);
+        drop(pending);
+
+        // And: completing it should remove it from the store
+        restored.complete(&retry);
+        assert!(store.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_due_should_requeue_until_max_attempts() {
+        // Given: an operation that always fails, with two retries allowed
+        let mut queue: RetryQueue<BoxedRetryOperation> = RetryQueue::new().with_max_attempts(2);
+        let operation: BoxedRetryOperation = Box::new(|| Err(flaky("still down")));
+        assert!(queue.push(operation, flaky("down")).is_ok());
+
+        // When: running due operations repeatedly
+        let (_, first) = queue.run_due();
+        let (succeeded, dropped) = queue.run_due();
+
+        // Then: the operation should be dropped after its last retry
+        assert!(first.is_empty());
+        assert_eq!(succeeded, 0);
+        assert_eq!(dropped[0].attempt, 3);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Secrets-aware `Debug` output for errors and contexts.
+//!
+//! This module provides the [`SafeDebug`] wrapper that masks metadata values
+//! flagged as sensitive and truncates very long messages, so that an accidental
+//! `dbg!` of an error cannot leak credentials into logs. The plain `{:?}` output
+//! of [`TylError`](crate::TylError) and [`ErrorContext`](crate::ErrorContext)
+//! applies the same rules when `TYL_ERROR_REDACT=true`.
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// Placeholder written in place of sensitive values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Maximum number of characters of a message shown in redacted `Debug` output.
+pub const SAFE_DEBUG_MAX_MESSAGE_LEN: usize = 256;
+
+/// Types whose `Debug` output can be rendered with sensitive data masked.
+pub trait RedactedDebug {
+    /// Format this value for debugging, masking sensitive data when `redact` is true.
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>, redact: bool) -> fmt::Result;
+}
+
+/// Wrapper whose `Debug` output always redacts, regardless of `TYL_ERROR_REDACT`.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorCategory, ErrorContext};
+///
+/// let context = ErrorContext::new(
+///     "login".to_string(),
+///     ErrorCategory::authentication(),
+///     "Invalid credentials".to_string(),
+/// )
+/// .with_sensitive_metadata("password".to_string(), serde_json::json!("hunter2"));
+///
+/// let output = format!("{:?}", context.safe_debug());
+/// assert!(output.contains("[REDACTED]"));
+/// assert!(!output.contains("hunter2"));
+/// ```
+pub struct SafeDebug<'a, T: ?Sized>(pub &'a T);
+
+impl<T: RedactedDebug + ?Sized> fmt::Debug for SafeDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_debug(f, true)
+    }
+}
+
+/// Truncate a message for redacted `Debug` output.
+///
+/// Messages longer than [`SAFE_DEBUG_MAX_MESSAGE_LEN`] characters are cut and
+/// suffixed with the number of characters omitted.
+pub(crate) fn truncate_message(message: &str) -> Cow<'_, str> {
+    match message.char_indices().nth(SAFE_DEBUG_MAX_MESSAGE_LEN) {
+        Some((byte_index, _)) => {
+            let omitted = message[byte_index..].chars().count();
+            Cow::Owned(format!(
+                "{}…[truncated {omitted} chars]",
+                &message[..byte_index]
+            ))
+        }
+        None => Cow::Borrowed(message),
+    }
+}
+
+/// Choose between the raw and truncated message for `Debug` output.
+pub(crate) fn debug_message(message: &str, redact: bool) -> Cow<'_, str> {
+    if redact {
+        truncate_message(message)
+    } else {
+        Cow::Borrowed(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorCategory, ErrorContext, TylError};
+
+    #[test]
+    fn test_truncate_message_should_keep_short_messages() {
+        // Given: a short message
+        // When: truncating
+        // Then: it should be returned unchanged
+        assert_eq!(truncate_message("short"), "short");
+    }
+
+    #[test]
+    fn test_truncate_message_should_cut_long_messages() {
+        // Given: a message longer than the limit
+        let message = "x".repeat(SAFE_DEBUG_MAX_MESSAGE_LEN + 10);
+
+        // When: truncating
+        let truncated = truncate_message(&message);
+
+        // Then: it should be cut and report the omitted characters
+        assert!(truncated.starts_with(&"x".repeat(SAFE_DEBUG_MAX_MESSAGE_LEN)));
+        assert!(truncated.ends_with("…[truncated 10 chars]"));
+    }
+
+    #[test]
+    fn test_safe_debug_should_mask_sensitive_metadata() {
+        // Given: a context with sensitive and regular metadata
+        let context = ErrorContext::new(
+            "login".to_string(),
+            ErrorCategory::authentication(),
+            "Invalid credentials".to_string(),
+        )
+        .with_metadata("user".to_string(), serde_json::json!("alice"))
+        .with_sensitive_metadata("token".to_string(), serde_json::json!("s3cr3t"));
+
+        // When: formatting with SafeDebug
+        let output = format!("{:?}", context.safe_debug());
+
+        // Then: only the sensitive value should be masked
+        assert!(output.contains("alice"));
+        assert!(output.contains(REDACTED));
+        assert!(!output.contains("s3cr3t"));
+    }
+
+    #[test]
+    fn test_safe_debug_should_truncate_error_messages() {
+        // Given: an error with a huge message
+        let error = TylError::internal("y".repeat(10_000));
+
+        // When: formatting with SafeDebug
+        let output = format!("{:?}", error.safe_debug());
+
+        // Then: the message should be truncated
+        assert!(output.starts_with("Internal { message: "));
+        assert!(output.contains("[truncated"));
+        assert!(output.len() < 1_000);
+    }
+}
@@ -0,0 +1,822 @@
+//! Compound resilience pipelines.
+//!
+//! Services wrap calls to their dependencies in the same primitives: a
+//! timeout, a bulkhead limiting concurrent calls, a circuit breaker, retries
+//! and a fallback. This module provides [`ResiliencePipeline`], composing
+//! them in one documented order around an async operation, configurable from
+//! code or from a serde [`ResilienceConfig`].
+
+use crate::category::{BuiltinCategory, ErrorCategory};
+use crate::clock::Instant;
+use crate::diagnostics::OpenCircuitBreaker;
+use crate::error::{TylError, TylResult};
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+use crate::retry::GlooSleeper;
+#[cfg(feature = "tokio")]
+use crate::retry::TokioSleeper;
+use crate::retry::{RetryPolicy, Sleeper};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::Poll;
+use std::time::Duration;
+
+/// Circuit breaker settings of a [`ResilienceConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Name of the circuit breaker in diagnostics, see
+    /// [`ResiliencePipeline::with_named_circuit_breaker`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Consecutive failures opening the circuit.
+    pub failure_threshold: u32,
+    /// Time the circuit stays open before letting probe calls through, in milliseconds.
+    pub open_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            failure_threshold: 5,
+            open_ms: 30_000,
+        }
+    }
+}
+
+/// Retry settings of a [`ResilienceConfig`], see [`RetryPolicy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicyConfig {
+    /// Maximum number of retry attempts.
+    pub max_attempts: usize,
+    /// Base delay for exponential backoff, in milliseconds.
+    pub base_delay_ms: u64,
+    /// Maximum delay, in milliseconds.
+    pub max_delay_ms: u64,
+    /// Multiplier for exponential backoff.
+    pub backoff_multiplier: f64,
+    /// Whether to add jitter to delays.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicyConfig {
+    fn default() -> Self {
+        let policy = RetryPolicy::default();
+        Self {
+            max_attempts: policy.max_attempts,
+            base_delay_ms: policy.base_delay.as_millis() as u64,
+            max_delay_ms: policy.max_delay.as_millis() as u64,
+            backoff_multiplier: policy.backoff_multiplier,
+            jitter: policy.jitter,
+        }
+    }
+}
+
+impl From<&RetryPolicyConfig> for RetryPolicy {
+    fn from(config: &RetryPolicyConfig) -> Self {
+        RetryPolicy::new()
+            .with_max_attempts(config.max_attempts)
+            .with_base_delay(Duration::from_millis(config.base_delay_ms))
+            .with_max_delay(Duration::from_millis(config.max_delay_ms))
+            .with_backoff_multiplier(config.backoff_multiplier)
+            .with_jitter(config.jitter)
+    }
+}
+
+/// Serializable configuration of a [`ResiliencePipeline`]; absent stages are
+/// disabled.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ResilienceConfig, ResiliencePipeline};
+///
+/// let config = ResilienceConfig::from_json(r#"{
+///     "timeout_ms": 2000,
+///     "max_concurrent": 16,
+///     "circuit_breaker": { "failure_threshold": 3 },
+///     "retry": { "max_attempts": 2 }
+/// }"#).unwrap();
+/// let pipeline: ResiliencePipeline<String> = ResiliencePipeline::from_config(&config);
+/// assert_eq!(config.circuit_breaker.unwrap().open_ms, 30_000);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResilienceConfig {
+    /// Timeout of each attempt, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Maximum number of concurrent attempts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    /// Circuit breaker settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Retry settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicyConfig>,
+}
+
+impl ResilienceConfig {
+    /// Check that limits are positive.
+    pub fn validate(&self) -> TylResult<()> {
+        if self.timeout_ms == Some(0) {
+            return Err(TylError::config_key(
+                "timeout_ms",
+                "a positive duration",
+                "0",
+            ));
+        }
+        if self.max_concurrent == Some(0) {
+            return Err(TylError::config_key(
+                "max_concurrent",
+                "a positive limit",
+                "0",
+            ));
+        }
+        if let Some(breaker) = &self.circuit_breaker {
+            if breaker.failure_threshold == 0 {
+                return Err(TylError::config_key(
+                    "circuit_breaker.failure_threshold",
+                    "a positive threshold",
+                    "0",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load and validate a configuration from JSON.
+    pub fn from_json(json: &str) -> TylResult<Self> {
+        let config: Self = serde_json::from_str(json).map_err(|err| {
+            TylError::configuration(format!("Invalid resilience configuration: {err}"))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load and validate a configuration from YAML. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> TylResult<Self> {
+        let config: Self = serde_yaml::from_str(yaml).map_err(|err| {
+            TylError::configuration(format!("Invalid resilience configuration: {err}"))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are rejected without running the operation.
+    Open,
+    /// The open period has passed; a single call goes through as a probe,
+    /// whose failure opens the circuit again and whose success closes it.
+    HalfOpen,
+}
+
+/// Circuit breakers of live pipelines, listed by [`open_circuit_breakers`].
+static CIRCUIT_BREAKERS: Mutex<Vec<Weak<CircuitBreaker>>> = Mutex::new(Vec::new());
+
+/// List the circuit breakers of live pipelines that are not closed, by name.
+pub(crate) fn open_circuit_breakers() -> Vec<OpenCircuitBreaker> {
+    let breakers = CIRCUIT_BREAKERS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut open: Vec<OpenCircuitBreaker> = breakers
+        .iter()
+        .filter_map(Weak::upgrade)
+        .filter_map(|breaker| {
+            let state = breaker.state();
+            (state != CircuitState::Closed).then(|| OpenCircuitBreaker {
+                name: breaker.name.clone(),
+                state,
+                consecutive_failures: breaker.consecutive_failures(),
+            })
+        })
+        .collect();
+    open.sort_by(|a, b| a.name.cmp(&b.name));
+    open
+}
+
+#[derive(Debug)]
+struct CircuitBreaker {
+    name: Option<String>,
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the probe of a half-open circuit was let through.
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed circuit breaker, listed in diagnostics while it lives.
+    fn new(name: Option<String>, failure_threshold: u32, open_for: Duration) -> Arc<Self> {
+        let breaker = Arc::new(Self {
+            name,
+            failure_threshold,
+            open_for,
+            state: Mutex::new(BreakerState::default()),
+        });
+        let mut breakers = CIRCUIT_BREAKERS.lock().unwrap_or_else(|e| e.into_inner());
+        breakers.retain(|breaker| breaker.strong_count() > 0);
+        breakers.push(Arc::downgrade(&breaker));
+        breaker
+    }
+
+    fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_for => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn consecutive_failures(&self) -> u32 {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .consecutive_failures
+    }
+
+    fn check(&self) -> TylResult<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+        if opened_at.elapsed() >= self.open_for {
+            // A probe that never reported back, e.g. because its attempt was
+            // dropped, is replaced after another open period.
+            let probing = state
+                .probe_started
+                .is_some_and(|started| started.elapsed() < self.open_for);
+            if !probing {
+                state.probe_started = Some(Instant::now());
+                return Ok(());
+            }
+        }
+        // Not retriable: retrying cannot succeed before the circuit closes.
+        let category = ErrorCategory::Builtin(BuiltinCategory::ServiceUnavailable)
+            .and_then(ErrorCategory::permanent());
+        Err(TylError::business_logic(
+            "Circuit breaker open",
+            category.into_classifier(),
+        ))
+    }
+
+    fn record(&self, failed: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !failed {
+            *state = BreakerState::default();
+            return;
+        }
+        state.probe_started = None;
+        state.consecutive_failures += 1;
+        if state.opened_at.is_some() || state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bulkhead {
+    max_concurrent: usize,
+    in_flight: AtomicUsize,
+}
+
+impl Bulkhead {
+    fn try_acquire(self: &Arc<Self>) -> TylResult<BulkheadPermit> {
+        let acquired =
+            self.in_flight
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_flight| {
+                    (in_flight < self.max_concurrent).then_some(in_flight + 1)
+                });
+        match acquired {
+            Ok(_) => Ok(BulkheadPermit(Arc::clone(self))),
+            Err(_) => Err(TylError::business_logic(
+                format!("Bulkhead full ({} concurrent calls)", self.max_concurrent),
+                Box::new(BuiltinCategory::ResourceExhaustion),
+            )),
+        }
+    }
+}
+
+/// Slot of a [`Bulkhead`], released on drop.
+struct BulkheadPermit(Arc<Bulkhead>);
+
+impl Drop for BulkheadPermit {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+type Fallback<T> = Arc<dyn Fn(TylError) -> TylResult<T> + Send + Sync>;
+
+/// What the attempts of one [`ResiliencePipeline`] execution observed.
+#[derive(Default)]
+struct Execution {
+    /// Error of the last attempt that reached the operation.
+    last_failure: Option<TylError>,
+    /// Whether the last attempt was rejected by an open circuit.
+    circuit_open: bool,
+}
+
+/// Timeout, bulkhead, circuit breaker, retry and fallback around an async
+/// operation.
+///
+/// Stages wrap each other from the innermost out, each optional:
+///
+/// 1. **timeout** - each attempt fails with [`TylError::Timeout`] once it runs
+///    longer than the timeout;
+/// 2. **bulkhead** - attempts beyond the concurrency limit fail at once with
+///    a `ResourceExhaustion` error;
+/// 3. **circuit breaker** - after consecutive retriable failures of the inner
+///    stages the circuit opens and attempts fail at once with a
+///    non-retriable `ServiceUnavailable` error, replaced by the last failure
+///    of the dependency if there was one; once the open period has passed, a
+///    single probe attempt decides whether it closes. Non-retriable errors
+///    (e.g. validation) count as the dependency answering, and bulkhead
+///    rejections do not count;
+/// 4. **retry** - failed attempts are retried with a [`RetryPolicy`];
+/// 5. **fallback** - the final error is turned into a value or another error.
+///
+/// Clones share the bulkhead and circuit breaker, so one pipeline per
+/// dependency protects it across tasks.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{ResiliencePipeline, RetryPolicy, SleepFuture, Sleeper, TylError};
+///
+/// # struct NoWait;
+/// # impl Sleeper for NoWait {
+/// #     fn sleep(&self, _duration: Duration) -> SleepFuture {
+/// #         Box::pin(std::future::ready(()))
+/// #     }
+/// # }
+/// let pipeline = ResiliencePipeline::new()
+///     .with_timeout(Duration::from_secs(2))
+///     .with_bulkhead(16)
+///     .with_circuit_breaker(3, Duration::from_secs(30))
+///     .with_retry(RetryPolicy::network())
+///     .with_fallback(|_error| Ok("cached profile".to_string()));
+///
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let profile = pipeline
+///     .execute_async_with(&NoWait, || async { Err(TylError::network("Connection reset")) })
+///     .await;
+/// assert_eq!(profile.unwrap(), "cached profile");
+/// # });
+/// ```
+pub struct ResiliencePipeline<T> {
+    timeout: Option<Duration>,
+    bulkhead: Option<Arc<Bulkhead>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    retry: Option<RetryPolicy>,
+    fallback: Option<Fallback<T>>,
+}
+
+impl<T> ResiliencePipeline<T> {
+    /// Create a pipeline without stages.
+    pub fn new() -> Self {
+        Self {
+            timeout: None,
+            bulkhead: None,
+            circuit_breaker: None,
+            retry: None,
+            fallback: None,
+        }
+    }
+
+    /// Create a pipeline with the stages of a configuration.
+    ///
+    /// The configuration is expected to be [validated](ResilienceConfig::validate).
+    pub fn from_config(config: &ResilienceConfig) -> Self {
+        let mut pipeline = Self::new();
+        if let Some(timeout_ms) = config.timeout_ms {
+            pipeline = pipeline.with_timeout(Duration::from_millis(timeout_ms));
+        }
+        if let Some(max_concurrent) = config.max_concurrent {
+            pipeline = pipeline.with_bulkhead(max_concurrent);
+        }
+        if let Some(breaker) = &config.circuit_breaker {
+            pipeline.circuit_breaker = Some(CircuitBreaker::new(
+                breaker.name.clone(),
+                breaker.failure_threshold,
+                Duration::from_millis(breaker.open_ms),
+            ));
+        }
+        if let Some(retry) = &config.retry {
+            pipeline = pipeline.with_retry(retry.into());
+        }
+        pipeline
+    }
+
+    /// Fail attempts running longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limit the number of concurrent attempts.
+    pub fn with_bulkhead(mut self, max_concurrent: usize) -> Self {
+        self.bulkhead = Some(Arc::new(Bulkhead {
+            max_concurrent,
+            in_flight: AtomicUsize::new(0),
+        }));
+        self
+    }
+
+    /// Open the circuit after `failure_threshold` consecutive failures, for `open_for`.
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, open_for: Duration) -> Self {
+        self.circuit_breaker = Some(CircuitBreaker::new(None, failure_threshold, open_for));
+        self
+    }
+
+    /// Open the circuit after `failure_threshold` consecutive failures, for
+    /// `open_for`, naming the circuit breaker in
+    /// [`diagnostics::snapshot`](crate::diagnostics::snapshot) while it is not closed.
+    pub fn with_named_circuit_breaker(
+        mut self,
+        name: impl Into<String>,
+        failure_threshold: u32,
+        open_for: Duration,
+    ) -> Self {
+        self.circuit_breaker = Some(CircuitBreaker::new(
+            Some(name.into()),
+            failure_threshold,
+            open_for,
+        ));
+        self
+    }
+
+    /// Retry failed attempts with `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Turn the final error into a value or another error.
+    pub fn with_fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(TylError) -> TylResult<T> + Send + Sync + 'static,
+    {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Get the state of the circuit breaker, if the pipeline has one.
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.circuit_breaker.as_ref().map(|breaker| breaker.state())
+    }
+
+    /// Run an async operation through the pipeline, sleeping with
+    /// `tokio::time::sleep` (or `gloo-timers` on `wasm32`).
+    ///
+    /// Requires the `tokio` feature, or the `wasm` feature on `wasm32`; see
+    /// [`ResiliencePipeline::execute_async_with`] for other runtimes.
+    #[cfg(any(feature = "tokio", all(target_arch = "wasm32", feature = "wasm")))]
+    pub async fn execute_async<F, Fut>(&self, operation: F) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+    {
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        let sleeper = GlooSleeper;
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        let sleeper = TokioSleeper;
+        self.execute_async_with(&sleeper, operation).await
+    }
+
+    /// Run an async operation through the pipeline, waiting for timeouts and
+    /// between retries with the given [`Sleeper`].
+    pub async fn execute_async_with<F, Fut, S>(&self, sleeper: &S, mut operation: F) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+    {
+        let execution = Mutex::new(Execution::default());
+        let mut attempt = || {
+            let admitted = self.admit(&execution).map(|permit| (permit, operation()));
+            self.attempt(sleeper, admitted, &execution)
+        };
+        let result = match &self.retry {
+            Some(policy) => policy.execute_async_with(sleeper, attempt).await,
+            None => attempt().await,
+        };
+        let result = result.map_err(|error| {
+            let execution = execution.into_inner().unwrap_or_else(|e| e.into_inner());
+            match execution.last_failure {
+                Some(failure) if execution.circuit_open => match error.retry_telemetry() {
+                    Some(telemetry) => failure.with_retry_telemetry(telemetry.clone()),
+                    None => failure,
+                },
+                _ => error,
+            }
+        });
+        match (result, &self.fallback) {
+            (Err(error), Some(fallback)) => fallback(error),
+            (result, _) => result,
+        }
+    }
+
+    /// Pass the circuit breaker and take a bulkhead slot for one attempt,
+    /// before the operation is called.
+    ///
+    /// A full bulkhead says nothing about the dependency, so it is not
+    /// recorded in the circuit breaker.
+    fn admit(&self, execution: &Mutex<Execution>) -> TylResult<Option<BulkheadPermit>> {
+        if let Some(breaker) = &self.circuit_breaker {
+            let checked = breaker.check();
+            execution
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .circuit_open = checked.is_err();
+            checked?;
+        }
+        match &self.bulkhead {
+            Some(bulkhead) => bulkhead.try_acquire().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Run one admitted attempt with the timeout, recording its outcome in
+    /// the circuit breaker and the execution.
+    async fn attempt<Fut, S>(
+        &self,
+        sleeper: &S,
+        admitted: TylResult<(Option<BulkheadPermit>, Fut)>,
+        execution: &Mutex<Execution>,
+    ) -> TylResult<T>
+    where
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+    {
+        let (permit, attempt) = admitted?;
+        let result = self.run_with_timeout(sleeper, attempt).await;
+        drop(permit);
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.record(matches!(&result, Err(error) if error.is_retriable()));
+        }
+        if let Err(error) = &result {
+            execution
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .last_failure = Some(error.clone());
+        }
+        result
+    }
+
+    async fn run_with_timeout<Fut, S>(&self, sleeper: &S, attempt: Fut) -> TylResult<T>
+    where
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+    {
+        let Some(timeout) = self.timeout else {
+            return attempt.await;
+        };
+        let mut attempt = std::pin::pin!(attempt);
+        let mut expired = sleeper.sleep(timeout);
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(result) = attempt.as_mut().poll(cx) {
+                return Poll::Ready(result);
+            }
+            expired.as_mut().poll(cx).map(|()| {
+                Err(TylError::timeout(format!(
+                    "Attempt timed out after {timeout:?}"
+                )))
+            })
+        })
+        .await
+    }
+}
+
+impl<T> Default for ResiliencePipeline<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ResiliencePipeline<T> {
+    fn clone(&self) -> Self {
+        Self {
+            timeout: self.timeout,
+            bulkhead: self.bulkhead.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            retry: self.retry.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ResiliencePipeline<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResiliencePipeline")
+            .field("timeout", &self.timeout)
+            .field("bulkhead", &self.bulkhead)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("retry", &self.retry)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::SleepFuture;
+
+    /// Sleeper completing at once, or never for the given durations.
+    struct TestSleeper {
+        never: Option<Duration>,
+    }
+
+    impl Sleeper for TestSleeper {
+        fn sleep(&self, duration: Duration) -> SleepFuture {
+            if Some(duration) == self.never {
+                Box::pin(std::future::pending())
+            } else {
+                Box::pin(std::future::ready(()))
+            }
+        }
+    }
+
+    const NO_WAIT: TestSleeper = TestSleeper { never: None };
+
+    fn quick_retry() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_attempts(4)
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_should_open_and_stop_retries() {
+        // Given: a pipeline opening its circuit after two failures
+        let pipeline: ResiliencePipeline<u32> = ResiliencePipeline::new()
+            .with_circuit_breaker(2, Duration::from_secs(60))
+            .with_retry(quick_retry());
+
+        // When: the dependency keeps failing
+        let mut calls = 0;
+        let result = pipeline
+            .execute_async_with(&NO_WAIT, || {
+                calls += 1;
+                async { Err(TylError::network("Connection reset")) }
+            })
+            .await;
+
+        // Then: the open circuit should stop retries and keep the last failure
+        let error = result.unwrap_err();
+        assert_eq!(calls, 2);
+        assert_eq!(error.retry_telemetry().unwrap().attempts, 3);
+        assert_eq!(error.to_string(), "Network error: Connection reset");
+        assert_eq!(pipeline.circuit_state(), Some(CircuitState::Open));
+
+        // And: calls while it is open should fail at once without retries
+        let result = pipeline
+            .execute_async_with(&NO_WAIT, || async { Ok(1) })
+            .await;
+        let rejected = result.unwrap_err();
+        assert_eq!(rejected.to_string(), "Custom error: Circuit breaker open");
+        assert!(!rejected.is_retriable());
+        assert_eq!(rejected.http_status(), 503);
+        assert!(rejected.retry_telemetry().is_none());
+
+        // And: non-retriable errors should not open a fresh circuit
+        let fresh = pipeline
+            .clone()
+            .with_circuit_breaker(1, Duration::from_secs(60));
+        let result = fresh
+            .execute_async_with(&NO_WAIT, || async {
+                Err(TylError::validation("id", "Missing"))
+            })
+            .await;
+        assert_eq!(result.unwrap_err().code(), "validation");
+        assert_eq!(fresh.circuit_state(), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_should_fail_slow_attempts_before_fallback() {
+        // Given: a pipeline whose attempts never finish within the timeout
+        let timeout = Duration::from_millis(500);
+        let sleeper = TestSleeper {
+            never: Some(Duration::from_secs(3600)),
+        };
+        let pipeline = ResiliencePipeline::new()
+            .with_timeout(timeout)
+            .with_fallback(|error: TylError| Ok(error.code().to_string()));
+
+        // When: running an operation slower than the timeout
+        let result = pipeline
+            .execute_async_with(&sleeper, || async {
+                std::future::pending::<()>().await;
+                Ok("unreachable".to_string())
+            })
+            .await;
+
+        // Then: the fallback should receive the timeout error
+        assert_eq!(result.unwrap(), "timeout");
+    }
+
+    #[test]
+    fn test_half_open_circuit_should_let_a_single_probe_through() {
+        // Given: a circuit opened by a failure, whose open period has passed
+        let breaker = CircuitBreaker::new(None, 1, Duration::from_millis(50));
+        breaker.record(true);
+        assert!(breaker.check().is_err());
+        std::thread::sleep(Duration::from_millis(60));
+
+        // When/Then: only one call should go through until the probe reports
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err());
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_should_list_open_circuit_breakers() {
+        // Given: a named circuit breaker opened by a failure
+        let pipeline: ResiliencePipeline<u32> = ResiliencePipeline::new()
+            .with_named_circuit_breaker("payments", 1, Duration::from_secs(60));
+        let result = pipeline
+            .execute_async_with(&NO_WAIT, || async {
+                Err(TylError::network("Connection reset"))
+            })
+            .await;
+        assert!(result.is_err());
+
+        // When: taking a diagnostics snapshot
+        let snapshot = crate::diagnostics::snapshot();
+
+        // Then: the breaker should be listed while the pipeline lives
+        let breaker = snapshot
+            .open_circuit_breakers
+            .iter()
+            .find(|breaker| breaker.name.as_deref() == Some("payments"))
+            .unwrap();
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert_eq!(breaker.consecutive_failures, 1);
+        drop(pipeline);
+        assert!(open_circuit_breakers()
+            .iter()
+            .all(|breaker| breaker.name.as_deref() != Some("payments")));
+    }
+
+    #[tokio::test]
+    async fn test_full_bulkhead_should_not_open_the_circuit() {
+        // Given: a pipeline whose only bulkhead slot is taken
+        let pipeline: ResiliencePipeline<u32> = ResiliencePipeline::new()
+            .with_bulkhead(1)
+            .with_circuit_breaker(1, Duration::from_secs(60));
+        let _permit = pipeline.bulkhead.as_ref().unwrap().try_acquire().unwrap();
+
+        // When: calling through it
+        let result = pipeline
+            .execute_async_with(&NO_WAIT, || async { Ok(1) })
+            .await;
+
+        // Then: the call should be rejected without counting as a failure
+        let error = result.unwrap_err();
+        assert_eq!(error.category().category_name(), "ResourceExhaustion");
+        assert_eq!(pipeline.circuit_state(), Some(CircuitState::Closed));
+    }
+
+    #[test]
+    fn test_bulkhead_should_reject_calls_beyond_the_limit() {
+        let bulkhead = Arc::new(Bulkhead {
+            max_concurrent: 1,
+            in_flight: AtomicUsize::new(0),
+        });
+        let permit = bulkhead.try_acquire().unwrap();
+        let rejected = bulkhead.try_acquire().err().unwrap();
+        assert_eq!(rejected.category().category_name(), "ResourceExhaustion");
+        drop(permit);
+        assert!(bulkhead.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn test_config_should_validate_limits() {
+        let error = ResilienceConfig::from_json(r#"{"max_concurrent": 0}"#).unwrap_err();
+        assert_eq!(error.metadata()["config_key"], "max_concurrent");
+
+        let config = ResilienceConfig::from_json(r#"{"retry": {"max_attempts": 1}}"#).unwrap();
+        let pipeline: ResiliencePipeline<()> = ResiliencePipeline::from_config(&config);
+        assert_eq!(pipeline.retry.as_ref().unwrap().max_attempts, 1);
+        assert!(pipeline.circuit_state().is_none());
+    }
+}
@@ -3,8 +3,20 @@
 //! This module provides abstractions for retry logic, policies, and utilities
 //! for implementing robust retry mechanisms in error-prone operations.
 
-use crate::category::ErrorCategory;
-use crate::error::TylError;
+use crate::cancel::{sleep_unless_cancelled, NeverCancelled, ShouldCancel};
+use crate::category::{BuiltinCategory, ErrorCategory, RetryScope};
+use crate::clock::Instant;
+use crate::error::{TylError, TylResult, SENSITIVE_FIELDS};
+use crate::extras::ErrorExtras;
+use crate::limits::limit_value;
+use crate::retry_budget::RetryBudget;
+use crate::settings::{ErrorSettings, LogLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Trait for errors that support retry logic.
@@ -29,7 +41,7 @@ impl RetryableError for TylError {
     }
 
     fn retry_delay(&self, attempt: usize) -> Duration {
-        self.category().retry_delay(attempt)
+        self.category().retry_delay_for(self, attempt)
     }
 
     fn max_retries(&self) -> usize {
@@ -37,6 +49,127 @@ impl RetryableError for TylError {
     }
 }
 
+/// Consolidated answer to whether and when an error should be retried, see
+/// [`TylError::retry_hint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryHint {
+    /// How long to wait before the next attempt.
+    pub delay: Duration,
+    /// Who may perform the retry.
+    pub scope: RetryScope,
+    /// Retries left, including the hinted one, within every configured limit.
+    pub remaining_attempts: usize,
+}
+
+/// Algorithm used to randomize retry delays.
+///
+/// Randomizing delays spreads out retries of clients that failed together,
+/// avoiding thundering herds against a recovering dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum JitterKind {
+    /// No randomization; the exponential delay is used as is.
+    None,
+    /// Uniform deviation of up to `jitter_fraction` around the delay (±25% by default).
+    #[default]
+    Proportional,
+    /// Uniform delay between zero and the exponential delay.
+    Full,
+    /// Half the exponential delay plus a uniform random half.
+    Equal,
+    /// Uniform delay between the base delay and three times the previous
+    /// attempt's delay, capped at the maximum delay.
+    Decorrelated,
+}
+
+/// Whether an operation may safely be repeated.
+///
+/// Retrying an operation that already had side effects (a charge, a sent
+/// message) can apply them twice. Retry executors therefore retry
+/// [`NonIdempotent`](Idempotency::NonIdempotent) operations only for errors
+/// their category reports as
+/// [failing before side effects](crate::ErrorClassifier::failed_before_side_effects),
+/// such as refused connections marked with
+/// [`TylError::with_before_side_effects`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Idempotency {
+    /// Repeating the operation has the same effect as running it once.
+    #[default]
+    Idempotent,
+    /// Repeating the operation may apply its side effects again.
+    NonIdempotent,
+    /// The operation carries an idempotency key that lets the receiver
+    /// deduplicate repeats, so it is retried like an idempotent one.
+    Keyed(String),
+}
+
+impl Idempotency {
+    /// Create a keyed idempotency marker.
+    pub fn keyed<K: Into<String>>(key: K) -> Self {
+        Self::Keyed(key.into())
+    }
+
+    /// Check if the operation may be repeated after any retriable error.
+    pub fn is_safe_to_repeat(&self) -> bool {
+        !matches!(self, Self::NonIdempotent)
+    }
+
+    /// Get the idempotency key, if any.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Keyed(key) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+/// Future returned by a [`Sleeper`].
+///
+/// `Send` except on `wasm32`, whose single-threaded timers are not.
+#[cfg(not(target_arch = "wasm32"))]
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Future returned by a [`Sleeper`].
+///
+/// `Send` except on `wasm32`, whose single-threaded timers are not.
+#[cfg(target_arch = "wasm32")]
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Timer used by async retry executors to wait between attempts.
+///
+/// Lets [`RetryPolicy::execute_async_with`] run on any async runtime: the
+/// returned future must complete once `duration` has elapsed, waking its task
+/// through the runtime's timer rather than blocking the thread.
+pub trait Sleeper: Send + Sync {
+    /// Create a future completing after `duration`.
+    fn sleep(&self, duration: Duration) -> SleepFuture;
+}
+
+/// [`Sleeper`] backed by `tokio::time::sleep`. Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioSleeper;
+
+#[cfg(feature = "tokio")]
+impl Sleeper for TokioSleeper {
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// [`Sleeper`] backed by `gloo-timers` (browser and edge `setTimeout`).
+///
+/// Requires the `wasm` feature on `wasm32` targets.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlooSleeper;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+impl Sleeper for GlooSleeper {
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(gloo_timers::future::sleep(duration))
+    }
+}
+
 /// Configurable retry policy for operations.
 ///
 /// Provides a flexible way to define retry behavior that can be customized
@@ -53,6 +186,26 @@ pub struct RetryPolicy {
     pub backoff_multiplier: f64,
     /// Whether to add jitter to delays.
     pub jitter: bool,
+    /// Jitter algorithm applied when `jitter` is enabled; [`JitterKind::None`]
+    /// disables jitter.
+    jitter_kind: JitterKind,
+    /// Maximum relative deviation of [`JitterKind::Proportional`] jitter, in `0.0..=1.0`.
+    jitter_fraction: f64,
+    /// Seed making jitter reproducible; `None` draws fresh randomness.
+    jitter_seed: Option<u64>,
+    /// Idempotency of the operations run with this policy.
+    idempotency: Idempotency,
+    /// Retry non-idempotent operations after any retriable error, not only
+    /// errors that happened before side effects.
+    retry_non_idempotent: bool,
+    /// Who runs this policy; errors whose [`RetryScope`] does not allow it are not retried.
+    retry_scope: RetryScope,
+    /// Budget retries are taken from, [`RetryBudget::global`] unless set with
+    /// [`RetryPolicy::with_retry_budget`].
+    retry_budget: Arc<RetryBudget>,
+    /// Observer recording diagnostics of each failed attempt, set with
+    /// [`RetryPolicy::on_attempt`]; `None` records nothing.
+    attempt_observer: Option<Arc<dyn AttemptObserver>>,
 }
 
 impl Default for RetryPolicy {
@@ -63,6 +216,14 @@ impl Default for RetryPolicy {
             max_delay: Duration::from_secs(30),
             backoff_multiplier: 2.0,
             jitter: true,
+            jitter_kind: JitterKind::Proportional,
+            jitter_fraction: 0.25,
+            jitter_seed: None,
+            idempotency: Idempotency::Idempotent,
+            retry_non_idempotent: false,
+            retry_scope: RetryScope::Caller,
+            retry_budget: RetryBudget::global(),
+            attempt_observer: None,
         }
     }
 }
@@ -98,39 +259,210 @@ impl RetryPolicy {
     }
 
     /// Enable or disable jitter.
+    ///
+    /// Enabling jitter keeps the selected algorithm, or selects
+    /// [`JitterKind::Proportional`] if [`JitterKind::None`] was selected.
     pub fn with_jitter(mut self, jitter: bool) -> Self {
         self.jitter = jitter;
+        if jitter && self.jitter_kind == JitterKind::None {
+            self.jitter_kind = JitterKind::Proportional;
+        }
         self
     }
 
+    /// Select the jitter algorithm; [`JitterKind::None`] disables jitter.
+    pub fn with_jitter_kind(mut self, kind: JitterKind) -> Self {
+        self.jitter_kind = kind;
+        self.jitter = kind != JitterKind::None;
+        self
+    }
+
+    /// Set the maximum relative deviation of proportional jitter, clamped to `0.0..=1.0`.
+    pub fn with_jitter_fraction(mut self, fraction: f64) -> Self {
+        self.jitter_fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Make jitter reproducible by deriving it from a seed and the attempt number.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = Some(seed);
+        self
+    }
+
+    /// Declare the idempotency of the operations run with this policy.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{Idempotency, RetryPolicy, TylError, TylResult};
+    ///
+    /// let policy = RetryPolicy::fast()
+    ///     .with_base_delay(Duration::from_millis(1))
+    ///     .with_idempotency(Idempotency::NonIdempotent);
+    ///
+    /// // The request may have reached the server: not retried.
+    /// let mut calls = 0;
+    /// let result: TylResult<()> = policy.execute(|| {
+    ///     calls += 1;
+    ///     Err(TylError::network("Connection reset by peer"))
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(calls, 1);
+    ///
+    /// // The connection was never established: safe to retry.
+    /// let mut calls = 0;
+    /// let result: TylResult<()> = policy.execute(|| {
+    ///     calls += 1;
+    ///     Err(TylError::network("Connection refused").with_before_side_effects())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(calls, 4);
+    /// ```
+    pub fn with_idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = idempotency;
+        self
+    }
+
+    /// Retry non-idempotent operations after any retriable error.
+    ///
+    /// Use only when duplicate side effects are acceptable or handled elsewhere.
+    pub fn allow_non_idempotent_retries(mut self, allow: bool) -> Self {
+        self.retry_non_idempotent = allow;
+        self
+    }
+
+    /// Set who runs this policy, [`RetryScope::Caller`] by default.
+    ///
+    /// Retry queues, workers and other infrastructure retrying on behalf of
+    /// callers use [`RetryScope::Infrastructure`], so that errors restricted
+    /// to infrastructure are retried by them but not by application code.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, RetryPolicy, RetryScope, TylError};
+    ///
+    /// let category = ErrorCategory::network().with_retry_scope(RetryScope::Infrastructure);
+    /// let error = TylError::business_logic("Sidecar reset the connection", category.into_classifier());
+    ///
+    /// assert!(!RetryPolicy::new().explain_retry(&error, 0).retriable);
+    /// let infrastructure = RetryPolicy::new().with_retry_scope(RetryScope::Infrastructure);
+    /// assert!(infrastructure.explain_retry(&error, 0).retriable);
+    /// ```
+    pub fn with_retry_scope(mut self, scope: RetryScope) -> Self {
+        self.retry_scope = scope;
+        self
+    }
+
+    /// Take retries from the given budget instead of the process-wide
+    /// [`RetryBudget::global`], e.g. to isolate a client from the others.
+    pub fn with_retry_budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Record every failed attempt, letting `observer` add diagnostics to it.
+    ///
+    /// The records are kept in [`RetryTelemetry::attempt_records`] when the
+    /// executor gives up after retrying, and so reach the metadata of the
+    /// final error's context under `retry_attempt_records`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{AttemptRecord, RetryPolicy, TylError, TylResult};
+    ///
+    /// let hosts = ["db-1", "db-2", "db-3"];
+    /// let policy = RetryPolicy::new()
+    ///     .with_max_attempts(2)
+    ///     .with_base_delay(Duration::from_millis(1))
+    ///     .on_attempt(move |_: &TylError, record: &mut AttemptRecord| {
+    ///         record.insert("host", hosts[record.attempt - 1]);
+    ///     });
+    ///
+    /// let result: TylResult<()> = policy.execute(|| Err(TylError::database("Deadlock")));
+    /// let context = result.unwrap_err().to_context("orders.save".into());
+    /// let records = context.get_metadata("retry_attempt_records").unwrap();
+    /// assert_eq!(records[2]["metadata"]["host"], "db-3");
+    /// ```
+    pub fn on_attempt<O: AttemptObserver + 'static>(mut self, observer: O) -> Self {
+        self.attempt_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Get the jitter algorithm.
+    pub fn jitter_kind(&self) -> JitterKind {
+        self.jitter_kind
+    }
+
+    /// Get the idempotency of the operations run with this policy.
+    pub fn idempotency(&self) -> &Idempotency {
+        &self.idempotency
+    }
+
+    /// Get who runs this policy.
+    pub fn retry_scope(&self) -> RetryScope {
+        self.retry_scope
+    }
+
+    /// Get the budget retries are taken from.
+    pub fn retry_budget(&self) -> &Arc<RetryBudget> {
+        &self.retry_budget
+    }
+
     /// Calculate the delay for a given attempt number.
     ///
     /// # Arguments
     /// * `attempt` - The attempt number (1-based)
     ///
     /// # Returns
-    /// The calculated delay duration.
+    /// The calculated delay duration, never shorter than `TYL_ERROR_MIN_DELAY`.
     pub fn calculate_delay(&self, attempt: usize) -> Duration {
+        let delay = self.backoff_delay(attempt);
+
+        // Apply jitter if enabled
+        let delay = if self.jitter {
+            self.add_jitter(delay, attempt)
+        } else {
+            delay
+        };
+        ErrorSettings::global().clamp_delay(delay)
+    }
+
+    /// Calculate the capped exponential delay for an attempt, without jitter.
+    ///
+    /// # Arguments
+    /// * `attempt` - The attempt number (1-based); attempt 0 has no delay
+    pub(crate) fn backoff_delay(&self, attempt: usize) -> Duration {
         if attempt == 0 {
             return Duration::from_millis(0);
         }
 
+        let exponent = (attempt - 1).min(i32::MAX as usize) as i32;
         let exponential_delay =
-            self.base_delay.as_millis() as f64 * self.backoff_multiplier.powi((attempt - 1) as i32);
-
-        let mut delay = Duration::from_millis(exponential_delay as u64);
+            self.base_delay.as_millis() as f64 * self.backoff_multiplier.powi(exponent);
 
         // Apply maximum delay cap
-        if delay > self.max_delay {
-            delay = self.max_delay;
-        }
-
-        // Apply jitter if enabled
-        if self.jitter {
-            delay = self.add_jitter(delay);
-        }
+        Duration::from_millis(exponential_delay as u64).min(self.max_delay)
+    }
 
-        delay
+    /// Preview the delays before the next `retries` retries.
+    ///
+    /// Jitter is applied only when the policy has a
+    /// [seed](RetryPolicy::with_jitter_seed), so the preview matches what a
+    /// seeded executor sleeps; unseeded policies show the delays without
+    /// jitter. `max_attempts` is not applied.
+    pub fn schedule(&self, retries: usize) -> RetrySchedule {
+        let delays = (1..=retries)
+            .map(|attempt| match self.jitter_seed {
+                Some(_) => self.calculate_delay(attempt),
+                None => ErrorSettings::global().clamp_delay(self.backoff_delay(attempt)),
+            })
+            .collect();
+        RetrySchedule { delays }
     }
 
     /// Check if a retry should be attempted for the given attempt number.
@@ -139,30 +471,674 @@ impl RetryPolicy {
     /// * `attempt` - The current attempt number (0-based)
     ///
     /// # Returns
-    /// True if retry should be attempted.
+    /// True if retry should be attempted. `max_attempts` is lowered to
+    /// `TYL_ERROR_MAX_ATTEMPTS_CEILING` when that is set.
     pub fn should_retry(&self, attempt: usize) -> bool {
-        attempt < self.max_attempts
+        attempt < ErrorSettings::global().cap_attempts(self.max_attempts)
+    }
+
+    /// Check whether this policy's executors may retry `error`, regardless of
+    /// the attempt count.
+    ///
+    /// The error's category must be retriable, its [`RetryScope`] must allow
+    /// the policy's and, for [non-idempotent](Idempotency::NonIdempotent)
+    /// operations, the error must have happened before side effects.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{Idempotency, RetryPolicy, TylError};
+    ///
+    /// let policy = RetryPolicy::new();
+    /// assert!(policy.is_retriable(&TylError::network("Connection reset")));
+    /// assert!(!policy.is_retriable(&TylError::validation("id", "Missing")));
+    ///
+    /// let non_idempotent = policy.with_idempotency(Idempotency::NonIdempotent);
+    /// assert!(!non_idempotent.is_retriable(&TylError::timeout("Upstream slow")));
+    /// ```
+    pub fn is_retriable(&self, error: &TylError) -> bool {
+        error.category().is_retriable_for(error) && self.in_scope(error) && self.may_repeat(error)
+    }
+
+    /// Add jitter to a delay duration according to the jitter kind.
+    fn add_jitter(&self, delay: Duration, attempt: usize) -> Duration {
+        if delay.is_zero() || self.jitter_kind == JitterKind::None {
+            return delay;
+        }
+        let unit = self.jitter_unit(attempt);
+
+        match self.jitter_kind {
+            JitterKind::None => delay,
+            JitterKind::Proportional => {
+                let fraction = self.jitter_fraction.clamp(0.0, 1.0);
+                delay.mul_f64(1.0 - fraction + 2.0 * fraction * unit)
+            }
+            JitterKind::Full => delay.mul_f64(unit),
+            JitterKind::Equal => delay / 2 + (delay / 2).mul_f64(unit),
+            JitterKind::Decorrelated => {
+                let previous = if attempt > 1 {
+                    self.backoff_delay(attempt - 1)
+                } else {
+                    self.base_delay
+                };
+                let upper = (previous * 3).min(self.max_delay).max(self.base_delay);
+                (self.base_delay + (upper - self.base_delay).mul_f64(unit)).min(self.max_delay)
+            }
+        }
+    }
+
+    /// Draw a uniform random number in `[0, 1)` for an attempt's jitter.
+    ///
+    /// Seeded policies always use the built-in generator so results are
+    /// reproducible; otherwise the `rand` feature provides the randomness, with
+    /// a time-seeded fallback when it is disabled.
+    fn jitter_unit(&self, attempt: usize) -> f64 {
+        if let Some(seed) = self.jitter_seed {
+            return unit_from_bits(splitmix64(
+                seed ^ (attempt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+            ));
+        }
+
+        #[cfg(feature = "rand")]
+        {
+            rand::random::<f64>()
+        }
+
+        #[cfg(not(feature = "rand"))]
+        {
+            use std::collections::hash_map::DefaultHasher;
+            use std::hash::{Hash, Hasher};
+
+            let mut hasher = DefaultHasher::new();
+            std::thread::current().id().hash(&mut hasher);
+            crate::clock::now().hash(&mut hasher);
+            attempt.hash(&mut hasher);
+            unit_from_bits(splitmix64(hasher.finish()))
+        }
     }
+}
+
+/// SplitMix64 step, used as the seedable jitter generator.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Map random bits to a uniform `f64` in `[0, 1)`.
+fn unit_from_bits(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
 
-    /// Add jitter to a delay duration.
+/// Observer of the failed attempts of retry executors.
+///
+/// Called with the error of each failed attempt and its [`AttemptRecord`],
+/// to which it adds diagnostics such as the host tried; see
+/// [`RetryPolicy::on_attempt`]. Implemented for closures.
+pub trait AttemptObserver: Send + Sync {
+    /// Add diagnostics of a failed attempt to its record.
+    fn on_attempt(&self, error: &TylError, record: &mut AttemptRecord);
+}
+
+impl<F> AttemptObserver for F
+where
+    F: Fn(&TylError, &mut AttemptRecord) + Send + Sync,
+{
+    fn on_attempt(&self, error: &TylError, record: &mut AttemptRecord) {
+        self(error, record)
+    }
+}
+
+impl fmt::Debug for dyn AttemptObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AttemptObserver")
+    }
+}
+
+/// One failed attempt of a retry executor with an [`AttemptObserver`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    /// Attempt number, starting at 1.
+    pub attempt: usize,
+    /// Time taken by the attempt, in milliseconds.
+    pub duration_ms: u64,
+    /// Code of the attempt's error (see [`TylError::code`]).
+    pub code: String,
+    /// Diagnostics added by the observer.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, serde_json::Value>,
+    /// Keys of diagnostics that must never appear in redacted output.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    sensitive_keys: BTreeSet<String>,
+}
+
+impl AttemptRecord {
+    /// Add a diagnostic entry to this attempt.
     ///
-    /// Adds up to ±25% jitter to prevent thundering herd problems.
-    fn add_jitter(&self, delay: Duration) -> Duration {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// Values are limited like context metadata, and structured fields that
+    /// identify people (e.g. `subject`) are flagged as sensitive.
+    pub fn insert<K: Into<String>, V: Into<serde_json::Value>>(&mut self, key: K, value: V) {
+        let key = key.into();
+        if SENSITIVE_FIELDS.contains(&key.as_str()) {
+            self.sensitive_keys.insert(key.clone());
+        }
+        self.metadata.insert(key, limit_value(value.into()));
+    }
 
-        let mut hasher = DefaultHasher::new();
-        std::thread::current().id().hash(&mut hasher);
-        std::time::SystemTime::now().hash(&mut hasher);
+    /// Add a diagnostic entry whose value must never appear in redacted output.
+    pub fn insert_sensitive<K: Into<String>, V: Into<serde_json::Value>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) {
+        let key = key.into();
+        self.sensitive_keys.insert(key.clone());
+        self.metadata.insert(key, limit_value(value.into()));
+    }
 
-        let hash = hasher.finish();
-        let jitter_factor = (hash % 50) as f64 / 100.0 + 0.75; // 0.75 to 1.25
+    /// Get the diagnostics added by the observer.
+    pub fn metadata(&self) -> &HashMap<String, serde_json::Value> {
+        &self.metadata
+    }
+
+    /// Check if the diagnostic entry `key` is flagged as sensitive.
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive_keys.contains(key)
+    }
+}
+
+/// Attempts made by a retry executor before it gave up.
+///
+/// Attached to the returned error with [`TylError::with_retry_telemetry`]
+/// whenever at least one retry was made, so callers and logs can tell an
+/// immediate failure from one after several attempts.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetryTelemetry {
+    /// Total number of attempts, including the first one.
+    pub attempts: usize,
+    /// Time from the start of the first attempt to the final failure, in milliseconds.
+    pub elapsed_ms: u64,
+    /// Delay slept before each retry, in milliseconds.
+    pub delays_ms: Vec<u64>,
+    /// Record of every attempt, when the policy has an [`AttemptObserver`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempt_records: Vec<AttemptRecord>,
+}
+
+impl RetryTelemetry {
+    /// Attach telemetry to the final error, unless no retry was made.
+    fn attach(
+        error: TylError,
+        started: Instant,
+        delays: Vec<Duration>,
+        attempt_records: Vec<AttemptRecord>,
+    ) -> TylError {
+        if delays.is_empty() {
+            return error;
+        }
+        let telemetry = Self {
+            attempts: delays.len() + 1,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            delays_ms: delays.iter().map(|d| d.as_millis() as u64).collect(),
+            attempt_records,
+        };
+        error.with_retry_telemetry(telemetry)
+    }
+
+    /// Check if an attempt record holds diagnostics flagged as sensitive.
+    pub(crate) fn has_sensitive_records(&self) -> bool {
+        self.attempt_records
+            .iter()
+            .any(|record| !record.sensitive_keys.is_empty())
+    }
 
-        let jittered_millis = (delay.as_millis() as f64 * jitter_factor) as u64;
-        Duration::from_millis(jittered_millis)
+    /// Build the error of a cancelled executor, given the error of the last
+    /// attempt, kept as its source, the number of attempts and the delays
+    /// fully slept.
+    #[track_caller]
+    fn cancelled(
+        last_error: Option<TylError>,
+        attempts: usize,
+        started: Instant,
+        delays: Vec<Duration>,
+        attempt_records: Vec<AttemptRecord>,
+    ) -> TylError {
+        let Some(last_error) = last_error else {
+            return TylError::cancelled("Retries cancelled before the first attempt");
+        };
+        let error = TylError::Cancelled {
+            message: format!("Retries cancelled after {attempts} attempt(s)"),
+            source: Some(Arc::new(last_error)),
+            extras: ErrorExtras::new(),
+        };
+        let telemetry = Self {
+            attempts,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            delays_ms: delays.iter().map(|d| d.as_millis() as u64).collect(),
+            attempt_records,
+        };
+        error.with_retry_telemetry(telemetry)
     }
 }
 
+/// Planned delays before successive retries.
+///
+/// Returned by [`RetryPolicy::schedule`] and [`ErrorCategory::schedule`] to
+/// preview backoff without running an operation. Displays as
+/// `#1 100ms, #2 200ms (total 300ms)`.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::RetryPolicy;
+///
+/// let schedule = RetryPolicy::new()
+///     .with_base_delay(Duration::from_millis(100))
+///     .schedule(3);
+/// assert_eq!(schedule.total(), Duration::from_millis(700));
+/// assert_eq!(schedule.to_string(), "#1 100ms, #2 200ms, #3 400ms (total 700ms)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetrySchedule {
+    /// Delay before each retry, first retry first.
+    pub delays: Vec<Duration>,
+}
+
+impl RetrySchedule {
+    /// Get the number of retries in the schedule.
+    pub fn len(&self) -> usize {
+        self.delays.len()
+    }
+
+    /// Check if the schedule contains no retries.
+    pub fn is_empty(&self) -> bool {
+        self.delays.is_empty()
+    }
+
+    /// Get the total time spent waiting across all retries.
+    pub fn total(&self) -> Duration {
+        self.delays.iter().sum()
+    }
+}
+
+impl fmt::Display for RetrySchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no retries");
+        }
+        for (index, delay) in self.delays.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "#{} {delay:?}", index + 1)?;
+        }
+        write!(f, " (total {:?})", self.total())
+    }
+}
+
+/// Retry executors running an operation until it succeeds or retries are exhausted.
+impl RetryPolicy {
+    /// Run a blocking operation, retrying retriable failures with this policy's delays.
+    ///
+    /// The operation is retried only while the returned error's category is
+    /// retriable and fewer than `max_attempts` retries have been made;
+    /// [non-idempotent](Idempotency::NonIdempotent) operations additionally
+    /// require the error to have happened before side effects. Every retry is
+    /// taken from the policy's [`RetryBudget`]; once it is spent, the executor
+    /// gives up with a resource exhaustion error wrapping the last error. Once
+    /// retrying stops after at least one retry, the last error is returned
+    /// with the attempt [`RetryTelemetry`] attached, see
+    /// [`TylError::retry_telemetry`]; errors not retried at all are returned
+    /// unchanged.
+    ///
+    /// Blocks the thread between attempts, which `wasm32` targets do not
+    /// support; use [`RetryPolicy::execute_async`] there.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{RetryPolicy, TylError};
+    ///
+    /// let policy = RetryPolicy::fast().with_base_delay(Duration::from_millis(1));
+    /// let mut calls = 0;
+    /// let result = policy.execute(|| {
+    ///     calls += 1;
+    ///     if calls < 3 {
+    ///         Err(TylError::network("Connection reset"))
+    ///     } else {
+    ///         Ok(calls)
+    ///     }
+    /// });
+    /// assert_eq!(result.unwrap(), 3);
+    /// ```
+    pub fn execute<T, F>(&self, mut operation: F) -> TylResult<T>
+    where
+        F: FnMut() -> TylResult<T>,
+    {
+        let started = Instant::now();
+        let mut delays = Vec::new();
+        let mut records = Vec::new();
+        loop {
+            let attempt_started = Instant::now();
+            match operation() {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    self.record_attempt(&mut records, &error, attempt_started);
+                    let delay = match self.take_retry(error, delays.len()) {
+                        Ok(delay) => delay,
+                        Err(error) => {
+                            return Err(RetryTelemetry::attach(error, started, delays, records))
+                        }
+                    };
+                    delays.push(delay);
+                    std::thread::sleep(delay);
+                }
+            }
+        }
+    }
+
+    /// Run an async operation, retrying retriable failures with this policy's delays.
+    ///
+    /// Same semantics as [`RetryPolicy::execute`], sleeping with `tokio::time::sleep`
+    /// between attempts. Requires the `tokio` feature, or the `wasm` feature on
+    /// `wasm32`, where [`GlooSleeper`] is used instead; see
+    /// [`RetryPolicy::execute_async_with`] for other runtimes.
+    #[cfg(any(feature = "tokio", all(target_arch = "wasm32", feature = "wasm")))]
+    pub async fn execute_async<T, F, Fut>(&self, operation: F) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+    {
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        let sleeper = GlooSleeper;
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        let sleeper = TokioSleeper;
+        self.execute_async_with(&sleeper, operation).await
+    }
+
+    /// Run an async operation, waiting between attempts with the given [`Sleeper`].
+    ///
+    /// Same semantics as [`RetryPolicy::execute`]; available without the
+    /// `tokio` feature so any runtime can supply its timer.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tyl_errors::{RetryPolicy, SleepFuture, Sleeper, TylError};
+    ///
+    /// /// Sleeper of a runtime without timers, yielding immediately.
+    /// struct NoWait;
+    ///
+    /// impl Sleeper for NoWait {
+    ///     fn sleep(&self, _duration: Duration) -> SleepFuture {
+    ///         Box::pin(std::future::ready(()))
+    ///     }
+    /// }
+    ///
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # runtime.block_on(async {
+    /// let mut calls = 0;
+    /// let result = RetryPolicy::network()
+    ///     .execute_async_with(&NoWait, || {
+    ///         calls += 1;
+    ///         let attempt = calls;
+    ///         async move {
+    ///             if attempt < 3 {
+    ///                 Err(TylError::network("Connection reset"))
+    ///             } else {
+    ///                 Ok(attempt)
+    ///             }
+    ///         }
+    ///     })
+    ///     .await;
+    /// assert_eq!(result.unwrap(), 3);
+    /// # });
+    /// ```
+    pub async fn execute_async_with<T, F, Fut, S>(&self, sleeper: &S, operation: F) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+    {
+        self.run_async(sleeper, None::<&NeverCancelled>, operation)
+            .await
+    }
+
+    /// Run an async operation like [`RetryPolicy::execute_async`], stopping
+    /// once `cancel` signals shutdown.
+    ///
+    /// Requires the `tokio` feature, or the `wasm` feature on `wasm32`; see
+    /// [`RetryPolicy::execute_async_with_cancel`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tokio_util::sync::CancellationToken;
+    /// use tyl_errors::{RetryPolicy, TylError, TylResult};
+    ///
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// # runtime.block_on(async {
+    /// let token = CancellationToken::new();
+    /// let policy = RetryPolicy::new().with_base_delay(Duration::from_secs(60));
+    /// let result: TylResult<()> = policy
+    ///     .execute_async_cancellable(&token, || {
+    ///         token.cancel();
+    ///         async { Err(TylError::network("Connection reset")) }
+    ///     })
+    ///     .await;
+    ///
+    /// let error = result.unwrap_err();
+    /// assert_eq!(error.code(), "cancelled");
+    /// assert_eq!(error.retry_telemetry().unwrap().attempts, 1);
+    /// # });
+    /// ```
+    #[cfg(any(feature = "tokio", all(target_arch = "wasm32", feature = "wasm")))]
+    pub async fn execute_async_cancellable<T, F, Fut, C>(
+        &self,
+        cancel: &C,
+        operation: F,
+    ) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+        C: ShouldCancel + ?Sized,
+    {
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        let sleeper = GlooSleeper;
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        let sleeper = TokioSleeper;
+        self.execute_async_with_cancel(&sleeper, cancel, operation)
+            .await
+    }
+
+    /// Run an async operation like [`RetryPolicy::execute_async_with`],
+    /// stopping once `cancel` signals shutdown.
+    ///
+    /// `cancel` is checked before every attempt and aborts the delay between
+    /// attempts. A cancelled executor returns a [`TylError::Cancelled`] error
+    /// whose source is the last attempt's error, with the [`RetryTelemetry`]
+    /// of the attempts made so far (if any) attached.
+    pub async fn execute_async_with_cancel<T, F, Fut, S, C>(
+        &self,
+        sleeper: &S,
+        cancel: &C,
+        operation: F,
+    ) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+        C: ShouldCancel + ?Sized,
+    {
+        self.run_async(sleeper, Some(cancel), operation).await
+    }
+
+    /// Run an async operation, stopping once `cancel`, if any, signals shutdown.
+    async fn run_async<T, F, Fut, S, C>(
+        &self,
+        sleeper: &S,
+        cancel: Option<&C>,
+        mut operation: F,
+    ) -> TylResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TylResult<T>>,
+        S: Sleeper + ?Sized,
+        C: ShouldCancel + ?Sized,
+    {
+        let started = Instant::now();
+        let mut delays = Vec::new();
+        let mut records = Vec::new();
+        let mut last_error = None;
+        let mut attempts = 0;
+        loop {
+            if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+                let cancelled =
+                    RetryTelemetry::cancelled(last_error, attempts, started, delays, records);
+                return Err(cancelled);
+            }
+            let attempt_started = Instant::now();
+            attempts += 1;
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    self.record_attempt(&mut records, &error, attempt_started);
+                    let delay = match self.take_retry(error.clone(), delays.len()) {
+                        Ok(delay) => delay,
+                        Err(error) => {
+                            return Err(RetryTelemetry::attach(error, started, delays, records))
+                        }
+                    };
+                    if sleep_unless_cancelled(sleeper, cancel, delay).await {
+                        let cancelled = RetryTelemetry::cancelled(
+                            Some(error),
+                            attempts,
+                            started,
+                            delays,
+                            records,
+                        );
+                        return Err(cancelled);
+                    }
+                    delays.push(delay);
+                    last_error = Some(error);
+                }
+            }
+        }
+    }
+
+    /// Record a failed attempt through the attempt observer, if any.
+    fn record_attempt(
+        &self,
+        records: &mut Vec<AttemptRecord>,
+        error: &TylError,
+        attempt_started: Instant,
+    ) {
+        let Some(observer) = &self.attempt_observer else {
+            return;
+        };
+        let mut record = AttemptRecord {
+            attempt: records.len() + 1,
+            duration_ms: attempt_started.elapsed().as_millis() as u64,
+            code: error.code().to_string(),
+            ..AttemptRecord::default()
+        };
+        observer.on_attempt(error, &mut record);
+        records.push(record);
+    }
+
+    /// Decide whether to retry and take the retry from the budget.
+    ///
+    /// The decision is explained and logged when debug logging is enabled
+    /// (see [`RetryPolicy::explain_retry`]). Returns the delay before the next
+    /// attempt, or the error to give up with when no retry should be made.
+    ///
+    /// # Arguments
+    /// * `error` - The error returned by the last attempt
+    /// * `retries` - Number of retries already made (0-based)
+    pub(crate) fn take_retry(&self, error: TylError, retries: usize) -> Result<Duration, TylError> {
+        let delay = if TylError::log_enabled(LogLevel::Debug) {
+            let decision = self.explain_retry(&error, retries);
+            eprintln!("[{}] {}", LogLevel::Debug.as_str(), decision);
+            decision.chosen_delay.filter(|_| decision.retriable)
+        } else {
+            self.next_delay(&error, retries)
+        };
+        let Some(delay) = delay else {
+            return Err(error);
+        };
+        if self.retry_budget.try_acquire() {
+            Ok(delay)
+        } else {
+            Err(self.retry_budget.exhausted(error))
+        }
+    }
+
+    /// Get the delay before retrying `error` after `retries` retries, `None`
+    /// if it is not retried.
+    ///
+    /// Decides as [`RetryPolicy::explain_retry`] does, without explaining.
+    fn next_delay(&self, error: &TylError, retries: usize) -> Option<Duration> {
+        let category = error.category();
+        let retriable = category.is_retriable_for(error)
+            && category.max_retries().map_or(true, |limit| retries < limit)
+            && self.should_retry(retries)
+            && self.in_scope(error)
+            && self.may_repeat(error);
+        retriable.then(|| self.calculate_delay(retries + 1))
+    }
+
+    /// Check if the operation may be repeated after `error` given its idempotency.
+    pub(crate) fn may_repeat(&self, error: &TylError) -> bool {
+        self.idempotency.is_safe_to_repeat()
+            || self.retry_non_idempotent
+            || error.category().failed_before_side_effects(error)
+    }
+
+    /// Check if this policy's [`RetryScope`] may retry `error`.
+    pub(crate) fn in_scope(&self, error: &TylError) -> bool {
+        error.retry_scope().allows(self.retry_scope)
+    }
+}
+
+/// Run an operation with a [`RetryPolicy`], retrying retriable failures.
+///
+/// The block is re-evaluated for every attempt and must produce a
+/// [`TylResult`]. By default the block is async and the macro must be used
+/// inside an async context (requires the `tokio` feature); prefix the policy
+/// with `sync` to run a blocking block instead.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use tyl_errors::{with_retry, RetryPolicy, TylError, TylResult};
+///
+/// fn fetch(attempts: &mut u32) -> TylResult<&'static str> {
+///     *attempts += 1;
+///     if *attempts < 2 {
+///         Err(TylError::network("Connection refused"))
+///     } else {
+///         Ok("payload")
+///     }
+/// }
+///
+/// let policy = RetryPolicy::network().with_base_delay(Duration::from_millis(1));
+/// let mut attempts = 0;
+/// let body = with_retry!(sync policy, { fetch(&mut attempts) });
+/// assert_eq!(body.unwrap(), "payload");
+///
+/// // Async call sites (with the `tokio` feature):
+/// // let body = with_retry!(RetryPolicy::network(), { client.get(url).await });
+/// ```
+#[macro_export]
+macro_rules! with_retry {
+    (sync $policy:expr, $body:block) => {
+        $crate::RetryPolicy::execute(&$policy, || $body)
+    };
+    ($policy:expr, $body:block) => {
+        $crate::RetryPolicy::execute_async(&$policy, || async { $body }).await
+    };
+}
+
 /// Predefined retry policies for common scenarios.
 impl RetryPolicy {
     /// Fast retry policy for quick operations.
@@ -173,6 +1149,7 @@ impl RetryPolicy {
             max_delay: Duration::from_secs(1),
             backoff_multiplier: 1.5,
             jitter: true,
+            ..Self::default()
         }
     }
 
@@ -189,6 +1166,7 @@ impl RetryPolicy {
             max_delay: Duration::from_secs(60),
             backoff_multiplier: 2.0,
             jitter: true,
+            ..Self::default()
         }
     }
 
@@ -200,6 +1178,7 @@ impl RetryPolicy {
             max_delay: Duration::from_secs(30),
             backoff_multiplier: 2.0,
             jitter: true,
+            ..Self::default()
         }
     }
 
@@ -211,6 +1190,62 @@ impl RetryPolicy {
             max_delay: Duration::from_secs(10),
             backoff_multiplier: 2.0,
             jitter: true,
+            ..Self::default()
+        }
+    }
+
+    /// Retry policy tuned for errors of the given category.
+    ///
+    /// Builtin categories (and custom categories named like one) get the
+    /// preset that also drives [`ErrorCategory::retry_delay`]. Other custom
+    /// categories get the standard policy, without retries if the category is
+    /// not retriable.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorCategory, RetryPolicy};
+    ///
+    /// let policy = RetryPolicy::for_category(&ErrorCategory::service_unavailable());
+    /// assert_eq!(policy.base_delay, std::time::Duration::from_secs(1));
+    /// assert_eq!(RetryPolicy::for_category(&ErrorCategory::validation()).max_attempts, 0);
+    /// ```
+    pub fn for_category(category: &ErrorCategory) -> Self {
+        let builtin = match category {
+            ErrorCategory::Builtin(builtin) => Some(builtin.clone()),
+            ErrorCategory::Custom(custom) => BuiltinCategory::from_name(custom.category_name()),
+        };
+        match builtin {
+            Some(builtin) => Self::for_builtin(&builtin),
+            None if category.is_retriable() => Self::standard(),
+            None => Self::standard().with_max_attempts(0),
+        }
+    }
+
+    /// Preset for a builtin category.
+    ///
+    /// Delays start at the category's base delay and are capped at 60 times it.
+    pub(crate) fn for_builtin(category: &BuiltinCategory) -> Self {
+        let (max_attempts, base_delay) = match category {
+            BuiltinCategory::Transient => (3, Duration::from_millis(100)),
+            BuiltinCategory::Network => (4, Duration::from_millis(500)),
+            BuiltinCategory::ServiceUnavailable => (5, Duration::from_secs(1)),
+            BuiltinCategory::ResourceExhaustion => (3, Duration::from_secs(5)),
+            _ => (0, Duration::from_millis(100)),
+        };
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay: base_delay * 60,
+            backoff_multiplier: 2.0,
+            jitter: true,
+            jitter_kind: JitterKind::Proportional,
+            jitter_fraction: 0.25,
+            jitter_seed: None,
+            idempotency: Idempotency::Idempotent,
+            retry_non_idempotent: false,
+            retry_scope: RetryScope::Caller,
+            retry_budget: RetryBudget::global(),
+            attempt_observer: None,
         }
     }
 }
@@ -250,3 +1285,435 @@ pub fn is_retriable(category: &ErrorCategory) -> bool {
 pub fn calculate_retry_delay(category: &ErrorCategory, attempt: usize) -> Duration {
     category.retry_delay(attempt)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[test]
+    fn test_retry_hint_should_merge_limit_scope_and_server_delay() {
+        // Given: an error that infrastructure may retry twice
+        let category = ErrorCategory::custom_fn("HintTest", true, |attempt| {
+            Duration::from_millis(100 * attempt as u64)
+        })
+        .with_max_attempts(3)
+        .with_retry_scope(RetryScope::Infrastructure);
+        let error = TylError::business_logic("Queue full", category.into_classifier());
+
+        // When: asking for hints as retries are made
+        let first = error.retry_hint(0).unwrap();
+        let second = error
+            .retry_hint_with(1, Some(Duration::from_secs(5)))
+            .unwrap();
+
+        // Then: each hint should reflect every source
+        assert_eq!(first.delay, error.category().retry_delay_for(&error, 1));
+        assert_eq!(first.scope, RetryScope::Infrastructure);
+        assert_eq!(first.remaining_attempts, 2);
+        assert_eq!(second.delay, Duration::from_secs(5));
+        assert_eq!(second.remaining_attempts, 1);
+        assert!(error.retry_hint(2).is_none());
+        assert_eq!(error.retry_hint(1).is_some(), error.should_retry(1));
+        assert!(TylError::conflict("Duplicate").retry_hint(0).is_none());
+    }
+
+    #[test]
+    fn test_execute_should_retry_retriable_errors_until_success() {
+        // Given: an operation failing twice with a network error
+        let mut calls = 0;
+
+        // When: executing it with a retry policy
+        let result = quick_policy().execute(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(TylError::network("timeout"))
+            } else {
+                Ok("done")
+            }
+        });
+
+        // Then: it should eventually succeed
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_execute_should_not_retry_permanent_errors() {
+        // Given: an operation failing with a validation error
+        let mut calls = 0;
+
+        // When: executing it
+        let result: TylResult<()> = quick_policy().execute(|| {
+            calls += 1;
+            Err(TylError::validation("email", "invalid"))
+        });
+
+        // Then: it should fail after a single attempt, unchanged
+        assert!(result.unwrap_err().retry_telemetry().is_none());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_execute_should_stop_after_max_attempts() {
+        // Given: an operation that always fails transiently
+        let mut calls = 0;
+
+        // When: executing it with two retries allowed
+        let result: TylResult<()> = with_retry!(sync quick_policy().with_max_attempts(2), {
+            calls += 1;
+            Err(TylError::database("deadlock"))
+        });
+
+        // Then: it should run once plus two retries and report them
+        assert_eq!(calls, 3);
+        let error = result.unwrap_err();
+        let telemetry = error.retry_telemetry().unwrap();
+        assert_eq!(telemetry.attempts, 3);
+        assert_eq!(telemetry.delays_ms, vec![1, 2]);
+        assert_eq!(error.metadata()["retry_attempts"], 3);
+        assert!(error.is_retriable());
+        assert!(matches!(error, TylError::Database { .. }));
+    }
+
+    #[test]
+    fn test_unexplained_decisions_should_match_explained_ones() {
+        // Given: policies and errors covering every rule of a decision
+        let policies = [
+            quick_policy().with_max_attempts(2),
+            quick_policy().with_idempotency(Idempotency::NonIdempotent),
+            quick_policy().with_retry_scope(RetryScope::Infrastructure),
+        ];
+        let errors = [
+            TylError::network("Connection reset"),
+            TylError::network("Connection refused").with_before_side_effects(),
+            TylError::validation("email", "Invalid"),
+            TylError::business_logic(
+                "Declined",
+                ErrorCategory::transient()
+                    .with_max_attempts(1)
+                    .into_classifier(),
+            ),
+        ];
+
+        // When/Then: the fast path should agree with the explanation
+        for policy in &policies {
+            for error in &errors {
+                for retries in 0..4 {
+                    let decision = policy.explain_retry(error, retries);
+                    assert_eq!(
+                        policy.next_delay(error, retries),
+                        decision.chosen_delay.filter(|_| decision.retriable),
+                        "{error} after {retries} retries"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_idempotent_operations_should_retry_only_before_side_effects() {
+        // Given: a non-idempotent policy
+        let policy = quick_policy()
+            .with_max_attempts(2)
+            .with_idempotency(Idempotency::NonIdempotent);
+        let run = |policy: &RetryPolicy, message: &'static str| {
+            let mut calls = 0;
+            let _: TylResult<()> = policy.execute(|| {
+                calls += 1;
+                let error = TylError::network(message);
+                Err(if message.contains("refused") {
+                    error.with_before_side_effects()
+                } else {
+                    error
+                })
+            });
+            calls
+        };
+
+        // When/Then: only failures before side effects should be retried
+        assert_eq!(run(&policy, "Connection refused"), 3);
+        assert_eq!(run(&policy, "Connection reset by peer"), 1);
+
+        // And: the override and keyed operations should retry any retriable error
+        let overridden = policy.clone().allow_non_idempotent_retries(true);
+        assert_eq!(run(&overridden, "Connection reset by peer"), 3);
+        let keyed = policy.with_idempotency(Idempotency::keyed("order-42"));
+        assert_eq!(run(&keyed, "Connection reset by peer"), 3);
+        assert_eq!(keyed.idempotency.key(), Some("order-42"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_with_retry_should_support_async_blocks() {
+        // Given: an async operation failing once
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let call = || async {
+            if calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                Err(TylError::network("reset"))
+            } else {
+                Ok(42)
+            }
+        };
+
+        // When: running it through the macro
+        let result = with_retry!(quick_policy(), { call().await });
+
+        // Then: it should succeed on the retry
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_with_should_wait_through_the_sleeper() {
+        use std::sync::Mutex;
+
+        /// Sleeper recording the requested delays instead of waiting.
+        #[derive(Default)]
+        struct Recording(Mutex<Vec<Duration>>);
+
+        impl Sleeper for Recording {
+            fn sleep(&self, duration: Duration) -> SleepFuture {
+                self.0.lock().unwrap().push(duration);
+                Box::pin(std::future::ready(()))
+            }
+        }
+
+        // Given: an always-failing operation and a recording sleeper
+        let sleeper = Recording::default();
+
+        // When: retrying it twice
+        let result: TylResult<()> = quick_policy()
+            .with_max_attempts(2)
+            .execute_async_with(&sleeper, || async { Err(TylError::database("deadlock")) })
+            .await;
+
+        // Then: the sleeper should have been asked for each backoff delay
+        assert_eq!(result.unwrap_err().retry_telemetry().unwrap().attempts, 3);
+        assert_eq!(
+            *sleeper.0.lock().unwrap(),
+            [Duration::from_millis(1), Duration::from_millis(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_with_cancel_should_stop_mid_sleep() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        /// Sleeper cancelling the executor after some slept time.
+        struct CancellingSleeper<'a> {
+            slept: AtomicUsize,
+            cancel_after: usize,
+            cancelled: &'a AtomicBool,
+        }
+
+        impl Sleeper for CancellingSleeper<'_> {
+            fn sleep(&self, duration: Duration) -> SleepFuture {
+                let slept = self
+                    .slept
+                    .fetch_add(duration.as_millis() as usize, Ordering::SeqCst);
+                if slept + duration.as_millis() as usize >= self.cancel_after {
+                    self.cancelled.store(true, Ordering::SeqCst);
+                }
+                Box::pin(std::future::ready(()))
+            }
+        }
+
+        // Given: a policy sleeping 100ms then 200ms, cancelled after 150ms of sleep
+        let cancelled = AtomicBool::new(false);
+        let sleeper = CancellingSleeper {
+            slept: AtomicUsize::new(0),
+            cancel_after: 150,
+            cancelled: &cancelled,
+        };
+        let policy = quick_policy().with_base_delay(Duration::from_millis(100));
+        let cancel = || cancelled.load(Ordering::SeqCst);
+
+        // When: retrying an always-failing operation
+        let mut calls = 0;
+        let result: TylResult<()> = policy
+            .execute_async_with_cancel(&sleeper, &cancel, || {
+                calls += 1;
+                async { Err(TylError::network("Connection reset")) }
+            })
+            .await;
+
+        // Then: the second delay should be aborted in its first poll slices
+        let error = result.unwrap_err();
+        assert_eq!(calls, 2);
+        assert!(matches!(error, TylError::Cancelled { .. }));
+        assert!(!error.is_retriable());
+        assert_eq!(
+            error.to_string(),
+            "Cancelled: Retries cancelled after 2 attempt(s)"
+        );
+        assert_eq!(
+            std::error::Error::source(&error).unwrap().to_string(),
+            "Network error: Connection reset"
+        );
+        let telemetry = error.retry_telemetry().unwrap();
+        assert_eq!((telemetry.attempts, telemetry.delays_ms.len()), (2, 1));
+        assert_eq!(sleeper.slept.load(Ordering::SeqCst), 150);
+
+        // And: an executor cancelled upfront should not run the operation
+        let result: TylResult<()> = policy
+            .execute_async_with_cancel(&sleeper, &cancel, || async { Ok(()) })
+            .await;
+        assert!(matches!(result, Err(TylError::Cancelled { .. })));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_on_attempt_should_record_per_attempt_diagnostics() {
+        // Given: a policy whose observer records the host each attempt tried
+        let policy = quick_policy().with_max_attempts(1).on_attempt(
+            |error: &TylError, record: &mut AttemptRecord| {
+                record.insert("host", format!("replica-{}", record.attempt));
+                record.insert("message", error.to_string());
+                record.insert_sensitive("token", "secret");
+            },
+        );
+
+        // When: the operation fails with a different error on each attempt
+        let mut calls = 0;
+        let result: TylResult<()> = policy
+            .execute_async(|| {
+                calls += 1;
+                let call = calls;
+                async move { Err(TylError::network(format!("reset #{call}"))) }
+            })
+            .await;
+
+        // Then: each attempt should keep its own diagnostics
+        let error = result.unwrap_err();
+        let records = &error.retry_telemetry().unwrap().attempt_records;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].attempt, 2);
+        assert_eq!(records[1].code, "network");
+        assert_eq!(records[1].metadata()["host"], "replica-2");
+        assert_eq!(records[0].metadata()["message"], "Network error: reset #1");
+        assert!(records[0].is_sensitive("token"));
+
+        // And: they should be serialized in the final context
+        let context = error.to_context("replica.read".to_string());
+        let serialized = context.get_metadata("retry_attempt_records").unwrap();
+        assert_eq!(serialized[0]["metadata"]["host"], "replica-1");
+        assert!(context.is_sensitive("retry_attempt_records"));
+    }
+
+    #[test]
+    fn test_for_category_should_drive_builtin_delays() {
+        // Given: the preset of every builtin category
+        for builtin in BuiltinCategory::ALL {
+            let policy = RetryPolicy::for_builtin(&builtin).with_jitter(false);
+            let category = ErrorCategory::Builtin(builtin.clone());
+
+            // When/Then: category delays should match the preset's backoff
+            for attempt in [0, 1, 5, 20] {
+                assert_eq!(
+                    category.retry_delay(attempt),
+                    policy.calculate_delay(attempt + 1)
+                );
+            }
+            assert_eq!(policy.max_attempts > 0, category.is_retriable());
+        }
+    }
+
+    #[test]
+    fn test_jitter_kinds_should_stay_within_bounds() {
+        // Given: a seeded policy whose third attempt backs off to 400ms
+        let policy = RetryPolicy::new()
+            .with_max_delay(Duration::from_secs(1))
+            .with_jitter_seed(7);
+        let base = policy.backoff_delay(3);
+
+        for seed in 0..50 {
+            let policy = policy.clone().with_jitter_seed(seed);
+
+            // When: calculating delays with each jitter kind
+            let proportional = policy.clone().with_jitter_fraction(0.1).calculate_delay(3);
+            let full = policy
+                .clone()
+                .with_jitter_kind(JitterKind::Full)
+                .calculate_delay(3);
+            let equal = policy
+                .clone()
+                .with_jitter_kind(JitterKind::Equal)
+                .calculate_delay(3);
+            let decorrelated = policy
+                .clone()
+                .with_jitter_kind(JitterKind::Decorrelated)
+                .calculate_delay(3);
+
+            // Then: every delay should stay within its algorithm's range
+            assert!(proportional >= base.mul_f64(0.9) && proportional <= base.mul_f64(1.1));
+            assert!(full <= base);
+            assert!(equal >= base / 2 && equal <= base);
+            assert!(decorrelated >= policy.base_delay);
+            assert!(decorrelated <= Duration::from_millis(600));
+        }
+        let mut disabled = policy.clone();
+        disabled.jitter = false;
+        assert_eq!(disabled.calculate_delay(3), base);
+        assert_eq!(
+            policy.with_jitter_kind(JitterKind::None).calculate_delay(3),
+            base
+        );
+    }
+
+    #[test]
+    fn test_seeded_jitter_should_be_reproducible() {
+        let policy = RetryPolicy::new()
+            .with_jitter_kind(JitterKind::Full)
+            .with_jitter_seed(42);
+        let delays: Vec<_> = (1..=5).map(|a| policy.calculate_delay(a)).collect();
+        assert_eq!(
+            delays,
+            (1..=5)
+                .map(|a| policy.calculate_delay(a))
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(
+            delays[0],
+            policy.clone().with_jitter_seed(43).calculate_delay(1)
+        );
+    }
+
+    #[test]
+    fn test_schedule_should_preview_executor_delays() {
+        // Given: an unseeded and a seeded jittered policy
+        let unseeded = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300));
+        let seeded = unseeded.clone().with_jitter_seed(9);
+
+        // When: previewing four retries
+        let plain = unseeded.schedule(4);
+        let jittered = seeded.schedule(4);
+
+        // Then: unseeded schedules omit jitter, seeded ones match the executor
+        assert_eq!(
+            plain.to_string(),
+            "#1 100ms, #2 200ms, #3 300ms, #4 300ms (total 900ms)"
+        );
+        assert_eq!(
+            jittered.delays,
+            (1..=4)
+                .map(|a| seeded.calculate_delay(a))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ErrorCategory::transient().schedule(2).delays,
+            [
+                ErrorCategory::transient().retry_delay(1),
+                ErrorCategory::transient().retry_delay(2)
+            ]
+        );
+        assert_eq!(RetryPolicy::new().schedule(0).to_string(), "no retries");
+    }
+}
@@ -0,0 +1,236 @@
+//! Adapters for third-party retry crates.
+//!
+//! Teams already retrying with `backon` or `futures-retry` keep their retry
+//! loops and plug a [`RetryPolicy`] in as the source of delays and decisions:
+//! with the `backon` feature a policy is a `backon::BackoffBuilder`, and with
+//! the `futures-retry` feature it is a `futures_retry::ErrorHandler` for
+//! [`TylError`]. Both apply the policy's and the failed errors' category
+//! attempt limits and take every retry from its
+//! [`RetryBudget`](crate::RetryBudget), like the built-in executors.
+
+use crate::error::TylError;
+use crate::retry::RetryPolicy;
+#[cfg(feature = "backon")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "backon")]
+use std::sync::Arc;
+#[cfg(feature = "backon")]
+use std::time::Duration;
+
+/// Delays of a [`RetryPolicy`] as a `backon` backoff. Requires the `backon`
+/// feature.
+///
+/// Yields [`RetryPolicy::calculate_delay`] for each retry until the policy's
+/// `max_attempts` or the retry limit of a failed error's category is
+/// reached, or its retry budget is spent. The backoff learns the errors'
+/// categories through its [`retry_filter`](Self::retry_filter), which must be the
+/// `when` filter of the retries.
+///
+/// # Example
+/// ```rust
+/// use backon::BlockingRetryable;
+/// use std::time::Duration;
+/// use tyl_errors::{ErrorCategory, RetryPolicy, TylError};
+///
+/// let category = ErrorCategory::network().with_max_attempts(2);
+/// let backoff = RetryPolicy::fast()
+///     .with_base_delay(Duration::from_millis(1))
+///     .backoff();
+/// let filter = backoff.retry_filter();
+/// let mut calls = 0;
+/// let result: Result<(), TylError> = (|| {
+///     calls += 1;
+///     Err(TylError::business_logic("Connection reset", category.clone().into_classifier()))
+/// })
+/// .retry(backoff)
+/// .sleep(std::thread::sleep)
+/// .when(filter)
+/// .call();
+/// assert!(result.is_err());
+/// assert_eq!(calls, 2);
+/// ```
+#[cfg(feature = "backon")]
+#[derive(Debug, Clone)]
+pub struct PolicyBackoff {
+    policy: RetryPolicy,
+    retries: usize,
+    /// Lowest retry limit of the categories of the errors seen by the filter.
+    category_limit: Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "backon")]
+impl PolicyBackoff {
+    /// Create the `when` filter of retries using this backoff.
+    ///
+    /// Retries errors [retriable](RetryPolicy::is_retriable) by the policy,
+    /// and records their category's retry limit for the backoff.
+    pub fn retry_filter(&self) -> impl FnMut(&TylError) -> bool + Send + Sync + 'static {
+        let policy = self.policy.clone();
+        let category_limit = Arc::clone(&self.category_limit);
+        move |error| {
+            let category = error.category();
+            if let Some(limit) = category.max_retries() {
+                category_limit.fetch_min(limit, Ordering::Relaxed);
+            }
+            policy.is_retriable(error)
+        }
+    }
+}
+
+#[cfg(feature = "backon")]
+impl Iterator for PolicyBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if !self.policy.should_retry(self.retries)
+            || self.retries >= self.category_limit.load(Ordering::Relaxed)
+            || !self.policy.retry_budget().try_acquire()
+        {
+            return None;
+        }
+        self.retries += 1;
+        Some(self.policy.calculate_delay(self.retries))
+    }
+}
+
+#[cfg(feature = "backon")]
+impl backon::BackoffBuilder for RetryPolicy {
+    type Backoff = PolicyBackoff;
+
+    fn build(self) -> PolicyBackoff {
+        self.backoff()
+    }
+}
+
+#[cfg(feature = "backon")]
+impl RetryPolicy {
+    /// Create a `backon` backoff following this policy, to be used with its
+    /// [`retry_filter`](PolicyBackoff::retry_filter). Requires the `backon` feature.
+    pub fn backoff(&self) -> PolicyBackoff {
+        PolicyBackoff {
+            policy: self.clone(),
+            retries: 0,
+            category_limit: Arc::new(AtomicUsize::new(usize::MAX)),
+        }
+    }
+}
+
+#[cfg(feature = "futures-retry")]
+impl futures_retry::ErrorHandler<TylError> for RetryPolicy {
+    type OutError = TylError;
+
+    /// Wait the policy's delay before the next attempt, or forward the error
+    /// when [`RetryPolicy::explain_retry`] refuses the retry or the retry
+    /// budget is spent.
+    fn handle(&mut self, attempt: usize, error: TylError) -> futures_retry::RetryPolicy<TylError> {
+        match self.take_retry(error, attempt.saturating_sub(1)) {
+            Ok(delay) => futures_retry::RetryPolicy::WaitRetry(delay),
+            Err(error) => futures_retry::RetryPolicy::ForwardError(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "backon")]
+    use crate::retry::Idempotency;
+    #[cfg(feature = "backon")]
+    use crate::retry_budget::RetryBudget;
+    #[cfg(any(feature = "backon", feature = "futures-retry"))]
+    use std::sync::Arc;
+
+    #[cfg(feature = "backon")]
+    #[test]
+    fn test_policy_backoff_should_follow_attempts_and_budget() {
+        // Given: a policy without jitter allowing three retries
+        let policy = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_base_delay(Duration::from_millis(10))
+            .with_jitter(false);
+
+        // When: building its backon backoff
+        let delays: Vec<Duration> = backon::BackoffBuilder::build(policy.clone()).collect();
+
+        // Then: it should yield the policy's delays up to max_attempts
+        assert_eq!(delays, [10, 20, 40].map(Duration::from_millis));
+
+        // And: a spent budget should stop the backoff
+        let budget = Arc::new(RetryBudget::new(1, Duration::from_secs(60)));
+        let limited = policy.with_retry_budget(Arc::clone(&budget));
+        assert_eq!(backon::BackoffBuilder::build(limited).count(), 1);
+    }
+
+    #[cfg(feature = "backon")]
+    #[test]
+    fn test_filter_should_apply_category_limits_and_idempotency() {
+        use crate::category::ErrorCategory;
+
+        // Given: a backoff allowing three retries and its filter
+        let mut backoff = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_base_delay(Duration::from_millis(1))
+            .backoff();
+        let mut filter = backoff.retry_filter();
+
+        // When: the filter sees an error whose category allows two attempts
+        let capped = ErrorCategory::network().with_max_attempts(2);
+        assert!(filter(&TylError::business_logic(
+            "reset",
+            capped.into_classifier()
+        )));
+
+        // Then: the backoff should stop after one retry
+        assert!(backoff.next().is_some());
+        assert!(backoff.next().is_none());
+
+        // And: non-idempotent policies should not retry after side effects
+        let non_idempotent = RetryPolicy::new().with_idempotency(Idempotency::NonIdempotent);
+        let mut filter = non_idempotent.backoff().retry_filter();
+        assert!(!filter(&TylError::timeout("Upstream slow")));
+        assert!(!filter(&TylError::validation("id", "Missing")));
+    }
+
+    #[cfg(feature = "futures-retry")]
+    #[tokio::test]
+    async fn test_futures_retry_should_retry_retriable_errors_only() {
+        use futures_retry::FutureRetry;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Given: an operation failing twice with a network error
+        let policy = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_base_delay(std::time::Duration::from_millis(1))
+            .with_jitter(false);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        // When: retrying it with the policy as error handler
+        let result = FutureRetry::new(
+            move || {
+                let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if call < 3 {
+                        Err(TylError::network("Connection reset"))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            },
+            policy.clone(),
+        )
+        .await;
+
+        // Then: it should succeed on the third call
+        assert_eq!(result.unwrap(), (3, 3));
+
+        // And: validation errors should be forwarded without retrying
+        let result = FutureRetry::new(
+            || async { Err::<(), _>(TylError::validation("id", "Missing")) },
+            policy,
+        )
+        .await;
+        let (error, attempts) = result.unwrap_err();
+        assert_eq!(error.code(), "validation");
+        assert_eq!(attempts, 1);
+    }
+}
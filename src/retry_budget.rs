@@ -0,0 +1,220 @@
+//! Limits on retries.
+//!
+//! This module provides [`RetryBudget`], a sliding-window limiter consulted by
+//! [`RetryPolicy`](crate::RetryPolicy) executors before every retry. Every
+//! policy takes retries from one process-wide budget, limited by
+//! `TYL_ERROR_MAX_RETRIES_PER_MINUTE`, so a misbehaving dependency cannot fill
+//! the worker pool with retry sleeps; once it is spent, executors give up with
+//! a non-retriable resource exhaustion error. Policies opt out with their own
+//! budget through [`with_retry_budget`](crate::RetryPolicy::with_retry_budget).
+
+use crate::category::ErrorCategory;
+use crate::clock::Instant;
+use crate::error::TylError;
+use crate::extras::ErrorExtras;
+use crate::settings::ErrorSettings;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Limit on the number of retries granted per time window.
+///
+/// A limit of `0` grants every retry.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tyl_errors::{RetryBudget, RetryPolicy, TylError, TylResult};
+///
+/// let budget = Arc::new(RetryBudget::new(1, Duration::from_secs(60)));
+/// let policy = RetryPolicy::new()
+///     .with_base_delay(Duration::from_millis(1))
+///     .with_retry_budget(budget);
+///
+/// let result: TylResult<()> = policy.execute(|| Err(TylError::network("Connection reset")));
+///
+/// let error = result.unwrap_err();
+/// assert_eq!(error.retry_telemetry().unwrap().attempts, 2);
+/// assert_eq!(error.category().category_name(), "ResourceExhaustion");
+/// assert!(!error.is_retriable());
+/// ```
+#[derive(Debug)]
+pub struct RetryBudget {
+    max_retries: usize,
+    window: Duration,
+    granted: Mutex<VecDeque<Instant>>,
+}
+
+impl RetryBudget {
+    /// Create a budget granting at most `max_retries` retries per `window`.
+    pub fn new(max_retries: usize, window: Duration) -> Self {
+        Self {
+            max_retries,
+            window,
+            granted: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Create a budget that grants every retry.
+    pub fn unlimited() -> Self {
+        Self::new(0, Duration::from_secs(60))
+    }
+
+    /// Create a budget of `TYL_ERROR_MAX_RETRIES_PER_MINUTE` retries per
+    /// minute (unlimited by default).
+    pub fn from_settings() -> Self {
+        Self::new(
+            ErrorSettings::global().max_retries_per_minute,
+            Duration::from_secs(60),
+        )
+    }
+
+    /// Get the process-wide budget, created with [`RetryBudget::from_settings`]
+    /// on first use, that every policy takes retries from by default.
+    pub fn global() -> Arc<RetryBudget> {
+        static GLOBAL: OnceLock<Arc<RetryBudget>> = OnceLock::new();
+        Arc::clone(GLOBAL.get_or_init(|| Arc::new(RetryBudget::from_settings())))
+    }
+
+    /// Get the maximum number of retries per window; `0` means unlimited.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    /// Take one retry from the budget, returning `false` if it is spent.
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    /// Get the number of retries still available in the current window.
+    ///
+    /// Returns `usize::MAX` for an unlimited budget.
+    pub fn remaining(&self) -> usize {
+        if self.max_retries == 0 {
+            return usize::MAX;
+        }
+        let mut granted = self.granted.lock().unwrap_or_else(|e| e.into_inner());
+        self.expire(&mut granted, Instant::now());
+        self.max_retries.saturating_sub(granted.len())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> bool {
+        if self.max_retries == 0 {
+            return true;
+        }
+        let mut granted = self.granted.lock().unwrap_or_else(|e| e.into_inner());
+        self.expire(&mut granted, now);
+        if granted.len() >= self.max_retries {
+            return false;
+        }
+        granted.push_back(now);
+        true
+    }
+
+    /// Forget retries granted before the current window.
+    fn expire(&self, granted: &mut VecDeque<Instant>, now: Instant) {
+        while granted
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) >= self.window)
+        {
+            granted.pop_front();
+        }
+    }
+
+    /// Error returned instead of retrying `error` once the budget is spent.
+    ///
+    /// Not retriable: retrying would only spend the budget again. `error` is
+    /// kept as the source.
+    #[track_caller]
+    pub(crate) fn exhausted(&self, error: TylError) -> TylError {
+        TylError::Custom {
+            message: format!(
+                "Retry budget exhausted ({} retries per {}s)",
+                self.max_retries,
+                self.window.as_secs()
+            ),
+            classifier: ErrorCategory::resource_exhaustion()
+                .and_then(ErrorCategory::permanent())
+                .into_classifier(),
+            source: Some(Arc::new(error)),
+            extras: ErrorExtras::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TylResult;
+    use crate::retry::RetryPolicy;
+
+    #[test]
+    fn test_budget_should_refill_as_the_window_slides() {
+        // Given: a budget of two retries per second
+        let budget = RetryBudget::new(2, Duration::from_secs(1));
+        let start = Instant::now();
+
+        // When/Then: a third retry in the window should be refused
+        assert!(budget.try_acquire_at(start));
+        assert!(budget.try_acquire_at(start + Duration::from_millis(500)));
+        assert!(!budget.try_acquire_at(start + Duration::from_millis(900)));
+
+        // And: retries should be granted again once the oldest one expires
+        assert!(budget.try_acquire_at(start + Duration::from_millis(1000)));
+        assert!(!budget.try_acquire_at(start + Duration::from_millis(1200)));
+        assert!(RetryBudget::unlimited().try_acquire());
+        assert_eq!(RetryBudget::unlimited().remaining(), usize::MAX);
+    }
+
+    #[test]
+    fn test_policies_should_share_the_process_budget_by_default() {
+        // Given: policies built in different ways
+        let policies = [
+            RetryPolicy::new(),
+            RetryPolicy::network(),
+            RetryPolicy::database(),
+            RetryPolicy::default().with_max_attempts(7),
+        ];
+
+        // When/Then: all of them should take retries from the global budget
+        let global = RetryBudget::global();
+        for policy in &policies {
+            assert!(Arc::ptr_eq(policy.retry_budget(), &global));
+        }
+    }
+
+    #[test]
+    fn test_policies_should_opt_out_of_the_process_budget() {
+        // Given: a policy with its own spent budget, and a default policy
+        let noisy = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(1))
+            .with_retry_budget(Arc::new(RetryBudget::new(1, Duration::from_secs(60))));
+        let quiet = RetryPolicy::new().with_base_delay(Duration::from_millis(1));
+        assert!(!Arc::ptr_eq(noisy.retry_budget(), quiet.retry_budget()));
+        assert!(noisy.retry_budget().try_acquire());
+
+        // When: both retry a network error
+        let exhausted = noisy
+            .execute(|| -> TylResult<()> { Err(TylError::network("Connection reset")) })
+            .unwrap_err();
+        let mut attempts = 0;
+        let _ = quiet.execute(|| -> TylResult<()> {
+            attempts += 1;
+            Err(TylError::network("Connection reset"))
+        });
+
+        // Then: only the noisy policy should give up, without retriable errors
+        assert!(exhausted.retry_telemetry().is_none());
+        assert!(!exhausted.is_retriable());
+        assert_eq!(
+            exhausted.to_string(),
+            "Custom error: Retry budget exhausted (1 retries per 60s)"
+        );
+        assert_eq!(
+            std::error::Error::source(&exhausted).unwrap().to_string(),
+            "Network error: Connection reset"
+        );
+        assert_eq!(attempts, 4);
+    }
+}
@@ -0,0 +1,92 @@
+//! salvo response integration.
+//!
+//! This module (enabled by the `salvo` feature) implements salvo's `Writer`
+//! for [`TylError`] and `Scribe` for [`ProblemDetails`], so handlers can
+//! return `Result<T, TylError>` and failures are rendered as
+//! `application/problem+json` with the error's HTTP status.
+
+use crate::error::TylError;
+use crate::problem::{ProblemDetails, PROBLEM_JSON_CONTENT_TYPE};
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use salvo_core::http::StatusCode;
+use salvo_core::{async_trait, Depot, Request, Response, Scribe, Writer};
+
+/// Render the problem as `application/problem+json` with its status code, and
+/// a `Retry-After` header when the error may be retried.
+impl Scribe for ProblemDetails {
+    fn render(self, res: &mut Response) {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        res.status_code(status);
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+        );
+        if let Some(value) = self
+            .retry_after_header()
+            .and_then(|seconds| HeaderValue::from_str(&seconds).ok())
+        {
+            res.headers_mut().insert(RETRY_AFTER, value);
+        }
+        // Serializing a map of JSON values cannot fail.
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        let _ = res.write_body(body);
+    }
+}
+
+/// Write the error as problem details (see [`TylError::to_problem_details`]).
+///
+/// # Example
+/// ```rust
+/// use salvo_core::prelude::*;
+/// use tyl_errors::{TylError, TylResult};
+///
+/// #[handler]
+/// async fn get_user(req: &mut Request) -> TylResult<String> {
+///     let id = req.param::<String>("id").unwrap_or_default();
+///     Err(TylError::not_found("user", id))
+/// }
+///
+/// let router = Router::with_path("users/{id}").get(get_user);
+/// # let _ = router;
+/// ```
+#[async_trait]
+impl Writer for TylError {
+    async fn write(self, _req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+        res.render(self.to_problem_details());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use salvo_core::http::ResBody;
+
+    #[tokio::test]
+    async fn test_errors_should_be_written_as_problem_json() {
+        // Given: a conflict error and an empty response
+        let error = TylError::conflict("Order already paid");
+        let mut res = Response::new();
+
+        // When: writing the error
+        error
+            .write(&mut Request::new(), &mut Depot::new(), &mut res)
+            .await;
+
+        // Then: the response should carry the status, media type and body
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+        assert_eq!(res.headers()[CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+        let ResBody::Once(body) = res.take_body() else {
+            panic!("expected a single-chunk body");
+        };
+        let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.extensions["code"], "conflict");
+        assert!(res.headers().get(RETRY_AFTER).is_none());
+
+        // And: retriable errors should carry a Retry-After header
+        let mut res = Response::new();
+        TylError::network("Connection reset")
+            .write(&mut Request::new(), &mut Depot::new(), &mut res)
+            .await;
+        assert!(res.headers().contains_key(RETRY_AFTER));
+    }
+}
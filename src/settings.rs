@@ -4,6 +4,53 @@
 //! via environment variables, avoiding circular dependencies while maintaining
 //! flexible configuration options.
 
+use crate::code_registry::CodeRegistry;
+use crate::error::TylError;
+use crate::group::TylErrorGroup;
+use crate::http_overrides::{HttpMappingOverrides, HTTP_MAPPING_OVERRIDES_ENV};
+use crate::limits::{
+    DEFAULT_MAX_BAGGAGE_LEN, DEFAULT_MAX_MESSAGE_LEN, DEFAULT_MAX_METADATA_VALUE_LEN,
+};
+use crate::overrides::{ClassificationOverrides, CLASSIFICATION_OVERRIDES_ENV};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::env::VarError;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::Duration;
+
+/// Default number of log lines per error fingerprint before rate limiting.
+pub const DEFAULT_LOG_BURST: u64 = 5;
+
+/// Default interval between log lines of a rate-limited fingerprint.
+pub const DEFAULT_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default number of times an error may be forwarded between services.
+pub const DEFAULT_MAX_HOPS: u32 = 8;
+
+/// How the `error_id` of new error contexts is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorIdMode {
+    /// Random UUIDv4 per occurrence.
+    #[default]
+    Random,
+    /// UUIDv5 derived from the error fingerprint and idempotency key, so the
+    /// same logical failure always gets the same ID; random for contexts
+    /// without idempotency key.
+    Deterministic,
+}
+
+impl ErrorIdMode {
+    /// Parse a case-insensitive mode name (`random` or `deterministic`).
+    fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "random" => Some(ErrorIdMode::Random),
+            "deterministic" => Some(ErrorIdMode::Deterministic),
+            _ => None,
+        }
+    }
+}
+
 /// Log level for error output.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -14,23 +61,71 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
-    /// Parse log level from environment variable string.
-    ///
-    /// # Returns
-    /// Some(LogLevel) if the string is recognized, None otherwise.
-    fn from_env() -> Option<Self> {
-        std::env::var("TYL_ERROR_LOG_LEVEL").ok().and_then(|level| {
-            match level.to_uppercase().as_str() {
-                "ERROR" => Some(LogLevel::Error),
-                "WARN" | "WARNING" => Some(LogLevel::Warn),
-                "INFO" => Some(LogLevel::Info),
-                "DEBUG" => Some(LogLevel::Debug),
-                _ => None,
+    /// Parse a case-insensitive level name.
+    fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_uppercase().as_str() {
+            "ERROR" => Some(LogLevel::Error),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "INFO" => Some(LogLevel::Info),
+            "DEBUG" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// Get the uppercase name of this level.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+}
+
+/// Parse `Category=LEVEL` pairs separated by commas, skipping malformed
+/// entries and returning the first one.
+fn parse_category_log_levels(spec: &str) -> (HashMap<String, LogLevel>, Option<&str>) {
+    let mut malformed = None;
+    let levels = spec
+        .split(',')
+        .filter_map(|entry| {
+            let pair = entry.split_once('=').and_then(|(category, level)| {
+                let category = category.trim();
+                if category.is_empty() {
+                    return None;
+                }
+                Some((category.to_lowercase(), LogLevel::parse(level)?))
+            });
+            if pair.is_none() {
+                malformed.get_or_insert(entry);
             }
+            pair
         })
+        .collect();
+    (levels, malformed)
+}
+
+/// Parse `true` or `false` in any case.
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
     }
 }
 
+/// Parse comma-separated baggage keys, skipping empty entries.
+fn parse_baggage_keys(spec: &str) -> Vec<String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Global error configuration from environment variables.
 ///
 /// Zero-config with sensible defaults to avoid circular dependencies.
@@ -44,6 +139,32 @@ pub struct ErrorSettings {
     pub log_errors: bool,
     /// Minimum log level for error output.
     pub log_level: LogLevel,
+    /// Whether `{:?}` output masks sensitive metadata and truncates long messages.
+    pub redact: bool,
+    /// Maximum error message length in bytes; `0` disables the limit.
+    pub max_message_len: usize,
+    /// Maximum serialized size of a metadata value in bytes; `0` disables the limit.
+    pub max_metadata_value_len: usize,
+    /// Log levels overriding the severity-based level, keyed by lowercase category name.
+    pub category_log_levels: HashMap<String, LogLevel>,
+    /// Maximum retries per minute across all retry executors; `0` disables the limit.
+    pub max_retries_per_minute: usize,
+    /// Log lines per error fingerprint before rate limiting; `0` disables the limit.
+    pub log_burst: u64,
+    /// Minimum interval between log lines of a rate-limited fingerprint.
+    pub log_interval: Duration,
+    /// Minimum delay between retries, enforced on every category and policy.
+    pub min_delay: Duration,
+    /// Maximum retries allowed by any category, policy or queue; `0` disables the ceiling.
+    pub max_attempts_ceiling: usize,
+    /// Maximum times an error may be forwarded between services; `0` disables the check.
+    pub max_hops: u32,
+    /// Baggage keys propagated between services; empty allows any key.
+    pub baggage_keys: Vec<String>,
+    /// Maximum encoded size of the `baggage` header in bytes; `0` disables the limit.
+    pub max_baggage_len: usize,
+    /// How error IDs of contexts created by [`TylError::to_context`](crate::TylError::to_context) are generated.
+    pub error_id_mode: ErrorIdMode,
 }
 
 impl ErrorSettings {
@@ -61,44 +182,39 @@ impl ErrorSettings {
     /// | `TYL_ERROR_MAX_RETRIES` | `3` | Maximum retry attempts for retriable errors |
     /// | `TYL_ERROR_LOG_ERRORS` | `true` | Log errors to stderr (`true`/`false`) |
     /// | `TYL_ERROR_LOG_LEVEL` | `INFO` | Log level (`ERROR`/`WARN`/`INFO`/`DEBUG`) |
+    /// | `TYL_ERROR_REDACT` | `false` | Redact sensitive data in `Debug` output (`true`/`false`) |
+    /// | `TYL_ERROR_CLASSIFICATION_OVERRIDES` | - | JSON table of classification overrides (read by `ClassificationOverrides`) |
+    /// | `TYL_ERROR_HTTP_MAPPING_OVERRIDES` | - | JSON table of HTTP status overrides (read by `HttpMappingOverrides`) |
+    /// | `TYL_ERROR_MAX_MESSAGE_LEN` | `16384` | Maximum message length in bytes (`0` = unlimited) |
+    /// | `TYL_ERROR_MAX_METADATA_VALUE_LEN` | `4096` | Maximum serialized metadata value size in bytes (`0` = unlimited) |
+    /// | `TYL_ERROR_CATEGORY_LOG_LEVELS` | - | Per-category log levels, e.g. `Validation=DEBUG,Network=WARN` |
+    /// | `TYL_ERROR_MAX_RETRIES_PER_MINUTE` | `0` | Retries per minute shared by every retry policy in the process (`0` = unlimited) |
+    /// | `TYL_ERROR_LOG_BURST` | `5` | Log lines per error fingerprint before rate limiting (`0` = unlimited) |
+    /// | `TYL_ERROR_LOG_INTERVAL_SECS` | `60` | Once rate limited, at most one log line per fingerprint per interval |
+    /// | `TYL_ERROR_MIN_DELAY` | `0` | Minimum delay between retries in milliseconds, enforced on every category and policy |
+    /// | `TYL_ERROR_MAX_ATTEMPTS_CEILING` | `0` | Maximum retries allowed by any category, policy or queue (`0` = no ceiling) |
+    /// | `TYL_ERROR_MAX_HOPS` | `8` | Maximum times an error may be forwarded between services (`0` = unlimited) |
+    /// | `TYL_ERROR_ID_MODE` | `random` | Error ID generation (`random` UUIDv4 or `deterministic` UUIDv5) |
+    /// | `TYL_ERROR_BAGGAGE_KEYS` | - | Comma-separated baggage keys propagated between services (unset = any key) |
+    /// | `TYL_ERROR_MAX_BAGGAGE_LEN` | `2048` | Maximum encoded size of the `baggage` header in bytes (`0` = unlimited) |
     /// | `RUST_BACKTRACE` | - | Standard Rust backtrace (overrides TYL_ERROR_BACKTRACE) |
     ///
     /// # Returns
-    /// A static reference to the global ErrorSettings instance.
-    pub fn global() -> &'static Self {
-        use std::sync::OnceLock;
-        static SETTINGS: OnceLock<ErrorSettings> = OnceLock::new();
-
-        SETTINGS.get_or_init(|| {
-            let backtrace_enabled = std::env::var("TYL_ERROR_BACKTRACE")
-                .map(|v| v.to_lowercase() == "true")
-                .unwrap_or_else(|_| {
-                    // Fall back to RUST_BACKTRACE if TYL_ERROR_BACKTRACE not set
-                    std::env::var("RUST_BACKTRACE").is_ok()
-                });
-
-            let max_retries = std::env::var("TYL_ERROR_MAX_RETRIES")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(3);
-
-            let log_errors = std::env::var("TYL_ERROR_LOG_ERRORS")
-                .map(|v| v.to_lowercase() != "false")
-                .unwrap_or(true);
-
-            let log_level = LogLevel::from_env().unwrap_or(LogLevel::Info);
+    /// The global ErrorSettings instance, or the settings of the active
+    /// [`override_scope`](Self::override_scope) on any thread.
+    pub fn global() -> Arc<Self> {
+        if let Some(settings) = OVERRIDE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return Arc::clone(settings);
+        }
+        static SETTINGS: OnceLock<Arc<ErrorSettings>> = OnceLock::new();
 
-            ErrorSettings {
-                backtrace_enabled,
-                max_retries,
-                log_errors,
-                log_level,
-            }
-        })
+        Arc::clone(SETTINGS.get_or_init(|| Arc::new(load_env(|name| std::env::var(name)).0)))
     }
 
     /// Create ErrorSettings with custom values (primarily for testing).
     ///
+    /// Settings not covered by the arguments take their default values.
+    ///
     /// # Arguments
     /// * `backtrace_enabled` - Whether to enable backtraces
     /// * `max_retries` - Maximum retry attempts
@@ -118,8 +234,132 @@ impl ErrorSettings {
             max_retries,
             log_errors,
             log_level,
+            redact: false,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            max_metadata_value_len: DEFAULT_MAX_METADATA_VALUE_LEN,
+            category_log_levels: HashMap::new(),
+            max_retries_per_minute: 0,
+            log_burst: DEFAULT_LOG_BURST,
+            log_interval: DEFAULT_LOG_INTERVAL,
+            min_delay: Duration::ZERO,
+            max_attempts_ceiling: 0,
+            max_hops: DEFAULT_MAX_HOPS,
+            error_id_mode: ErrorIdMode::Random,
+            baggage_keys: Vec::new(),
+            max_baggage_len: DEFAULT_MAX_BAGGAGE_LEN,
         }
     }
+
+    /// Replace the global settings of the whole process until the returned
+    /// guard is dropped.
+    ///
+    /// Meant for tests that need specific settings without racing on
+    /// environment variables and the cached [`global`](Self::global) settings.
+    /// Every thread, including spawned threads and async runtime workers,
+    /// reads the override. Guards hold a process-wide lock, so scopes on
+    /// different threads run one at a time; scopes on the same thread nest,
+    /// and dropping a guard restores the settings that were active when it
+    /// was created.
+    ///
+    /// Limiters already created from the settings ([`RetryBudget::global`](crate::RetryBudget::global),
+    /// [`LogRateLimiter::global`](crate::LogRateLimiter::global)) keep their
+    /// configuration.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{ErrorSettings, LogLevel, TylError};
+    ///
+    /// {
+    ///     let _settings =
+    ///         ErrorSettings::override_scope(ErrorSettings::new(false, 7, false, LogLevel::Error));
+    ///     assert_eq!(TylError::max_retries(), 7);
+    /// }
+    /// assert_eq!(TylError::max_retries(), 3);
+    /// ```
+    pub fn override_scope(settings: ErrorSettings) -> SettingsOverride {
+        let lock = match SCOPE_DEPTH.with(Cell::get) {
+            0 => Some(SCOPE_LOCK.lock().unwrap_or_else(|e| e.into_inner())),
+            _ => None,
+        };
+        SCOPE_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        let mut current = OVERRIDE.write().unwrap_or_else(|e| e.into_inner());
+        SettingsOverride {
+            previous: current.replace(Arc::new(settings)),
+            _lock: lock,
+        }
+    }
+
+    /// Log errors of a category at a fixed level instead of their severity's level.
+    ///
+    /// Category names are matched case-insensitively.
+    pub fn with_category_log_level(mut self, category: &str, level: LogLevel) -> Self {
+        self.category_log_levels
+            .insert(category.to_lowercase(), level);
+        self
+    }
+
+    /// Restrict the baggage propagated between services to the given keys.
+    pub fn with_baggage_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.baggage_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check whether a baggage key may be propagated between services.
+    pub fn allows_baggage_key(&self, key: &str) -> bool {
+        self.baggage_keys.is_empty() || self.baggage_keys.iter().any(|k| k == key)
+    }
+
+    /// Raise a retry delay to the configured minimum delay.
+    pub fn clamp_delay(&self, delay: Duration) -> Duration {
+        delay.max(self.min_delay)
+    }
+
+    /// Lower a retry limit to the configured attempt ceiling.
+    pub fn cap_attempts(&self, max_attempts: usize) -> usize {
+        match self.max_attempts_ceiling {
+            0 => max_attempts,
+            ceiling => max_attempts.min(ceiling),
+        }
+    }
+
+    /// Get the log level configured for a category, if any.
+    pub fn category_log_level(&self, category: &str) -> Option<LogLevel> {
+        self.category_log_levels
+            .get(&category.to_lowercase())
+            .copied()
+    }
+}
+
+/// Settings of the active [`ErrorSettings::override_scope`].
+static OVERRIDE: RwLock<Option<Arc<ErrorSettings>>> = RwLock::new(None);
+
+/// Lock held by the outermost [`ErrorSettings::override_scope`] of a thread.
+static SCOPE_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    /// Number of this thread's active [`ErrorSettings::override_scope`] guards.
+    static SCOPE_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Guard of [`ErrorSettings::override_scope`], restoring the previous
+/// settings when dropped.
+#[must_use = "the override ends when the guard is dropped"]
+pub struct SettingsOverride {
+    previous: Option<Arc<ErrorSettings>>,
+    /// Lock serializing scopes, held by the outermost guard of a thread;
+    /// guards are released on the thread they were created on.
+    _lock: Option<MutexGuard<'static, ()>>,
+}
+
+impl Drop for SettingsOverride {
+    fn drop(&mut self) {
+        *OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = self.previous.take();
+        let _ = SCOPE_DEPTH.try_with(|depth| depth.set(depth.get().saturating_sub(1)));
+    }
 }
 
 impl Default for ErrorSettings {
@@ -129,10 +369,236 @@ impl Default for ErrorSettings {
             max_retries: 3,
             log_errors: true,
             log_level: LogLevel::Info,
+            redact: false,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            max_metadata_value_len: DEFAULT_MAX_METADATA_VALUE_LEN,
+            category_log_levels: HashMap::new(),
+            max_retries_per_minute: 0,
+            log_burst: DEFAULT_LOG_BURST,
+            log_interval: DEFAULT_LOG_INTERVAL,
+            min_delay: Duration::ZERO,
+            max_attempts_ceiling: 0,
+            max_hops: DEFAULT_MAX_HOPS,
+            error_id_mode: ErrorIdMode::Random,
+            baggage_keys: Vec::new(),
+            max_baggage_len: DEFAULT_MAX_BAGGAGE_LEN,
         }
     }
 }
 
+/// Reads the settings of [`ErrorSettings::global`] from environment
+/// variables, collecting the values that do not parse.
+///
+/// Shared by [`ErrorSettings::global`], which falls back to the default of
+/// invalid values, and [`validate_configuration`], which reports them, so
+/// both accept exactly the same values.
+struct EnvReader<F> {
+    var: F,
+    invalid: TylErrorGroup,
+}
+
+impl<F: Fn(&str) -> Result<String, VarError>> EnvReader<F> {
+    /// Get the value of `name`, `None` if it is unset or not valid unicode.
+    fn raw(&mut self, name: &str, expected: &str) -> Option<String> {
+        match (self.var)(name) {
+            Ok(value) => Some(value),
+            Err(VarError::NotPresent) => None,
+            Err(VarError::NotUnicode(_)) => {
+                let error = TylError::Configuration {
+                    message: format!(
+                        "{name}: expected {expected}, got a value that is not valid unicode"
+                    ),
+                    key: Some(name.to_string()),
+                    expected: Some(expected.to_string()),
+                    actual: None,
+                    location: None,
+                    extras: crate::extras::ErrorExtras::new(),
+                };
+                self.invalid.push_with_id(self.invalid.len(), name, error);
+                None
+            }
+        }
+    }
+
+    /// Parse the trimmed value of `name`, `None` if it is unset or invalid.
+    fn parse<T>(
+        &mut self,
+        name: &str,
+        expected: &str,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let value = self.raw(name, expected)?;
+        let parsed = parse(value.trim());
+        if parsed.is_none() {
+            self.reject(name, expected, &value);
+        }
+        parsed
+    }
+
+    /// Parse an integer setting.
+    fn integer<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        self.parse(name, expected, |value| value.parse().ok())
+    }
+
+    /// Parse a boolean setting.
+    fn boolean(&mut self, name: &str) -> Option<bool> {
+        self.parse(name, "true or false", parse_bool)
+    }
+
+    fn reject(&mut self, name: &str, expected: &str, value: &str) {
+        let error = TylError::config_key(name, expected, value);
+        self.invalid.push_with_id(self.invalid.len(), name, error);
+    }
+}
+
+/// Load the settings from the variables returned by `var`, with the errors
+/// of the values that do not parse.
+fn load_env<F>(var: F) -> (ErrorSettings, TylErrorGroup)
+where
+    F: Fn(&str) -> Result<String, VarError>,
+{
+    let mut env = EnvReader {
+        var,
+        invalid: TylErrorGroup::new(),
+    };
+    let defaults = ErrorSettings::default();
+    const COUNT: &str = "a non-negative integer";
+    const LENGTH: &str = "a length in bytes";
+
+    let max_retries = env.integer("TYL_ERROR_MAX_RETRIES", COUNT);
+    let max_message_len = env.integer("TYL_ERROR_MAX_MESSAGE_LEN", LENGTH);
+    let max_metadata_value_len = env.integer("TYL_ERROR_MAX_METADATA_VALUE_LEN", LENGTH);
+    let max_retries_per_minute = env.integer("TYL_ERROR_MAX_RETRIES_PER_MINUTE", COUNT);
+    let log_burst = env.integer("TYL_ERROR_LOG_BURST", COUNT);
+    let log_interval = env.parse(
+        "TYL_ERROR_LOG_INTERVAL_SECS",
+        "a positive number of seconds",
+        |value| value.parse().ok().filter(|secs| *secs > 0),
+    );
+    let min_delay = env.integer("TYL_ERROR_MIN_DELAY", "a duration in milliseconds");
+    let max_attempts_ceiling = env.integer("TYL_ERROR_MAX_ATTEMPTS_CEILING", COUNT);
+    let max_hops = env.integer("TYL_ERROR_MAX_HOPS", COUNT);
+    let max_baggage_len = env.integer("TYL_ERROR_MAX_BAGGAGE_LEN", LENGTH);
+
+    // Fall back to RUST_BACKTRACE if TYL_ERROR_BACKTRACE is not set.
+    let backtrace_enabled = env
+        .boolean("TYL_ERROR_BACKTRACE")
+        .unwrap_or_else(|| (env.var)("RUST_BACKTRACE").is_ok());
+    let log_errors = env.boolean("TYL_ERROR_LOG_ERRORS");
+    let redact = env.boolean("TYL_ERROR_REDACT");
+
+    let log_level = env.parse(
+        "TYL_ERROR_LOG_LEVEL",
+        "ERROR, WARN, INFO or DEBUG",
+        LogLevel::parse,
+    );
+    let error_id_mode = env.parse(
+        "TYL_ERROR_ID_MODE",
+        "random or deterministic",
+        ErrorIdMode::parse,
+    );
+    const PAIRS: &str = "comma-separated Category=LEVEL pairs";
+    let category_log_levels = env.raw("TYL_ERROR_CATEGORY_LOG_LEVELS", PAIRS).map(|spec| {
+        let (levels, malformed) = parse_category_log_levels(&spec);
+        if let Some(entry) = malformed {
+            env.reject("TYL_ERROR_CATEGORY_LOG_LEVELS", PAIRS, entry);
+        }
+        levels
+    });
+    let baggage_keys = env
+        .raw("TYL_ERROR_BAGGAGE_KEYS", "comma-separated keys")
+        .map(|keys| parse_baggage_keys(&keys));
+
+    let settings = ErrorSettings {
+        backtrace_enabled,
+        max_retries: max_retries.unwrap_or(defaults.max_retries),
+        log_errors: log_errors.unwrap_or(defaults.log_errors),
+        log_level: log_level.unwrap_or(defaults.log_level),
+        redact: redact.unwrap_or(defaults.redact),
+        max_message_len: max_message_len.unwrap_or(defaults.max_message_len),
+        max_metadata_value_len: max_metadata_value_len.unwrap_or(defaults.max_metadata_value_len),
+        category_log_levels: category_log_levels.unwrap_or_default(),
+        max_retries_per_minute: max_retries_per_minute.unwrap_or(defaults.max_retries_per_minute),
+        log_burst: log_burst.unwrap_or(defaults.log_burst),
+        log_interval: log_interval
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.log_interval),
+        min_delay: min_delay
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.min_delay),
+        max_attempts_ceiling: max_attempts_ceiling.unwrap_or(defaults.max_attempts_ceiling),
+        max_hops: max_hops.unwrap_or(defaults.max_hops),
+        error_id_mode: error_id_mode.unwrap_or(defaults.error_id_mode),
+        baggage_keys: baggage_keys.unwrap_or_default(),
+        max_baggage_len: max_baggage_len.unwrap_or(defaults.max_baggage_len),
+    };
+    (settings, env.invalid)
+}
+
+/// Check the error configuration at startup.
+///
+/// [`ErrorSettings::global`] and the override tables silently fall back to
+/// their defaults on invalid values; call this from `main` to fail fast
+/// instead. It checks that every `TYL_ERROR_*` variable that is set parses
+/// (integers and durations, booleans, log levels, `TYL_ERROR_ID_MODE`,
+/// `TYL_ERROR_CATEGORY_LOG_LEVELS` pairs), that `TYL_ERROR_LOG_INTERVAL_SECS`
+/// is not zero, that the classification and HTTP mapping override tables
+/// load, and that the codes registered in the global
+/// [`CodeRegistry`](crate::CodeRegistry) do not conflict.
+///
+/// # Errors
+/// A group of configuration errors, identified by the variable name (or
+/// `codes` for code conflicts).
+///
+/// # Example
+/// ```rust
+/// if let Err(errors) = tyl_errors::validate_configuration() {
+///     for failure in errors.errors() {
+///         eprintln!("invalid setting {:?}: {}", failure.id, failure.error);
+///     }
+///     std::process::exit(78);
+/// }
+/// ```
+pub fn validate_configuration() -> Result<(), TylErrorGroup> {
+    let errors = validate_env(|name| std::env::var(name));
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Collect the configuration errors of the variables returned by `var`.
+fn validate_env<F: Fn(&str) -> Result<String, VarError>>(var: F) -> TylErrorGroup {
+    let (_, mut group) = load_env(&var);
+    let tables = [
+        (
+            CLASSIFICATION_OVERRIDES_ENV,
+            var(CLASSIFICATION_OVERRIDES_ENV)
+                .ok()
+                .map(|json| ClassificationOverrides::from_json(&json).map(drop)),
+        ),
+        (
+            HTTP_MAPPING_OVERRIDES_ENV,
+            var(HTTP_MAPPING_OVERRIDES_ENV)
+                .ok()
+                .map(|json| HttpMappingOverrides::from_json(&json).map(drop)),
+        ),
+    ];
+    for (name, loaded) in tables {
+        if let Some(Err(error)) = loaded {
+            group.push_with_id(group.len(), name, error);
+        }
+    }
+    if let Err(conflicts) = CodeRegistry::global().validate() {
+        for conflict in conflicts {
+            let error = TylError::configuration(format!("Conflicting error codes: {conflict}"));
+            group.push_with_id(group.len(), "codes", error);
+        }
+    }
+    group
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,22 +614,17 @@ mod tests {
     #[test]
     fn test_log_level_from_env() {
         // Test parsing various log level strings
-        std::env::set_var("TYL_ERROR_LOG_LEVEL", "ERROR");
-        assert_eq!(LogLevel::from_env(), Some(LogLevel::Error));
-
-        std::env::set_var("TYL_ERROR_LOG_LEVEL", "warn");
-        assert_eq!(LogLevel::from_env(), Some(LogLevel::Warn));
-
-        std::env::set_var("TYL_ERROR_LOG_LEVEL", "INFO");
-        assert_eq!(LogLevel::from_env(), Some(LogLevel::Info));
-
-        std::env::set_var("TYL_ERROR_LOG_LEVEL", "debug");
-        assert_eq!(LogLevel::from_env(), Some(LogLevel::Debug));
-
-        std::env::set_var("TYL_ERROR_LOG_LEVEL", "invalid");
-        assert_eq!(LogLevel::from_env(), None);
-
-        std::env::remove_var("TYL_ERROR_LOG_LEVEL");
+        let level = |value: &str| {
+            let env = HashMap::from([("TYL_ERROR_LOG_LEVEL", value.to_string())]);
+            load_env(|name| env.get(name).cloned().ok_or(VarError::NotPresent))
+                .0
+                .log_level
+        };
+        assert_eq!(level("ERROR"), LogLevel::Error);
+        assert_eq!(level("warn"), LogLevel::Warn);
+        assert_eq!(level(" INFO"), LogLevel::Info);
+        assert_eq!(level("debug"), LogLevel::Debug);
+        assert_eq!(level("invalid"), LogLevel::Info);
     }
 
     #[test]
@@ -174,6 +635,14 @@ mod tests {
         assert_eq!(settings.max_retries, 3);
         assert!(settings.log_errors);
         assert_eq!(settings.log_level, LogLevel::Info);
+        assert!(!settings.redact);
+        assert_eq!(settings.max_message_len, DEFAULT_MAX_MESSAGE_LEN);
+        assert_eq!(
+            settings.max_metadata_value_len,
+            DEFAULT_MAX_METADATA_VALUE_LEN
+        );
+        assert_eq!(settings.log_burst, DEFAULT_LOG_BURST);
+        assert_eq!(settings.log_interval, Duration::from_secs(60));
     }
 
     #[test]
@@ -185,4 +654,163 @@ mod tests {
         assert!(!settings.log_errors);
         assert_eq!(settings.log_level, LogLevel::Debug);
     }
+
+    #[test]
+    fn test_override_scope_should_swap_settings_until_dropped() {
+        // Given: the cached global settings and two overrides
+        let cached = ErrorSettings::global().max_hops;
+        let outer = ErrorSettings {
+            max_hops: cached + 90,
+            ..ErrorSettings::default()
+        };
+        let inner = ErrorSettings {
+            max_hops: cached + 91,
+            ..ErrorSettings::default()
+        };
+
+        // When: overriding them in a scope
+        let guard = ErrorSettings::override_scope(outer);
+
+        // Then: every thread should see the override
+        assert_eq!(ErrorSettings::global().max_hops, cached + 90);
+        let seen = std::thread::spawn(|| ErrorSettings::global().max_hops)
+            .join()
+            .unwrap();
+        assert_eq!(seen, cached + 90);
+
+        // And: nested scopes should restore the enclosing settings
+        {
+            let _guard = ErrorSettings::override_scope(inner);
+            assert_eq!(ErrorSettings::global().max_hops, cached + 91);
+        }
+        assert_eq!(ErrorSettings::global().max_hops, cached + 90);
+
+        // And: scopes on other threads should wait for the guard to be dropped
+        let waiting = std::thread::spawn(|| {
+            let _guard = ErrorSettings::override_scope(ErrorSettings::default());
+            ErrorSettings::global().max_hops
+        });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert_eq!(ErrorSettings::global().max_hops, cached + 90);
+        drop(guard);
+        assert_eq!(waiting.join().unwrap(), DEFAULT_MAX_HOPS);
+        assert_eq!(ErrorSettings::global().max_hops, cached);
+    }
+
+    #[test]
+    fn test_category_log_levels_should_parse_pairs() {
+        let (levels, malformed) =
+            parse_category_log_levels("Validation=debug, Network = WARN,bad,=INFO,X=loud");
+        assert_eq!(malformed, Some("bad"));
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels["validation"], LogLevel::Debug);
+        assert_eq!(levels["network"], LogLevel::Warn);
+
+        let settings = ErrorSettings::default().with_category_log_level("Internal", LogLevel::Warn);
+        assert_eq!(
+            settings.category_log_level("INTERNAL"),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(settings.category_log_level("Network"), None);
+    }
+
+    #[test]
+    fn test_guardrails_should_clamp_delays_and_attempts() {
+        // Given: settings with a delay floor and an attempt ceiling
+        let settings = ErrorSettings {
+            min_delay: Duration::from_millis(50),
+            max_attempts_ceiling: 4,
+            ..ErrorSettings::default()
+        };
+
+        // When/Then: shorter delays and higher limits should be clamped
+        assert_eq!(
+            settings.clamp_delay(Duration::ZERO),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            settings.clamp_delay(Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(settings.cap_attempts(10), 4);
+        assert_eq!(settings.cap_attempts(2), 2);
+        assert_eq!(ErrorSettings::default().cap_attempts(10), 10);
+    }
+
+    #[test]
+    fn test_loading_and_validation_should_accept_the_same_values() {
+        // Given: a padded integer and a value that is not valid unicode
+        let lookup = |name: &str| match name {
+            "TYL_ERROR_MAX_HOPS" => Ok(" 5 ".to_string()),
+            "TYL_ERROR_REDACT" => Err(VarError::NotUnicode(std::ffi::OsString::from(
+                "tr\u{fffd}e",
+            ))),
+            _ => Err(VarError::NotPresent),
+        };
+
+        // When: loading and validating them
+        let (settings, invalid) = load_env(lookup);
+
+        // Then: the padded value should be used, and the other one reported
+        assert_eq!(settings.max_hops, 5);
+        assert!(!settings.redact);
+        assert_eq!(invalid.len(), 1);
+        let failure = &invalid.errors()[0];
+        assert_eq!(failure.id.as_deref(), Some("TYL_ERROR_REDACT"));
+        assert!(!failure.error.metadata().contains_key("actual"));
+        assert!(validate_env(lookup)
+            .errors()
+            .iter()
+            .all(|failure| failure.id.as_deref() != Some("TYL_ERROR_MAX_HOPS")));
+    }
+
+    #[test]
+    fn test_validate_env_should_report_every_invalid_setting() {
+        // Given: an environment with valid and invalid settings
+        let env = HashMap::from([
+            ("TYL_ERROR_MAX_RETRIES", "5"),
+            ("TYL_ERROR_MIN_DELAY", "100ms"),
+            ("TYL_ERROR_LOG_INTERVAL_SECS", "0"),
+            ("TYL_ERROR_REDACT", "yes"),
+            ("TYL_ERROR_LOG_LEVEL", "verbose"),
+            ("TYL_ERROR_CATEGORY_LOG_LEVELS", "Network=WARN,Internal"),
+            (
+                "TYL_ERROR_HTTP_MAPPING_OVERRIDES",
+                r#"{"rules": [{"status": 410}]}"#,
+            ),
+        ]);
+
+        // When: validating it
+        let lookup = |name: &str| {
+            env.get(name)
+                .map(|value| value.to_string())
+                .ok_or(VarError::NotPresent)
+        };
+        let group = validate_env(lookup);
+
+        // Then: each invalid setting should be reported under its name
+        let invalid: Vec<&str> = group
+            .errors()
+            .iter()
+            .filter_map(|failure| failure.id.as_deref())
+            .filter(|id| *id != "codes")
+            .collect();
+        assert_eq!(
+            invalid,
+            [
+                "TYL_ERROR_LOG_INTERVAL_SECS",
+                "TYL_ERROR_MIN_DELAY",
+                "TYL_ERROR_REDACT",
+                "TYL_ERROR_LOG_LEVEL",
+                "TYL_ERROR_CATEGORY_LOG_LEVELS",
+                "TYL_ERROR_HTTP_MAPPING_OVERRIDES",
+            ]
+        );
+        let min_delay = &group.errors()[1].error;
+        assert_eq!(min_delay.metadata()["actual"], "100ms");
+        assert!(validate_env(|_| Err(VarError::NotPresent))
+            .errors()
+            .iter()
+            .all(|e| e.id.as_deref() == Some("codes")));
+    }
 }
@@ -0,0 +1,309 @@
+//! Graceful shutdown of buffering components.
+//!
+//! Reporters, retry queues and log rate limiting hold errors in memory. This
+//! module keeps a registry of [`ShutdownHook`]s that flush them, and
+//! [`shutdown`] runs them within an enforced deadline when a pod terminates,
+//! then logs the occurrences the [`LogRateLimiter`] suppressed since their
+//! last line.
+
+use crate::clock::Instant;
+use crate::error::{TylError, TylResult};
+use crate::log_limit::LogRateLimiter;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Component flushing buffered errors on [`shutdown`].
+pub trait ShutdownHook: Send + Sync {
+    /// Flush buffered state, taking at most `remaining`.
+    fn on_shutdown(&self, remaining: Duration) -> TylResult<()>;
+}
+
+impl<F> ShutdownHook for F
+where
+    F: Fn(Duration) -> TylResult<()> + Send + Sync,
+{
+    fn on_shutdown(&self, remaining: Duration) -> TylResult<()> {
+        self(remaining)
+    }
+}
+
+/// Identifier of a registered hook, used with [`remove_shutdown_hook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShutdownHookId(u64);
+
+/// Outcome of [`shutdown`].
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// Hooks that flushed successfully, in run order.
+    pub flushed: Vec<String>,
+    /// Hooks that failed, with their error.
+    pub failed: Vec<(String, TylError)>,
+    /// Hooks not run because the deadline had passed.
+    pub skipped: Vec<String>,
+    /// Suppressed log occurrences written out before exiting.
+    pub suppressed_logged: u64,
+}
+
+impl ShutdownReport {
+    /// Check whether every hook flushed successfully.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+struct Registered {
+    id: ShutdownHookId,
+    name: String,
+    hook: Arc<dyn ShutdownHook>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Ordered set of [`ShutdownHook`]s.
+///
+/// The process-wide set used by [`register_shutdown_hook`] and [`shutdown`]
+/// is [`ShutdownHooks::global`]; separate sets serve components with their
+/// own lifecycle, and tests.
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Mutex<Vec<Registered>>,
+}
+
+impl ShutdownHooks {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide set.
+    pub fn global() -> &'static ShutdownHooks {
+        static GLOBAL: OnceLock<ShutdownHooks> = OnceLock::new();
+        GLOBAL.get_or_init(ShutdownHooks::new)
+    }
+
+    /// Register a hook, run after the hooks registered before it.
+    pub fn register<N, H>(&self, name: N, hook: Arc<H>) -> ShutdownHookId
+    where
+        N: Into<String>,
+        H: ShutdownHook + 'static,
+    {
+        let id = ShutdownHookId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Registered {
+                id,
+                name: name.into(),
+                hook,
+            });
+        id
+    }
+
+    /// Remove a registered hook.
+    ///
+    /// # Returns
+    /// True if the hook was registered.
+    pub fn remove(&self, id: ShutdownHookId) -> bool {
+        let mut hooks = self.hooks.lock().unwrap_or_else(|e| e.into_inner());
+        let before = hooks.len();
+        hooks.retain(|registered| registered.id != id);
+        hooks.len() != before
+    }
+
+    /// Run the hooks within `timeout`, then log the occurrences suppressed
+    /// by `limiter`.
+    ///
+    /// Each hook runs on its own thread and is told the time left until
+    /// `timeout` elapses. A hook still running when that time is up is
+    /// reported as failed with a timeout error and left to finish in the
+    /// background; hooks not started by then are skipped. Hooks are removed
+    /// once run, so a second call only flushes what was registered since.
+    /// The suppressed occurrences are taken from `limiter` either way, but
+    /// only logged, and counted in the report, when error logging is enabled.
+    pub fn run(&self, timeout: Duration, limiter: &LogRateLimiter) -> ShutdownReport {
+        let started = Instant::now();
+        let registered: Vec<Registered> = {
+            let mut hooks = self.hooks.lock().unwrap_or_else(|e| e.into_inner());
+            hooks.drain(..).collect()
+        };
+
+        let mut report = ShutdownReport::default();
+        for Registered { name, hook, .. } in registered {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                report.skipped.push(name);
+                continue;
+            }
+            match run_hook(&name, hook, remaining) {
+                Ok(()) => report.flushed.push(name),
+                Err(error) => report.failed.push((name, error)),
+            }
+        }
+
+        let log = TylError::log_errors_enabled();
+        for count in limiter.take_pending() {
+            if log {
+                eprintln!(
+                    "[WARN] {} similar errors suppressed before shutdown (fingerprint {})",
+                    count.pending, count.fingerprint
+                );
+                report.suppressed_logged += count.pending;
+            }
+        }
+        report
+    }
+}
+
+/// Run a hook on its own thread, waiting at most `remaining` for it.
+///
+/// Falls back to running the hook on the current thread when no thread can
+/// be spawned, where the deadline is only as good as the hook's own.
+fn run_hook(name: &str, hook: Arc<dyn ShutdownHook>, remaining: Duration) -> TylResult<()> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let spawned = thread::Builder::new()
+        .name(format!("shutdown:{name}"))
+        .spawn({
+            let hook = Arc::clone(&hook);
+            move || {
+                let _ = sender.send(hook.on_shutdown(remaining));
+            }
+        });
+    if spawned.is_err() {
+        return hook.on_shutdown(remaining);
+    }
+    match receiver.recv_timeout(remaining) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(TylError::timeout(format!(
+            "Shutdown hook `{name}` did not finish within {remaining:?}"
+        ))),
+        Err(RecvTimeoutError::Disconnected) => Err(TylError::internal(format!(
+            "Shutdown hook `{name}` panicked"
+        ))),
+    }
+}
+
+/// Register a hook run by [`shutdown`], after the hooks registered before it.
+///
+/// # Example
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+/// use tyl_errors::{register_shutdown_hook, shutdown, RetryQueue, TylError};
+///
+/// let queue = Arc::new(Mutex::new(RetryQueue::new()));
+/// queue.lock().unwrap().push("webhook-1", TylError::network("reset")).unwrap();
+/// register_shutdown_hook("webhook retries", RetryQueue::shutdown_hook(&queue));
+///
+/// let report = shutdown(Duration::from_secs(5));
+/// assert!(report.flushed.contains(&"webhook retries".to_string()));
+/// ```
+pub fn register_shutdown_hook<N, H>(name: N, hook: Arc<H>) -> ShutdownHookId
+where
+    N: Into<String>,
+    H: ShutdownHook + 'static,
+{
+    ShutdownHooks::global().register(name, hook)
+}
+
+/// Remove a hook registered with [`register_shutdown_hook`].
+///
+/// # Returns
+/// True if the hook was registered.
+pub fn remove_shutdown_hook(id: ShutdownHookId) -> bool {
+    ShutdownHooks::global().remove(id)
+}
+
+/// Flush buffered reports, queues and log summaries before the process exits.
+///
+/// Runs the [global hooks](ShutdownHooks::global) within `timeout`, then
+/// logs the occurrences suppressed by the global [`LogRateLimiter`]; see
+/// [`ShutdownHooks::run`].
+pub fn shutdown(timeout: Duration) -> ShutdownReport {
+    ShutdownHooks::global().run(timeout, &LogRateLimiter::global())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ErrorSettings;
+
+    #[test]
+    fn test_shutdown_should_run_hooks_in_order_until_the_deadline() {
+        // Given: a failing hook, a hook blocking past its deadline and a hook
+        // after the deadline
+        let hooks = ShutdownHooks::new();
+        let failing = hooks.register(
+            "shutdown.test.failing",
+            Arc::new(|_: Duration| Err(TylError::network("Store unreachable"))),
+        );
+        hooks.register(
+            "shutdown.test.blocking",
+            Arc::new(|_: Duration| {
+                thread::sleep(Duration::from_secs(5));
+                Ok(())
+            }),
+        );
+        hooks.register("shutdown.test.late", Arc::new(|_: Duration| Ok(())));
+
+        // When: shutting down with a short timeout
+        let started = Instant::now();
+        let report = hooks.run(
+            Duration::from_millis(50),
+            &LogRateLimiter::new(1, Duration::from_secs(60)),
+        );
+
+        // Then: the blocking hook should not stall shutdown
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let failed: Vec<(&str, &str)> = report
+            .failed
+            .iter()
+            .map(|(name, error)| (name.as_str(), error.code()))
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                ("shutdown.test.failing", "network"),
+                ("shutdown.test.blocking", "timeout")
+            ]
+        );
+        assert_eq!(report.skipped, ["shutdown.test.late"]);
+        assert!(!report.is_complete());
+
+        // And: hooks should only run once
+        assert!(!hooks.remove(failing));
+        assert!(hooks
+            .run(Duration::from_secs(1), &LogRateLimiter::unlimited())
+            .failed
+            .is_empty());
+    }
+
+    #[test]
+    fn test_shutdown_should_count_suppressed_occurrences_only_when_logged() {
+        // Given: limiters with suppressed occurrences, and default settings
+        let _defaults = ErrorSettings::override_scope(ErrorSettings::default());
+        let hooks = ShutdownHooks::new();
+        let limiter = LogRateLimiter::new(1, Duration::from_secs(60));
+        for _ in 0..3 {
+            limiter.check("shutdown.test.fingerprint");
+        }
+
+        // When: shutting down with error logging enabled, then disabled
+        let logged = hooks.run(Duration::from_secs(1), &limiter);
+        for _ in 0..3 {
+            limiter.check("shutdown.test.fingerprint");
+        }
+        let _settings = ErrorSettings::override_scope(ErrorSettings {
+            log_errors: false,
+            ..ErrorSettings::default()
+        });
+        let silent = hooks.run(Duration::from_secs(1), &limiter);
+
+        // Then: only logged occurrences should be counted, and none left pending
+        assert_eq!(logged.suppressed_logged, 2);
+        assert_eq!(silent.suppressed_logged, 0);
+        assert!(limiter.take_pending().is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! Process-wide values read without locking.
+//!
+//! This module provides [`Snapshot`], holding a value replaced rarely (e.g.
+//! by operators installing override tables) but read on every error. Each
+//! thread keeps the last value it saw together with its generation, so reads
+//! only check an atomic counter; the shared value is locked and cloned again
+//! only after a replacement.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::thread::LocalKey;
+
+/// Per-thread cache of a [`Snapshot`]: the last value seen, with its generation.
+pub(crate) type SnapshotCache<T> = RefCell<Option<(u64, Arc<T>)>>;
+
+/// A process-wide value with lock-free reads.
+pub(crate) struct Snapshot<T: 'static> {
+    generation: AtomicU64,
+    value: OnceLock<RwLock<Arc<T>>>,
+    init: fn() -> T,
+    cache: &'static LocalKey<SnapshotCache<T>>,
+}
+
+impl<T: 'static> Snapshot<T> {
+    /// Create a snapshot initialized lazily with `init`, cached per thread in `cache`.
+    pub(crate) const fn new(init: fn() -> T, cache: &'static LocalKey<SnapshotCache<T>>) -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+            value: OnceLock::new(),
+            init,
+            cache,
+        }
+    }
+
+    /// Run `f` with the current value.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let generation = self.generation.load(Ordering::Acquire);
+        let mut f = Some(f);
+        let cached = self.cache.try_with(|cache| {
+            let fresh = matches!(&*cache.borrow(), Some((seen, _)) if *seen == generation);
+            if !fresh {
+                // Reads nested in `f` cannot refresh: they use the shared value.
+                let Ok(mut cache) = cache.try_borrow_mut() else {
+                    return None;
+                };
+                *cache = Some((generation, self.load()));
+            }
+            let cache = cache.borrow();
+            let (_, value) = cache.as_ref()?;
+            f.take().map(|f| f(value))
+        });
+        match (cached.ok().flatten(), f) {
+            (Some(result), _) => result,
+            (None, Some(f)) => f(&self.load()),
+            (None, None) => unreachable!("`f` runs at most once"),
+        }
+    }
+
+    /// Get the current value.
+    pub(crate) fn current(&self) -> Arc<T> {
+        self.load()
+    }
+
+    /// Replace the value for every thread.
+    pub(crate) fn replace(&self, value: T) {
+        *self.shared().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(value);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    fn load(&self) -> Arc<T> {
+        Arc::clone(&self.shared().read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn shared(&self) -> &RwLock<Arc<T>> {
+        self.value
+            .get_or_init(|| RwLock::new(Arc::new((self.init)())))
+    }
+}
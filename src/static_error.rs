@@ -0,0 +1,121 @@
+//! Statically allocated errors for fixed failures on hot paths.
+//!
+//! This module provides [`StaticTylError`] and the [`static_tyl_error!`]
+//! macro. A static error is built once, on first use, and then handed out as a
+//! `&'static TylError`, so tight loops reporting the same failure (for example
+//! "rate limited" or "queue full") do not allocate a new error every time.
+
+use crate::error::TylError;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+/// Lazily initialized error stored in a `static`.
+///
+/// Usually declared with [`static_tyl_error!`]; dereferences to [`TylError`].
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{static_tyl_error, TylError, TylResult};
+///
+/// static_tyl_error! {
+///     /// Returned whenever the ingest buffer is full.
+///     pub static BUFFER_FULL = TylError::business_logic(
+///         "Ingest buffer full",
+///         tyl_errors::ErrorCategory::resource_exhaustion().into_classifier(),
+///     );
+/// }
+///
+/// fn push(len: usize) -> Result<(), &'static TylError> {
+///     if len >= 1024 { Err(BUFFER_FULL.get()) } else { Ok(()) }
+/// }
+///
+/// let first = push(1024).unwrap_err();
+/// assert!(std::ptr::eq(first, push(2048).unwrap_err()));
+/// assert!(BUFFER_FULL.is_retriable());
+///
+/// let owned: TylResult<()> = Err(BUFFER_FULL.to_owned());
+/// assert_eq!(owned.unwrap_err().to_string(), BUFFER_FULL.to_string());
+/// ```
+pub struct StaticTylError {
+    cell: OnceLock<TylError>,
+    init: fn() -> TylError,
+}
+
+impl StaticTylError {
+    /// Create a static error built by `init` on first access.
+    pub const fn new(init: fn() -> TylError) -> Self {
+        Self {
+            cell: OnceLock::new(),
+            init,
+        }
+    }
+
+    /// Get the shared error, building it on first access.
+    pub fn get(&self) -> &TylError {
+        self.cell.get_or_init(self.init)
+    }
+
+    /// Get an owned copy of the error, e.g. to return it in a [`TylResult`](crate::TylResult).
+    ///
+    /// Copies the already-built error; sources are shared rather than cloned.
+    pub fn to_owned(&self) -> TylError {
+        self.get().clone()
+    }
+}
+
+impl Deref for StaticTylError {
+    type Target = TylError;
+
+    fn deref(&self) -> &TylError {
+        self.get()
+    }
+}
+
+impl fmt::Debug for StaticTylError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.get(), f)
+    }
+}
+
+impl fmt::Display for StaticTylError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.get(), f)
+    }
+}
+
+/// Declare a [`StaticTylError`] built from a constructor expression on first use.
+///
+/// See [`StaticTylError`] for an example.
+#[macro_export]
+macro_rules! static_tyl_error {
+    ($($(#[$meta:meta])* $vis:vis static $name:ident = $init:expr;)+) => {
+        $(
+            $(#[$meta])*
+            $vis static $name: $crate::StaticTylError = $crate::StaticTylError::new(|| $init);
+        )+
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static_tyl_error! {
+        static RATE_LIMITED = TylError::network("Rate limited");
+        static CLOSED = TylError::cancelled("Channel closed");
+    }
+
+    #[test]
+    fn test_static_errors_should_be_built_once_and_shared() {
+        // Given/When: accessing a static error repeatedly
+        let first: &'static TylError = RATE_LIMITED.get();
+        let second: &'static TylError = &RATE_LIMITED;
+
+        // Then: the same instance should be returned and behave like the error
+        assert!(std::ptr::eq(first, second));
+        assert_eq!(RATE_LIMITED.code(), "network");
+        assert_eq!(CLOSED.to_owned().code(), "cancelled");
+        assert_eq!(format!("{CLOSED}"), "Cancelled: Channel closed");
+    }
+}
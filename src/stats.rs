@@ -0,0 +1,270 @@
+//! Process-wide error counters.
+//!
+//! This module provides [`ErrorStats`], which counts errors per code,
+//! category, severity, owning team and domain. The global collector is fed
+//! once per error by [`TylError::report`] and [`TylError::report_context`], and its
+//! [`snapshot`](ErrorStats::snapshot) is serializable for metrics endpoints
+//! and scrapers. Counters are sharded by thread so that services reporting
+//! from many threads do not contend on one lock.
+
+use crate::clock;
+use crate::context::ErrorContext;
+use crate::domain::TylDomain;
+use crate::error::{TylError, TylSeverity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+/// Number of independently locked counter shards.
+const SHARDS: usize = 16;
+
+#[derive(Debug, Default)]
+struct Shard {
+    total: u64,
+    by_code: HashMap<(Option<TylDomain>, &'static str), u64>,
+    by_category: HashMap<String, u64>,
+    by_severity: HashMap<TylSeverity, u64>,
+    by_owner: HashMap<String, u64>,
+    by_domain: HashMap<String, u64>,
+}
+
+/// Sharded error counters.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorStats, TylError};
+///
+/// let stats = ErrorStats::new();
+/// stats.record(&TylError::network("Connection reset"));
+/// stats.record(&TylError::network("Connection refused"));
+/// stats.record(&TylError::validation("email", "Invalid"));
+///
+/// let snapshot = stats.snapshot();
+/// assert_eq!(snapshot.total, 3);
+/// assert_eq!(snapshot.by_code["network"], 2);
+/// assert_eq!(snapshot.by_category["Validation"], 1);
+/// println!("{}", serde_json::to_string(&snapshot).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ErrorStats {
+    shards: Vec<Mutex<Shard>>,
+    since: Mutex<DateTime<Utc>>,
+}
+
+/// Immutable view of [`ErrorStats`] counters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorStatsSnapshot {
+    /// When counting started (creation or last reset).
+    pub since: DateTime<Utc>,
+    /// When the snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+    /// Number of errors recorded.
+    pub total: u64,
+    /// Errors per code, [qualified](TylError::qualified_code) with their domain.
+    pub by_code: BTreeMap<String, u64>,
+    /// Errors per category name.
+    pub by_category: BTreeMap<String, u64>,
+    /// Errors per severity.
+    pub by_severity: BTreeMap<TylSeverity, u64>,
+    /// Errors per owning team; errors without an owner are not counted.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_owner: BTreeMap<String, u64>,
+    /// Errors per [domain](TylError::domain); errors without a domain are
+    /// not counted.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_domain: BTreeMap<String, u64>,
+}
+
+impl Default for ErrorStats {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+            since: Mutex::new(clock::now()),
+        }
+    }
+}
+
+impl ErrorStats {
+    /// Create empty counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide counters, fed by [`TylError::report`].
+    pub fn global() -> &'static ErrorStats {
+        static GLOBAL: OnceLock<ErrorStats> = OnceLock::new();
+        GLOBAL.get_or_init(ErrorStats::new)
+    }
+
+    /// Count an error, attributed to its [owner](TylError::owner).
+    pub fn record(&self, error: &TylError) {
+        self.record_owned(error, error.owner().as_deref());
+    }
+
+    /// Count an error, attributed to the owner of its context.
+    pub fn record_context(&self, error: &TylError, context: &ErrorContext) {
+        self.record_owned(error, context.owner.as_deref());
+    }
+
+    fn record_owned(&self, error: &TylError, owner: Option<&str>) {
+        let code = error.code();
+        // Resolve the category once: severity and domain derive from it.
+        let category = error.category();
+        let severity = error.severity_in(&category);
+        let domain = error.domain_in(&category);
+
+        let mut shard = self.shard().lock().unwrap_or_else(|e| e.into_inner());
+        shard.total += 1;
+        *shard.by_code.entry((domain.clone(), code)).or_default() += 1;
+        increment(&mut shard.by_category, category.category_name());
+        *shard.by_severity.entry(severity).or_default() += 1;
+        if let Some(owner) = owner {
+            increment(&mut shard.by_owner, owner);
+        }
+        if let Some(domain) = &domain {
+            increment(&mut shard.by_domain, domain.as_str());
+        }
+    }
+
+    /// Take an immutable view of the counters.
+    ///
+    /// Shards are read one after another, so errors recorded meanwhile may
+    /// be counted in some totals only.
+    pub fn snapshot(&self) -> ErrorStatsSnapshot {
+        let mut snapshot = ErrorStatsSnapshot {
+            since: *self.since.lock().unwrap_or_else(|e| e.into_inner()),
+            taken_at: clock::now(),
+            total: 0,
+            by_code: BTreeMap::new(),
+            by_category: BTreeMap::new(),
+            by_severity: BTreeMap::new(),
+            by_owner: BTreeMap::new(),
+            by_domain: BTreeMap::new(),
+        };
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap_or_else(|e| e.into_inner());
+            snapshot.total += shard.total;
+            for ((domain, code), count) in &shard.by_code {
+                let code = match domain {
+                    Some(domain) => format!("{domain}.{code}"),
+                    None => code.to_string(),
+                };
+                *snapshot.by_code.entry(code).or_default() += count;
+            }
+            for (category, count) in &shard.by_category {
+                *snapshot.by_category.entry(category.clone()).or_default() += count;
+            }
+            for (severity, count) in &shard.by_severity {
+                *snapshot.by_severity.entry(*severity).or_default() += count;
+            }
+            for (owner, count) in &shard.by_owner {
+                *snapshot.by_owner.entry(owner.clone()).or_default() += count;
+            }
+            for (domain, count) in &shard.by_domain {
+                *snapshot.by_domain.entry(domain.clone()).or_default() += count;
+            }
+        }
+        snapshot
+    }
+
+    /// Reset every counter to zero.
+    pub fn reset(&self) {
+        *self.since.lock().unwrap_or_else(|e| e.into_inner()) = clock::now();
+        for shard in &self.shards {
+            *shard.lock().unwrap_or_else(|e| e.into_inner()) = Shard::default();
+        }
+    }
+
+    /// Get the shard of the current thread.
+    fn shard(&self) -> &Mutex<Shard> {
+        let mut hasher = DefaultHasher::new();
+        std::thread::current().id().hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+}
+
+/// Increment the count of `key`, allocating it only when first seen.
+fn increment(counts: &mut HashMap<String, u64>, key: &str) {
+    match counts.get_mut(key) {
+        Some(count) => *count += 1,
+        None => {
+            counts.insert(key.to_string(), 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_counts_from_many_threads_should_add_up() {
+        // Given: counters shared by several threads
+        let stats = Arc::new(ErrorStats::new());
+
+        // When: each thread records errors
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let stats = Arc::clone(&stats);
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        stats.record(&TylError::timeout("Upstream slow"));
+                        stats.record(&TylError::internal("Bug"));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Then: the snapshot should hold every count
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total, 800);
+        assert_eq!(snapshot.by_code["timeout"], 400);
+        assert_eq!(snapshot.by_severity.values().sum::<u64>(), 800);
+        assert!(snapshot.by_domain.is_empty());
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: ErrorStatsSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+
+        // And: resetting should clear the counts
+        stats.reset();
+        assert_eq!(stats.snapshot().total, 0);
+
+        // And: codes of errors with a domain should be qualified with it
+        stats.record(&TylError::database("Connection lost"));
+        assert_eq!(stats.snapshot().by_code["storage.database"], 1);
+    }
+
+    #[test]
+    fn test_reported_errors_should_feed_the_global_stats() {
+        // Given: the global counters before reporting
+        let before = ErrorStats::global().snapshot();
+        let count = |snapshot: &ErrorStatsSnapshot| {
+            snapshot
+                .by_code
+                .get("not_implemented")
+                .copied()
+                .unwrap_or(0)
+        };
+
+        // When: reporting an error
+        TylError::not_implemented("stats.test").report("stats.test");
+
+        // Then: the global counters should have counted it
+        assert!(count(&ErrorStats::global().snapshot()) > count(&before));
+
+        // And: building contexts alone should not count errors
+        let stats = ErrorStats::new();
+        let error = TylError::timeout("Upstream slow");
+        let context = error.to_context("stats.test".to_string());
+        let _ = error.to_context("stats.test".to_string());
+        stats.record_context(&error, &context);
+        assert_eq!(stats.snapshot().total, 1);
+    }
+}
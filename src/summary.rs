@@ -0,0 +1,223 @@
+//! Summaries of batch failures.
+//!
+//! This module provides [`ErrorReport`], a digest of a [`TylErrorGroup`]
+//! that counts failures per error code and category and keeps a few example
+//! messages and affected item identifiers for each code, so that a batch job
+//! can end with one readable summary instead of one log line per failure.
+
+use crate::group::TylErrorGroup;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Default number of distinct example messages kept per error code.
+pub const DEFAULT_REPORT_EXAMPLES: usize = 3;
+
+/// Default number of affected item identifiers kept per error code.
+pub const DEFAULT_REPORT_ITEMS: usize = 10;
+
+/// Failures sharing one error code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeSummary {
+    /// Error code (see [`TylError::code`](crate::TylError::code)).
+    pub code: String,
+    /// Category name of the first failure with this code.
+    pub category: String,
+    /// Number of failures with this code.
+    pub count: usize,
+    /// Distinct example messages, in batch order.
+    pub examples: Vec<String>,
+    /// Identifiers of affected items (the item ID, or `#<index>` without one).
+    pub items: Vec<String>,
+    /// Number of affected items not listed in `items`.
+    #[serde(default)]
+    pub omitted_items: usize,
+}
+
+/// Summary of the failures of a batch.
+///
+/// Codes are ordered by descending count (ties by code), so
+/// [`top`](Self::top) returns the most frequent failures.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorReport, TylError, TylErrorGroup};
+///
+/// let mut group = TylErrorGroup::new();
+/// group.push_with_id(0, "order-1", TylError::network("Connection reset"));
+/// group.push_with_id(4, "order-5", TylError::network("Connection reset"));
+/// group.push(7, TylError::validation("amount", "must be positive"));
+///
+/// let report = ErrorReport::from_group(&group);
+/// assert_eq!(report.total, 3);
+/// assert_eq!(report.top(1)[0].code, "network");
+/// assert_eq!(report.top(1)[0].items, ["order-1", "order-5"]);
+/// assert_eq!(report.by_category["Validation"], 1);
+/// println!("{report}");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Total number of failures.
+    pub total: usize,
+    /// Failures per error code, most frequent first.
+    pub by_code: Vec<CodeSummary>,
+    /// Number of failures per category name.
+    pub by_category: BTreeMap<String, usize>,
+}
+
+impl ErrorReport {
+    /// Summarize a group with the default example and item limits.
+    pub fn from_group(group: &TylErrorGroup) -> Self {
+        Self::from_group_with_limits(group, DEFAULT_REPORT_EXAMPLES, DEFAULT_REPORT_ITEMS)
+    }
+
+    /// Summarize a group, keeping at most `max_examples` messages and
+    /// `max_items` item identifiers per error code.
+    pub fn from_group_with_limits(
+        group: &TylErrorGroup,
+        max_examples: usize,
+        max_items: usize,
+    ) -> Self {
+        let mut by_code: Vec<CodeSummary> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let mut by_category = BTreeMap::new();
+
+        for item in group.errors() {
+            let category = item.error.category();
+            *by_category
+                .entry(category.category_name().to_string())
+                .or_insert(0) += 1;
+
+            let code = item.error.code();
+            let position = *positions.entry(code).or_insert_with(|| {
+                by_code.push(CodeSummary {
+                    code: code.to_string(),
+                    category: category.category_name().to_string(),
+                    count: 0,
+                    examples: Vec::new(),
+                    items: Vec::new(),
+                    omitted_items: 0,
+                });
+                by_code.len() - 1
+            });
+
+            let summary = &mut by_code[position];
+            summary.count += 1;
+            let message = item.error.to_string();
+            if summary.examples.len() < max_examples && !summary.examples.contains(&message) {
+                summary.examples.push(message);
+            }
+            if summary.items.len() < max_items {
+                summary.items.push(match &item.id {
+                    Some(id) => id.clone(),
+                    None => format!("#{}", item.index),
+                });
+            } else {
+                summary.omitted_items += 1;
+            }
+        }
+
+        by_code.sort_by(|left, right| {
+            right
+                .count
+                .cmp(&left.count)
+                .then_with(|| left.code.cmp(&right.code))
+        });
+
+        Self {
+            total: group.len(),
+            by_code,
+            by_category,
+        }
+    }
+
+    /// Get the `n` most frequent error codes.
+    pub fn top(&self, n: usize) -> &[CodeSummary] {
+        &self.by_code[..n.min(self.by_code.len())]
+    }
+
+    /// Check if the report contains no failures.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+impl From<&TylErrorGroup> for ErrorReport {
+    fn from(group: &TylErrorGroup) -> Self {
+        Self::from_group(group)
+    }
+}
+
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no batch items failed");
+        }
+
+        write!(f, "{} batch item(s) failed", self.total)?;
+        let mut categories: Vec<_> = self.by_category.iter().collect();
+        categories.sort_by(|left, right| right.1.cmp(left.1).then_with(|| left.0.cmp(right.0)));
+        f.write_str("\n  by category:")?;
+        for (index, (category, count)) in categories.into_iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{separator}{category} {count}")?;
+        }
+
+        for summary in &self.by_code {
+            write!(
+                f,
+                "\n  {} ({}) x{}",
+                summary.code, summary.category, summary.count
+            )?;
+            for example in &summary.examples {
+                write!(f, "\n    e.g. {example}")?;
+            }
+            write!(f, "\n    items: {}", summary.items.join(", "))?;
+            if summary.omitted_items > 0 {
+                write!(f, " (+{} more)", summary.omitted_items)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TylError;
+
+    #[test]
+    fn test_report_should_group_by_code_and_limit_examples_and_items() {
+        // Given: a batch with many network failures and one validation failure
+        let mut group = TylErrorGroup::new();
+        for index in 0..5 {
+            let message = if index % 2 == 0 { "Reset" } else { "Refused" };
+            group.push(index, TylError::network(message));
+        }
+        group.push_with_id(5, "row-6", TylError::validation("email", "invalid"));
+
+        // When: summarizing with small limits
+        let report = ErrorReport::from_group_with_limits(&group, 1, 2);
+
+        // Then: codes should be ordered by count and lists capped
+        assert_eq!(report.total, 6);
+        let network = &report.by_code[0];
+        assert_eq!((network.code.as_str(), network.count), ("network", 5));
+        assert_eq!(network.examples, ["Network error: Reset"]);
+        assert_eq!(network.items, ["#0", "#1"]);
+        assert_eq!(network.omitted_items, 3);
+        assert_eq!(report.by_code[1].items, ["row-6"]);
+        assert_eq!(report.by_category["Network"], 5);
+
+        // And: the report should render as text and JSON
+        let text = report.to_string();
+        assert!(text.starts_with("6 batch item(s) failed\n  by category: Network 5, Validation 1"));
+        assert!(text.contains("items: #0, #1 (+3 more)"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["by_code"][1]["code"], "validation");
+        assert_eq!(
+            ErrorReport::from_group(&TylErrorGroup::new()).to_string(),
+            "no batch items failed"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Time-boxed suppression of error codes ("maintenance mode").
+//!
+//! During planned maintenance some failures are expected, e.g. `database`
+//! errors while the primary fails over, and should not page anyone. This
+//! module lets operators [`suppress`] error codes until a deadline: errors of
+//! those codes have their [severity](TylError::severity) capped at
+//! [`TylSeverity::Info`], log at info level, are not routed by an
+//! `AlertRouter` (`alert` feature), and the contexts created for them
+//! record the suppression under [`SUPPRESSED_UNTIL_KEY`] for auditing.
+
+use crate::clock;
+use crate::error::{TylError, TylSeverity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Context metadata key recording until when the error's code was suppressed.
+pub const SUPPRESSED_UNTIL_KEY: &str = "suppressed_until";
+
+/// Error codes suppressed until a deadline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    /// Suppressed error codes: plain codes as returned by [`TylError::code`]
+    /// match errors of every domain, [qualified](TylError::qualified_code)
+    /// ones such as `billing.custom` only errors of that domain.
+    pub codes: Vec<String>,
+    /// When the suppression ends.
+    pub until: DateTime<Utc>,
+}
+
+impl Suppression {
+    fn covers(&self, code: &str, qualified: &str) -> bool {
+        self.codes.iter().any(|c| c == code || c == qualified)
+    }
+}
+
+/// A set of suppressions, see [`suppress`].
+///
+/// Errors consult [`Suppressions::global`]; tests install their own set on
+/// their thread with [`Suppressions::override_scope`].
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    entries: RwLock<Vec<Suppression>>,
+    /// Number of entries, read without locking while nothing is suppressed.
+    count: AtomicUsize,
+}
+
+thread_local! {
+    /// Suppressions of this thread's active [`Suppressions::override_scope`].
+    static OVERRIDE: Cell<Option<&'static Suppressions>> = const { Cell::new(None) };
+}
+
+impl Suppressions {
+    /// Create an empty set of suppressions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide suppressions, or those of this thread's active
+    /// [`override_scope`](Self::override_scope).
+    pub fn global() -> &'static Suppressions {
+        if let Some(suppressions) = OVERRIDE.with(Cell::get) {
+            return suppressions;
+        }
+        static GLOBAL: OnceLock<Suppressions> = OnceLock::new();
+        GLOBAL.get_or_init(Suppressions::new)
+    }
+
+    /// Use `suppressions` instead of the process-wide ones on this thread
+    /// until the returned guard is dropped.
+    pub fn override_scope(suppressions: &'static Suppressions) -> SuppressionsOverride {
+        SuppressionsOverride {
+            previous: OVERRIDE.with(|current| current.replace(Some(suppressions))),
+            _thread: PhantomData,
+        }
+    }
+
+    /// Suppress errors of the given codes until `until`, see [`suppress`].
+    pub fn suppress<I, S>(&self, codes: I, until: DateTime<Utc>)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let suppression = Suppression {
+            codes: codes.into_iter().map(Into::into).collect(),
+            until,
+        };
+        let now = clock::now();
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|existing| existing.until > now);
+        if suppression.until > now {
+            entries.push(suppression);
+        }
+        self.count.store(entries.len(), Ordering::Release);
+    }
+
+    /// End every suppression of this set.
+    pub fn lift(&self) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.count.store(0, Ordering::Release);
+    }
+
+    /// Get the suppressions of this set that have not expired yet.
+    pub fn active(&self) -> Vec<Suppression> {
+        let now = clock::now();
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|suppression| suppression.until > now)
+            .cloned()
+            .collect()
+    }
+
+    /// Get until when `error` is suppressed by this set, if it is.
+    pub fn suppressed_until(&self, error: &TylError) -> Option<DateTime<Utc>> {
+        if self.count.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        let code = error.code();
+        let qualified = error.qualified_code();
+        let now = clock::now();
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|suppression| suppression.until > now)
+            .filter(|suppression| suppression.covers(code, &qualified))
+            .map(|suppression| suppression.until)
+            .max()
+    }
+}
+
+/// Guard of [`Suppressions::override_scope`], restoring the previous
+/// suppressions of its thread when dropped.
+#[must_use = "the override ends when the guard is dropped"]
+pub struct SuppressionsOverride {
+    previous: Option<&'static Suppressions>,
+    /// Guards restore the suppressions of the thread they were created on.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for SuppressionsOverride {
+    fn drop(&mut self) {
+        let _ = OVERRIDE.try_with(|current| current.set(self.previous));
+    }
+}
+
+/// Suppress errors of the given codes process-wide until `until`.
+///
+/// Codes are plain ([`TylError::code`]), suppressing errors of every domain,
+/// or [qualified](TylError::qualified_code), e.g. `billing.custom`,
+/// suppressing one domain's errors only. Suppressions add up: a code stays
+/// suppressed until the latest deadline covering it. Expired suppressions
+/// are dropped.
+///
+/// # Example
+/// ```rust
+/// use chrono::{Duration, Utc};
+/// use tyl_errors::{suppress, TylError, TylSeverity, SUPPRESSED_UNTIL_KEY};
+///
+/// suppress(["not_implemented"], Utc::now() + Duration::minutes(30));
+///
+/// let error = TylError::not_implemented("Exports are being migrated");
+/// assert_eq!(error.severity(), TylSeverity::Info);
+/// assert!(error.suppressed_until().is_some());
+/// assert!(error.to_context("exports.run".to_string()).has_metadata(SUPPRESSED_UNTIL_KEY));
+/// # tyl_errors::lift_suppressions();
+/// ```
+pub fn suppress<I, S>(codes: I, until: DateTime<Utc>)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    Suppressions::global().suppress(codes, until);
+}
+
+/// End every suppression, e.g. when maintenance finishes early.
+pub fn lift_suppressions() {
+    Suppressions::global().lift();
+}
+
+/// Get the suppressions that have not expired yet.
+pub fn active_suppressions() -> Vec<Suppression> {
+    Suppressions::global().active()
+}
+
+impl TylError {
+    /// Get until when this error's code is [suppressed](suppress), if it is.
+    pub fn suppressed_until(&self) -> Option<DateTime<Utc>> {
+        Suppressions::global().suppressed_until(self)
+    }
+
+    /// Check whether this error's code is currently [suppressed](suppress).
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed_until().is_some()
+    }
+}
+
+/// Cap the severity of a suppressed error.
+pub(crate) fn downgrade(severity: TylSeverity) -> TylSeverity {
+    severity.min(TylSeverity::Info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorCategory, ErrorSettings, LogLevel, TylDomain};
+    use chrono::Duration;
+
+    #[test]
+    fn test_suppressed_codes_should_be_downgraded_until_the_deadline() {
+        // Given: this thread's own suppressions, and unknown errors routed
+        // to a pager and configured to log at error
+        let _suppressions = Suppressions::override_scope(Box::leak(Box::default()));
+        let unknown = || -> TylError { serde_json::from_str(r#"{"Quantum":{}}"#).unwrap() };
+        #[cfg(feature = "alert")]
+        let router = crate::AlertRouter::from_json(
+            r#"{"routes":[{"name":"pager"}],"rules":[],"default_route":"pager"}"#,
+        )
+        .unwrap();
+        let settings = ErrorSettings::default().with_category_log_level("unknown", LogLevel::Error);
+        assert_eq!(unknown().severity(), TylSeverity::Error);
+
+        // When: suppressing the code, alongside an already expired suppression
+        let until = clock::now() + Duration::minutes(5);
+        suppress(["unknown"], clock::now() - Duration::minutes(1));
+        suppress(["unknown", "suppress.test.other"], until);
+
+        // Then: the error should be downgraded, unrouted and audited
+        let error = unknown();
+        assert_eq!(error.suppressed_until(), Some(until));
+        assert_eq!(error.severity(), TylSeverity::Info);
+        assert_eq!(error.effective_log_level_with(&settings), LogLevel::Info);
+        #[cfg(feature = "alert")]
+        assert!(router.route(&error).is_none());
+        let context = error.to_context("suppress.test".to_string());
+        assert_eq!(
+            context.get_metadata(SUPPRESSED_UNTIL_KEY),
+            Some(&serde_json::json!(until.to_rfc3339()))
+        );
+        assert_eq!(active_suppressions().len(), 1);
+        assert!(!TylError::internal("Bug").is_suppressed());
+
+        // And: lifting suppressions should restore it
+        lift_suppressions();
+        assert_eq!(unknown().severity(), TylSeverity::Error);
+        #[cfg(feature = "alert")]
+        assert!(router.route(&unknown()).is_some());
+    }
+
+    #[test]
+    fn test_qualified_codes_should_suppress_one_domain_only() {
+        // Given: custom errors of two domains
+        let suppressions = Suppressions::new();
+        let error_of = |domain: TylDomain| {
+            let category = ErrorCategory::transient().with_domain(domain);
+            TylError::business_logic("Ledger is closing", category.into_classifier())
+        };
+        let payments = error_of(TylDomain::Payments);
+        let messaging = error_of(TylDomain::Messaging);
+
+        // When: suppressing the custom errors of one domain
+        let until = clock::now() + Duration::minutes(5);
+        suppressions.suppress([payments.qualified_code()], until);
+
+        // Then: only that domain's errors should be suppressed
+        assert_eq!(suppressions.suppressed_until(&payments), Some(until));
+        assert_eq!(suppressions.suppressed_until(&messaging), None);
+
+        // And: the plain code should still cover every domain
+        suppressions.suppress(["custom"], until);
+        assert_eq!(suppressions.suppressed_until(&messaging), Some(until));
+        assert!(!Suppressions::global()
+            .active()
+            .iter()
+            .any(|s| s.until == until));
+    }
+}
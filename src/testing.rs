@@ -0,0 +1,135 @@
+//! Helpers for snapshot and golden-file tests of errors.
+//!
+//! Error responses contain volatile parts (UUIDs, timestamps, jittered delays)
+//! that make exact comparisons flaky. This module compares only the stable
+//! parts of an error: variant, code, category, validated field and missing resource.
+
+use crate::error::TylError;
+
+/// List the stable properties in which two errors differ.
+///
+/// # Arguments
+/// * `left` - First error to compare
+/// * `right` - Second error to compare
+///
+/// # Returns
+/// One human-readable line per differing property; empty if the errors are equivalent.
+pub fn differences(left: &TylError, right: &TylError) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    if left.variant_name() != right.variant_name() {
+        differences.push(format!(
+            "variant: {} != {}",
+            left.variant_name(),
+            right.variant_name()
+        ));
+    }
+
+    if left.code() != right.code() {
+        differences.push(format!("code: {} != {}", left.code(), right.code()));
+    }
+
+    let (left_category, right_category) = (left.category(), right.category());
+    if left_category.category_name() != right_category.category_name() {
+        differences.push(format!(
+            "category: {} != {}",
+            left_category.category_name(),
+            right_category.category_name()
+        ));
+    }
+
+    if let (TylError::Validation { field: left, .. }, TylError::Validation { field: right, .. }) =
+        (left, right)
+    {
+        if left != right {
+            differences.push(format!("field: {left} != {right}"));
+        }
+    }
+
+    if let (
+        TylError::NotFound { resource: left, .. },
+        TylError::NotFound {
+            resource: right, ..
+        },
+    ) = (left, right)
+    {
+        if left != right {
+            differences.push(format!("resource: {left} != {right}"));
+        }
+    }
+
+    differences
+}
+
+/// Assert that two errors are equivalent, ignoring volatile parts.
+///
+/// Panics with the list of differing properties otherwise. See
+/// [`TylError::similar_to`] for what is compared.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{testing::assert_errors_equivalent, TylError};
+///
+/// let actual = TylError::validation("email", "Invalid format: 'bob@'");
+/// assert_errors_equivalent!(actual, TylError::validation("email", "<any>"));
+/// ```
+#[macro_export]
+macro_rules! assert_errors_equivalent {
+    ($left:expr, $right:expr $(,)?) => {{
+        let (left, right) = (&$left, &$right);
+        let differences = $crate::testing::differences(left, right);
+        if !differences.is_empty() {
+            panic!(
+                "errors are not equivalent:\n  left: {}\n right: {}\n  diff: {}",
+                left,
+                right,
+                differences.join("; ")
+            );
+        }
+    }};
+}
+
+pub use crate::assert_errors_equivalent;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differences_should_ignore_messages_and_ids() {
+        // Given: two not-found errors differing only in id
+        let left = TylError::not_found("user", "1f0e");
+        let right = TylError::not_found("user", "a9b2");
+
+        // When/Then: they should be equivalent
+        assert!(differences(&left, &right).is_empty());
+        assert_errors_equivalent!(left, right);
+    }
+
+    #[test]
+    fn test_differences_should_report_variant_and_field() {
+        // Given: errors of different shapes
+        let validation = TylError::validation("email", "bad");
+        let other_field = TylError::validation("name", "bad");
+        let network = TylError::network("bad");
+
+        // When: comparing them
+        let field_diff = differences(&validation, &other_field);
+        let variant_diff = differences(&validation, &network);
+
+        // Then: each stable difference should be listed
+        assert_eq!(field_diff, vec!["field: email != name".to_string()]);
+        assert!(variant_diff.iter().any(|d| d.starts_with("variant:")));
+        assert!(variant_diff.iter().any(|d| d.starts_with("code:")));
+        assert!(variant_diff.iter().any(|d| d.starts_with("category:")));
+    }
+
+    #[test]
+    #[should_panic(expected = "errors are not equivalent")]
+    fn test_assert_errors_equivalent_should_panic_on_mismatch() {
+        assert_errors_equivalent!(
+            TylError::not_found("user", "1"),
+            TylError::not_found("order", "1")
+        );
+    }
+}
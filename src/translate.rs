@@ -0,0 +1,278 @@
+//! Translation of TYL error codes into partner-specific error codes.
+//!
+//! This module provides the [`ErrorTranslator`] used on egress to map
+//! [`TylError::code`] values onto the codes and messages an external partner
+//! API expects. Translators are plain serde data, so mappings can be loaded
+//! from configuration files.
+
+use crate::error::{is_client_hidden, TylError, TylResult};
+use crate::problem::{status_title, ProblemDetails};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// External representation of one TYL error code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Translation {
+    /// Partner-specific error code.
+    pub code: String,
+    /// Message template; `{placeholder}`s are replaced by error fields.
+    pub message: String,
+    /// HTTP status override, if the partner expects a different status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+impl Translation {
+    /// Create a translation to an external code and message template.
+    pub fn new<C: Into<String>, M: Into<String>>(code: C, message: M) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+            status: None,
+        }
+    }
+
+    /// Override the HTTP status using builder pattern.
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+}
+
+/// Result of translating an error for an external partner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslatedError {
+    /// Partner-specific error code.
+    pub code: String,
+    /// Rendered message.
+    pub message: String,
+    /// HTTP status to respond with.
+    pub status: u16,
+}
+
+/// Mapping table from TYL error codes to partner-specific errors.
+///
+/// Message templates may reference `{code}`, `{message}`, `{category}`,
+/// `{status}`, the variant fields (`{field}`, `{resource}`, `{id}`, `{feature}`)
+/// and the structured fields of [`TylError::metadata`] that problem details
+/// include; internal fields such as driver codes, panic locations or the
+/// subject of permission errors are not rendered. `{message}` is the detail
+/// of problem details, generic for server errors. Unknown placeholders are
+/// left untouched.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{ErrorTranslator, TylError};
+///
+/// let translator = ErrorTranslator::from_json(r#"{
+///     "mappings": {
+///         "not_found": { "code": "E404", "message": "No {resource} with id {id}" }
+///     },
+///     "fallback": { "code": "E500", "message": "Internal failure" }
+/// }"#).unwrap();
+///
+/// let translated = translator.translate(&TylError::not_found("order", "42"));
+/// assert_eq!(translated.code, "E404");
+/// assert_eq!(translated.message, "No order with id 42");
+/// assert_eq!(translator.translate(&TylError::internal("boom")).code, "E500");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorTranslator {
+    /// Translations keyed by TYL error code.
+    #[serde(default)]
+    pub mappings: HashMap<String, Translation>,
+    /// Translation used for codes without a mapping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Translation>,
+}
+
+impl ErrorTranslator {
+    /// Create an empty translator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a translator from its JSON representation.
+    pub fn from_json(json: &str) -> TylResult<Self> {
+        serde_json::from_str(json).map_err(|err| {
+            TylError::configuration(format!("Invalid error translation table: {err}"))
+        })
+    }
+
+    /// Add a translation for a TYL error code using builder pattern.
+    pub fn with_mapping<S: Into<String>>(mut self, tyl_code: S, translation: Translation) -> Self {
+        self.mappings.insert(tyl_code.into(), translation);
+        self
+    }
+
+    /// Set the translation used for unmapped codes using builder pattern.
+    pub fn with_fallback(mut self, translation: Translation) -> Self {
+        self.fallback = Some(translation);
+        self
+    }
+
+    /// Translate an error for the partner.
+    ///
+    /// Unmapped codes use the fallback translation, or keep the TYL code and
+    /// message if no fallback is configured; server errors (5xx) then get
+    /// the generic [`SERVER_ERROR_DETAIL`](crate::SERVER_ERROR_DETAIL).
+    pub fn translate(&self, error: &TylError) -> TranslatedError {
+        let status = error.http_status();
+        match self.mappings.get(error.code()).or(self.fallback.as_ref()) {
+            Some(translation) => TranslatedError {
+                code: translation.code.clone(),
+                message: render(&translation.message, error),
+                status: translation.status.unwrap_or(status),
+            },
+            None => TranslatedError {
+                code: error.code().to_string(),
+                message: error.client_detail(),
+                status,
+            },
+        }
+    }
+
+    /// Build problem details for an error using the partner's codes and messages.
+    pub fn to_problem_details(&self, error: &TylError) -> ProblemDetails {
+        let translated = self.translate(error);
+        let mut problem = error.to_problem_details();
+        problem.problem_type = format!("urn:tyl:error:{}", translated.code);
+        problem.title = status_title(translated.status).to_string();
+        problem.status = translated.status;
+        problem.detail = translated.message;
+        problem
+            .extensions
+            .insert("code".to_string(), serde_json::json!(translated.code));
+        problem
+    }
+}
+
+/// Replace `{placeholder}`s in a template with the error's fields.
+fn render(template: &str, error: &TylError) -> String {
+    let mut values: HashMap<String, String> = error
+        .metadata()
+        .into_iter()
+        .filter(|(key, _)| !is_client_hidden(key))
+        .map(|(key, value)| match value {
+            serde_json::Value::String(text) => (key, text),
+            other => (key, other.to_string()),
+        })
+        .collect();
+    values.insert("code".to_string(), error.code().to_string());
+    values.insert("message".to_string(), error.client_detail());
+    values.insert(
+        "category".to_string(),
+        error.category().category_name().to_string(),
+    );
+    values.insert("status".to_string(), error.http_status().to_string());
+    match error {
+        TylError::Validation { field, .. } => {
+            values.insert("field".to_string(), field.clone());
+        }
+        TylError::NotFound { resource, id, .. } => {
+            values.insert("resource".to_string(), resource.clone());
+            values.insert("id".to_string(), id.clone());
+        }
+        TylError::NotImplemented { feature, .. } => {
+            values.insert("feature".to_string(), feature.clone());
+        }
+        _ => {}
+    }
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => match values.get(&after[..end]) {
+                Some(value) => {
+                    rendered.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = after;
+                }
+            },
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_should_render_templates_and_override_status() {
+        // Given: a translator with a status override
+        let translator = ErrorTranslator::new().with_mapping(
+            "validation",
+            Translation::new("INVALID_{field}", "Field {field} rejected ({unknown})")
+                .with_status(422),
+        );
+
+        // When: translating a validation error
+        let translated = translator.translate(&TylError::validation("email", "bad"));
+
+        // Then: the template should be rendered and the status overridden
+        assert_eq!(translated.code, "INVALID_{field}");
+        assert_eq!(translated.message, "Field email rejected ({unknown})");
+        assert_eq!(translated.status, 422);
+    }
+
+    #[test]
+    fn test_translate_should_not_render_internal_fields() {
+        // Given: a template referencing internal fields of a database error
+        let translator = ErrorTranslator::new().with_fallback(Translation::new(
+            "E500",
+            "{message} ({driver_code}, {panic_file}, {subject})",
+        ));
+        let database = TylError::database("Deadlock on orders_pkey").with_driver_code("40P01");
+        let denied = TylError::permission_denied("alice@example.com", "refund", "order:9");
+
+        // When: translating them for the partner
+        let database = translator.translate(&database);
+        let denied = translator.translate(&denied);
+
+        // Then: only what problem details expose should be rendered
+        assert_eq!(
+            database.message,
+            format!(
+                "{} ({{driver_code}}, {{panic_file}}, {{subject}})",
+                crate::SERVER_ERROR_DETAIL
+            )
+        );
+        assert!(!denied.message.contains("alice@example.com"));
+    }
+
+    #[test]
+    fn test_translate_should_keep_tyl_code_without_mapping_or_fallback() {
+        let translated = ErrorTranslator::new().translate(&TylError::conflict("dup"));
+        assert_eq!(translated.code, "conflict");
+        assert_eq!(translated.status, 409);
+    }
+
+    #[test]
+    fn test_to_problem_details_should_use_partner_code() {
+        // Given: a translator for configuration errors
+        let translator = ErrorTranslator::new().with_mapping(
+            "configuration_missing_key",
+            Translation::new("CFG-01", "Missing setting {config_key}"),
+        );
+
+        // When: building problem details
+        let problem = translator.to_problem_details(&TylError::config_missing("API_KEY"));
+
+        // Then: partner code and message should be used
+        assert_eq!(problem.extensions["code"], "CFG-01");
+        assert_eq!(problem.detail, "Missing setting API_KEY");
+        assert_eq!(problem.problem_type, "urn:tyl:error:CFG-01");
+    }
+}
@@ -0,0 +1,504 @@
+//! Aggregated field validation failures.
+//!
+//! This module provides [`TylValidationErrors`], a list of per-field
+//! violations (field path, code, message and parameters) that converts into a
+//! single [`TylError::Validation`], and [`ValidationConstraint`], the
+//! structured rule a value failed, which clients render without parsing
+//! messages. With the `validator` feature, `validator::ValidationErrors`
+//! produced by derive-based validation converts into it directly, keeping
+//! nested field paths, messages and parameters.
+
+use crate::error::TylError;
+use crate::extras::ErrorExtras;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::Arc;
+
+/// Structured description of the rule a value failed.
+///
+/// Serialized with a `type` tag, e.g. `{"type":"length","min":3,"max":64}`;
+/// absent bounds are omitted.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{TylError, ValidationConstraint};
+///
+/// let error = TylError::validation("username", "Must be 3 to 64 characters")
+///     .with_constraint(ValidationConstraint::length(Some(3), Some(64)));
+/// assert_eq!(
+///     error.metadata()["constraint"],
+///     serde_json::json!({"type": "length", "min": 3, "max": 64})
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValidationConstraint {
+    /// A value must be present.
+    Required,
+    /// Length bounds of a string or collection, inclusive.
+    Length {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<u64>,
+    },
+    /// Bounds of a number, inclusive.
+    Range {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    /// A regular expression the value must match.
+    Pattern { pattern: String },
+    /// A well-known format, e.g. `email`, `url` or `uuid`.
+    Format { format: String },
+    /// The allowed values.
+    OneOf { values: Vec<String> },
+}
+
+impl ValidationConstraint {
+    /// Create a length constraint.
+    pub fn length(min: Option<u64>, max: Option<u64>) -> Self {
+        Self::Length { min, max }
+    }
+
+    /// Create a numeric range constraint.
+    pub fn range(min: Option<f64>, max: Option<f64>) -> Self {
+        Self::Range { min, max }
+    }
+
+    /// Create a pattern constraint.
+    pub fn pattern<P: Into<String>>(pattern: P) -> Self {
+        Self::Pattern {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Create a format constraint.
+    pub fn format<F: Into<String>>(format: F) -> Self {
+        Self::Format {
+            format: format.into(),
+        }
+    }
+
+    /// Create a constraint allowing only `values`.
+    pub fn one_of<V, I>(values: I) -> Self
+    where
+        V: Into<String>,
+        I: IntoIterator<Item = V>,
+    {
+        Self::OneOf {
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A single failed validation rule on a field.
+///
+/// Build violations with [`FieldViolation::new`] or
+/// [`TylValidationErrors::add`]; fields may be added in minor releases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct FieldViolation {
+    /// Path of the field, e.g. `address.city` or `items[2].sku`.
+    pub field: String,
+    /// Machine-readable rule code, e.g. `email` or `length`.
+    pub code: String,
+    /// Human-readable explanation.
+    pub message: String,
+    /// Rule parameters, such as `min`/`max` bounds and the rejected value.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub params: Map<String, Value>,
+    /// Structured rule the field failed, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<ValidationConstraint>,
+}
+
+impl FieldViolation {
+    /// Create a violation of the rule `code` on `field`.
+    pub fn new<F, C, M>(field: F, code: C, message: M) -> Self
+    where
+        F: Into<String>,
+        C: Into<String>,
+        M: Into<String>,
+    {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+            params: Map::new(),
+            constraint: None,
+        }
+    }
+
+    /// Add a rule parameter using builder pattern.
+    pub fn with_param<K: Into<String>>(mut self, key: K, value: Value) -> Self {
+        self.params.insert(key.into(), value);
+        self
+    }
+
+    /// Set the structured rule the field failed using builder pattern.
+    pub fn with_constraint(mut self, constraint: ValidationConstraint) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+}
+
+/// Collection of field violations reported together.
+///
+/// Converts into a [`TylError::Validation`] whose field lists every failing
+/// field, with the constraint of the violation if there is only one; the
+/// collection stays available as the error's source through
+/// [`TylError::downcast_source_ref`].
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{TylError, TylValidationErrors};
+///
+/// let mut errors = TylValidationErrors::new();
+/// errors.add("email", "email", "must be a valid email address");
+/// errors
+///     .add("age", "range", "must be at least 18")
+///     .params
+///     .insert("min".to_string(), serde_json::json!(18));
+///
+/// let error = TylError::from(errors);
+/// assert_eq!(
+///     error.to_string(),
+///     "Validation error: email, age: email: must be a valid email address; age: must be at least 18"
+/// );
+/// let details = error.downcast_source_ref::<TylValidationErrors>().unwrap();
+/// assert_eq!(details.violations[1].params["min"], 18);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TylValidationErrors {
+    /// Violations in the order they were reported.
+    pub violations: Vec<FieldViolation>,
+}
+
+impl TylValidationErrors {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a violation and return it for adding parameters.
+    pub fn add<F, C, M>(&mut self, field: F, code: C, message: M) -> &mut FieldViolation
+    where
+        F: Into<String>,
+        C: Into<String>,
+        M: Into<String>,
+    {
+        self.violations
+            .push(FieldViolation::new(field, code, message));
+        let last = self.violations.len() - 1;
+        &mut self.violations[last]
+    }
+
+    /// Check if no violations were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Get the number of violations.
+    pub fn len(&self) -> usize {
+        self.violations.len()
+    }
+
+    /// Get the distinct failing fields in reporting order.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields: Vec<&str> = Vec::new();
+        for violation in &self.violations {
+            if !fields.contains(&violation.field.as_str()) {
+                fields.push(&violation.field);
+            }
+        }
+        fields
+    }
+
+    /// Get the violations of one field.
+    pub fn field(&self, field: &str) -> impl Iterator<Item = &FieldViolation> {
+        let field = field.to_string();
+        self.violations
+            .iter()
+            .filter(move |violation| violation.field == field)
+    }
+
+    /// Convert into a result: `Ok(())` if empty, the validation error otherwise.
+    pub fn into_result(self) -> Result<(), TylError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+}
+
+impl fmt::Display for TylValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, violation) in self.violations.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", violation.field, violation.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TylValidationErrors {}
+
+impl From<TylValidationErrors> for TylError {
+    #[track_caller]
+    fn from(errors: TylValidationErrors) -> Self {
+        let mut extras = ErrorExtras::new();
+        if let [violation] = errors.violations.as_slice() {
+            if let Some(constraint) = &violation.constraint {
+                extras.set_constraint(constraint.clone());
+            }
+        }
+        TylError::Validation {
+            field: errors.fields().join(", "),
+            message: crate::limits::limit_message(errors.to_string()),
+            input: None,
+            source: Some(Arc::new(errors)),
+            extras,
+        }
+    }
+}
+
+#[cfg(feature = "validator")]
+mod validator_support {
+    use super::*;
+    use validator::{ValidationErrors, ValidationErrorsKind};
+
+    /// Collect the violations of a struct, prefixing field names with `prefix`.
+    fn collect(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldViolation>) {
+        // `validator` stores fields in a hash map; sort for a stable order.
+        let mut fields: Vec<_> = errors.errors().iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        for (name, kind) in fields {
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else if name == "__all__" {
+                prefix.to_string()
+            } else {
+                format!("{prefix}.{name}")
+            };
+            match kind {
+                ValidationErrorsKind::Field(rules) => {
+                    for rule in rules {
+                        let mut params: Map<String, Value> = rule
+                            .params
+                            .iter()
+                            .map(|(key, value)| (key.to_string(), value.clone()))
+                            .collect();
+                        params.sort_keys();
+                        let message = rule.message.as_ref().map_or_else(
+                            || format!("failed '{}' validation", rule.code),
+                            |m| m.to_string(),
+                        );
+                        let mut violation =
+                            FieldViolation::new(path.clone(), rule.code.as_ref(), message);
+                        violation.constraint = constraint_of(&rule.code, &params);
+                        violation.params = params;
+                        out.push(violation);
+                    }
+                }
+                ValidationErrorsKind::Struct(nested) => collect(nested, &path, out),
+                ValidationErrorsKind::List(items) => {
+                    for (index, nested) in items {
+                        collect(nested, &format!("{path}[{index}]"), out);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Map the built-in `validator` rules to constraints.
+    fn constraint_of(code: &str, params: &Map<String, Value>) -> Option<ValidationConstraint> {
+        match code {
+            "required" => Some(ValidationConstraint::Required),
+            "length" => {
+                let equal = params.get("equal").and_then(Value::as_u64);
+                Some(ValidationConstraint::length(
+                    params.get("min").and_then(Value::as_u64).or(equal),
+                    params.get("max").and_then(Value::as_u64).or(equal),
+                ))
+            }
+            "range" => Some(ValidationConstraint::range(
+                params.get("min").and_then(Value::as_f64),
+                params.get("max").and_then(Value::as_f64),
+            )),
+            "email" | "url" => Some(ValidationConstraint::format(code)),
+            _ => None,
+        }
+    }
+
+    /// Flatten nested `validator` errors into field paths such as `items[0].sku`.
+    impl From<&ValidationErrors> for TylValidationErrors {
+        fn from(errors: &ValidationErrors) -> Self {
+            let mut violations = Vec::new();
+            collect(errors, "", &mut violations);
+            Self { violations }
+        }
+    }
+
+    impl From<ValidationErrors> for TylValidationErrors {
+        fn from(errors: ValidationErrors) -> Self {
+            Self::from(&errors)
+        }
+    }
+
+    /// Convert derive-based validation failures into a validation error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use tyl_errors::{TylError, TylResult, TylValidationErrors};
+    /// use validator::Validate;
+    ///
+    /// #[derive(Validate)]
+    /// struct SignUp {
+    ///     #[validate(email(message = "must be a valid email address"))]
+    ///     email: String,
+    ///     #[validate(length(min = 8))]
+    ///     password: String,
+    /// }
+    ///
+    /// fn sign_up(form: &SignUp) -> TylResult<()> {
+    ///     form.validate()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// let error = sign_up(&SignUp { email: "bob".into(), password: "short".into() }).unwrap_err();
+    /// assert_eq!(error.code(), "validation");
+    /// let details = error.downcast_source_ref::<TylValidationErrors>().unwrap();
+    /// assert_eq!(details.fields(), ["email", "password"]);
+    /// assert_eq!(details.violations[1].params["min"], 8);
+    /// ```
+    impl From<ValidationErrors> for TylError {
+        #[track_caller]
+        fn from(errors: ValidationErrors) -> Self {
+            TylValidationErrors::from(&errors).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violations_should_convert_to_one_validation_error() {
+        // Given: two violations on one field and one on another
+        let mut errors = TylValidationErrors::new();
+        errors.add("name", "required", "is required");
+        errors.add("email", "email", "is invalid");
+        errors.add("name", "length", "is too short");
+
+        // When: converting to a TylError
+        let error = TylError::from(errors.clone());
+
+        // Then: every field should be listed once and the details kept as source
+        assert!(matches!(&error, TylError::Validation { field, .. } if field == "name, email"));
+        assert_eq!(errors.field("name").count(), 2);
+        assert_eq!(
+            error.downcast_source_ref::<TylValidationErrors>(),
+            Some(&errors)
+        );
+        assert!(TylValidationErrors::new().into_result().is_ok());
+        assert!(errors.into_result().is_err());
+    }
+
+    #[test]
+    fn test_constraint_should_serialize_tagged_and_reach_the_error() {
+        // Given: a single violation with a length constraint
+        let mut errors = TylValidationErrors::new();
+        errors
+            .add("username", "length", "must be 3 to 64 characters")
+            .constraint = Some(ValidationConstraint::length(Some(3), Some(64)));
+
+        // When: converting it into an error and its problem details
+        let error = TylError::from(errors.clone());
+        let problem = serde_json::to_value(error.to_problem_details()).unwrap();
+
+        // Then: the constraint should be exposed as structured JSON
+        let expected = serde_json::json!({"type": "length", "min": 3, "max": 64});
+        assert_eq!(error.constraint(), errors.violations[0].constraint.as_ref());
+        assert_eq!(problem["constraint"], expected);
+        assert_eq!(
+            serde_json::to_value(&errors.violations[0]).unwrap()["constraint"],
+            expected
+        );
+
+        // And: open bounds should be omitted and constraints should roundtrip
+        for constraint in [
+            ValidationConstraint::Required,
+            ValidationConstraint::range(None, Some(0.5)),
+            ValidationConstraint::pattern("^[a-z]+$"),
+            ValidationConstraint::one_of(["eur", "usd"]),
+        ] {
+            let json = serde_json::to_string(&constraint).unwrap();
+            assert!(!json.contains("null"), "{json}");
+            assert_eq!(
+                serde_json::from_str::<ValidationConstraint>(&json).unwrap(),
+                constraint
+            );
+        }
+
+        // And: several violations should not pick one constraint
+        errors.add("email", "email", "is invalid");
+        assert_eq!(TylError::from(errors).constraint(), None);
+    }
+
+    #[cfg(feature = "validator")]
+    #[test]
+    fn test_nested_validator_errors_should_keep_paths_messages_and_params() {
+        use std::collections::BTreeMap;
+        use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+        // Given: validator errors on a nested struct and a list item
+        let mut address = ValidationErrors::new();
+        address.add("city", ValidationError::new("required"));
+        let mut item = ValidationErrors::new();
+        let mut quantity = ValidationError::new("range").with_message("must be positive".into());
+        quantity.add_param("min".into(), &1);
+        item.add("quantity", quantity);
+
+        let mut order = ValidationErrors::new();
+        order.add("email", ValidationError::new("email"));
+        let nested = order.errors_mut();
+        nested.insert(
+            "address".into(),
+            ValidationErrorsKind::Struct(Box::new(address)),
+        );
+        nested.insert(
+            "items".into(),
+            ValidationErrorsKind::List(BTreeMap::from([(1, Box::new(item))])),
+        );
+
+        // When: converting to TYL validation errors
+        let errors = TylValidationErrors::from(&order);
+
+        // Then: nested paths, messages and params should be preserved
+        assert_eq!(
+            errors.fields(),
+            ["address.city", "email", "items[1].quantity"]
+        );
+        let quantity = errors.field("items[1].quantity").next().unwrap();
+        assert_eq!(quantity.message, "must be positive");
+        assert_eq!(quantity.params["min"], 1);
+        assert_eq!(
+            quantity.constraint,
+            Some(ValidationConstraint::range(Some(1.0), None))
+        );
+        assert_eq!(errors.violations[1].message, "failed 'email' validation");
+        assert_eq!(
+            errors.violations[1].constraint,
+            Some(ValidationConstraint::format("email"))
+        );
+    }
+}
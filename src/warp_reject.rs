@@ -0,0 +1,89 @@
+//! warp rejection integration.
+//!
+//! This module (enabled by the `warp` feature) lets filters reject requests
+//! with a [`TylError`] and provides [`recover`], a rejection handler that
+//! turns those rejections into `application/problem+json` responses with the
+//! error's HTTP status.
+
+use crate::error::TylError;
+use crate::problem::{ProblemDetails, PROBLEM_JSON_CONTENT_TYPE};
+use warp::http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use warp::http::StatusCode;
+use warp::reply::{Reply, Response};
+use warp::Rejection;
+
+/// Lets filters reject with a `TylError`, either through `warp::reject::custom`
+/// or with `?` in `and_then` handlers returning `Result<_, Rejection>`.
+impl warp::reject::Reject for TylError {}
+
+/// Respond with the problem as `application/problem+json` and its status code,
+/// with a `Retry-After` header when the error may be retried.
+impl Reply for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let reply = warp::reply::with_header(
+            warp::reply::json(&self),
+            CONTENT_TYPE,
+            PROBLEM_JSON_CONTENT_TYPE,
+        );
+        let mut response = warp::reply::with_status(reply, status).into_response();
+        if let Some(value) = self
+            .retry_after_header()
+            .and_then(|seconds| HeaderValue::from_str(&seconds).ok())
+        {
+            response.headers_mut().insert(RETRY_AFTER, value);
+        }
+        response
+    }
+}
+
+/// Rejection handler converting [`TylError`] rejections into problem details.
+///
+/// Other rejections are passed on unchanged, so warp's default handling (or a
+/// later `recover`) still applies to them.
+///
+/// # Example
+/// ```rust
+/// use tyl_errors::{warp_reject, TylError};
+/// use warp::Filter;
+///
+/// let users = warp::path!("users" / String)
+///     .and_then(|id: String| async move {
+///         Err::<String, _>(warp::Rejection::from(TylError::not_found("user", id)))
+///     })
+///     .recover(warp_reject::recover);
+/// # let _ = users;
+/// ```
+pub async fn recover(rejection: Rejection) -> Result<Response, Rejection> {
+    match rejection.find::<TylError>() {
+        Some(error) => Ok(error.to_problem_details().into_response()),
+        None => Err(rejection),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recover_should_render_tyl_rejections_as_problem_json() {
+        // Given: a rejection carrying a not-found error
+        let rejection = Rejection::from(TylError::not_found("user", "42"));
+
+        // When: recovering from it
+        let response = recover(rejection).await.unwrap();
+
+        // Then: the response should be a 404 problem+json body
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+        assert!(response.headers().get(RETRY_AFTER).is_none());
+
+        // And: retriable errors should carry a Retry-After header
+        let rejection = Rejection::from(TylError::network("Connection reset"));
+        let response = recover(rejection).await.unwrap();
+        assert!(response.headers().contains_key(RETRY_AFTER));
+
+        // And: other rejections should pass through
+        assert!(recover(warp::reject::not_found()).await.is_err());
+    }
+}